@@ -0,0 +1,16 @@
+-- HD wallet deposit address derivation
+
+ALTER TABLE crypto_addresses
+    ADD COLUMN derivation_path VARCHAR(255),
+    ADD COLUMN derivation_index INTEGER;
+
+CREATE UNIQUE INDEX idx_crypto_addresses_chain_derivation_index
+    ON crypto_addresses(chain, derivation_index)
+    WHERE derivation_index IS NOT NULL;
+
+-- Next unused derivation index per chain
+CREATE TABLE derivation_indices (
+    chain chain_type PRIMARY KEY,
+    next_index INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
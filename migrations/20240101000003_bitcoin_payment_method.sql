@@ -0,0 +1,2 @@
+-- On-chain Bitcoin payments
+ALTER TYPE payment_method ADD VALUE IF NOT EXISTS 'bitcoin' AFTER 'solana';
@@ -0,0 +1,6 @@
+-- Last block processed by the background deposit scanner, per chain
+CREATE TABLE chain_scan_checkpoints (
+    chain VARCHAR(50) PRIMARY KEY,
+    last_block BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,16 @@
+-- Chain reorganization tracking
+
+-- Hash of the block a transaction was included in, re-checked for reorgs
+ALTER TABLE transactions ADD COLUMN block_hash VARCHAR(255);
+
+-- Audit trail for payment status reversals
+CREATE TABLE payment_status_history (
+    id UUID PRIMARY KEY,
+    payment_id UUID NOT NULL REFERENCES payments(id) ON DELETE CASCADE,
+    previous_status payment_status NOT NULL,
+    new_status payment_status NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_payment_status_history_payment_id ON payment_status_history(payment_id);
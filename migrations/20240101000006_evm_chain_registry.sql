@@ -0,0 +1,10 @@
+-- EVM chains come from the runtime chain registry, so chains are stored by name
+
+ALTER TABLE crypto_addresses ALTER COLUMN chain TYPE VARCHAR(50) USING chain::text;
+-- derivation_indices is keyed by derivation scope rather than chain: EVM chains
+-- derive the same address for an index, so they all reserve from 'evm'
+ALTER TABLE derivation_indices ALTER COLUMN chain TYPE VARCHAR(50) USING chain::text;
+DROP TYPE chain_type;
+
+-- Payments on any registered EVM chain; the chain itself is in payments.crypto_chain
+ALTER TYPE payment_method ADD VALUE IF NOT EXISTS 'evm' AFTER 'emi';
@@ -0,0 +1,2 @@
+-- Settled invoices are matched to payments by hash
+CREATE INDEX idx_payments_lightning_payment_hash ON payments(lightning_payment_hash);
@@ -0,0 +1,17 @@
+-- LNURL-pay links, also reachable as <name>@<domain> Lightning Addresses
+CREATE TABLE lnurl_pay_links (
+    id UUID PRIMARY KEY,
+    name VARCHAR(64) NOT NULL UNIQUE,
+    description TEXT NOT NULL,
+    min_sendable_msat BIGINT NOT NULL CHECK (min_sendable_msat >= 1000),
+    max_sendable_msat BIGINT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK (max_sendable_msat >= min_sendable_msat)
+);
+
+CREATE TRIGGER update_lnurl_pay_links_updated_at
+    BEFORE UPDATE ON lnurl_pay_links
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
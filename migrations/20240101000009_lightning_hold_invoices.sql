@@ -0,0 +1,23 @@
+-- Hold invoices for escrowed Lightning payments. The preimage stays encrypted
+-- with ENCRYPTION_KEY until the payment is captured.
+CREATE TYPE hold_invoice_state AS ENUM (
+    'open', 'accepted', 'settled', 'cancelled'
+);
+
+CREATE TABLE lightning_hold_invoices (
+    payment_id UUID PRIMARY KEY REFERENCES payments(id) ON DELETE CASCADE,
+    payment_hash VARCHAR(64) NOT NULL UNIQUE,
+    encrypted_preimage TEXT NOT NULL,
+    state hold_invoice_state NOT NULL DEFAULT 'open',
+    -- Earliest CLTV expiry of the held HTLCs, known once accepted
+    htlc_expiry_height INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_lightning_hold_invoices_state ON lightning_hold_invoices(state);
+
+CREATE TRIGGER update_lightning_hold_invoices_updated_at
+    BEFORE UPDATE ON lightning_hold_invoices
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,44 @@
+-- Razorpay subscriptions. Every successful charge is recorded as a completed
+-- payment and linked back to its subscription through subscription_charges.
+CREATE TYPE subscription_status AS ENUM (
+    'created', 'authenticated', 'active', 'pending', 'halted', 'paused',
+    'cancelled', 'completed', 'expired'
+);
+
+CREATE TABLE subscriptions (
+    id UUID PRIMARY KEY,
+    razorpay_subscription_id VARCHAR(255) NOT NULL UNIQUE,
+    razorpay_plan_id VARCHAR(255) NOT NULL,
+    status subscription_status NOT NULL DEFAULT 'created',
+    -- Amount of each charge in the smallest currency unit
+    amount BIGINT NOT NULL,
+    currency currency_type NOT NULL,
+    quantity INTEGER NOT NULL DEFAULT 1,
+    total_count INTEGER NOT NULL,
+    paid_count INTEGER NOT NULL DEFAULT 0,
+    customer_email VARCHAR(255),
+    customer_phone VARCHAR(50),
+    short_url TEXT,
+    metadata JSONB,
+    current_start TIMESTAMP WITH TIME ZONE,
+    current_end TIMESTAMP WITH TIME ZONE,
+    ended_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_subscriptions_status ON subscriptions(status);
+
+CREATE TABLE subscription_charges (
+    payment_id UUID PRIMARY KEY REFERENCES payments(id) ON DELETE CASCADE,
+    subscription_id UUID NOT NULL REFERENCES subscriptions(id) ON DELETE CASCADE,
+    razorpay_payment_id VARCHAR(255) NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_subscription_charges_subscription_id ON subscription_charges(subscription_id);
+
+CREATE TRIGGER update_subscriptions_updated_at
+    BEFORE UPDATE ON subscriptions
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,19 @@
+-- Razorpay Payment Links, each collecting one payment
+CREATE TYPE payment_link_status AS ENUM (
+    'created', 'partially_paid', 'paid', 'expired', 'cancelled'
+);
+
+CREATE TABLE razorpay_payment_links (
+    payment_id UUID PRIMARY KEY REFERENCES payments(id) ON DELETE CASCADE,
+    razorpay_payment_link_id VARCHAR(255) NOT NULL UNIQUE,
+    short_url TEXT NOT NULL,
+    status payment_link_status NOT NULL DEFAULT 'created',
+    expire_by TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_razorpay_payment_links_updated_at
+    BEFORE UPDATE ON razorpay_payment_links
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
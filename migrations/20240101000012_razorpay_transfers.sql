@@ -0,0 +1,34 @@
+-- Razorpay Route transfers to linked accounts. Splits attached to an order are
+-- stored without a Razorpay ID until Razorpay creates them on capture.
+CREATE TYPE transfer_status AS ENUM (
+    'created', 'pending', 'processed', 'failed', 'reversed', 'partially_reversed'
+);
+
+CREATE TABLE razorpay_transfers (
+    id UUID PRIMARY KEY,
+    -- NULL for direct transfers from the account balance
+    payment_id UUID REFERENCES payments(id) ON DELETE CASCADE,
+    razorpay_transfer_id VARCHAR(255) UNIQUE,
+    linked_account_id VARCHAR(255) NOT NULL,
+    amount BIGINT NOT NULL,
+    currency currency_type NOT NULL,
+    status transfer_status NOT NULL DEFAULT 'created',
+    amount_reversed BIGINT NOT NULL DEFAULT 0,
+    on_hold BOOLEAN NOT NULL DEFAULT false,
+    on_hold_until TIMESTAMP WITH TIME ZONE,
+    notes JSONB,
+    error_message TEXT,
+    processed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_razorpay_transfers_payment_id ON razorpay_transfers(payment_id);
+
+-- Transfer webhooks name their source payment by its Razorpay ID
+CREATE INDEX idx_payments_razorpay_payment_id ON payments(razorpay_payment_id);
+
+CREATE TRIGGER update_razorpay_transfers_updated_at
+    BEFORE UPDATE ON razorpay_transfers
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,19 @@
+-- Our customers and the Razorpay customers their saved cards are stored under
+CREATE TABLE customers (
+    id UUID PRIMARY KEY,
+    razorpay_customer_id VARCHAR(255) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    contact VARCHAR(20),
+    gstin VARCHAR(15),
+    notes JSONB,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_customers_email ON customers(email);
+
+CREATE TRIGGER update_customers_updated_at
+    BEFORE UPDATE ON customers
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,51 @@
+-- Razorpay Smart Collect virtual accounts receiving NEFT/RTGS/IMPS transfers
+ALTER TYPE payment_method ADD VALUE IF NOT EXISTS 'bank_transfer';
+
+CREATE TYPE virtual_account_status AS ENUM ('active', 'paid', 'closed');
+
+CREATE TABLE virtual_accounts (
+    id UUID PRIMARY KEY,
+    razorpay_virtual_account_id VARCHAR(255) NOT NULL UNIQUE,
+    -- Set when the account collects one payment; credits add up towards it
+    payment_id UUID UNIQUE REFERENCES payments(id) ON DELETE CASCADE,
+    -- Set when the account belongs to a customer; every credit is a new payment
+    customer_id UUID REFERENCES customers(id) ON DELETE CASCADE,
+    status virtual_account_status NOT NULL DEFAULT 'active',
+    amount_expected BIGINT,
+    amount_paid BIGINT NOT NULL DEFAULT 0,
+    -- Received beyond amount_expected, which the merchant owes the customer back
+    -- or keeps as credit
+    amount_overpaid BIGINT NOT NULL DEFAULT 0,
+    account_number VARCHAR(50) NOT NULL,
+    ifsc VARCHAR(20) NOT NULL,
+    bank_name VARCHAR(255),
+    beneficiary_name VARCHAR(255),
+    close_by TIMESTAMP WITH TIME ZONE,
+    closed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK (payment_id IS NOT NULL OR customer_id IS NOT NULL)
+);
+
+CREATE INDEX idx_virtual_accounts_customer_id ON virtual_accounts(customer_id);
+
+CREATE TABLE virtual_account_credits (
+    id UUID PRIMARY KEY,
+    virtual_account_id UUID NOT NULL REFERENCES virtual_accounts(id) ON DELETE CASCADE,
+    payment_id UUID NOT NULL REFERENCES payments(id) ON DELETE CASCADE,
+    razorpay_payment_id VARCHAR(255) NOT NULL UNIQUE,
+    amount BIGINT NOT NULL,
+    -- NEFT, RTGS or IMPS
+    mode VARCHAR(10),
+    bank_reference VARCHAR(255),
+    payer_name VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_virtual_account_credits_virtual_account_id
+    ON virtual_account_credits(virtual_account_id);
+
+CREATE TRIGGER update_virtual_accounts_updated_at
+    BEFORE UPDATE ON virtual_accounts
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,40 @@
+-- Razorpay UPI QR codes. A single-use QR code collects one payment; a multi-use
+-- QR code takes any number of payments, each recorded as a payment of its own
+CREATE TYPE qr_code_status AS ENUM ('active', 'closed');
+
+CREATE TABLE razorpay_qr_codes (
+    id UUID PRIMARY KEY,
+    -- Set when the QR code collects one payment
+    payment_id UUID UNIQUE REFERENCES payments(id) ON DELETE CASCADE,
+    -- Customer the payments of a multi-use QR code are made for
+    customer_id UUID REFERENCES customers(id) ON DELETE SET NULL,
+    multiple_use BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Amount every payment must be; a multi-use QR code without it takes any amount
+    payment_amount BIGINT,
+    razorpay_qr_code_id VARCHAR(255) NOT NULL UNIQUE,
+    image_url TEXT NOT NULL,
+    status qr_code_status NOT NULL DEFAULT 'active',
+    -- paid, on_demand or expired, once closed
+    close_reason VARCHAR(50),
+    close_by TIMESTAMP WITH TIME ZONE,
+    closed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK (multiple_use OR payment_id IS NOT NULL)
+);
+
+CREATE TABLE razorpay_qr_code_credits (
+    id UUID PRIMARY KEY,
+    qr_code_id UUID NOT NULL REFERENCES razorpay_qr_codes(id) ON DELETE CASCADE,
+    payment_id UUID NOT NULL REFERENCES payments(id) ON DELETE CASCADE,
+    razorpay_payment_id VARCHAR(255) NOT NULL UNIQUE,
+    amount BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_razorpay_qr_code_credits_qr_code_id ON razorpay_qr_code_credits(qr_code_id);
+
+CREATE TRIGGER update_razorpay_qr_codes_updated_at
+    BEFORE UPDATE ON razorpay_qr_codes
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
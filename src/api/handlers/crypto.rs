@@ -6,7 +6,7 @@ use validator::Validate;
 use crate::db::repositories::PaymentRepository;
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    BalanceResponse, ChainType, CreatePaymentRequest, CurrencyType,
+    AddressResponse, BalanceResponse, ChainType, CreatePaymentRequest, CurrencyType,
     PaymentMethod, PaymentResponse, PaymentStatus,
 };
 use crate::services::crypto::WalletConnectVerifier;
@@ -55,28 +55,52 @@ pub async fn create_crypto_payment(
         AppError::Validation(format!("Invalid request: {}", e))
     })?;
 
+    // Lightning is requested as its own chain but settles in BTC
+    let is_lightning = matches!(request.chain.to_lowercase().as_str(), "lightning" | "ln");
+
     // Parse and validate chain
-    let chain_type: ChainType = request
-        .chain
-        .parse()
-        .map_err(|e| AppError::Validation(format!(
-            "Invalid chain: {}. Supported: ethereum, polygon, bsc, arbitrum, solana, bitcoin", e
-        )))?;
+    let chain_type: ChainType = if is_lightning {
+        ChainType::Bitcoin
+    } else {
+        request
+            .chain
+            .parse()
+            .map_err(|e| AppError::Validation(format!("Invalid chain: {}", e)))?
+    };
 
     // Determine currency and payment method based on chain
-    let (currency, method) = match chain_type {
-        ChainType::Ethereum => (CurrencyType::ETH, PaymentMethod::Ethereum),
-        ChainType::Polygon => (CurrencyType::MATIC, PaymentMethod::Polygon),
-        ChainType::Bsc => (CurrencyType::BNB, PaymentMethod::Bsc),
-        ChainType::Arbitrum => (CurrencyType::ETH, PaymentMethod::Arbitrum),
+    let (native_currency, method) = match chain_type {
+        ChainType::Evm(_) => {
+            let service = state
+                .payment_processor
+                .get_evm_service(&chain_type)
+                .ok_or_else(|| {
+                    AppError::Validation(format!(
+                        "Unsupported chain: {}. Supported: {}, solana, bitcoin, lightning",
+                        chain_type,
+                        state.payment_processor.evm_chain_names().join(", ")
+                    ))
+                })?;
+            (service.native_currency().clone(), PaymentMethod::Evm)
+        }
         ChainType::Solana => (CurrencyType::SOL, PaymentMethod::Solana),
-        ChainType::Bitcoin => (CurrencyType::BTC, PaymentMethod::Lightning),
+        ChainType::Bitcoin if is_lightning => (CurrencyType::BTC, PaymentMethod::Lightning),
+        ChainType::Bitcoin => (CurrencyType::BTC, PaymentMethod::Bitcoin),
+    };
+
+    // Stablecoins are ERC-20 or SPL token transfers, amounts in micro-units
+    let is_evm = chain_type.is_evm();
+    let currency = match request.currency.to_uppercase().as_str() {
+        "USDT" if is_evm => CurrencyType::USDT,
+        "USDC" if is_evm || chain_type == ChainType::Solana => CurrencyType::USDC,
+        _ => native_currency,
     };
 
     let payment_request = CreatePaymentRequest {
         amount: request.amount,
         currency,
         method,
+        chain: Some(chain_type.clone()),
         description: request.description,
         customer_email: request.customer_email,
         customer_phone: None,
@@ -90,7 +114,7 @@ pub async fn create_crypto_payment(
         .await?;
 
     let instructions = match chain_type {
-        ChainType::Bitcoin => "Pay the Lightning invoice using any Lightning-compatible wallet".to_string(),
+        ChainType::Bitcoin if is_lightning => "Pay the Lightning invoice using any Lightning-compatible wallet".to_string(),
         _ => format!(
             "Send exactly {} {} to the provided address. Transaction will be confirmed after required block confirmations.",
             request.amount, request.currency
@@ -107,7 +131,7 @@ pub async fn create_crypto_payment(
     Ok(Json(CreateCryptoPaymentResponse {
         success: true,
         payment_id: result.payment_id,
-        chain: chain_type.to_string(),
+        chain: result.chain.unwrap_or_else(|| chain_type.to_string()),
         address: result.crypto_address,
         lightning_invoice: result.lightning_invoice,
         amount: request.amount,
@@ -242,7 +266,7 @@ pub async fn get_balance(
     }
 
     let (balance, balance_wei) = match chain_type {
-        ChainType::Ethereum | ChainType::Polygon | ChainType::Bsc | ChainType::Arbitrum => {
+        ChainType::Evm(_) => {
             let service = state
                 .payment_processor
                 .get_evm_service(&chain_type)
@@ -256,14 +280,20 @@ pub async fn get_balance(
             let balance_lamports = state
                 .payment_processor
                 .solana()
-                .get_balance(&params.address)?;
+                .get_balance(&params.address)
+                .await?;
             let balance = crate::services::SolanaService::lamports_to_sol(balance_lamports);
             (balance, balance_lamports.to_string())
         }
         ChainType::Bitcoin => {
-            return Err(AppError::Payment(
-                "Bitcoin balance check requires Lightning node. Use /api/v1/crypto/lightning/balance instead.".to_string(),
-            ))
+            let service = state
+                .payment_processor
+                .bitcoin()
+                .ok_or_else(|| AppError::Payment("bitcoin not configured".to_string()))?;
+
+            let balance = service.get_balance(&params.address).await?;
+            let balance_btc = crate::services::BitcoinService::sats_to_btc(balance.confirmed_sats);
+            (balance_btc, balance.confirmed_sats.to_string())
         }
     };
 
@@ -370,14 +400,14 @@ pub async fn get_token_balance(
     }
 
     // Validate token address for EVM chains
-    if matches!(chain_type, ChainType::Ethereum | ChainType::Polygon | ChainType::Bsc | ChainType::Arbitrum) {
+    if chain_type.is_evm() {
         if !WalletConnectVerifier::validate_address(&params.token_address, &chain_type) {
             return Err(AppError::InvalidAddress("Invalid token contract address format".to_string()));
         }
     }
 
     match chain_type {
-        ChainType::Ethereum | ChainType::Polygon | ChainType::Bsc | ChainType::Arbitrum => {
+        ChainType::Evm(_) => {
             let service = state
                 .payment_processor
                 .get_evm_service(&chain_type)
@@ -399,7 +429,8 @@ pub async fn get_token_balance(
             let balance = state
                 .payment_processor
                 .solana()
-                .get_token_balance(&params.token_address)?;
+                .get_token_balance(&params.token_address)
+                .await?;
 
             Ok(Json(BalanceResponse {
                 address: params.wallet_address,
@@ -415,18 +446,55 @@ pub async fn get_token_balance(
     }
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct GenerateAddressParams {
+    #[serde(default)]
+    #[validate(length(max = 255, message = "Label too long"))]
+    pub label: Option<String>,
+}
+
 pub async fn generate_address(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     axum::extract::Path(chain): axum::extract::Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
+    axum::extract::Query(params): axum::extract::Query<GenerateAddressParams>,
+) -> AppResult<Json<AddressResponse>> {
+    params.validate().map_err(|e| {
+        AppError::Validation(format!("Invalid parameters: {}", e))
+    })?;
+
     let chain_type: ChainType = chain
         .parse()
         .map_err(|e| AppError::Validation(format!("Invalid chain: {}", e)))?;
 
-    // Address generation requires HD wallet configuration
-    // This would be implemented with proper key derivation in production
-    Err(AppError::Payment(format!(
-        "Dynamic address generation for {} requires HD wallet configuration. Contact administrator to set up merchant wallet.",
-        chain_type
-    )))
+    if chain_type.is_evm()
+        && state.payment_processor.get_evm_service(&chain_type).is_none()
+    {
+        return Err(AppError::Payment(format!("{} RPC not configured", chain_type)));
+    }
+
+    let address = state
+        .payment_processor
+        .allocate_deposit_address(
+            &state.db,
+            chain_type,
+            None,
+            None,
+            params.label.as_deref(),
+            None,
+        )
+        .await?;
+
+    tracing::info!(
+        address = %address.address,
+        chain = %address.chain,
+        derivation_path = ?address.derivation_path,
+        "Deposit address generated"
+    );
+
+    Ok(Json(AddressResponse {
+        address: address.address,
+        chain: address.chain,
+        payment_id: address.payment_id,
+        expected_amount: address.expected_amount,
+    }))
 }
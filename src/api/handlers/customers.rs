@@ -0,0 +1,190 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::db::repositories::CustomerRepository;
+use crate::error::{AppError, AppResult};
+use crate::models::Customer;
+use crate::services::razorpay::{
+    CreateCustomerRequest, DeletedTokenResponse, TokensResponse, UpdateCustomerRequest,
+};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayCustomerRequest {
+    #[validate(length(min = 3, max = 50, message = "Name must be 3-50 characters"))]
+    pub name: String,
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    pub email: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 8, max = 15, message = "Invalid phone number"))]
+    pub contact: Option<String>,
+    #[serde(default)]
+    #[validate(length(equal = 15, message = "GSTIN must be 15 characters"))]
+    pub gstin: Option<String>,
+    #[serde(default)]
+    pub notes: Option<serde_json::Value>,
+}
+
+/// Create a customer, or return the one already registered with the same
+/// email and contact
+pub async fn create_customer(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayCustomerRequest>,
+) -> AppResult<Json<Customer>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    let razorpay_customer = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_customer(&CreateCustomerRequest {
+            name: request.name,
+            email: request.email,
+            contact: request.contact,
+            fail_existing: "0".to_string(),
+            gstin: request.gstin,
+            notes: request.notes,
+        })
+        .await?;
+
+    let customer = CustomerRepository::upsert(
+        &state.db,
+        &razorpay_customer.id,
+        &razorpay_customer.to_details(),
+    )
+    .await?;
+
+    tracing::info!(
+        customer_id = %customer.id,
+        razorpay_customer_id = %customer.razorpay_customer_id,
+        "Customer created"
+    );
+
+    Ok(Json(customer))
+}
+
+/// Get a customer, refreshed from Razorpay
+pub async fn get_customer(
+    State(state): State<AppState>,
+    Path(customer_id): Path<Uuid>,
+) -> AppResult<Json<Customer>> {
+    let customer = CustomerRepository::find_by_id(&state.db, customer_id).await?;
+
+    let razorpay_customer = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .get_customer(&customer.razorpay_customer_id)
+        .await?;
+
+    let customer =
+        CustomerRepository::update_details(&state.db, customer.id, &razorpay_customer.to_details())
+            .await?;
+
+    Ok(Json(customer))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateRazorpayCustomerRequest {
+    #[serde(default)]
+    #[validate(length(min = 3, max = 50, message = "Name must be 3-50 characters"))]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    pub email: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 8, max = 15, message = "Invalid phone number"))]
+    pub contact: Option<String>,
+}
+
+pub async fn update_customer(
+    State(state): State<AppState>,
+    Path(customer_id): Path<Uuid>,
+    Json(request): Json<UpdateRazorpayCustomerRequest>,
+) -> AppResult<Json<Customer>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    if request.name.is_none() && request.email.is_none() && request.contact.is_none() {
+        return Err(AppError::Validation(
+            "Nothing to update: provide name, email or contact".to_string(),
+        ));
+    }
+
+    let customer = CustomerRepository::find_by_id(&state.db, customer_id).await?;
+
+    let razorpay_customer = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .update_customer(
+            &customer.razorpay_customer_id,
+            &UpdateCustomerRequest {
+                name: request.name,
+                email: request.email,
+                contact: request.contact,
+            },
+        )
+        .await?;
+
+    let customer =
+        CustomerRepository::update_details(&state.db, customer.id, &razorpay_customer.to_details())
+            .await?;
+
+    tracing::info!(customer_id = %customer.id, "Customer updated");
+
+    Ok(Json(customer))
+}
+
+/// List the cards and other instruments a customer saved at checkout
+pub async fn list_customer_tokens(
+    State(state): State<AppState>,
+    Path(customer_id): Path<Uuid>,
+) -> AppResult<Json<TokensResponse>> {
+    let customer = CustomerRepository::find_by_id(&state.db, customer_id).await?;
+
+    let tokens = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .get_customer_tokens(&customer.razorpay_customer_id)
+        .await?;
+
+    Ok(Json(tokens))
+}
+
+/// Remove a saved card, e.g. when the customer asks for it to be forgotten
+pub async fn delete_customer_token(
+    State(state): State<AppState>,
+    Path((customer_id, token_id)): Path<(Uuid, String)>,
+) -> AppResult<Json<DeletedTokenResponse>> {
+    if !token_id.starts_with("token_") {
+        return Err(AppError::Validation(format!("Invalid token: {}", token_id)));
+    }
+
+    let customer = CustomerRepository::find_by_id(&state.db, customer_id).await?;
+
+    let response = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .delete_customer_token(&customer.razorpay_customer_id, &token_id)
+        .await?;
+
+    tracing::info!(
+        customer_id = %customer.id,
+        token_id = %token_id,
+        "Saved token deleted"
+    );
+
+    Ok(Json(response))
+}
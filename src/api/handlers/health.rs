@@ -4,6 +4,7 @@ use sqlx::PgPool;
 use std::sync::Arc;
 
 use crate::error::AppResult;
+use crate::AppState;
 
 #[derive(Serialize)]
 pub struct HealthResponse {
@@ -45,21 +46,29 @@ pub async fn health_check(State(pool): State<Arc<PgPool>>) -> AppResult<Json<Hea
 }
 
 pub async fn service_status(
-    State(pool): State<Arc<PgPool>>,
+    State(state): State<AppState>,
 ) -> AppResult<Json<StatusResponse>> {
     // Check database
     let db_ok = sqlx::query("SELECT 1")
-        .execute(pool.as_ref())
+        .execute(state.db.as_ref())
         .await
         .is_ok();
 
+    let lightning_ok = state
+        .payment_processor
+        .lightning()
+        .node_info()
+        .await
+        .map(|info| info.synced_to_chain)
+        .unwrap_or(false);
+
     // In production, you would check each service's health
     let services = ServiceStatus {
         database: db_ok,
         razorpay: true, // Would ping Razorpay API
         ethereum: true, // Would check RPC connection
         solana: true,   // Would check RPC connection
-        lightning: true, // Would check node connection
+        lightning: lightning_ok,
     };
 
     Ok(Json(StatusResponse {
@@ -0,0 +1,393 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::db::repositories::{LnurlPayLinkRepository, PaymentRepository};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    is_valid_lnurl_name, CreateLnurlPayLinkRequest, CreatePaymentRequest, CurrencyType,
+    HoldInvoiceState, LnurlErrorResponse, LnurlInvoiceResponse, LnurlPayLink, LnurlPayResponse,
+    PaymentMethod, PaymentResponse, Transaction, TransactionResponse, TransactionStatus,
+    TransactionType,
+};
+use crate::services::crypto::lightning::PaymentStatus as InvoiceStatus;
+use crate::services::monitor::InvoiceWatcher;
+use crate::AppState;
+
+/// Errors on the public LNURL endpoints, rendered in the LNURL error format
+/// wallets understand
+pub struct LnurlError(AppError);
+
+impl From<AppError> for LnurlError {
+    fn from(error: AppError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for LnurlError {
+    fn into_response(self) -> Response {
+        let (status, reason) = match self.0 {
+            AppError::Validation(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            e => {
+                tracing::error!("LNURL-pay request failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Unable to create an invoice right now".to_string(),
+                )
+            }
+        };
+
+        (status, Json(LnurlErrorResponse::new(reason))).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LnurlCallbackParams {
+    /// Amount the wallet wants to pay, in millisatoshis
+    pub amount: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LnurlPayLinkResponse {
+    #[serde(flatten)]
+    pub link: LnurlPayLink,
+    pub lightning_address: String,
+    /// URL to bech32-encode as an LNURL for wallets without Lightning Address support
+    pub url: String,
+}
+
+/// Public base URL of the LNURL endpoints and the Lightning Address domain
+fn lnurl_endpoint(state: &AppState) -> AppResult<(&str, String)> {
+    let base_url = state
+        .config
+        .lightning
+        .lnurl_base_url
+        .as_deref()
+        .ok_or_else(|| AppError::NotFound("LNURL-pay is not enabled".to_string()))?;
+
+    let domain = reqwest::Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .ok_or_else(|| AppError::Config(format!("Invalid LNURL base URL: {}", base_url)))?;
+
+    Ok((base_url, domain))
+}
+
+async fn find_link(state: &AppState, name: &str) -> AppResult<LnurlPayLink> {
+    let name = name.to_lowercase();
+
+    if !is_valid_lnurl_name(&name) {
+        return Err(AppError::NotFound(format!(
+            "Unknown Lightning Address: {}",
+            name
+        )));
+    }
+
+    LnurlPayLinkRepository::find_active_by_name(&state.db, &name)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Unknown Lightning Address: {}", name)))
+}
+
+/// `GET /.well-known/lnurlp/:name`, the first step of LNURL-pay (LUD-06, LUD-16)
+pub async fn lnurl_pay_request(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<LnurlPayResponse>, LnurlError> {
+    let (base_url, domain) = lnurl_endpoint(&state)?;
+    let link = find_link(&state, &name).await?;
+
+    Ok(Json(LnurlPayResponse {
+        callback: format!("{}/lnurlp/{}/callback", base_url, link.name),
+        max_sendable: link.max_sendable_msat as u64,
+        min_sendable: link.min_sendable_msat as u64,
+        metadata: link.metadata(&domain),
+        tag: "payRequest".to_string(),
+    }))
+}
+
+/// `GET /lnurlp/:name/callback?amount=`, mints an invoice for the requested amount
+pub async fn lnurl_pay_callback(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<LnurlCallbackParams>,
+) -> Result<Json<LnurlInvoiceResponse>, LnurlError> {
+    let (_, domain) = lnurl_endpoint(&state)?;
+    let link = find_link(&state, &name).await?;
+
+    if !link.accepts(params.amount) {
+        return Err(AppError::Validation(format!(
+            "Amount must be between {} and {} msat",
+            link.min_sendable_msat, link.max_sendable_msat
+        ))
+        .into());
+    }
+
+    let result = state
+        .payment_processor
+        .create_lnurl_payment(&state.db, &link, params.amount, &link.metadata(&domain))
+        .await?;
+
+    tracing::info!(
+        payment_id = %result.payment_id,
+        lnurl = %link.name,
+        amount_msat = params.amount,
+        "LNURL-pay invoice created"
+    );
+
+    Ok(Json(LnurlInvoiceResponse {
+        pr: result.lightning_invoice.unwrap_or_default(),
+        routes: Vec::new(),
+    }))
+}
+
+pub async fn create_lnurl_pay_link(
+    State(state): State<AppState>,
+    Json(request): Json<CreateLnurlPayLinkRequest>,
+) -> AppResult<Json<LnurlPayLinkResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    if !is_valid_lnurl_name(&request.name) {
+        return Err(AppError::Validation(
+            "Name may only contain lowercase letters, digits, '-', '_' and '.'".to_string(),
+        ));
+    }
+
+    if request.max_sendable_msat < request.min_sendable_msat {
+        return Err(AppError::Validation(
+            "Maximum amount is below the minimum".to_string(),
+        ));
+    }
+
+    let (base_url, domain) = lnurl_endpoint(&state)?;
+    let link = LnurlPayLinkRepository::create(&state.db, &request).await?;
+
+    Ok(Json(link_response(link, base_url, &domain)))
+}
+
+pub async fn list_lnurl_pay_links(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<LnurlPayLinkResponse>>> {
+    let (base_url, domain) = lnurl_endpoint(&state)?;
+    let links = LnurlPayLinkRepository::list(&state.db).await?;
+
+    Ok(Json(
+        links
+            .into_iter()
+            .map(|link| link_response(link, base_url, &domain))
+            .collect(),
+    ))
+}
+
+fn link_response(link: LnurlPayLink, base_url: &str, domain: &str) -> LnurlPayLinkResponse {
+    LnurlPayLinkResponse {
+        lightning_address: format!("{}@{}", link.name, domain),
+        url: format!("{}/.well-known/lnurlp/{}", base_url, link.name),
+        link,
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LightningPayoutRequest {
+    pub payment_id: Uuid,
+    /// BOLT11 invoice supplied by the recipient
+    #[validate(length(min = 1, max = 4096, message = "Invalid invoice"))]
+    pub invoice: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LightningPayoutResponse {
+    pub success: bool,
+    pub transaction: TransactionResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preimage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `POST /api/v1/lightning/refund`, pays a customer's invoice against their payment
+pub async fn lightning_refund(
+    State(state): State<AppState>,
+    Json(request): Json<LightningPayoutRequest>,
+) -> AppResult<Json<LightningPayoutResponse>> {
+    pay_out(state, request, TransactionType::Refund).await
+}
+
+/// `POST /api/v1/lightning/payout`, pays out funds received by a payment
+pub async fn lightning_payout(
+    State(state): State<AppState>,
+    Json(request): Json<LightningPayoutRequest>,
+) -> AppResult<Json<LightningPayoutResponse>> {
+    pay_out(state, request, TransactionType::Withdrawal).await
+}
+
+async fn pay_out(
+    state: AppState,
+    request: LightningPayoutRequest,
+    tx_type: TransactionType,
+) -> AppResult<Json<LightningPayoutResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    let tx = state
+        .payment_processor
+        .pay_lightning_invoice(&state.db, request.payment_id, &request.invoice, tx_type)
+        .await?;
+
+    Ok(Json(payout_response(tx)))
+}
+
+fn payout_response(tx: Transaction) -> LightningPayoutResponse {
+    let preimage = tx
+        .raw_data
+        .as_ref()
+        .and_then(|data| data["preimage"].as_str())
+        .map(str::to_string);
+
+    LightningPayoutResponse {
+        success: tx.status == TransactionStatus::Confirmed,
+        preimage,
+        fee: tx.fee,
+        error: tx.error_message.clone(),
+        transaction: tx.into(),
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateHoldInvoiceRequest {
+    /// Amount in satoshis
+    #[validate(range(min = 1, message = "Amount must be positive"))]
+    pub amount: i64,
+    #[serde(default)]
+    #[validate(length(max = 255, message = "Description too long"))]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    pub customer_email: Option<String>,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateHoldInvoiceResponse {
+    pub success: bool,
+    pub payment_id: Uuid,
+    pub lightning_invoice: String,
+    pub amount: i64,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HoldInvoiceActionResponse {
+    pub success: bool,
+    pub hold_state: HoldInvoiceState,
+    pub payment: PaymentResponse,
+}
+
+/// `POST /api/v1/lightning/hold-invoices`, an escrowed payment captured or
+/// cancelled later by the merchant
+pub async fn create_hold_invoice(
+    State(state): State<AppState>,
+    Json(request): Json<CreateHoldInvoiceRequest>,
+) -> AppResult<Json<CreateHoldInvoiceResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    let result = state
+        .payment_processor
+        .create_hold_payment(
+            &state.db,
+            &CreatePaymentRequest {
+                amount: request.amount,
+                currency: CurrencyType::BTC,
+                method: PaymentMethod::Lightning,
+                chain: None,
+                description: request.description,
+                customer_email: request.customer_email,
+                customer_phone: None,
+                metadata: request.metadata,
+                callback_url: None,
+            },
+        )
+        .await?;
+
+    tracing::info!(
+        payment_id = %result.payment_id,
+        amount = request.amount,
+        "Lightning hold invoice created"
+    );
+
+    Ok(Json(CreateHoldInvoiceResponse {
+        success: true,
+        payment_id: result.payment_id,
+        lightning_invoice: result.lightning_invoice.unwrap_or_default(),
+        amount: request.amount,
+        expires_at: result.expires_at,
+    }))
+}
+
+/// `POST /api/v1/lightning/hold-invoices/:payment_id/settle`, captures a held payment
+pub async fn settle_hold_invoice(
+    State(state): State<AppState>,
+    Path(payment_id): Path<Uuid>,
+) -> AppResult<Json<HoldInvoiceActionResponse>> {
+    let hold_invoice = state
+        .payment_processor
+        .settle_hold_payment(&state.db, payment_id)
+        .await?;
+
+    // Complete the payment now rather than waiting for the settlement stream;
+    // recording is idempotent, so the watcher seeing it later is harmless
+    let invoice = state
+        .payment_processor
+        .lightning()
+        .lookup_invoice(&hold_invoice.payment_hash)
+        .await?;
+
+    if let Some(invoice) = invoice.filter(|i| matches!(i.status, InvoiceStatus::Settled)) {
+        InvoiceWatcher::new(state.clone())
+            .record_settlement(&invoice)
+            .await?;
+    }
+
+    let payment = PaymentRepository::find_by_id(&state.db, payment_id).await?;
+
+    Ok(Json(HoldInvoiceActionResponse {
+        success: true,
+        hold_state: hold_invoice.state,
+        payment: payment.into(),
+    }))
+}
+
+/// `POST /api/v1/lightning/hold-invoices/:payment_id/cancel`, returns a held payment
+pub async fn cancel_hold_invoice(
+    State(state): State<AppState>,
+    Path(payment_id): Path<Uuid>,
+) -> AppResult<Json<HoldInvoiceActionResponse>> {
+    let payment = state
+        .payment_processor
+        .cancel_hold_payment(&state.db, payment_id)
+        .await?;
+
+    if let Some(ref broadcaster) = state.ws_broadcaster {
+        let _ = broadcaster.broadcast_payment_update(&payment).await;
+    }
+
+    Ok(Json(HoldInvoiceActionResponse {
+        success: true,
+        hold_state: HoldInvoiceState::Cancelled,
+        payment: payment.into(),
+    }))
+}
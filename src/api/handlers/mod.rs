@@ -1,9 +1,25 @@
 pub mod health;
 pub mod razorpay;
 pub mod crypto;
+pub mod customers;
+pub mod lightning;
+pub mod payment_links;
+pub mod payouts;
+pub mod qr_codes;
+pub mod subscriptions;
+pub mod transfers;
+pub mod virtual_accounts;
 pub mod webhooks;
 
 pub use health::*;
 pub use razorpay::*;
 pub use crypto::*;
+pub use customers::*;
+pub use lightning::*;
+pub use payment_links::*;
+pub use payouts::*;
+pub use qr_codes::*;
+pub use subscriptions::*;
+pub use transfers::*;
+pub use virtual_accounts::*;
 pub use webhooks::*;
@@ -0,0 +1,287 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::razorpay::razorpay_currency;
+use crate::db::repositories::{PaymentLinkRepository, PaymentRepository};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreatePaymentRequest, Payment, PaymentLink, PaymentLinkStatus, PaymentMethod, PaymentStatus,
+};
+use crate::services::razorpay::{
+    CreatePaymentLinkRequest, PaymentLinkCustomer, PaymentLinkNotify, RazorpayPaymentLink,
+};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayPaymentLinkRequest {
+    #[validate(range(min = 100, message = "Amount must be at least 100 (1 INR in paise)"))]
+    pub amount: i64,
+    #[validate(length(min = 3, max = 3, message = "Currency must be 3 characters"))]
+    pub currency: String,
+    #[serde(default)]
+    #[validate(length(max = 255, message = "Description too long"))]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[validate(length(max = 255, message = "Customer name too long"))]
+    pub customer_name: Option<String>,
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    pub customer_email: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 10, max = 15, message = "Invalid phone number"))]
+    pub customer_phone: Option<String>,
+    /// Have Razorpay text the link to `customer_phone`
+    #[serde(default)]
+    pub notify_sms: bool,
+    /// Have Razorpay email the link to `customer_email`
+    #[serde(default)]
+    pub notify_email: bool,
+    #[serde(default)]
+    pub expire_by: Option<DateTime<Utc>>,
+    /// Where the customer is redirected after paying
+    #[serde(default)]
+    #[validate(url(message = "Invalid callback URL"))]
+    pub callback_url: Option<String>,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaymentLinkResponse {
+    pub success: bool,
+    pub payment_id: Uuid,
+    pub payment_status: PaymentStatus,
+    pub razorpay_payment_link_id: String,
+    pub short_url: String,
+    pub status: PaymentLinkStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_by: Option<DateTime<Utc>>,
+}
+
+impl PaymentLinkResponse {
+    fn new(payment: &Payment, link: PaymentLink) -> Self {
+        Self {
+            success: true,
+            payment_id: payment.id,
+            payment_status: payment.status.clone(),
+            razorpay_payment_link_id: link.razorpay_payment_link_id,
+            short_url: link.short_url,
+            status: link.status,
+            expire_by: link.expire_by,
+        }
+    }
+}
+
+/// Create a payment collected through a Razorpay Payment Link, which can be
+/// sent to the customer by SMS or email instead of a checkout page
+pub async fn create_payment_link(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayPaymentLinkRequest>,
+) -> AppResult<Json<PaymentLinkResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    let currency = razorpay_currency(&request.currency)?;
+
+    // Razorpay rejects links expiring in less than 15 minutes
+    if request
+        .expire_by
+        .is_some_and(|expire_by| expire_by < Utc::now() + Duration::minutes(15))
+    {
+        return Err(AppError::Validation(
+            "expire_by must be at least 15 minutes in the future".to_string(),
+        ));
+    }
+    if request.notify_sms && request.customer_phone.is_none() {
+        return Err(AppError::Validation(
+            "customer_phone is required to notify by SMS".to_string(),
+        ));
+    }
+    if request.notify_email && request.customer_email.is_none() {
+        return Err(AppError::Validation(
+            "customer_email is required to notify by email".to_string(),
+        ));
+    }
+
+    let payment = PaymentRepository::create(
+        &state.db,
+        &CreatePaymentRequest {
+            amount: request.amount,
+            currency,
+            // The customer picks the method on Razorpay's page
+            method: PaymentMethod::Card,
+            chain: None,
+            description: request.description.clone(),
+            customer_email: request.customer_email.clone(),
+            customer_phone: request.customer_phone.clone(),
+            metadata: request.metadata,
+            callback_url: request.callback_url.clone(),
+        },
+    )
+    .await?;
+
+    let link_request = CreatePaymentLinkRequest {
+        amount: request.amount,
+        currency: request.currency.to_uppercase(),
+        description: request.description,
+        reference_id: Some(payment.id.to_string()),
+        customer: Some(PaymentLinkCustomer {
+            name: request.customer_name,
+            email: request.customer_email,
+            contact: request.customer_phone,
+        }),
+        notify: Some(PaymentLinkNotify {
+            sms: request.notify_sms,
+            email: request.notify_email,
+        }),
+        reminder_enable: Some(request.notify_sms || request.notify_email),
+        expire_by: request.expire_by.map(|expire_by| expire_by.timestamp()),
+        callback_method: request.callback_url.as_ref().map(|_| "get".to_string()),
+        callback_url: request.callback_url,
+        notes: Some(serde_json::json!({ "payment_id": payment.id })),
+    };
+
+    let razorpay_link = match state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_payment_link(&link_request)
+        .await
+    {
+        Ok(link) => link,
+        Err(e) => {
+            PaymentRepository::update_status(&state.db, payment.id, PaymentStatus::Failed).await?;
+            return Err(e);
+        }
+    };
+
+    let link = PaymentLinkRepository::create(
+        &state.db,
+        payment.id,
+        &razorpay_link.id,
+        &razorpay_link.short_url,
+        request.expire_by,
+    )
+    .await?;
+
+    tracing::info!(
+        payment_id = %payment.id,
+        razorpay_payment_link_id = %link.razorpay_payment_link_id,
+        "Razorpay payment link created"
+    );
+
+    Ok(Json(PaymentLinkResponse::new(&payment, link)))
+}
+
+/// Fetch a payment link from Razorpay and bring its payment up to date
+pub async fn get_payment_link(
+    State(state): State<AppState>,
+    Path(payment_id): Path<Uuid>,
+) -> AppResult<Json<PaymentLinkResponse>> {
+    let link = PaymentLinkRepository::find_by_payment_id(&state.db, payment_id).await?;
+
+    let razorpay_link = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .get_payment_link(&link.razorpay_payment_link_id)
+        .await?;
+
+    sync_payment_link(&state, &link, &razorpay_link).await
+}
+
+pub async fn cancel_payment_link(
+    State(state): State<AppState>,
+    Path(payment_id): Path<Uuid>,
+) -> AppResult<Json<PaymentLinkResponse>> {
+    let link = PaymentLinkRepository::find_by_payment_id(&state.db, payment_id).await?;
+
+    if link.status != PaymentLinkStatus::Created {
+        return Err(AppError::Payment(format!(
+            "Payment link cannot be cancelled in current state: {:?}",
+            link.status
+        )));
+    }
+
+    let razorpay_link = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .cancel_payment_link(&link.razorpay_payment_link_id)
+        .await?;
+
+    tracing::info!(payment_id = %payment_id, "Razorpay payment link cancelled");
+
+    sync_payment_link(&state, &link, &razorpay_link).await
+}
+
+async fn sync_payment_link(
+    state: &AppState,
+    link: &PaymentLink,
+    razorpay_link: &RazorpayPaymentLink,
+) -> AppResult<Json<PaymentLinkResponse>> {
+    let status = PaymentLinkStatus::from_razorpay(&razorpay_link.status).ok_or_else(|| {
+        AppError::Razorpay(format!(
+            "Unknown payment link status: {}",
+            razorpay_link.status
+        ))
+    })?;
+
+    // Keep the Razorpay payment for refunds, in case its webhook never arrived
+    let captured = razorpay_link
+        .payments
+        .iter()
+        .flatten()
+        .find(|payment| payment.status == "captured");
+    if let (Some(order_id), Some(captured)) = (&razorpay_link.order_id, captured) {
+        PaymentRepository::update_razorpay_details(
+            &state.db,
+            link.payment_id,
+            order_id,
+            Some(&captured.payment_id),
+            None,
+        )
+        .await?;
+    }
+
+    let (payment, link) = apply_payment_link_status(state, link, status).await?;
+
+    Ok(Json(PaymentLinkResponse::new(&payment, link)))
+}
+
+/// Record a payment link's new status and settle its payment once the link
+/// is paid, expires or is cancelled. A payment that has already left
+/// `pending` is left as is.
+pub(crate) async fn apply_payment_link_status(
+    state: &AppState,
+    link: &PaymentLink,
+    status: PaymentLinkStatus,
+) -> AppResult<(Payment, PaymentLink)> {
+    let link = PaymentLinkRepository::update_status(&state.db, link.payment_id, status).await?;
+    let payment = PaymentRepository::find_by_id(&state.db, link.payment_id).await?;
+
+    let Some(payment_status) = status.payment_status() else {
+        return Ok((payment, link));
+    };
+    if !matches!(
+        payment.status,
+        PaymentStatus::Pending | PaymentStatus::Processing
+    ) {
+        return Ok((payment, link));
+    }
+
+    let payment = PaymentRepository::update_status(&state.db, payment.id, payment_status).await?;
+
+    if let Some(ref broadcaster) = state.ws_broadcaster {
+        let _ = broadcaster.broadcast_payment_update(&payment).await;
+    }
+
+    Ok((payment, link))
+}
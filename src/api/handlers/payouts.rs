@@ -0,0 +1,410 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::db::repositories::{PaymentRepository, TransactionRepository};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CurrencyType, FundAccountDetails, PaymentStatus, PayoutMode, PayoutUpdate, Transaction,
+    TransactionResponse, TransactionStatus, TransactionType,
+};
+use crate::services::razorpay::{
+    CreateContactRequest, CreateFundAccountRequest, CreatePayoutRequest, RazorpayContact,
+    RazorpayFundAccount,
+};
+use crate::AppState;
+
+/// Stored as the `chain` of payout transactions
+const PAYOUT_CHAIN: &str = "razorpayx";
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayContactRequest {
+    #[validate(length(min = 3, max = 50, message = "Invalid contact name length"))]
+    pub name: String,
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    pub email: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 8, max = 15, message = "Invalid phone number"))]
+    pub contact: Option<String>,
+    /// `customer`, `vendor`, `employee` or `self`
+    #[serde(default, rename = "type")]
+    #[validate(length(max = 40, message = "Invalid contact type"))]
+    pub contact_type: Option<String>,
+    /// Our own identifier for the customer or vendor
+    #[serde(default)]
+    #[validate(length(max = 40, message = "Reference ID too long"))]
+    pub reference_id: Option<String>,
+    #[serde(default)]
+    pub notes: Option<serde_json::Value>,
+}
+
+/// Register a customer or vendor that payouts can be sent to
+pub async fn create_contact(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayContactRequest>,
+) -> AppResult<Json<RazorpayContact>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    let contact = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_contact(&CreateContactRequest {
+            name: request.name,
+            email: request.email,
+            contact: request.contact,
+            contact_type: request.contact_type,
+            reference_id: request.reference_id,
+            notes: request.notes,
+        })
+        .await?;
+
+    tracing::info!(contact_id = %contact.id, "RazorpayX contact created");
+
+    Ok(Json(contact))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRazorpayFundAccountRequest {
+    /// Contact ID (`cont_...`)
+    pub contact_id: String,
+    #[serde(flatten)]
+    pub details: FundAccountDetails,
+}
+
+/// Add a bank account or UPI VPA to a contact
+pub async fn create_fund_account(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayFundAccountRequest>,
+) -> AppResult<Json<RazorpayFundAccount>> {
+    if !request.contact_id.starts_with("cont_") {
+        return Err(AppError::Validation(format!(
+            "Invalid contact: {}",
+            request.contact_id
+        )));
+    }
+    request.details.validate().map_err(AppError::Validation)?;
+
+    let fund_account = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_fund_account(&CreateFundAccountRequest {
+            contact_id: request.contact_id,
+            details: request.details,
+        })
+        .await?;
+
+    tracing::info!(
+        fund_account_id = %fund_account.id,
+        contact_id = %fund_account.contact_id,
+        account_type = %fund_account.account_type,
+        "RazorpayX fund account created"
+    );
+
+    Ok(Json(fund_account))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayPayoutRequest {
+    /// Completed INR payment the payout is made against, e.g. the
+    /// cash-on-delivery order being refunded or the sale a vendor is paid for
+    pub payment_id: Uuid,
+    /// Fund account ID (`fa_...`)
+    pub fund_account_id: String,
+    #[validate(range(min = 100, message = "Amount must be at least 100 (1 INR in paise)"))]
+    pub amount: i64,
+    pub mode: PayoutMode,
+    /// e.g. `refund` or `vendor bill`; `payout` when omitted
+    #[serde(default)]
+    #[validate(length(min = 1, max = 40, message = "Invalid purpose"))]
+    pub purpose: Option<String>,
+    /// Shown on the recipient's bank statement
+    #[serde(default)]
+    #[validate(length(max = 30, message = "Narration too long"))]
+    pub narration: Option<String>,
+    #[serde(default)]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PayoutResponse {
+    /// False once the payout has failed, been reversed or been cancelled
+    pub success: bool,
+    pub transaction: TransactionResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub razorpay_payout_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_status: Option<String>,
+    /// Bank reference of the transfer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utr: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Pay part or all of a completed INR payment out to a bank account or UPI
+/// VPA through RazorpayX, recorded as a withdrawal against the payment
+pub async fn create_payout(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayPayoutRequest>,
+) -> AppResult<Json<PayoutResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    let client = state.payment_processor.razorpay().client();
+    let account_number = client.payout_account_number().ok_or_else(|| {
+        AppError::Config(
+            "RazorpayX payouts not configured. Set RAZORPAY_PAYOUT_ACCOUNT_NUMBER in environment."
+                .to_string(),
+        )
+    })?;
+
+    let payment = PaymentRepository::find_by_id(&state.db, request.payment_id).await?;
+
+    if payment.currency != CurrencyType::INR {
+        return Err(AppError::Validation(
+            "Payouts are only supported for INR payments".to_string(),
+        ));
+    }
+    if payment.status != PaymentStatus::Completed {
+        return Err(AppError::Validation(format!(
+            "Payment {} is {:?}, only completed payments can be paid out",
+            payment.id, payment.status
+        )));
+    }
+
+    if !request.fund_account_id.starts_with("fa_") {
+        return Err(AppError::Validation(format!(
+            "Invalid fund account: {}",
+            request.fund_account_id
+        )));
+    }
+    let fund_account = client.get_fund_account(&request.fund_account_id).await?;
+    if !fund_account.active {
+        return Err(AppError::Validation(format!(
+            "Fund account {} is not active",
+            fund_account.id
+        )));
+    }
+    if !request.mode.supports(&fund_account.account_type) {
+        return Err(AppError::Validation(format!(
+            "{:?} payouts cannot be sent to a {} fund account",
+            request.mode, fund_account.account_type
+        )));
+    }
+
+    // Sent as X-Payout-Idempotency and kept as the transaction's hash until
+    // Razorpay assigns the payout its ID
+    let idempotency_key = Uuid::new_v4().to_string();
+
+    let tx = TransactionRepository::create_outgoing(
+        &state.db,
+        payment.id,
+        TransactionType::Withdrawal,
+        request.amount,
+        &payment.currency.to_string(),
+        PAYOUT_CHAIN,
+        &idempotency_key,
+    )
+    .await?;
+
+    let payout_request = CreatePayoutRequest {
+        account_number: account_number.to_string(),
+        fund_account_id: fund_account.id.clone(),
+        amount: request.amount,
+        currency: "INR".to_string(),
+        mode: request.mode,
+        purpose: request.purpose.unwrap_or_else(|| "payout".to_string()),
+        queue_if_low_balance: true,
+        reference_id: tx.id.to_string(),
+        narration: request.narration,
+        notes: request.notes,
+    };
+
+    TransactionRepository::update_raw_data(
+        &state.db,
+        tx.id,
+        serde_json::json!({
+            "idempotency_key": idempotency_key,
+            "request": payout_request,
+        }),
+    )
+    .await?;
+
+    let payout = match client
+        .create_payout(&payout_request, &idempotency_key)
+        .await
+    {
+        Ok(payout) => payout,
+        Err(e @ AppError::RazorpayRejected(_)) => return fail_payout(&state, &tx, &e).await,
+        // Anything else, e.g. a lost connection, a 5xx or an answer that does
+        // not parse, may come after Razorpay created the payout; keep the
+        // amount reserved until it is reconciled
+        Err(e) => {
+            tracing::warn!(tx_id = %tx.id, "RazorpayX payout outcome unknown: {}", e);
+            TransactionRepository::update_status(
+                &state.db,
+                tx.id,
+                TransactionStatus::Pending,
+                Some(&format!("Outcome unknown: {}", e)),
+            )
+            .await?;
+            return Err(AppError::Razorpay(format!(
+                "RazorpayX payout outcome unknown, transaction {} is pending",
+                tx.id
+            )));
+        }
+    };
+
+    let tx = apply_payout(&state, &tx, &payout.to_update()?).await?;
+
+    tracing::info!(
+        tx_id = %tx.id,
+        payment_id = %payment.id,
+        razorpay_payout_id = %payout.id,
+        status = %payout.status,
+        "RazorpayX payout created"
+    );
+
+    Ok(Json(payout_response(tx)))
+}
+
+/// Get a payout after reconciling it with RazorpayX. A payout whose creation
+/// never got an answer is requested again with its idempotency key, which
+/// returns the payout if Razorpay did create it.
+pub async fn get_payout(
+    State(state): State<AppState>,
+    Path(transaction_id): Path<Uuid>,
+) -> AppResult<Json<PayoutResponse>> {
+    let tx = TransactionRepository::find_by_id(&state.db, transaction_id).await?;
+
+    if tx.tx_type != TransactionType::Withdrawal || tx.chain.as_deref() != Some(PAYOUT_CHAIN) {
+        return Err(AppError::NotFound(format!(
+            "Payout {} not found",
+            transaction_id
+        )));
+    }
+
+    let client = state.payment_processor.razorpay().client();
+
+    let payout = match tx.tx_hash.as_deref() {
+        Some(payout_id) if payout_id.starts_with("pout_") => {
+            Some(client.get_payout(payout_id).await?)
+        }
+        _ if tx.status == TransactionStatus::Pending => {
+            let raw_data = tx.raw_data.clone().unwrap_or_default();
+            match (
+                raw_data["idempotency_key"].as_str(),
+                serde_json::from_value::<CreatePayoutRequest>(raw_data["request"].clone()),
+            ) {
+                (Some(idempotency_key), Ok(payout_request)) => {
+                    match client.create_payout(&payout_request, idempotency_key).await {
+                        Ok(payout) => Some(payout),
+                        // No payout was made under the key
+                        Err(e @ AppError::RazorpayRejected(_)) => {
+                            return fail_payout(&state, &tx, &e).await
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    let tx = match payout {
+        Some(payout) => apply_payout(&state, &tx, &payout.to_update()?).await?,
+        None => tx,
+    };
+
+    Ok(Json(payout_response(tx)))
+}
+
+/// Fail a payout Razorpay refused, releasing its amount
+async fn fail_payout(
+    state: &AppState,
+    tx: &Transaction,
+    error: &AppError,
+) -> AppResult<Json<PayoutResponse>> {
+    tracing::warn!(tx_id = %tx.id, "RazorpayX payout failed: {}", error);
+
+    let tx = TransactionRepository::update_status(
+        &state.db,
+        tx.id,
+        TransactionStatus::Failed,
+        Some(&error.to_string()),
+    )
+    .await?;
+
+    Ok(Json(payout_response(tx)))
+}
+
+/// Bring a payout's withdrawal transaction to the state Razorpay reports
+pub(crate) async fn apply_payout(
+    state: &AppState,
+    tx: &Transaction,
+    update: &PayoutUpdate,
+) -> AppResult<Transaction> {
+    let (updated, applied) = TransactionRepository::apply_payout(&state.db, tx.id, update).await?;
+
+    if !applied {
+        tracing::info!(
+            tx_id = %tx.id,
+            status = ?updated.status,
+            payout_status = %update.razorpay_status,
+            "Ignoring stale payout update"
+        );
+    } else if updated.status != tx.status {
+        match updated.status {
+            TransactionStatus::Failed | TransactionStatus::Cancelled => tracing::warn!(
+                tx_id = %tx.id,
+                razorpay_payout_id = %update.razorpay_payout_id,
+                payout_status = %update.razorpay_status,
+                reason = ?update.failure_reason,
+                "Payout did not go through, amount released"
+            ),
+            _ => tracing::info!(
+                tx_id = %tx.id,
+                razorpay_payout_id = %update.razorpay_payout_id,
+                payout_status = %update.razorpay_status,
+                "Payout status updated"
+            ),
+        }
+    }
+
+    Ok(updated)
+}
+
+fn payout_response(tx: Transaction) -> PayoutResponse {
+    let raw_data = tx.raw_data.as_ref();
+    let field = |name: &str| {
+        raw_data
+            .and_then(|data| data[name].as_str())
+            .map(str::to_string)
+    };
+
+    PayoutResponse {
+        success: !matches!(
+            tx.status,
+            TransactionStatus::Failed | TransactionStatus::Cancelled
+        ),
+        razorpay_payout_id: tx.tx_hash.clone().filter(|id| id.starts_with("pout_")),
+        payout_status: field("payout_status"),
+        utr: field("utr"),
+        fee: tx.fee,
+        error: tx.error_message.clone(),
+        transaction: tx.into(),
+    }
+}
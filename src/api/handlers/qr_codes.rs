@@ -0,0 +1,373 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::db::repositories::{CustomerRepository, PaymentRepository, QrCodeRepository};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    qr_code_closed_payment_status, CreatePaymentRequest, CurrencyType, NewQrCode, Payment,
+    PaymentMethod, PaymentStatus, QrCode, QrCodeCredit, QrCodeStatus,
+};
+use crate::services::razorpay::{CreateQrCodeRequest, RazorpayQrCode};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayQrCodeRequest {
+    /// Amount in paise every payment must be. Required for a single-use QR
+    /// code; a multi-use QR code without it takes any amount.
+    #[serde(default)]
+    #[validate(range(min = 100, message = "Amount must be at least 100 (1 INR in paise)"))]
+    pub amount: Option<i64>,
+    /// Take any number of payments, each recorded as a payment of its own,
+    /// instead of the one payment created with the QR code
+    #[serde(default)]
+    pub multiple_use: bool,
+    /// Shown to the customer in their UPI app, e.g. the store name
+    #[serde(default)]
+    #[validate(length(max = 255, message = "Name too long"))]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[validate(length(max = 255, message = "Description too long"))]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub customer_id: Option<Uuid>,
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    pub customer_email: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 10, max = 15, message = "Invalid phone number"))]
+    pub customer_phone: Option<String>,
+    /// When Razorpay closes the QR code
+    #[serde(default)]
+    pub close_by: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QrCodeResponse {
+    pub success: bool,
+    #[serde(flatten)]
+    pub qr_code: QrCode,
+    /// Status of the payment a single-use QR code collects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_status: Option<PaymentStatus>,
+    pub credits: Vec<QrCodeCredit>,
+}
+
+impl QrCodeResponse {
+    async fn load(state: &AppState, qr_code: QrCode) -> AppResult<Self> {
+        let payment_status = match qr_code.payment_id {
+            Some(payment_id) => {
+                Some(PaymentRepository::find_by_id(&state.db, payment_id).await?.status)
+            }
+            None => None,
+        };
+        let credits = QrCodeRepository::find_credits(&state.db, qr_code.id).await?;
+
+        Ok(Self {
+            success: true,
+            qr_code,
+            payment_status,
+            credits,
+        })
+    }
+}
+
+/// Create a UPI QR code, e.g. shown on a store counter's screen. A single-use
+/// QR code collects a payment created with it for its exact amount; a
+/// multi-use QR code records every payment it receives.
+pub async fn create_qr_code(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayQrCodeRequest>,
+) -> AppResult<Json<QrCodeResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    if !request.multiple_use && request.amount.is_none() {
+        return Err(AppError::Validation(
+            "amount is required for a single-use QR code".to_string(),
+        ));
+    }
+    // Razorpay rejects QR codes closing in less than 2 minutes
+    if request
+        .close_by
+        .is_some_and(|close_by| close_by < Utc::now() + Duration::minutes(2))
+    {
+        return Err(AppError::Validation(
+            "close_by must be at least 2 minutes in the future".to_string(),
+        ));
+    }
+
+    let customer = match request.customer_id {
+        Some(customer_id) => Some(CustomerRepository::find_by_id(&state.db, customer_id).await?),
+        None => None,
+    };
+
+    let payment = match request.amount.filter(|_| !request.multiple_use) {
+        Some(amount) => Some(
+            PaymentRepository::create(
+                &state.db,
+                &CreatePaymentRequest {
+                    amount,
+                    currency: CurrencyType::INR,
+                    method: PaymentMethod::Upi,
+                    chain: None,
+                    description: request.description.clone(),
+                    customer_email: request
+                        .customer_email
+                        .or_else(|| customer.as_ref().and_then(|c| c.email.clone())),
+                    customer_phone: request
+                        .customer_phone
+                        .or_else(|| customer.as_ref().and_then(|c| c.contact.clone())),
+                    metadata: request.metadata,
+                    callback_url: None,
+                },
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    let usage = match payment {
+        Some(ref payment) => CreateQrCodeRequest::single_use(payment.amount),
+        None => CreateQrCodeRequest::multiple_use(request.amount),
+    };
+    let qr_request = CreateQrCodeRequest {
+        name: request.name,
+        description: request.description,
+        customer_id: customer.as_ref().map(|c| c.razorpay_customer_id.clone()),
+        close_by: request.close_by.map(|close_by| close_by.timestamp()),
+        notes: Some(serde_json::json!({
+            "payment_id": payment.as_ref().map(|p| p.id),
+            "customer_id": customer.as_ref().map(|c| c.id),
+        })),
+        ..usage
+    };
+
+    let razorpay_qr_code = match state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_qr_code(&qr_request)
+        .await
+    {
+        Ok(qr_code) => qr_code,
+        Err(e) => {
+            if let Some(ref payment) = payment {
+                PaymentRepository::update_status(&state.db, payment.id, PaymentStatus::Failed)
+                    .await?;
+            }
+            return Err(e);
+        }
+    };
+
+    let qr_code = QrCodeRepository::create(
+        &state.db,
+        &NewQrCode {
+            razorpay_qr_code_id: razorpay_qr_code.id,
+            payment_id: payment.as_ref().map(|p| p.id),
+            customer_id: customer.as_ref().map(|c| c.id),
+            multiple_use: request.multiple_use,
+            payment_amount: request.amount,
+            image_url: razorpay_qr_code.image_url,
+            close_by: request.close_by,
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        qr_code_id = %qr_code.id,
+        razorpay_qr_code_id = %qr_code.razorpay_qr_code_id,
+        payment_id = ?qr_code.payment_id,
+        "Razorpay QR code created"
+    );
+
+    Ok(Json(QrCodeResponse::load(&state, qr_code).await?))
+}
+
+/// Fetch a QR code from Razorpay and bring its payments up to date
+pub async fn get_qr_code(
+    State(state): State<AppState>,
+    Path(qr_code_id): Path<Uuid>,
+) -> AppResult<Json<QrCodeResponse>> {
+    let qr_code = QrCodeRepository::find_by_id(&state.db, qr_code_id).await?;
+
+    let razorpay_qr_code = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .get_qr_code(&qr_code.razorpay_qr_code_id)
+        .await?;
+
+    sync_qr_code(&state, qr_code, &razorpay_qr_code).await
+}
+
+pub async fn close_qr_code(
+    State(state): State<AppState>,
+    Path(qr_code_id): Path<Uuid>,
+) -> AppResult<Json<QrCodeResponse>> {
+    let qr_code = QrCodeRepository::find_by_id(&state.db, qr_code_id).await?;
+
+    if qr_code.status != QrCodeStatus::Active {
+        return Err(AppError::Payment("QR code is already closed".to_string()));
+    }
+
+    let razorpay_qr_code = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .close_qr_code(&qr_code.razorpay_qr_code_id)
+        .await?;
+
+    tracing::info!(qr_code_id = %qr_code.id, "Razorpay QR code closed");
+
+    sync_qr_code(&state, qr_code, &razorpay_qr_code).await
+}
+
+async fn sync_qr_code(
+    state: &AppState,
+    qr_code: QrCode,
+    razorpay_qr_code: &RazorpayQrCode,
+) -> AppResult<Json<QrCodeResponse>> {
+    // Pick up payments whose webhook never arrived
+    if razorpay_qr_code.payments_count_received > 0 {
+        let recorded: HashSet<String> = QrCodeRepository::find_credits(&state.db, qr_code.id)
+            .await?
+            .into_iter()
+            .map(|credit| credit.razorpay_payment_id)
+            .collect();
+
+        let payments = state
+            .payment_processor
+            .razorpay()
+            .client()
+            .get_qr_code_payments(&razorpay_qr_code.id)
+            .await?;
+
+        for captured in payments
+            .items
+            .iter()
+            .filter(|p| p.status == "captured" && !recorded.contains(&p.id))
+        {
+            apply_qr_code_credit(state, &qr_code, &captured.id, captured.amount).await?;
+        }
+    }
+
+    let qr_code = match QrCodeStatus::from_razorpay(&razorpay_qr_code.status) {
+        Some(QrCodeStatus::Closed) => {
+            let closed_at = razorpay_qr_code
+                .closed_at
+                .and_then(|t| DateTime::from_timestamp(t, 0));
+            apply_qr_code_closed(
+                state,
+                &qr_code,
+                razorpay_qr_code.close_reason.as_deref(),
+                closed_at,
+            )
+            .await?
+        }
+        Some(QrCodeStatus::Active) => qr_code,
+        None => {
+            return Err(AppError::Razorpay(format!(
+                "Unknown QR code status: {}",
+                razorpay_qr_code.status
+            )))
+        }
+    };
+
+    Ok(Json(QrCodeResponse::load(state, qr_code).await?))
+}
+
+/// Record a payment to a QR code, completing the payment it collects or, for
+/// a multi-use QR code, recording a completed payment of its own
+pub(crate) async fn apply_qr_code_credit(
+    state: &AppState,
+    qr_code: &QrCode,
+    razorpay_payment_id: &str,
+    amount: i64,
+) -> AppResult<Payment> {
+    let customer = match qr_code.customer_id {
+        Some(customer_id) => Some(CustomerRepository::find_by_id(&state.db, customer_id).await?),
+        None => None,
+    };
+
+    let (payment, recorded) = QrCodeRepository::record_credit(
+        &state.db,
+        qr_code,
+        razorpay_payment_id,
+        amount,
+        customer.as_ref(),
+    )
+    .await?;
+
+    if !recorded {
+        return Ok(payment);
+    }
+
+    if payment.status == PaymentStatus::Completed {
+        tracing::info!(
+            qr_code_id = %qr_code.id,
+            payment_id = %payment.id,
+            razorpay_payment_id = %razorpay_payment_id,
+            "QR code payment completed"
+        );
+    } else {
+        tracing::warn!(
+            qr_code_id = %qr_code.id,
+            payment_id = %payment.id,
+            amount,
+            expected = ?qr_code.payment_amount,
+            "QR code credited without completing its payment"
+        );
+    }
+
+    if let Some(ref broadcaster) = state.ws_broadcaster {
+        let _ = broadcaster.broadcast_payment_update(&payment).await;
+    }
+
+    Ok(payment)
+}
+
+/// Close a QR code, expiring or cancelling the payment of a single-use QR code
+/// if it was never paid
+pub(crate) async fn apply_qr_code_closed(
+    state: &AppState,
+    qr_code: &QrCode,
+    close_reason: Option<&str>,
+    closed_at: Option<DateTime<Utc>>,
+) -> AppResult<QrCode> {
+    let qr_code =
+        QrCodeRepository::mark_closed(&state.db, qr_code.id, close_reason, closed_at).await?;
+
+    let Some(payment_id) = qr_code.payment_id else {
+        return Ok(qr_code);
+    };
+    let Some(payment_status) = qr_code_closed_payment_status(qr_code.close_reason.as_deref())
+    else {
+        return Ok(qr_code);
+    };
+    let payment = PaymentRepository::find_by_id(&state.db, payment_id).await?;
+    if !matches!(
+        payment.status,
+        PaymentStatus::Pending | PaymentStatus::Processing
+    ) {
+        return Ok(qr_code);
+    }
+
+    let payment = PaymentRepository::update_status(&state.db, payment.id, payment_status).await?;
+
+    if let Some(ref broadcaster) = state.ws_broadcaster {
+        let _ = broadcaster.broadcast_payment_update(&payment).await;
+    }
+
+    Ok(qr_code)
+}
@@ -1,12 +1,14 @@
-use axum::{extract::State, Json};
+use axum::{extract::State, Extension, Json};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::db::repositories::PaymentRepository;
+use crate::api::middleware::ApiKeyType;
+use crate::db::repositories::{CustomerRepository, PaymentRepository};
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    CreatePaymentRequest, CurrencyType, PaymentMethod, PaymentResponse, PaymentStatus,
+    validate_transfer_splits, CreatePaymentRequest, CurrencyType, PaymentMethod, PaymentResponse,
+    PaymentStatus, TransferSplit,
 };
 use crate::services::razorpay::RazorpayWebhookVerifier;
 use crate::AppState;
@@ -30,6 +32,12 @@ pub struct CreateRazorpayOrderRequest {
     pub metadata: Option<serde_json::Value>,
     #[serde(default)]
     pub method: Option<String>,
+    /// Razorpay Route split among linked accounts, made once the payment is captured
+    #[serde(default)]
+    pub transfers: Option<Vec<TransferSplit>>,
+    /// Our customer, whose cards saved at Razorpay checkout can be reused
+    #[serde(default)]
+    pub customer_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize)]
@@ -40,10 +48,14 @@ pub struct CreateOrderResponse {
     pub razorpay_key_id: String,
     pub amount: i64,
     pub currency: String,
+    /// Passed to checkout as `customer_id` to offer and save cards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub razorpay_customer_id: Option<String>,
 }
 
 pub async fn create_order(
     State(state): State<AppState>,
+    key_type: Option<Extension<ApiKeyType>>,
     Json(request): Json<CreateRazorpayOrderRequest>,
 ) -> AppResult<Json<CreateOrderResponse>> {
     // Validate request
@@ -57,14 +69,16 @@ pub async fn create_order(
     }
 
     // Validate currency
-    let currency = match request.currency.to_uppercase().as_str() {
-        "INR" => CurrencyType::INR,
-        "USD" => CurrencyType::USD,
-        "EUR" => CurrencyType::EUR,
-        _ => return Err(AppError::Validation(
-            "Unsupported currency. Supported: INR, USD, EUR".to_string()
-        )),
-    };
+    let currency = razorpay_currency(&request.currency)?;
+
+    if let Some(ref transfers) = request.transfers {
+        if currency != CurrencyType::INR {
+            return Err(AppError::Validation(
+                "Transfers are only supported for INR payments".to_string()
+            ));
+        }
+        validate_transfer_splits(request.amount, 0, transfers).map_err(AppError::Validation)?;
+    }
 
     // Determine payment method
     let method = match request.method.as_deref() {
@@ -79,20 +93,43 @@ pub async fn create_order(
         None => PaymentMethod::Card,
     };
 
+    let customer = match request.customer_id {
+        // A customer's order lets checkout charge their saved cards, so only
+        // the merchant's server may pick the customer
+        Some(_) if !key_type.is_some_and(|Extension(key_type)| key_type.is_secret()) => {
+            return Err(AppError::Unauthorized(
+                "customer_id requires a secret key (sk_*)".to_string(),
+            ));
+        }
+        Some(customer_id) => Some(CustomerRepository::find_by_id(&state.db, customer_id).await?),
+        None => None,
+    };
+
     let payment_request = CreatePaymentRequest {
         amount: request.amount,
         currency,
         method,
+        chain: None,
         description: request.description,
-        customer_email: request.customer_email,
-        customer_phone: request.customer_phone,
+        customer_email: request
+            .customer_email
+            .or_else(|| customer.as_ref().and_then(|c| c.email.clone())),
+        customer_phone: request
+            .customer_phone
+            .or_else(|| customer.as_ref().and_then(|c| c.contact.clone())),
         metadata: request.metadata,
         callback_url: None,
     };
+    let razorpay_customer_id = customer.map(|c| c.razorpay_customer_id);
 
     let result = state
         .payment_processor
-        .create_payment(&state.db, &payment_request)
+        .create_razorpay_order(
+            &state.db,
+            &payment_request,
+            request.transfers.as_deref().unwrap_or_default(),
+            razorpay_customer_id.as_deref(),
+        )
         .await?;
 
     tracing::info!(
@@ -110,9 +147,17 @@ pub async fn create_order(
         razorpay_key_id: state.config.razorpay.key_id.clone(),
         amount: request.amount,
         currency: request.currency,
+        razorpay_customer_id,
     }))
 }
 
+/// Currencies Razorpay settles in
+pub(crate) fn razorpay_currency(currency: &str) -> AppResult<CurrencyType> {
+    CurrencyType::from_razorpay(&currency.to_uppercase()).ok_or_else(|| {
+        AppError::Validation("Unsupported currency. Supported: INR, USD, EUR".to_string())
+    })
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct VerifyPaymentRequest {
     #[validate(length(min = 10, max = 50, message = "Invalid order ID format"))]
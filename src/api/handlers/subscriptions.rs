@@ -0,0 +1,401 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::razorpay::razorpay_currency;
+use crate::db::repositories::SubscriptionRepository;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    NewSubscription, PaymentMethod, Subscription, SubscriptionCharge,
+    SubscriptionResponse, SubscriptionStatus,
+};
+use crate::services::razorpay::{
+    CreatePlanRequest, CreateSubscriptionRequest, PlanItem, RazorpayPlan, RazorpaySubscription,
+    RazorpayWebhookVerifier,
+};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayPlanRequest {
+    #[validate(length(min = 1, max = 255, message = "Invalid plan name length"))]
+    pub name: String,
+    #[validate(range(min = 100, message = "Amount must be at least 100 (1 INR in paise)"))]
+    pub amount: i64,
+    #[validate(length(min = 3, max = 3, message = "Currency must be 3 characters"))]
+    pub currency: String,
+    /// `daily`, `weekly`, `monthly` or `yearly`
+    pub period: String,
+    #[validate(range(min = 1, message = "Interval must be at least 1"))]
+    pub interval: i32,
+    #[serde(default)]
+    #[validate(length(max = 255, message = "Description too long"))]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub notes: Option<serde_json::Value>,
+}
+
+pub async fn create_plan(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayPlanRequest>,
+) -> AppResult<Json<RazorpayPlan>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    if !matches!(
+        request.period.as_str(),
+        "daily" | "weekly" | "monthly" | "yearly"
+    ) {
+        return Err(AppError::Validation(
+            "Invalid period. Supported: daily, weekly, monthly, yearly".to_string(),
+        ));
+    }
+
+    razorpay_currency(&request.currency)?;
+
+    let plan_request = CreatePlanRequest {
+        period: request.period,
+        interval: request.interval,
+        item: PlanItem {
+            name: request.name,
+            amount: request.amount,
+            currency: request.currency.to_uppercase(),
+            description: request.description,
+        },
+        notes: request.notes,
+    };
+
+    let plan = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_plan(&plan_request)
+        .await?;
+
+    tracing::info!(plan_id = %plan.id, "Razorpay plan created");
+
+    Ok(Json(plan))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpaySubscriptionRequest {
+    #[validate(length(min = 10, max = 50, message = "Invalid plan ID format"))]
+    pub plan_id: String,
+    /// Number of billing cycles to charge
+    #[validate(range(min = 1, message = "Total count must be at least 1"))]
+    pub total_count: i32,
+    #[serde(default)]
+    #[validate(range(min = 1, message = "Quantity must be at least 1"))]
+    pub quantity: Option<i32>,
+    /// First charge; the customer is charged on checkout when omitted
+    #[serde(default)]
+    pub start_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    pub customer_email: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 10, max = 15, message = "Invalid phone number"))]
+    pub customer_phone: Option<String>,
+    /// Let Razorpay send the customer emails and SMS about the subscription
+    #[serde(default)]
+    pub customer_notify: Option<bool>,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateSubscriptionResponse {
+    pub success: bool,
+    pub subscription_id: Uuid,
+    pub razorpay_subscription_id: String,
+    pub razorpay_key_id: String,
+    pub status: SubscriptionStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_url: Option<String>,
+}
+
+pub async fn create_subscription(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpaySubscriptionRequest>,
+) -> AppResult<Json<CreateSubscriptionResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    if request
+        .start_at
+        .is_some_and(|start_at| start_at <= Utc::now())
+    {
+        return Err(AppError::Validation(
+            "start_at must be in the future".to_string(),
+        ));
+    }
+
+    let client = state.payment_processor.razorpay().client();
+
+    let plan = client.get_plan(&request.plan_id).await?;
+    let currency = razorpay_currency(&plan.item.currency)?;
+    let quantity = request.quantity.unwrap_or(1);
+    let amount = plan
+        .item
+        .amount
+        .checked_mul(quantity as i64)
+        .ok_or_else(|| AppError::Validation("Quantity too large".to_string()))?;
+
+    let subscription_request = CreateSubscriptionRequest {
+        plan_id: plan.id.clone(),
+        total_count: request.total_count,
+        quantity: request.quantity,
+        customer_notify: request.customer_notify,
+        start_at: request.start_at.map(|start_at| start_at.timestamp()),
+        notes: request.metadata.clone(),
+    };
+
+    let remote = client.create_subscription(&subscription_request).await?;
+
+    let subscription = SubscriptionRepository::create(
+        &state.db,
+        &NewSubscription {
+            razorpay_subscription_id: remote.id.clone(),
+            razorpay_plan_id: plan.id,
+            status: remote.to_update()?.status,
+            amount,
+            currency,
+            quantity,
+            total_count: request.total_count,
+            customer_email: request.customer_email,
+            customer_phone: request.customer_phone,
+            short_url: remote.short_url,
+            metadata: request.metadata,
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        subscription_id = %subscription.id,
+        razorpay_subscription_id = %subscription.razorpay_subscription_id,
+        "Razorpay subscription created"
+    );
+
+    Ok(Json(CreateSubscriptionResponse {
+        success: true,
+        subscription_id: subscription.id,
+        razorpay_subscription_id: subscription.razorpay_subscription_id,
+        razorpay_key_id: state.config.razorpay.key_id.clone(),
+        status: subscription.status,
+        short_url: subscription.short_url,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubscriptionDetailsResponse {
+    #[serde(flatten)]
+    pub subscription: SubscriptionResponse,
+    pub charges: Vec<SubscriptionCharge>,
+}
+
+pub async fn get_subscription(
+    State(state): State<AppState>,
+    Path(subscription_id): Path<Uuid>,
+) -> AppResult<Json<SubscriptionDetailsResponse>> {
+    let subscription = SubscriptionRepository::find_by_id(&state.db, subscription_id).await?;
+    let charges = SubscriptionRepository::find_charges(&state.db, subscription_id).await?;
+
+    Ok(Json(SubscriptionDetailsResponse {
+        subscription: subscription.into(),
+        charges,
+    }))
+}
+
+pub async fn pause_subscription(
+    State(state): State<AppState>,
+    Path(subscription_id): Path<Uuid>,
+) -> AppResult<Json<SubscriptionResponse>> {
+    let subscription = find_live_subscription(&state, subscription_id).await?;
+
+    let remote = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .pause_subscription(&subscription.razorpay_subscription_id)
+        .await?;
+
+    apply_remote_state(&state, &subscription, &remote).await
+}
+
+pub async fn resume_subscription(
+    State(state): State<AppState>,
+    Path(subscription_id): Path<Uuid>,
+) -> AppResult<Json<SubscriptionResponse>> {
+    let subscription = find_live_subscription(&state, subscription_id).await?;
+
+    let remote = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .resume_subscription(&subscription.razorpay_subscription_id)
+        .await?;
+
+    apply_remote_state(&state, &subscription, &remote).await
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CancelSubscriptionRequest {
+    /// Keep the subscription until the end of the current billing cycle
+    #[serde(default)]
+    pub cancel_at_cycle_end: bool,
+}
+
+pub async fn cancel_subscription(
+    State(state): State<AppState>,
+    Path(subscription_id): Path<Uuid>,
+    request: Option<Json<CancelSubscriptionRequest>>,
+) -> AppResult<Json<SubscriptionResponse>> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let subscription = find_live_subscription(&state, subscription_id).await?;
+
+    let remote = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .cancel_subscription(
+            &subscription.razorpay_subscription_id,
+            request.cancel_at_cycle_end,
+        )
+        .await?;
+
+    apply_remote_state(&state, &subscription, &remote).await
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifySubscriptionRequest {
+    #[validate(length(min = 10, max = 50, message = "Invalid payment ID format"))]
+    pub razorpay_payment_id: String,
+    #[validate(length(min = 10, max = 50, message = "Invalid subscription ID format"))]
+    pub razorpay_subscription_id: String,
+    #[validate(length(min = 64, max = 128, message = "Invalid signature format"))]
+    pub razorpay_signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifySubscriptionResponse {
+    pub success: bool,
+    pub subscription_id: Uuid,
+    pub status: SubscriptionStatus,
+    /// The payment recording the first charge, unless it is deferred to `start_at`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<Uuid>,
+}
+
+/// Verify the checkout that authenticates a subscription
+pub async fn verify_subscription(
+    State(state): State<AppState>,
+    Json(request): Json<VerifySubscriptionRequest>,
+) -> AppResult<Json<VerifySubscriptionResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    RazorpayWebhookVerifier::verify_subscription_signature(
+        &request.razorpay_payment_id,
+        &request.razorpay_subscription_id,
+        &request.razorpay_signature,
+        &state.config.razorpay.key_secret,
+    )?;
+
+    let subscription =
+        SubscriptionRepository::find_by_razorpay_id(&state.db, &request.razorpay_subscription_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Subscription not found".to_string()))?;
+
+    let client = state.payment_processor.razorpay().client();
+    let remote = client
+        .get_subscription(&subscription.razorpay_subscription_id)
+        .await?;
+    let subscription =
+        SubscriptionRepository::apply_update(&state.db, subscription.id, &remote.to_update()?)
+            .await?;
+
+    // With a future start_at the checkout only authorizes the mandate, and
+    // that authorization payment is refunded rather than kept as a charge
+    let mut payment_id = None;
+    if remote.paid_count > 0 {
+        let razorpay_payment = client.get_payment(&request.razorpay_payment_id).await?;
+
+        if razorpay_payment.status == "captured" {
+            let (payment, recorded) = SubscriptionRepository::record_charge(
+                &state.db,
+                &subscription,
+                &razorpay_payment.id,
+                razorpay_payment.order_id.as_deref(),
+                razorpay_payment.amount,
+                razorpay_payment
+                    .method
+                    .as_deref()
+                    .and_then(PaymentMethod::from_razorpay)
+                    .unwrap_or(PaymentMethod::Card),
+            )
+            .await?;
+
+            if recorded {
+                if let Some(ref broadcaster) = state.ws_broadcaster {
+                    let _ = broadcaster.broadcast_payment_update(&payment).await;
+                }
+            }
+
+            payment_id = Some(payment.id);
+        }
+    }
+
+    tracing::info!(
+        subscription_id = %subscription.id,
+        razorpay_payment_id = %request.razorpay_payment_id,
+        "Subscription checkout verified"
+    );
+
+    Ok(Json(VerifySubscriptionResponse {
+        success: true,
+        subscription_id: subscription.id,
+        status: subscription.status,
+        payment_id,
+    }))
+}
+
+async fn find_live_subscription(
+    state: &AppState,
+    subscription_id: Uuid,
+) -> AppResult<Subscription> {
+    let subscription = SubscriptionRepository::find_by_id(&state.db, subscription_id).await?;
+
+    if subscription.status.is_terminal() {
+        return Err(AppError::Payment(format!(
+            "Subscription cannot be changed in current state: {:?}",
+            subscription.status
+        )));
+    }
+
+    Ok(subscription)
+}
+
+async fn apply_remote_state(
+    state: &AppState,
+    subscription: &Subscription,
+    remote: &RazorpaySubscription,
+) -> AppResult<Json<SubscriptionResponse>> {
+    let updated =
+        SubscriptionRepository::apply_update(&state.db, subscription.id, &remote.to_update()?)
+            .await?;
+
+    tracing::info!(
+        subscription_id = %updated.id,
+        status = ?updated.status,
+        "Subscription updated"
+    );
+
+    Ok(Json(updated.into()))
+}
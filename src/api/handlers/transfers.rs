@@ -0,0 +1,417 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::db::repositories::{PaymentRepository, TransferRepository};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    validate_transfer_splits, CurrencyType, Payment, PaymentStatus, Transfer, TransferSplit,
+    TransferStatus,
+};
+use crate::services::razorpay::{
+    CreateLinkedAccountRequest, PaymentTransfersRequest, RazorpayLinkedAccount, ReversalRequest,
+    TransferHoldRequest, TransferRequest,
+};
+use crate::AppState;
+
+/// Route only moves INR
+const TRANSFER_CURRENCY: &str = "INR";
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayLinkedAccountRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+    #[validate(length(min = 8, max = 15, message = "Invalid phone number"))]
+    pub phone: String,
+    #[validate(length(min = 1, max = 200, message = "Invalid business name length"))]
+    pub legal_business_name: String,
+    /// e.g. `individual`, `proprietorship`, `private_limited`
+    #[validate(length(min = 1, max = 50, message = "Invalid business type"))]
+    pub business_type: String,
+    #[validate(length(min = 1, max = 255, message = "Invalid contact name length"))]
+    pub contact_name: String,
+    /// Our own identifier for the seller
+    #[serde(default)]
+    #[validate(length(max = 512, message = "Reference ID too long"))]
+    pub reference_id: Option<String>,
+    /// Business category and addresses, as Razorpay expects them
+    #[serde(default)]
+    pub profile: Option<serde_json::Value>,
+    /// PAN and GST details
+    #[serde(default)]
+    pub legal_info: Option<serde_json::Value>,
+    #[serde(default)]
+    pub notes: Option<serde_json::Value>,
+}
+
+/// Onboard a seller as a Route linked account
+pub async fn create_linked_account(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayLinkedAccountRequest>,
+) -> AppResult<Json<RazorpayLinkedAccount>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    let account = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_linked_account(&CreateLinkedAccountRequest {
+            email: request.email,
+            phone: request.phone,
+            account_type: "route".to_string(),
+            reference_id: request.reference_id,
+            legal_business_name: request.legal_business_name,
+            business_type: request.business_type,
+            contact_name: request.contact_name,
+            profile: request.profile,
+            legal_info: request.legal_info,
+            notes: request.notes,
+        })
+        .await?;
+
+    tracing::info!(account_id = %account.id, "Razorpay linked account created");
+
+    Ok(Json(account))
+}
+
+pub async fn get_linked_account(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+) -> AppResult<Json<RazorpayLinkedAccount>> {
+    if !account_id.starts_with("acc_") {
+        return Err(AppError::Validation(format!(
+            "Invalid linked account: {}",
+            account_id
+        )));
+    }
+
+    let account = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .get_linked_account(&account_id)
+        .await?;
+
+    Ok(Json(account))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayTransferRequest {
+    /// Linked account ID (`acc_...`)
+    pub account: String,
+    #[validate(range(min = 100, message = "Amount must be at least 100 (1 INR in paise)"))]
+    pub amount: i64,
+    #[serde(default)]
+    pub notes: Option<serde_json::Value>,
+}
+
+/// Transfer from the account balance to a linked account, not tied to a payment
+pub async fn create_transfer(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayTransferRequest>,
+) -> AppResult<Json<Transfer>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    if !request.account.starts_with("acc_") {
+        return Err(AppError::Validation(format!(
+            "Invalid linked account: {}",
+            request.account
+        )));
+    }
+
+    let razorpay_transfer = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_transfer(&TransferRequest {
+            account: request.account,
+            amount: request.amount,
+            currency: TRANSFER_CURRENCY.to_string(),
+            notes: request.notes,
+            on_hold: None,
+            on_hold_until: None,
+        })
+        .await?;
+
+    let transfer = TransferRepository::record(
+        &state.db,
+        None,
+        &razorpay_transfer.to_update()?,
+        razorpay_transfer.notes.as_ref(),
+    )
+    .await?;
+
+    tracing::info!(
+        transfer_id = %transfer.id,
+        linked_account_id = %transfer.linked_account_id,
+        amount = transfer.amount,
+        "Direct transfer created"
+    );
+
+    Ok(Json(transfer))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePaymentTransfersRequest {
+    pub transfers: Vec<TransferSplit>,
+}
+
+/// Split a captured payment among linked accounts
+pub async fn create_payment_transfers(
+    State(state): State<AppState>,
+    Path(payment_id): Path<Uuid>,
+    Json(request): Json<CreatePaymentTransfersRequest>,
+) -> AppResult<Json<Vec<Transfer>>> {
+    let payment = PaymentRepository::find_by_id(&state.db, payment_id).await?;
+
+    if payment.status != PaymentStatus::Completed {
+        return Err(AppError::Payment(format!(
+            "Cannot transfer from payment in state: {:?}. Only completed payments can be split.",
+            payment.status
+        )));
+    }
+    if payment.currency != CurrencyType::INR {
+        return Err(AppError::Validation(
+            "Transfers are only supported for INR payments".to_string(),
+        ));
+    }
+
+    let razorpay_payment_id = payment
+        .razorpay_payment_id
+        .as_deref()
+        .ok_or_else(|| AppError::Payment("No Razorpay payment ID found".to_string()))?;
+
+    let allocated = TransferRepository::allocated_amount(&state.db, payment.id).await?;
+    validate_transfer_splits(payment.amount, allocated, &request.transfers)
+        .map_err(AppError::Validation)?;
+
+    let transfers_request = PaymentTransfersRequest {
+        transfers: request
+            .transfers
+            .iter()
+            .map(|split| TransferRequest::from_split(split, TRANSFER_CURRENCY))
+            .collect(),
+    };
+
+    let response = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_payment_transfers(razorpay_payment_id, &transfers_request)
+        .await?;
+
+    let mut transfers = Vec::with_capacity(response.items.len());
+    for razorpay_transfer in &response.items {
+        transfers.push(
+            TransferRepository::record(
+                &state.db,
+                Some(payment.id),
+                &razorpay_transfer.to_update()?,
+                razorpay_transfer.notes.as_ref(),
+            )
+            .await?,
+        );
+    }
+
+    tracing::info!(
+        payment_id = %payment.id,
+        count = transfers.len(),
+        "Payment transfers created"
+    );
+
+    Ok(Json(transfers))
+}
+
+/// List a payment's transfers after reconciling them with Razorpay
+pub async fn get_payment_transfers(
+    State(state): State<AppState>,
+    Path(payment_id): Path<Uuid>,
+) -> AppResult<Json<Vec<Transfer>>> {
+    let payment = PaymentRepository::find_by_id(&state.db, payment_id).await?;
+
+    if let Some(ref razorpay_payment_id) = payment.razorpay_payment_id {
+        let response = state
+            .payment_processor
+            .razorpay()
+            .client()
+            .get_payment_transfers(razorpay_payment_id)
+            .await?;
+
+        for razorpay_transfer in &response.items {
+            TransferRepository::record(
+                &state.db,
+                Some(payment.id),
+                &razorpay_transfer.to_update()?,
+                razorpay_transfer.notes.as_ref(),
+            )
+            .await?;
+        }
+    }
+
+    let transfers = TransferRepository::find_by_payment_id(&state.db, payment.id).await?;
+
+    Ok(Json(transfers))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReverseTransferRequest {
+    /// Reverses what is left of the transfer when omitted
+    #[serde(default)]
+    #[validate(range(min = 1, message = "Reversal amount must be positive"))]
+    pub amount: Option<i64>,
+    #[serde(default)]
+    pub notes: Option<serde_json::Value>,
+}
+
+/// Pull funds back from a linked account, e.g. before refunding the customer
+pub async fn reverse_transfer(
+    State(state): State<AppState>,
+    Path(transfer_id): Path<Uuid>,
+    Json(request): Json<ReverseTransferRequest>,
+) -> AppResult<Json<Transfer>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    let transfer = TransferRepository::find_by_id(&state.db, transfer_id).await?;
+
+    if !matches!(
+        transfer.status,
+        TransferStatus::Processed | TransferStatus::PartiallyReversed
+    ) {
+        return Err(AppError::Payment(format!(
+            "Cannot reverse transfer in state: {:?}",
+            transfer.status
+        )));
+    }
+    if let Some(amount) = request.amount {
+        if amount > transfer.amount - transfer.amount_reversed {
+            return Err(AppError::Validation(
+                "Reversal amount cannot exceed the amount left on the transfer".to_string(),
+            ));
+        }
+    }
+
+    let razorpay_transfer_id = razorpay_transfer_id(&transfer)?;
+    let client = state.payment_processor.razorpay().client();
+
+    let reversal = client
+        .reverse_transfer(
+            razorpay_transfer_id,
+            &ReversalRequest {
+                amount: request.amount,
+                notes: request.notes,
+            },
+        )
+        .await?;
+
+    let razorpay_transfer = client.get_transfer(razorpay_transfer_id).await?;
+    let transfer = TransferRepository::record(
+        &state.db,
+        transfer.payment_id,
+        &razorpay_transfer.to_update()?,
+        None,
+    )
+    .await?;
+
+    tracing::info!(
+        transfer_id = %transfer.id,
+        reversal_id = %reversal.id,
+        amount = reversal.amount,
+        "Transfer reversed"
+    );
+
+    Ok(Json(transfer))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTransferHoldRequest {
+    pub on_hold: bool,
+    /// Release the settlement automatically at this time
+    #[serde(default)]
+    pub on_hold_until: Option<DateTime<Utc>>,
+}
+
+/// Hold a transfer's settlement to the linked account, or release it
+pub async fn update_transfer_hold(
+    State(state): State<AppState>,
+    Path(transfer_id): Path<Uuid>,
+    Json(request): Json<UpdateTransferHoldRequest>,
+) -> AppResult<Json<Transfer>> {
+    if let Some(on_hold_until) = request.on_hold_until {
+        if !request.on_hold {
+            return Err(AppError::Validation(
+                "on_hold_until requires on_hold".to_string(),
+            ));
+        }
+        if on_hold_until <= Utc::now() {
+            return Err(AppError::Validation(
+                "on_hold_until must be in the future".to_string(),
+            ));
+        }
+    }
+
+    let transfer = TransferRepository::find_by_id(&state.db, transfer_id).await?;
+
+    let razorpay_transfer = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .update_transfer_hold(
+            razorpay_transfer_id(&transfer)?,
+            &TransferHoldRequest {
+                on_hold: request.on_hold,
+                on_hold_until: request.on_hold_until.map(|t| t.timestamp()),
+            },
+        )
+        .await?;
+
+    let transfer = TransferRepository::record(
+        &state.db,
+        transfer.payment_id,
+        &razorpay_transfer.to_update()?,
+        None,
+    )
+    .await?;
+
+    tracing::info!(
+        transfer_id = %transfer.id,
+        on_hold = transfer.on_hold,
+        "Transfer settlement hold updated"
+    );
+
+    Ok(Json(transfer))
+}
+
+/// Payment a transfer's `source` refers to: a payment, or the order of one
+pub(crate) async fn find_transfer_source(
+    pool: &PgPool,
+    source: &str,
+) -> AppResult<Option<Payment>> {
+    if source.starts_with("pay_") {
+        PaymentRepository::find_by_razorpay_payment_id(pool, source).await
+    } else if source.starts_with("order_") {
+        PaymentRepository::find_by_razorpay_order_id(pool, source).await
+    } else {
+        Ok(None)
+    }
+}
+
+fn razorpay_transfer_id(transfer: &Transfer) -> AppResult<&str> {
+    transfer.razorpay_transfer_id.as_deref().ok_or_else(|| {
+        AppError::Payment(format!(
+            "Transfer {} has not been created by Razorpay yet",
+            transfer.id
+        ))
+    })
+}
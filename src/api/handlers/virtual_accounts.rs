@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::db::repositories::{CustomerRepository, PaymentRepository, VirtualAccountRepository};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    BankTransferCredit, Collection, CreatePaymentRequest, CurrencyType, NewVirtualAccount, Payment,
+    PaymentMethod, PaymentStatus, VirtualAccount, VirtualAccountCredit, VirtualAccountStatus,
+};
+use crate::services::razorpay::{
+    CreateVirtualAccountRequest, RazorpayVirtualAccount, VirtualAccountReceivers,
+};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRazorpayVirtualAccountRequest {
+    /// Amount expected in paise. The account then collects a single payment;
+    /// without it the account belongs to `customer_id` and takes any number
+    /// of payments.
+    #[serde(default)]
+    #[validate(range(min = 100, message = "Amount must be at least 100 (1 INR in paise)"))]
+    pub amount: Option<i64>,
+    #[serde(default)]
+    pub customer_id: Option<Uuid>,
+    #[serde(default)]
+    #[validate(length(max = 255, message = "Description too long"))]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    pub customer_email: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 10, max = 15, message = "Invalid phone number"))]
+    pub customer_phone: Option<String>,
+    /// When Razorpay closes the account
+    #[serde(default)]
+    pub close_by: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VirtualAccountResponse {
+    #[serde(flatten)]
+    pub account: VirtualAccount,
+    /// Received against the expected amount, for an account collecting a
+    /// single payment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<Collection>,
+    pub credits: Vec<VirtualAccountCredit>,
+}
+
+impl VirtualAccountResponse {
+    async fn load(state: &AppState, account: VirtualAccount) -> AppResult<Self> {
+        let credits = VirtualAccountRepository::find_credits(&state.db, account.id).await?;
+
+        Ok(Self {
+            collection: account.collection(),
+            account,
+            credits,
+        })
+    }
+}
+
+/// Create a virtual bank account for a payment or a customer, which B2B
+/// customers pay into by NEFT/RTGS/IMPS
+pub async fn create_virtual_account(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRazorpayVirtualAccountRequest>,
+) -> AppResult<Json<VirtualAccountResponse>> {
+    request
+        .validate()
+        .map_err(|e| AppError::Validation(format!("Invalid request: {}", e)))?;
+
+    if request.amount.is_none() && request.customer_id.is_none() {
+        return Err(AppError::Validation(
+            "Either amount or customer_id is required".to_string(),
+        ));
+    }
+    // Razorpay rejects accounts closing in less than 15 minutes
+    if request
+        .close_by
+        .is_some_and(|close_by| close_by < Utc::now() + Duration::minutes(15))
+    {
+        return Err(AppError::Validation(
+            "close_by must be at least 15 minutes in the future".to_string(),
+        ));
+    }
+
+    let customer = match request.customer_id {
+        Some(customer_id) => Some(CustomerRepository::find_by_id(&state.db, customer_id).await?),
+        None => None,
+    };
+
+    let payment = match request.amount {
+        Some(amount) => Some(
+            PaymentRepository::create(
+                &state.db,
+                &CreatePaymentRequest {
+                    amount,
+                    currency: CurrencyType::INR,
+                    method: PaymentMethod::BankTransfer,
+                    chain: None,
+                    description: request.description.clone(),
+                    customer_email: request
+                        .customer_email
+                        .or_else(|| customer.as_ref().and_then(|c| c.email.clone())),
+                    customer_phone: request
+                        .customer_phone
+                        .or_else(|| customer.as_ref().and_then(|c| c.contact.clone())),
+                    metadata: request.metadata,
+                    callback_url: None,
+                },
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    let va_request = CreateVirtualAccountRequest {
+        receivers: VirtualAccountReceivers {
+            types: vec!["bank_account".to_string()],
+        },
+        description: request.description,
+        customer_id: customer.as_ref().map(|c| c.razorpay_customer_id.clone()),
+        close_by: request.close_by.map(|close_by| close_by.timestamp()),
+        amount_expected: request.amount,
+        notes: Some(serde_json::json!({
+            "payment_id": payment.as_ref().map(|p| p.id),
+            "customer_id": customer.as_ref().map(|c| c.id),
+        })),
+    };
+
+    let razorpay_account = match state
+        .payment_processor
+        .razorpay()
+        .client()
+        .create_virtual_account(&va_request)
+        .await
+    {
+        Ok(account) => account,
+        Err(e) => {
+            if let Some(ref payment) = payment {
+                PaymentRepository::update_status(&state.db, payment.id, PaymentStatus::Failed)
+                    .await?;
+            }
+            return Err(e);
+        }
+    };
+
+    let bank_account = razorpay_account.bank_account().ok_or_else(|| {
+        AppError::Razorpay("Virtual account has no bank account receiver".to_string())
+    })?;
+
+    let account = VirtualAccountRepository::create(
+        &state.db,
+        &NewVirtualAccount {
+            razorpay_virtual_account_id: razorpay_account.id.clone(),
+            payment_id: payment.as_ref().map(|p| p.id),
+            customer_id: customer.as_ref().map(|c| c.id),
+            amount_expected: request.amount,
+            account_number: bank_account.account_number.clone().unwrap_or_default(),
+            ifsc: bank_account.ifsc.clone().unwrap_or_default(),
+            bank_name: bank_account.bank_name.clone(),
+            beneficiary_name: bank_account.name.clone(),
+            close_by: request.close_by,
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        virtual_account_id = %account.id,
+        razorpay_virtual_account_id = %account.razorpay_virtual_account_id,
+        payment_id = ?account.payment_id,
+        "Razorpay virtual account created"
+    );
+
+    Ok(Json(VirtualAccountResponse::load(&state, account).await?))
+}
+
+/// Get a virtual account after recording any credits its webhooks missed
+pub async fn get_virtual_account(
+    State(state): State<AppState>,
+    Path(virtual_account_id): Path<Uuid>,
+) -> AppResult<Json<VirtualAccountResponse>> {
+    let mut account = VirtualAccountRepository::find_by_id(&state.db, virtual_account_id).await?;
+    let client = state.payment_processor.razorpay().client();
+
+    let recorded: HashSet<String> = VirtualAccountRepository::find_credits(&state.db, account.id)
+        .await?
+        .into_iter()
+        .map(|credit| credit.razorpay_payment_id)
+        .collect();
+
+    let payments = client
+        .get_virtual_account_payments(&account.razorpay_virtual_account_id)
+        .await?;
+
+    for razorpay_payment in payments
+        .items
+        .iter()
+        .filter(|p| p.status == "captured" && !recorded.contains(&p.id))
+    {
+        let bank_transfer = client.get_bank_transfer(&razorpay_payment.id).await?;
+        (account, _) = record_virtual_account_credit(
+            &state,
+            &account,
+            &BankTransferCredit::from(&bank_transfer),
+        )
+        .await?;
+    }
+
+    let razorpay_account = client
+        .get_virtual_account(&account.razorpay_virtual_account_id)
+        .await?;
+    let account = sync_virtual_account(&state, &account, &razorpay_account).await?;
+
+    Ok(Json(VirtualAccountResponse::load(&state, account).await?))
+}
+
+/// Stop accepting transfers to a virtual account
+pub async fn close_virtual_account(
+    State(state): State<AppState>,
+    Path(virtual_account_id): Path<Uuid>,
+) -> AppResult<Json<VirtualAccountResponse>> {
+    let account = VirtualAccountRepository::find_by_id(&state.db, virtual_account_id).await?;
+
+    if account.status == VirtualAccountStatus::Closed {
+        return Err(AppError::Payment(
+            "Virtual account is already closed".to_string(),
+        ));
+    }
+
+    let razorpay_account = state
+        .payment_processor
+        .razorpay()
+        .client()
+        .close_virtual_account(&account.razorpay_virtual_account_id)
+        .await?;
+
+    tracing::info!(virtual_account_id = %account.id, "Razorpay virtual account closed");
+
+    let account = sync_virtual_account(&state, &account, &razorpay_account).await?;
+
+    Ok(Json(VirtualAccountResponse::load(&state, account).await?))
+}
+
+async fn sync_virtual_account(
+    state: &AppState,
+    account: &VirtualAccount,
+    razorpay_account: &RazorpayVirtualAccount,
+) -> AppResult<VirtualAccount> {
+    let status =
+        VirtualAccountStatus::from_razorpay(&razorpay_account.status).ok_or_else(|| {
+            AppError::Razorpay(format!(
+                "Unknown virtual account status: {}",
+                razorpay_account.status
+            ))
+        })?;
+    let closed_at = razorpay_account
+        .closed_at
+        .and_then(|t| DateTime::from_timestamp(t, 0));
+
+    apply_virtual_account_status(state, account, status, closed_at).await
+}
+
+/// Record a bank transfer to a virtual account, settling the payment it
+/// belongs to
+pub(crate) async fn record_virtual_account_credit(
+    state: &AppState,
+    account: &VirtualAccount,
+    credit: &BankTransferCredit,
+) -> AppResult<(VirtualAccount, Payment)> {
+    let customer = match account.customer_id {
+        Some(customer_id) => Some(CustomerRepository::find_by_id(&state.db, customer_id).await?),
+        None => None,
+    };
+
+    let (account, payment, recorded) =
+        VirtualAccountRepository::record_credit(&state.db, account, credit, customer.as_ref())
+            .await?;
+
+    if !recorded {
+        return Ok((account, payment));
+    }
+
+    match account.collection() {
+        Some(Collection::Underpaid { amount_due }) => tracing::warn!(
+            virtual_account_id = %account.id,
+            payment_id = %payment.id,
+            amount_due,
+            "Virtual account underpaid, waiting for the balance"
+        ),
+        Some(Collection::Overpaid { excess_amount }) => tracing::warn!(
+            virtual_account_id = %account.id,
+            payment_id = %payment.id,
+            excess_amount,
+            "Virtual account overpaid, excess needs to be refunded"
+        ),
+        _ => tracing::info!(
+            virtual_account_id = %account.id,
+            payment_id = %payment.id,
+            amount = credit.amount,
+            "Bank transfer credited to virtual account"
+        ),
+    }
+
+    if let Some(ref broadcaster) = state.ws_broadcaster {
+        let _ = broadcaster.broadcast_payment_update(&payment).await;
+    }
+
+    Ok((account, payment))
+}
+
+/// Bring a virtual account to the status Razorpay reports. Closing an account
+/// that never received anything cancels its payment; a partly paid payment
+/// is left for the merchant to settle with the customer.
+pub(crate) async fn apply_virtual_account_status(
+    state: &AppState,
+    account: &VirtualAccount,
+    status: VirtualAccountStatus,
+    closed_at: Option<DateTime<Utc>>,
+) -> AppResult<VirtualAccount> {
+    if account.status == status {
+        return Ok(account.clone());
+    }
+
+    let account =
+        VirtualAccountRepository::update_status(&state.db, account.id, status, closed_at).await?;
+
+    if status != VirtualAccountStatus::Closed {
+        return Ok(account);
+    }
+    let (Some(payment_id), Some(collection)) = (account.payment_id, account.collection()) else {
+        return Ok(account);
+    };
+
+    match collection {
+        Collection::Unpaid => {
+            let payment = PaymentRepository::find_by_id(&state.db, payment_id).await?;
+            if payment.status == PaymentStatus::Pending {
+                let payment = PaymentRepository::update_status(
+                    &state.db,
+                    payment.id,
+                    PaymentStatus::Cancelled,
+                )
+                .await?;
+
+                if let Some(ref broadcaster) = state.ws_broadcaster {
+                    let _ = broadcaster.broadcast_payment_update(&payment).await;
+                }
+            }
+        }
+        Collection::Underpaid { amount_due } => tracing::warn!(
+            virtual_account_id = %account.id,
+            payment_id = %payment_id,
+            amount_due,
+            "Virtual account closed before it was paid in full"
+        ),
+        Collection::Paid | Collection::Overpaid { .. } => {}
+    }
+
+    Ok(account)
+}
@@ -6,10 +6,20 @@ use axum::{
 };
 use serde::Serialize;
 
-use crate::db::repositories::{PaymentRepository, WebhookRepository};
+use super::payment_links::apply_payment_link_status;
+use super::payouts::apply_payout;
+use super::qr_codes::{apply_qr_code_closed, apply_qr_code_credit};
+use super::transfers::find_transfer_source;
+use super::virtual_accounts::{apply_virtual_account_status, record_virtual_account_credit};
+use crate::db::repositories::{
+    PaymentLinkRepository, PaymentRepository, QrCodeRepository, SubscriptionRepository,
+    TransactionRepository, TransferRepository, VirtualAccountRepository, WebhookRepository,
+};
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    PaymentStatus, RazorpayWebhookPayload, WebhookSource, WebhookStatus,
+    BankTransferCredit, PaymentLinkStatus, PaymentMethod, PaymentStatus, PayoutUpdate,
+    RazorpayWebhookPayload, SubscriptionUpdate, TransactionType, TransferStatus, TransferUpdate,
+    VirtualAccountStatus, WebhookSource, WebhookStatus,
 };
 use crate::services::razorpay::RazorpayWebhookVerifier;
 use crate::AppState;
@@ -226,6 +236,24 @@ async fn process_razorpay_webhook(
             // Handle refund events
             tracing::info!("Received refund webhook: {}", webhook.event);
         }
+        "payment_link.paid" | "payment_link.expired" | "payment_link.cancelled" => {
+            return process_payment_link_webhook(state, &webhook).await;
+        }
+        event if event.starts_with("subscription.") => {
+            return process_subscription_webhook(state, &webhook).await;
+        }
+        "transfer.processed" | "transfer.failed" => {
+            return process_transfer_webhook(state, &webhook).await;
+        }
+        "virtual_account.credited" | "virtual_account.closed" => {
+            return process_virtual_account_webhook(state, &webhook).await;
+        }
+        "qr_code.credited" | "qr_code.closed" => {
+            return process_qr_code_webhook(state, &webhook).await;
+        }
+        "payout.processed" | "payout.reversed" | "payout.failed" => {
+            return process_payout_webhook(state, &webhook).await;
+        }
         _ => {
             tracing::info!("Unhandled webhook event: {}", webhook.event);
         }
@@ -234,6 +262,302 @@ async fn process_razorpay_webhook(
     Ok(None)
 }
 
+/// Settle the payment behind a payment link that was paid, expired or cancelled
+async fn process_payment_link_webhook(
+    state: &AppState,
+    webhook: &RazorpayWebhookPayload,
+) -> AppResult<Option<uuid::Uuid>> {
+    let Some(ref link_entity) = webhook.payload.payment_link else {
+        return Ok(None);
+    };
+    let razorpay_link = &link_entity.entity;
+
+    let Some(link) = PaymentLinkRepository::find_by_razorpay_id(&state.db, &razorpay_link.id).await?
+    else {
+        tracing::info!(
+            razorpay_payment_link_id = %razorpay_link.id,
+            "Ignoring webhook for unknown payment link"
+        );
+        return Ok(None);
+    };
+
+    let status = PaymentLinkStatus::from_razorpay(&razorpay_link.status).ok_or_else(|| {
+        AppError::Razorpay(format!("Unknown payment link status: {}", razorpay_link.status))
+    })?;
+
+    if let Some(ref payment_entity) = webhook.payload.payment {
+        let razorpay_payment = &payment_entity.entity;
+        let order_id = razorpay_payment
+            .order_id
+            .as_deref()
+            .or(razorpay_link.order_id.as_deref());
+
+        if let Some(order_id) = order_id {
+            PaymentRepository::update_razorpay_details(
+                &state.db,
+                link.payment_id,
+                order_id,
+                Some(&razorpay_payment.id),
+                None,
+            )
+            .await?;
+        }
+    }
+
+    let (payment, _) = apply_payment_link_status(state, &link, status).await?;
+
+    tracing::info!(
+        payment_id = %payment.id,
+        status = ?payment.status,
+        "Payment link webhook processed: {}",
+        webhook.event
+    );
+
+    Ok(Some(payment.id))
+}
+
+/// Sync a subscription with the state carried by the event, and record the
+/// payment of `subscription.charged`
+async fn process_subscription_webhook(
+    state: &AppState,
+    webhook: &RazorpayWebhookPayload,
+) -> AppResult<Option<uuid::Uuid>> {
+    let Some(ref subscription_entity) = webhook.payload.subscription else {
+        return Ok(None);
+    };
+    let razorpay_subscription = &subscription_entity.entity;
+
+    let Some(subscription) =
+        SubscriptionRepository::find_by_razorpay_id(&state.db, &razorpay_subscription.id).await?
+    else {
+        tracing::info!(
+            razorpay_subscription_id = %razorpay_subscription.id,
+            "Ignoring webhook for unknown subscription"
+        );
+        return Ok(None);
+    };
+
+    let update = SubscriptionUpdate::try_from(razorpay_subscription).map_err(AppError::Razorpay)?;
+    let subscription =
+        SubscriptionRepository::apply_update(&state.db, subscription.id, &update).await?;
+
+    tracing::info!(
+        subscription_id = %subscription.id,
+        status = ?subscription.status,
+        "Subscription webhook processed: {}",
+        webhook.event
+    );
+
+    if webhook.event != "subscription.charged" {
+        return Ok(None);
+    }
+
+    let Some(ref payment_entity) = webhook.payload.payment else {
+        return Ok(None);
+    };
+    let razorpay_payment = &payment_entity.entity;
+
+    let (payment, recorded) = SubscriptionRepository::record_charge(
+        &state.db,
+        &subscription,
+        &razorpay_payment.id,
+        razorpay_payment.order_id.as_deref(),
+        razorpay_payment.amount,
+        razorpay_payment
+            .method
+            .as_deref()
+            .and_then(PaymentMethod::from_razorpay)
+            .unwrap_or(PaymentMethod::Card),
+    )
+    .await?;
+
+    if recorded {
+        if let Some(ref broadcaster) = state.ws_broadcaster {
+            let _ = broadcaster.broadcast_payment_update(&payment).await;
+        }
+    }
+
+    Ok(Some(payment.id))
+}
+
+/// Reconcile a Route transfer with its outcome
+async fn process_transfer_webhook(
+    state: &AppState,
+    webhook: &RazorpayWebhookPayload,
+) -> AppResult<Option<uuid::Uuid>> {
+    let Some(ref transfer_entity) = webhook.payload.transfer else {
+        return Ok(None);
+    };
+    let razorpay_transfer = &transfer_entity.entity;
+
+    let update = TransferUpdate::try_from(razorpay_transfer).map_err(AppError::Razorpay)?;
+    let payment_id = find_transfer_source(&state.db, &razorpay_transfer.source)
+        .await?
+        .map(|payment| payment.id);
+
+    let transfer = TransferRepository::record(&state.db, payment_id, &update, None).await?;
+
+    if transfer.status == TransferStatus::Failed {
+        tracing::warn!(
+            transfer_id = %transfer.id,
+            linked_account_id = %transfer.linked_account_id,
+            error = ?transfer.error_message,
+            "Transfer failed"
+        );
+    } else {
+        tracing::info!(
+            transfer_id = %transfer.id,
+            status = ?transfer.status,
+            "Transfer webhook processed: {}",
+            webhook.event
+        );
+    }
+
+    Ok(payment_id)
+}
+
+/// Record a bank transfer to a virtual account, or its closing
+async fn process_virtual_account_webhook(
+    state: &AppState,
+    webhook: &RazorpayWebhookPayload,
+) -> AppResult<Option<uuid::Uuid>> {
+    let Some(ref account_entity) = webhook.payload.virtual_account else {
+        return Ok(None);
+    };
+    let razorpay_account = &account_entity.entity;
+
+    let Some(account) =
+        VirtualAccountRepository::find_by_razorpay_id(&state.db, &razorpay_account.id).await?
+    else {
+        tracing::info!(
+            razorpay_virtual_account_id = %razorpay_account.id,
+            "Ignoring webhook for unknown virtual account"
+        );
+        return Ok(None);
+    };
+
+    if webhook.event == "virtual_account.closed" {
+        let account = apply_virtual_account_status(
+            state,
+            &account,
+            VirtualAccountStatus::Closed,
+            razorpay_account
+                .closed_at
+                .and_then(|t| chrono::DateTime::from_timestamp(t, 0)),
+        )
+        .await?;
+        return Ok(account.payment_id);
+    }
+
+    let credit = match (&webhook.payload.bank_transfer, &webhook.payload.payment) {
+        (Some(bank_transfer), _) => BankTransferCredit::from(&bank_transfer.entity),
+        (None, Some(payment)) => BankTransferCredit {
+            razorpay_payment_id: payment.entity.id.clone(),
+            amount: payment.entity.amount,
+            mode: None,
+            bank_reference: None,
+            payer_name: None,
+        },
+        (None, None) => return Ok(None),
+    };
+
+    let (_, payment) = record_virtual_account_credit(state, &account, &credit).await?;
+
+    Ok(Some(payment.id))
+}
+
+/// Record a payment to a QR code, or close the QR code
+async fn process_qr_code_webhook(
+    state: &AppState,
+    webhook: &RazorpayWebhookPayload,
+) -> AppResult<Option<uuid::Uuid>> {
+    let Some(ref qr_code_entity) = webhook.payload.qr_code else {
+        return Ok(None);
+    };
+    let razorpay_qr_code = &qr_code_entity.entity;
+
+    let Some(qr_code) =
+        QrCodeRepository::find_by_razorpay_id(&state.db, &razorpay_qr_code.id).await?
+    else {
+        tracing::info!(
+            razorpay_qr_code_id = %razorpay_qr_code.id,
+            "Ignoring webhook for unknown QR code"
+        );
+        return Ok(None);
+    };
+
+    if webhook.event == "qr_code.closed" {
+        let qr_code = apply_qr_code_closed(
+            state,
+            &qr_code,
+            razorpay_qr_code.close_reason.as_deref(),
+            razorpay_qr_code
+                .closed_at
+                .and_then(|t| chrono::DateTime::from_timestamp(t, 0)),
+        )
+        .await?;
+        return Ok(qr_code.payment_id);
+    }
+
+    let Some(ref payment_entity) = webhook.payload.payment else {
+        return Ok(None);
+    };
+    let razorpay_payment = &payment_entity.entity;
+
+    let payment = apply_qr_code_credit(
+        state,
+        &qr_code,
+        &razorpay_payment.id,
+        razorpay_payment.amount,
+    )
+    .await?;
+
+    Ok(Some(payment.id))
+}
+
+/// Settle the withdrawal transaction behind a RazorpayX payout
+async fn process_payout_webhook(
+    state: &AppState,
+    webhook: &RazorpayWebhookPayload,
+) -> AppResult<Option<uuid::Uuid>> {
+    let Some(ref payout_entity) = webhook.payload.payout else {
+        return Ok(None);
+    };
+    let razorpay_payout = &payout_entity.entity;
+
+    let update = PayoutUpdate::try_from(razorpay_payout).map_err(AppError::Razorpay)?;
+
+    // A payout whose creation timed out is only known by the transaction ID
+    // it was sent with as its reference
+    let tx = match TransactionRepository::find_by_tx_hash(&state.db, &razorpay_payout.id).await? {
+        Some(tx) => Some(tx),
+        None => match razorpay_payout
+            .reference_id
+            .as_deref()
+            .and_then(|reference_id| reference_id.parse::<uuid::Uuid>().ok())
+        {
+            Some(tx_id) => match TransactionRepository::find_by_id(&state.db, tx_id).await {
+                Ok(tx) => Some(tx),
+                Err(AppError::NotFound(_)) => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        },
+    };
+
+    let Some(tx) = tx.filter(|tx| tx.tx_type == TransactionType::Withdrawal) else {
+        tracing::info!(
+            razorpay_payout_id = %razorpay_payout.id,
+            "Ignoring webhook for unknown payout"
+        );
+        return Ok(None);
+    };
+
+    let tx = apply_payout(state, &tx, &update).await?;
+
+    Ok(Some(tx.payment_id))
+}
+
 fn headers_to_map(headers: &HeaderMap) -> std::collections::HashMap<String, String> {
     headers
         .iter()
@@ -48,7 +48,7 @@ impl AuthError {
 /// - sk_test_xxx: Test secret key
 pub async fn api_key_auth(
     State(state): State<AppState>,
-    request: Request<Body>,
+    mut request: Request<Body>,
     next: Next,
 ) -> Result<Response, Response> {
     let path = request.uri().path();
@@ -70,7 +70,7 @@ pub async fn api_key_auth(
             match validate_api_key_format(key) {
                 Ok(key_type) => {
                     // Additional validation based on endpoint sensitivity
-                    if requires_secret_key(path) && key_type != ApiKeyType::SecretLive && key_type != ApiKeyType::SecretTest {
+                    if requires_secret_key(path) && !key_type.is_secret() {
                         tracing::warn!(
                             path = %path,
                             key_type = ?key_type,
@@ -84,6 +84,8 @@ pub async fn api_key_auth(
 
                     // Verify the key hash against stored keys (in production, check database)
                     if verify_api_key(key, &state.config.security.api_key_hash_secret) {
+                        // Handlers whose body can make a request sensitive check it
+                        request.extensions_mut().insert(key_type);
                         Ok(next.run(request).await)
                     } else {
                         tracing::warn!(
@@ -121,12 +123,32 @@ fn is_public_endpoint(path: &str) -> bool {
         "/health" |
         "/api/v1/status"
     ) || path.starts_with("/webhooks/")
+        || path.starts_with("/.well-known/lnurlp/")
+        || path.starts_with("/lnurlp/")
 }
 
 fn requires_secret_key(path: &str) -> bool {
     // These endpoints require secret keys for security
     path.contains("/refund") ||
-    path.contains("/admin")
+    path.contains("/payout") ||
+    path.contains("/admin") ||
+    path.contains("/transfers") ||
+    path.contains("/linked-accounts") ||
+    path.contains("/customers") ||
+    path.contains("/plans") ||
+    // Creating a payment link, not fetching one
+    path.ends_with("/payment-links") ||
+    // Changing the state of an existing subscription, payment link, virtual account or QR code
+    (path.contains("/subscriptions/")
+        && (path.ends_with("/pause") || path.ends_with("/resume") || path.ends_with("/cancel"))) ||
+    (path.contains("/payment-links/") && path.ends_with("/cancel")) ||
+    ((path.contains("/virtual-accounts/") || path.contains("/qr-codes/"))
+        && path.ends_with("/close")) ||
+    path.contains("/contacts") ||
+    path.contains("/fund-accounts") ||
+    path.contains("/lightning/lnurl") ||
+    // Capturing or releasing held funds, not creating the hold
+    (path.contains("/hold-invoices/") && (path.ends_with("/settle") || path.ends_with("/cancel")))
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -137,6 +159,12 @@ pub enum ApiKeyType {
     SecretTest,
 }
 
+impl ApiKeyType {
+    pub fn is_secret(self) -> bool {
+        matches!(self, Self::SecretLive | Self::SecretTest)
+    }
+}
+
 fn validate_api_key_format(key: &str) -> Result<ApiKeyType, String> {
     if key.len() < 20 {
         return Err("API key too short".to_string());
@@ -247,6 +275,14 @@ mod tests {
         assert!(validate_api_key_format("short").is_err());
     }
 
+    #[test]
+    fn test_api_key_type_is_secret() {
+        assert!(ApiKeyType::SecretLive.is_secret());
+        assert!(ApiKeyType::SecretTest.is_secret());
+        assert!(!ApiKeyType::PublicLive.is_secret());
+        assert!(!ApiKeyType::PublicTest.is_secret());
+    }
+
     #[test]
     fn test_hash_api_key() {
         let hash1 = hash_api_key("pk_test_abc123xyz789def456", "secret");
@@ -270,12 +306,40 @@ mod tests {
         assert!(is_public_endpoint("/health"));
         assert!(is_public_endpoint("/api/v1/status"));
         assert!(is_public_endpoint("/webhooks/razorpay"));
+        assert!(is_public_endpoint("/.well-known/lnurlp/coffee"));
+        assert!(!is_public_endpoint("/api/v1/lightning/lnurl"));
         assert!(!is_public_endpoint("/api/v1/payments"));
     }
 
     #[test]
     fn test_requires_secret_key() {
         assert!(requires_secret_key("/api/v1/razorpay/refund"));
+        assert!(requires_secret_key("/api/v1/lightning/payout"));
         assert!(!requires_secret_key("/api/v1/razorpay/orders"));
+        assert!(requires_secret_key("/api/v1/razorpay/transfers"));
+        assert!(requires_secret_key("/api/v1/razorpay/linked-accounts"));
+        assert!(requires_secret_key("/api/v1/razorpay/customers"));
+        assert!(requires_secret_key("/api/v1/razorpay/plans"));
+        assert!(!requires_secret_key("/api/v1/razorpay/subscriptions"));
+        assert!(!requires_secret_key("/api/v1/razorpay/subscriptions/sub_1"));
+        assert!(requires_secret_key("/api/v1/razorpay/subscriptions/sub_1/pause"));
+        assert!(requires_secret_key("/api/v1/razorpay/subscriptions/sub_1/resume"));
+        assert!(requires_secret_key("/api/v1/razorpay/subscriptions/sub_1/cancel"));
+        assert!(requires_secret_key("/api/v1/razorpay/payment-links"));
+        assert!(!requires_secret_key("/api/v1/razorpay/payment-links/plink_1"));
+        assert!(requires_secret_key("/api/v1/razorpay/payment-links/plink_1/cancel"));
+        assert!(!requires_secret_key("/api/v1/razorpay/virtual-accounts/va_1"));
+        assert!(requires_secret_key("/api/v1/razorpay/virtual-accounts/va_1/close"));
+        assert!(!requires_secret_key("/api/v1/razorpay/qr-codes/qr_1"));
+        assert!(requires_secret_key("/api/v1/razorpay/qr-codes/qr_1/close"));
+        assert!(requires_secret_key("/api/v1/razorpay/contacts"));
+        assert!(requires_secret_key("/api/v1/razorpay/fund-accounts"));
+        assert!(requires_secret_key("/api/v1/razorpay/payouts"));
+        assert!(requires_secret_key("/api/v1/lightning/lnurl"));
+        assert!(!requires_secret_key("/.well-known/lnurlp/coffee"));
+        assert!(!requires_secret_key("/lnurlp/coffee/callback"));
+        assert!(!requires_secret_key("/api/v1/lightning/hold-invoices"));
+        assert!(requires_secret_key("/api/v1/lightning/hold-invoices/abc/settle"));
+        assert!(requires_secret_key("/api/v1/lightning/hold-invoices/abc/cancel"));
     }
 }
@@ -1,6 +1,6 @@
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{delete, get, patch, post},
     Router,
 };
 use tower_http::{
@@ -20,7 +20,10 @@ pub fn create_router(state: AppState) -> Router {
     // Public routes (no auth required)
     let public_routes = Router::new()
         .route("/health", get(handlers::health_check))
-        .route("/api/v1/status", get(handlers::service_status));
+        .route("/api/v1/status", get(handlers::service_status))
+        // LNURL-pay and Lightning Addresses, called by the payer's wallet
+        .route("/.well-known/lnurlp/:name", get(handlers::lnurl_pay_request))
+        .route("/lnurlp/:name/callback", get(handlers::lnurl_pay_callback));
 
     // Webhook routes (signature verification instead of API key)
     let webhook_routes = Router::new()
@@ -32,7 +35,49 @@ pub fn create_router(state: AppState) -> Router {
         .route("/orders", post(handlers::create_order))
         .route("/verify", post(handlers::verify_payment))
         .route("/payments/:payment_id", get(handlers::get_payment))
-        .route("/refund", post(handlers::process_refund));
+        .route("/refund", post(handlers::process_refund))
+        .route("/payment-links", post(handlers::create_payment_link))
+        .route("/payment-links/:payment_id", get(handlers::get_payment_link))
+        .route("/payment-links/:payment_id/cancel", post(handlers::cancel_payment_link))
+        .route("/plans", post(handlers::create_plan))
+        .route("/subscriptions", post(handlers::create_subscription))
+        .route("/subscriptions/verify", post(handlers::verify_subscription))
+        .route("/subscriptions/:subscription_id", get(handlers::get_subscription))
+        .route("/subscriptions/:subscription_id/pause", post(handlers::pause_subscription))
+        .route("/subscriptions/:subscription_id/resume", post(handlers::resume_subscription))
+        .route("/subscriptions/:subscription_id/cancel", post(handlers::cancel_subscription))
+        .route("/linked-accounts", post(handlers::create_linked_account))
+        .route("/linked-accounts/:account_id", get(handlers::get_linked_account))
+        .route("/transfers", post(handlers::create_transfer))
+        .route("/transfers/:transfer_id/reversals", post(handlers::reverse_transfer))
+        .route("/transfers/:transfer_id/hold", patch(handlers::update_transfer_hold))
+        .route(
+            "/payments/:payment_id/transfers",
+            post(handlers::create_payment_transfers).get(handlers::get_payment_transfers),
+        )
+        .route("/customers", post(handlers::create_customer))
+        .route(
+            "/customers/:customer_id",
+            get(handlers::get_customer).patch(handlers::update_customer),
+        )
+        .route("/customers/:customer_id/tokens", get(handlers::list_customer_tokens))
+        .route(
+            "/customers/:customer_id/tokens/:token_id",
+            delete(handlers::delete_customer_token),
+        )
+        .route("/virtual-accounts", post(handlers::create_virtual_account))
+        .route("/virtual-accounts/:virtual_account_id", get(handlers::get_virtual_account))
+        .route(
+            "/virtual-accounts/:virtual_account_id/close",
+            post(handlers::close_virtual_account),
+        )
+        .route("/qr-codes", post(handlers::create_qr_code))
+        .route("/qr-codes/:qr_code_id", get(handlers::get_qr_code))
+        .route("/qr-codes/:qr_code_id/close", post(handlers::close_qr_code))
+        .route("/contacts", post(handlers::create_contact))
+        .route("/fund-accounts", post(handlers::create_fund_account))
+        .route("/payouts", post(handlers::create_payout))
+        .route("/payouts/:transaction_id", get(handlers::get_payout));
 
     // Crypto payment routes
     let crypto_routes = Router::new()
@@ -44,10 +89,23 @@ pub fn create_router(state: AppState) -> Router {
         .route("/token-balance", get(handlers::get_token_balance))
         .route("/verify-signature", post(handlers::verify_wallet_signature));
 
+    // Lightning routes
+    let lightning_routes = Router::new()
+        .route(
+            "/lnurl",
+            post(handlers::create_lnurl_pay_link).get(handlers::list_lnurl_pay_links),
+        )
+        .route("/refund", post(handlers::lightning_refund))
+        .route("/payout", post(handlers::lightning_payout))
+        .route("/hold-invoices", post(handlers::create_hold_invoice))
+        .route("/hold-invoices/:payment_id/settle", post(handlers::settle_hold_invoice))
+        .route("/hold-invoices/:payment_id/cancel", post(handlers::cancel_hold_invoice));
+
     // Protected API routes
     let api_routes = Router::new()
         .nest("/razorpay", razorpay_routes)
         .nest("/crypto", crypto_routes)
+        .nest("/lightning", lightning_routes)
         .layer(middleware::from_fn_with_state(state.clone(), api_key_auth));
 
     // WebSocket routes
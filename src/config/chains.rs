@@ -0,0 +1,293 @@
+use serde::Deserialize;
+use std::collections::HashSet;
+
+use crate::models::{is_valid_chain_name, CurrencyType};
+
+/// One entry of the EVM chain registry
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvmChainConfig {
+    /// Registry name, used as `chain` in API requests and stored on payments
+    pub name: String,
+    pub chain_id: u64,
+    /// HTTP RPC endpoints; requests fail over between them by health
+    pub rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    /// Race a request against the next endpoint when the first is this slow
+    #[serde(default)]
+    pub hedge_after_ms: Option<u64>,
+    /// Endpoints that must agree on block numbers and receipts; 1 disables quorum reads
+    #[serde(default = "default_quorum")]
+    pub quorum: usize,
+    /// Blocks an endpoint's head may trail the best known head before it is treated as stale
+    #[serde(default = "default_max_head_lag")]
+    pub max_head_lag: u64,
+    pub native_currency: CurrencyType,
+    #[serde(default = "default_native_decimals")]
+    pub native_decimals: u8,
+    #[serde(default)]
+    pub confirmations: ConfirmationPolicy,
+    /// ERC-20 contracts accepted for stablecoin payments
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
+}
+
+/// Confirmations required by payment size. Native payments are medium above
+/// 1 and large above 10 whole coins; stablecoins above $2,500 and $25,000.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfirmationPolicy {
+    pub small: i32,
+    pub medium: i32,
+    pub large: i32,
+}
+
+impl Default for ConfirmationPolicy {
+    fn default() -> Self {
+        Self {
+            small: 3,
+            medium: 6,
+            large: 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenConfig {
+    pub symbol: String,
+    pub address: String,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainsFile {
+    chains: Vec<EvmChainConfig>,
+}
+
+fn default_native_decimals() -> u8 {
+    18
+}
+
+fn default_quorum() -> usize {
+    1
+}
+
+fn default_max_head_lag() -> u64 {
+    5
+}
+
+/// Comma-separated `{prefix}_RPC_URL` plus the optional hedging and quorum settings
+fn rpc_settings(config: &config::Config, prefix: &str) -> (Vec<String>, Option<u64>, usize) {
+    let rpc_urls = config
+        .get_string(&format!("{}.rpc.url", prefix))
+        .unwrap_or_default()
+        .split(',')
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect();
+    let hedge_after_ms = config
+        .get_int(&format!("{}.rpc.hedge.ms", prefix))
+        .ok()
+        .map(|ms| ms as u64);
+    let quorum = config
+        .get_int(&format!("{}.rpc.quorum", prefix))
+        .map(|quorum| quorum.max(1) as usize)
+        .unwrap_or(1);
+
+    (rpc_urls, hedge_after_ms, quorum)
+}
+
+/// Load the registry from a TOML or JSON file with a `chains` list
+pub fn load_chains_file(path: &str) -> Result<Vec<EvmChainConfig>, config::ConfigError> {
+    let file: ChainsFile = config::Config::builder()
+        .add_source(config::File::with_name(path))
+        .build()?
+        .try_deserialize()?;
+
+    Ok(file.chains)
+}
+
+/// Registry built from the per-chain `ETH_*`, `POLYGON_*`, `BSC_*` and
+/// `ARBITRUM_*` variables, for deployments without a chains file
+pub fn legacy_chains(config: &config::Config) -> Result<Vec<EvmChainConfig>, config::ConfigError> {
+    // Required, as before the registry existed
+    config.get_string("eth.rpc.url")?;
+    let (rpc_urls, hedge_after_ms, quorum) = rpc_settings(config, "eth");
+
+    let mut chains = vec![EvmChainConfig {
+        name: "ethereum".to_string(),
+        chain_id: config.get_int("eth.chain.id").unwrap_or(1) as u64,
+        rpc_urls,
+        ws_url: config.get_string("eth.ws.url").ok(),
+        hedge_after_ms,
+        quorum,
+        max_head_lag: default_max_head_lag(),
+        native_currency: CurrencyType::ETH,
+        native_decimals: 18,
+        confirmations: ConfirmationPolicy::default(),
+        tokens: Vec::new(),
+    }];
+
+    let optional = [
+        // Faster block times, need more confirmations
+        ("polygon", 137, CurrencyType::MATIC, (10, 25, 50)),
+        ("bsc", 56, CurrencyType::BNB, (10, 25, 50)),
+        // L2, faster finality
+        ("arbitrum", 42161, CurrencyType::ETH, (5, 5, 20)),
+    ];
+
+    for (name, default_chain_id, native_currency, (small, medium, large)) in optional {
+        let (rpc_urls, hedge_after_ms, quorum) = rpc_settings(config, name);
+        if rpc_urls.is_empty() {
+            continue;
+        }
+
+        chains.push(EvmChainConfig {
+            name: name.to_string(),
+            chain_id: config
+                .get_int(&format!("{}.chain.id", name))
+                .unwrap_or(default_chain_id) as u64,
+            rpc_urls,
+            ws_url: config.get_string(&format!("{}.ws.url", name)).ok(),
+            hedge_after_ms,
+            quorum,
+            max_head_lag: default_max_head_lag(),
+            native_currency,
+            native_decimals: 18,
+            confirmations: ConfirmationPolicy {
+                small,
+                medium,
+                large,
+            },
+            tokens: Vec::new(),
+        });
+    }
+
+    for chain in &mut chains {
+        chain.tokens = known_tokens(chain.chain_id);
+    }
+
+    Ok(chains)
+}
+
+/// Stablecoin contracts of the chains supported before the registry existed
+const KNOWN_TOKENS: &[(u64, &str, &str, u8)] = &[
+    (1, "USDT", "0xdAC17F958D2ee523a2206206994597C13D831ec7", 6),
+    (1, "USDC", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 6),
+    (137, "USDT", "0xc2132D05D31c914a87C6611C10748AEb04B58e8F", 6),
+    (137, "USDC", "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359", 6),
+    (56, "USDT", "0x55d398326f99059fF775485246999027B3197955", 18),
+    (56, "USDC", "0x8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d", 18),
+    (42161, "USDT", "0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9", 6),
+    (42161, "USDC", "0xaf88d065e77c8cC2239327C5EDb3A432268e5831", 6),
+];
+
+fn known_tokens(chain_id: u64) -> Vec<TokenConfig> {
+    KNOWN_TOKENS
+        .iter()
+        .filter(|(id, _, _, _)| *id == chain_id)
+        .map(|(_, symbol, address, decimals)| TokenConfig {
+            symbol: symbol.to_string(),
+            address: address.to_string(),
+            decimals: *decimals,
+        })
+        .collect()
+}
+
+/// Reject registries that would make chain lookups ambiguous
+pub fn validate_chains(chains: &[EvmChainConfig]) -> Result<(), config::ConfigError> {
+    let mut names = HashSet::new();
+    let mut chain_ids = HashSet::new();
+
+    for chain in chains {
+        let error = |reason: &str| {
+            config::ConfigError::Message(format!("EVM chain '{}': {}", chain.name, reason))
+        };
+
+        if !is_valid_chain_name(&chain.name) {
+            return Err(error("name must be lowercase letters, digits, '-' or '_'"));
+        }
+        if matches!(chain.name.as_str(), "solana" | "bitcoin" | "lightning") {
+            return Err(error("name is reserved"));
+        }
+        if !names.insert(chain.name.as_str()) {
+            return Err(error("duplicate name"));
+        }
+        if !chain_ids.insert(chain.chain_id) {
+            return Err(error("duplicate chain id"));
+        }
+        let endpoints = chain.rpc_urls.iter().filter(|url| !url.is_empty()).count();
+        if endpoints == 0 {
+            return Err(error("at least one RPC URL is required"));
+        }
+        if chain.quorum == 0 || chain.quorum > endpoints {
+            return Err(error("quorum must be between 1 and the number of RPC URLs"));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chains_file_format() {
+        let json = r#"{
+            "chains": [{
+                "name": "base",
+                "chain_id": 8453,
+                "rpc_urls": ["https://mainnet.base.org"],
+                "native_currency": "ETH",
+                "confirmations": { "small": 5, "medium": 10, "large": 20 },
+                "tokens": [{
+                    "symbol": "USDC",
+                    "address": "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+                    "decimals": 6
+                }]
+            }]
+        }"#;
+
+        let file: ChainsFile = config::Config::builder()
+            .add_source(config::File::from_str(json, config::FileFormat::Json))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        let base = &file.chains[0];
+        assert_eq!(base.name, "base");
+        assert_eq!(base.native_currency, CurrencyType::ETH);
+        assert_eq!(base.native_decimals, 18);
+        assert_eq!(base.confirmations.large, 20);
+        assert_eq!(base.tokens[0].decimals, 6);
+        assert!(validate_chains(&file.chains).is_ok());
+    }
+
+    #[test]
+    fn test_validate_chains() {
+        let chain = |name: &str, chain_id: u64| EvmChainConfig {
+            name: name.to_string(),
+            chain_id,
+            rpc_urls: vec!["http://localhost:8545".to_string()],
+            ws_url: None,
+            hedge_after_ms: None,
+            quorum: 1,
+            max_head_lag: 5,
+            native_currency: CurrencyType::ETH,
+            native_decimals: 18,
+            confirmations: ConfirmationPolicy::default(),
+            tokens: Vec::new(),
+        };
+
+        assert!(validate_chains(&[chain("ethereum", 1), chain("optimism", 10)]).is_ok());
+        assert!(validate_chains(&[chain("ethereum", 1), chain("ethereum", 10)]).is_err());
+        assert!(validate_chains(&[chain("ethereum", 1), chain("mainnet", 1)]).is_err());
+        assert!(validate_chains(&[chain("Base", 8453)]).is_err());
+        assert!(validate_chains(&[chain("solana", 900)]).is_err());
+
+        let mut quorum = chain("ethereum", 1);
+        quorum.quorum = 2;
+        assert!(validate_chains(&[quorum]).is_err());
+    }
+}
@@ -1,3 +1,7 @@
+mod chains;
+
+pub use chains::{ConfirmationPolicy, EvmChainConfig, TokenConfig};
+
 use serde::Deserialize;
 use std::sync::Arc;
 
@@ -6,15 +10,16 @@ pub struct Config {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub razorpay: RazorpayConfig,
-    pub ethereum: EthereumConfig,
-    pub polygon: ChainConfig,
-    pub bsc: ChainConfig,
-    pub arbitrum: ChainConfig,
+    /// EVM chain registry, from `EVM_CHAINS_FILE` or the per-chain variables
+    pub evm_chains: Vec<EvmChainConfig>,
     pub solana: SolanaConfig,
+    pub bitcoin: BitcoinConfig,
     pub lightning: LightningConfig,
+    pub hd_wallet: HdWalletConfig,
     pub security: SecurityConfig,
     pub websocket: WebSocketConfig,
     pub rate_limit: RateLimitConfig,
+    pub monitor: MonitorConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -34,31 +39,65 @@ pub struct RazorpayConfig {
     pub key_id: String,
     pub key_secret: String,
     pub webhook_secret: String,
+    /// RazorpayX account payouts are debited from; payouts are disabled without it
+    pub payout_account_number: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct EthereumConfig {
+pub struct SolanaConfig {
     pub rpc_url: String,
-    pub ws_url: Option<String>,
-    pub chain_id: u64,
+    /// Commitment level for reads: processed, confirmed or finalized
+    pub commitment: String,
+    /// Per-request RPC timeout
+    pub timeout_secs: u64,
+    /// Retries of RPC calls that failed in transport before giving up
+    pub max_retries: u32,
+    /// SPL mint accepted for USDC payments (mainnet USDC by default)
+    pub usdc_mint: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct ChainConfig {
-    pub rpc_url: String,
-    pub chain_id: u64,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct SolanaConfig {
-    pub rpc_url: String,
+pub struct BitcoinConfig {
+    /// Esplora-compatible REST API (blockstream.info, mempool.space or electrs)
+    pub esplora_url: String,
+    /// bitcoin, testnet, signet or regtest
+    pub network: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct LightningConfig {
+    /// Node implementation behind `node_url`: lnd or cln
+    pub backend: String,
     pub node_url: String,
+    /// LND macaroon file
     pub macaroon_path: Option<String>,
     pub tls_cert_path: Option<String>,
+    /// Core Lightning commando rune for clnrest
+    pub rune: Option<String>,
+    /// Public base URL serving the LNURL-pay endpoints; its host is the
+    /// Lightning Address domain
+    pub lnurl_base_url: Option<String>,
+    /// Network outgoing invoices must be for, defaults to `BITCOIN_NETWORK`
+    pub network: String,
+    /// Routing fee budget for outgoing payments, in parts per million of the amount
+    pub max_fee_ppm: u64,
+    /// Lower bound of the routing fee budget, so small payments can still route
+    pub min_fee_limit_sat: u64,
+    /// CLTV delta requested for hold invoices, bounding how long a payment can be held
+    pub hold_cltv_expiry: u32,
+    /// Blocks before the held HTLCs expire at which an uncaptured hold invoice is cancelled
+    pub hold_cancel_margin_blocks: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HdWalletConfig {
+    /// Hex-encoded BIP39 seed, encrypted with `SecurityConfig::encryption_key`
+    pub encrypted_seed: Option<String>,
+    /// Account-level xpub (m/44'/60'/0') for watch-only EVM address derivation
+    pub evm_xpub: Option<String>,
+    /// Account-level xpub for watch-only P2WPKH address derivation: m/84'/0'/0' on
+    /// mainnet, m/84'/1'/0' on testnet, signet and regtest
+    pub bitcoin_xpub: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -80,6 +119,15 @@ pub struct RateLimitConfig {
     pub burst_size: u32,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct MonitorConfig {
+    /// Run background deposit scanners
+    pub enabled: bool,
+    pub poll_interval_secs: u64,
+    /// Upper bound on blocks scanned per poll, so catching up stays within RPC limits
+    pub max_blocks_per_poll: u64,
+}
+
 impl Config {
     pub fn from_env() -> Result<Self, config::ConfigError> {
         let config = config::Config::builder()
@@ -93,6 +141,35 @@ impl Config {
             .add_source(config::Environment::default().separator("_").try_parsing(true))
             .build()?;
 
+        let evm_chains = match config.get_string("evm.chains.file") {
+            Ok(path) => chains::load_chains_file(&path)?,
+            Err(_) => chains::legacy_chains(&config)?,
+        };
+        chains::validate_chains(&evm_chains)?;
+
+        let solana_commitment = config
+            .get_string("solana.commitment")
+            .unwrap_or_else(|_| "confirmed".to_string());
+        if !matches!(solana_commitment.as_str(), "processed" | "confirmed" | "finalized") {
+            return Err(config::ConfigError::Message(format!(
+                "SOLANA_COMMITMENT must be processed, confirmed or finalized, got '{}'",
+                solana_commitment
+            )));
+        }
+
+        let hold_cltv_expiry = config.get_int("lightning.hold.cltv.expiry").unwrap_or(144) as u32;
+        let hold_cancel_margin_blocks = config
+            .get_int("lightning.hold.cancel.margin.blocks")
+            .unwrap_or(12) as u32;
+        if hold_cancel_margin_blocks >= hold_cltv_expiry {
+            return Err(config::ConfigError::Message(format!(
+                "LIGHTNING_HOLD_CANCEL_MARGIN_BLOCKS ({}) must be below LIGHTNING_HOLD_CLTV_EXPIRY ({})",
+                hold_cancel_margin_blocks, hold_cltv_expiry
+            )));
+        }
+
+        let lnurl_base_url = lnurl_base_url(config.get_string("lnurl.base.url").ok())?;
+
         // Manual construction due to environment variable naming
         Ok(Config {
             server: ServerConfig {
@@ -107,31 +184,48 @@ impl Config {
                 key_id: config.get_string("razorpay.key.id")?,
                 key_secret: config.get_string("razorpay.key.secret")?,
                 webhook_secret: config.get_string("razorpay.webhook.secret")?,
+                payout_account_number: config.get_string("razorpay.payout.account.number").ok(),
             },
-            ethereum: EthereumConfig {
-                rpc_url: config.get_string("eth.rpc.url")?,
-                ws_url: config.get_string("eth.ws.url").ok(),
-                chain_id: config.get_int("eth.chain.id").unwrap_or(1) as u64,
-            },
-            polygon: ChainConfig {
-                rpc_url: config.get_string("polygon.rpc.url").unwrap_or_default(),
-                chain_id: config.get_int("polygon.chain.id").unwrap_or(137) as u64,
-            },
-            bsc: ChainConfig {
-                rpc_url: config.get_string("bsc.rpc.url").unwrap_or_default(),
-                chain_id: config.get_int("bsc.chain.id").unwrap_or(56) as u64,
-            },
-            arbitrum: ChainConfig {
-                rpc_url: config.get_string("arbitrum.rpc.url").unwrap_or_default(),
-                chain_id: config.get_int("arbitrum.chain.id").unwrap_or(42161) as u64,
-            },
+            evm_chains,
             solana: SolanaConfig {
                 rpc_url: config.get_string("solana.rpc.url")?,
+                commitment: solana_commitment,
+                timeout_secs: config.get_int("solana.rpc.timeout.secs").unwrap_or(30) as u64,
+                max_retries: config.get_int("solana.rpc.max.retries").unwrap_or(3) as u32,
+                usdc_mint: config
+                    .get_string("solana.usdc.mint")
+                    .unwrap_or_else(|_| "EPjFWdd5AufqSSqeM2qFJbbdyHa5AWVCyQ4K7ZUMU6pKmzS".to_string()),
+            },
+            bitcoin: BitcoinConfig {
+                esplora_url: config.get_string("bitcoin.esplora.url").unwrap_or_default(),
+                network: config
+                    .get_string("bitcoin.network")
+                    .unwrap_or_else(|_| "bitcoin".to_string()),
             },
             lightning: LightningConfig {
+                backend: config
+                    .get_string("lightning.backend")
+                    .unwrap_or_else(|_| "lnd".to_string())
+                    .to_lowercase(),
                 node_url: config.get_string("lightning.node.url").unwrap_or_default(),
                 macaroon_path: config.get_string("lightning.macaroon.path").ok(),
                 tls_cert_path: config.get_string("lightning.tls.cert.path").ok(),
+                rune: config.get_string("lightning.rune").ok(),
+                lnurl_base_url,
+                network: config
+                    .get_string("lightning.network")
+                    .or_else(|_| config.get_string("bitcoin.network"))
+                    .unwrap_or_else(|_| "bitcoin".to_string())
+                    .to_lowercase(),
+                max_fee_ppm: config.get_int("lightning.max.fee.ppm").unwrap_or(5_000) as u64,
+                min_fee_limit_sat: config.get_int("lightning.min.fee.limit.sat").unwrap_or(10) as u64,
+                hold_cltv_expiry,
+                hold_cancel_margin_blocks,
+            },
+            hd_wallet: HdWalletConfig {
+                encrypted_seed: config.get_string("hd.wallet.encrypted.seed").ok(),
+                evm_xpub: config.get_string("hd.wallet.evm.xpub").ok(),
+                bitcoin_xpub: config.get_string("hd.wallet.bitcoin.xpub").ok(),
             },
             security: SecurityConfig {
                 api_key_hash_secret: config.get_string("api.key.hash.secret")?,
@@ -146,8 +240,137 @@ impl Config {
                 requests_per_second: config.get_int("rate.limit.requests.per.second").unwrap_or(100) as u32,
                 burst_size: config.get_int("rate.limit.burst.size").unwrap_or(200) as u32,
             },
+            monitor: MonitorConfig {
+                enabled: config.get_bool("monitor.enabled").unwrap_or(true),
+                poll_interval_secs: config.get_int("monitor.poll.interval.secs").unwrap_or(15) as u64,
+                max_blocks_per_poll: config.get_int("monitor.max.blocks.per.poll").unwrap_or(100) as u64,
+            },
         })
     }
 }
 
+/// `LNURL_BASE_URL` without its trailing slash, `None` when unset or empty. Wallets
+/// resolve Lightning Addresses against its host, so anything else is rejected.
+fn lnurl_base_url(value: Option<String>) -> Result<Option<String>, config::ConfigError> {
+    let Some(value) = value.map(|url| url.trim().trim_end_matches('/').to_string()) else {
+        return Ok(None);
+    };
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    match reqwest::Url::parse(&value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host_str().is_some() => {
+            Ok(Some(value))
+        }
+        _ => Err(config::ConfigError::Message(format!(
+            "LNURL_BASE_URL must be an http(s) URL with a host, got '{}'",
+            value
+        ))),
+    }
+}
+
 pub type SharedConfig = Arc<Config>;
+
+#[cfg(test)]
+impl Config {
+    /// Defaults for tests against the database at `DATABASE_URL`, built directly so
+    /// tests never set process-wide variables under each other. Node URLs point at
+    /// local ports that only tests talking to a node need to serve.
+    pub fn for_tests() -> Self {
+        Config {
+            server: ServerConfig {
+                host: "127.0.0.1".to_string(),
+                port: 8080,
+            },
+            database: DatabaseConfig {
+                url: std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+                max_connections: 5,
+            },
+            razorpay: RazorpayConfig {
+                key_id: "rzp_test".to_string(),
+                key_secret: "secret".to_string(),
+                webhook_secret: "secret".to_string(),
+                payout_account_number: None,
+            },
+            evm_chains: vec![EvmChainConfig {
+                name: "ethereum".to_string(),
+                chain_id: 1,
+                rpc_urls: vec!["http://127.0.0.1:8545".to_string()],
+                ws_url: None,
+                hedge_after_ms: None,
+                quorum: 1,
+                max_head_lag: 5,
+                native_currency: crate::models::CurrencyType::ETH,
+                native_decimals: 18,
+                confirmations: ConfirmationPolicy::default(),
+                tokens: Vec::new(),
+            }],
+            solana: SolanaConfig {
+                rpc_url: "http://127.0.0.1:8899".to_string(),
+                commitment: "confirmed".to_string(),
+                timeout_secs: 5,
+                max_retries: 0,
+                usdc_mint: "EPjFWdd5AufqSSqeM2qFJbbdyHa5AWVCyQ4K7ZUMU6pKmzS".to_string(),
+            },
+            bitcoin: BitcoinConfig {
+                esplora_url: String::new(),
+                network: "regtest".to_string(),
+            },
+            lightning: LightningConfig {
+                backend: "lnd".to_string(),
+                node_url: String::new(),
+                macaroon_path: None,
+                tls_cert_path: None,
+                rune: None,
+                lnurl_base_url: None,
+                network: "regtest".to_string(),
+                max_fee_ppm: 5_000,
+                min_fee_limit_sat: 10,
+                hold_cltv_expiry: 144,
+                hold_cancel_margin_blocks: 12,
+            },
+            hd_wallet: HdWalletConfig {
+                encrypted_seed: None,
+                evm_xpub: None,
+                bitcoin_xpub: None,
+            },
+            security: SecurityConfig {
+                api_key_hash_secret: "secret".to_string(),
+                jwt_secret: "secret".to_string(),
+                encryption_key: "0123456789abcdef0123456789abcdef".to_string(),
+            },
+            websocket: WebSocketConfig {
+                heartbeat_interval: 30,
+                client_timeout: 60,
+            },
+            rate_limit: RateLimitConfig {
+                requests_per_second: 100,
+                burst_size: 200,
+            },
+            monitor: MonitorConfig {
+                enabled: false,
+                poll_interval_secs: 15,
+                max_blocks_per_poll: 100,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lnurl_base_url() {
+        assert_eq!(lnurl_base_url(None).unwrap(), None);
+        assert_eq!(lnurl_base_url(Some(String::new())).unwrap(), None);
+        assert_eq!(lnurl_base_url(Some("  ".to_string())).unwrap(), None);
+        assert_eq!(
+            lnurl_base_url(Some("https://pay.example.com/".to_string())).unwrap(),
+            Some("https://pay.example.com".to_string())
+        );
+        assert!(lnurl_base_url(Some("pay.example.com".to_string())).is_err());
+        assert!(lnurl_base_url(Some("ftp://pay.example.com".to_string())).is_err());
+    }
+}
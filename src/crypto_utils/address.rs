@@ -8,7 +8,7 @@ impl AddressValidator {
     /// Validate an address for a specific chain
     pub fn validate(address: &str, chain: &ChainType) -> bool {
         match chain {
-            ChainType::Ethereum | ChainType::Polygon | ChainType::Bsc | ChainType::Arbitrum => {
+            ChainType::Evm(_) => {
                 Self::is_valid_evm_address(address)
             }
             ChainType::Solana => Self::is_valid_solana_address(address),
@@ -102,7 +102,7 @@ impl AddressValidator {
     /// Get the chain type from an address (heuristic)
     pub fn detect_chain(address: &str) -> Option<ChainType> {
         if address.starts_with("0x") && address.len() == 42 {
-            Some(ChainType::Ethereum) // Could be any EVM chain
+            Some(ChainType::evm("ethereum")) // Could be any EVM chain
         } else if Self::is_valid_solana_address(address) {
             Some(ChainType::Solana)
         } else if Self::is_valid_bitcoin_address(address) {
@@ -180,7 +180,7 @@ mod tests {
     fn test_chain_detection() {
         assert_eq!(
             AddressValidator::detect_chain("0x742d35Cc6634C0532925a3b844Bc9e7595f1E8e4"),
-            Some(ChainType::Ethereum)
+            Some(ChainType::evm("ethereum"))
         );
     }
 }
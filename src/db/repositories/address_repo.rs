@@ -8,6 +8,7 @@ use crate::models::{ChainType, CryptoAddress};
 pub struct AddressRepository;
 
 impl AddressRepository {
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         pool: &PgPool,
         address: &str,
@@ -16,6 +17,8 @@ impl AddressRepository {
         expected_amount: Option<i64>,
         label: Option<&str>,
         token_address: Option<&str>,
+        derivation_path: Option<&str>,
+        derivation_index: Option<i32>,
     ) -> AppResult<CryptoAddress> {
         let id = Uuid::new_v4();
         let now = Utc::now();
@@ -25,14 +28,16 @@ impl AddressRepository {
             r#"
             INSERT INTO crypto_addresses (
                 id, address, chain, payment_id, expected_amount,
-                label, token_address, is_active, created_at, updated_at
+                label, token_address, derivation_path, derivation_index,
+                is_active, created_at, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING
                 id, payment_id, address,
                 chain as "chain: ChainType",
                 is_active, label, expected_amount, received_amount,
-                token_address, last_checked_at, created_at, updated_at
+                token_address, derivation_path, derivation_index,
+                last_checked_at, created_at, updated_at
             "#,
             id,
             address,
@@ -41,6 +46,8 @@ impl AddressRepository {
             expected_amount,
             label,
             token_address,
+            derivation_path,
+            derivation_index,
             true,
             now,
             now
@@ -63,7 +70,8 @@ impl AddressRepository {
                 id, payment_id, address,
                 chain as "chain: ChainType",
                 is_active, label, expected_amount, received_amount,
-                token_address, last_checked_at, created_at, updated_at
+                token_address, derivation_path, derivation_index,
+                last_checked_at, created_at, updated_at
             FROM crypto_addresses
             WHERE address = $1 AND chain = $2
             "#,
@@ -87,7 +95,8 @@ impl AddressRepository {
                 id, payment_id, address,
                 chain as "chain: ChainType",
                 is_active, label, expected_amount, received_amount,
-                token_address, last_checked_at, created_at, updated_at
+                token_address, derivation_path, derivation_index,
+                last_checked_at, created_at, updated_at
             FROM crypto_addresses
             WHERE payment_id = $1
             "#,
@@ -110,7 +119,8 @@ impl AddressRepository {
                 id, payment_id, address,
                 chain as "chain: ChainType",
                 is_active, label, expected_amount, received_amount,
-                token_address, last_checked_at, created_at, updated_at
+                token_address, derivation_path, derivation_index,
+                last_checked_at, created_at, updated_at
             FROM crypto_addresses
             WHERE chain = $1 AND is_active = true
             ORDER BY created_at DESC
@@ -123,6 +133,38 @@ impl AddressRepository {
         Ok(addrs)
     }
 
+    /// Add `amount` to the address's received total in the database, so concurrent
+    /// credits are not lost to a stale read
+    pub async fn add_received_amount(
+        pool: &PgPool,
+        id: Uuid,
+        amount: i64,
+    ) -> AppResult<CryptoAddress> {
+        let addr = sqlx::query_as!(
+            CryptoAddress,
+            r#"
+            UPDATE crypto_addresses
+            SET received_amount = COALESCE(received_amount, 0) + $2,
+                last_checked_at = $3, updated_at = $3
+            WHERE id = $1
+            RETURNING
+                id, payment_id, address,
+                chain as "chain: ChainType",
+                is_active, label, expected_amount, received_amount,
+                token_address, derivation_path, derivation_index,
+                last_checked_at, created_at, updated_at
+            "#,
+            id,
+            amount,
+            Utc::now()
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Address {} not found", id)))?;
+
+        Ok(addr)
+    }
+
     pub async fn update_received_amount(
         pool: &PgPool,
         id: Uuid,
@@ -138,7 +180,8 @@ impl AddressRepository {
                 id, payment_id, address,
                 chain as "chain: ChainType",
                 is_active, label, expected_amount, received_amount,
-                token_address, last_checked_at, created_at, updated_at
+                token_address, derivation_path, derivation_index,
+                last_checked_at, created_at, updated_at
             "#,
             id,
             received_amount,
@@ -152,6 +195,25 @@ impl AddressRepository {
         Ok(addr)
     }
 
+    /// Reserve the next HD derivation index for a chain's derivation scope
+    pub async fn next_derivation_index(pool: &PgPool, chain: &ChainType) -> AppResult<i32> {
+        let index = sqlx::query_scalar!(
+            r#"
+            INSERT INTO derivation_indices (chain, next_index, updated_at)
+            VALUES ($1, 1, $2)
+            ON CONFLICT (chain) DO UPDATE
+            SET next_index = derivation_indices.next_index + 1, updated_at = $2
+            RETURNING next_index - 1 as "index!"
+            "#,
+            chain.derivation_scope(),
+            Utc::now()
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(index)
+    }
+
     pub async fn deactivate(pool: &PgPool, id: Uuid) -> AppResult<()> {
         sqlx::query!(
             r#"
@@ -0,0 +1,58 @@
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::error::AppResult;
+
+pub struct ScanCheckpointRepository;
+
+impl ScanCheckpointRepository {
+    pub async fn get_last_block(pool: &PgPool, chain: &str) -> AppResult<Option<i64>> {
+        let last_block = sqlx::query_scalar!(
+            r#"
+            SELECT last_block FROM chain_scan_checkpoints
+            WHERE chain = $1
+            "#,
+            chain
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(last_block)
+    }
+
+    pub async fn set_last_block(pool: &PgPool, chain: &str, last_block: i64) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO chain_scan_checkpoints (chain, last_block, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (chain) DO UPDATE
+            SET last_block = $2, updated_at = $3
+            "#,
+            chain,
+            last_block,
+            Utc::now()
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move the checkpoint back so blocks after `last_block` are scanned again
+    pub async fn rewind_to(pool: &PgPool, chain: &str, last_block: i64) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE chain_scan_checkpoints
+            SET last_block = LEAST(last_block, $2), updated_at = $3
+            WHERE chain = $1
+            "#,
+            chain,
+            last_block,
+            Utc::now()
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
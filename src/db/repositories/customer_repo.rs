@@ -0,0 +1,103 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Customer, CustomerDetails};
+
+pub struct CustomerRepository;
+
+impl CustomerRepository {
+    /// Store a Razorpay customer, or refresh the customer already mapped to it
+    /// (Razorpay hands back the existing customer for a known email and contact)
+    pub async fn upsert(
+        pool: &PgPool,
+        razorpay_customer_id: &str,
+        details: &CustomerDetails,
+    ) -> AppResult<Customer> {
+        let now = Utc::now();
+
+        let customer = sqlx::query_as!(
+            Customer,
+            r#"
+            INSERT INTO customers (
+                id, razorpay_customer_id, name, email, contact, gstin, notes,
+                created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (razorpay_customer_id) DO UPDATE
+            SET name = EXCLUDED.name,
+                email = EXCLUDED.email,
+                contact = EXCLUDED.contact,
+                gstin = EXCLUDED.gstin,
+                notes = EXCLUDED.notes,
+                updated_at = EXCLUDED.updated_at
+            RETURNING
+                id, razorpay_customer_id, name, email, contact, gstin, notes,
+                created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            razorpay_customer_id,
+            details.name,
+            details.email,
+            details.contact,
+            details.gstin,
+            details.notes,
+            now,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(customer)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> AppResult<Customer> {
+        let customer = sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT
+                id, razorpay_customer_id, name, email, contact, gstin, notes,
+                created_at, updated_at
+            FROM customers
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Customer {} not found", id)))?;
+
+        Ok(customer)
+    }
+
+    pub async fn update_details(
+        pool: &PgPool,
+        id: Uuid,
+        details: &CustomerDetails,
+    ) -> AppResult<Customer> {
+        let customer = sqlx::query_as!(
+            Customer,
+            r#"
+            UPDATE customers
+            SET name = $2, email = $3, contact = $4, gstin = $5, notes = $6, updated_at = $7
+            WHERE id = $1
+            RETURNING
+                id, razorpay_customer_id, name, email, contact, gstin, notes,
+                created_at, updated_at
+            "#,
+            id,
+            details.name,
+            details.email,
+            details.contact,
+            details.gstin,
+            details.notes,
+            Utc::now()
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Customer {} not found", id)))?;
+
+        Ok(customer)
+    }
+}
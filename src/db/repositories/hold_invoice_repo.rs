@@ -0,0 +1,119 @@
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{HoldInvoice, HoldInvoiceState};
+
+pub struct HoldInvoiceRepository;
+
+impl HoldInvoiceRepository {
+    /// Store the preimage for a new hold invoice. Takes a connection so it is
+    /// written in the same DB transaction as its payment.
+    pub async fn create(
+        conn: &mut PgConnection,
+        payment_id: Uuid,
+        payment_hash: &str,
+        encrypted_preimage: &str,
+    ) -> AppResult<HoldInvoice> {
+        let now = Utc::now();
+
+        let hold_invoice = sqlx::query_as!(
+            HoldInvoice,
+            r#"
+            INSERT INTO lightning_hold_invoices (
+                payment_id, payment_hash, encrypted_preimage, state, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING
+                payment_id, payment_hash, encrypted_preimage,
+                state as "state: HoldInvoiceState",
+                htlc_expiry_height, created_at, updated_at
+            "#,
+            payment_id,
+            payment_hash,
+            encrypted_preimage,
+            HoldInvoiceState::Open as HoldInvoiceState,
+            now,
+            now
+        )
+        .fetch_one(conn)
+        .await?;
+
+        Ok(hold_invoice)
+    }
+
+    pub async fn find_by_payment_id(pool: &PgPool, payment_id: Uuid) -> AppResult<HoldInvoice> {
+        let hold_invoice = sqlx::query_as!(
+            HoldInvoice,
+            r#"
+            SELECT
+                payment_id, payment_hash, encrypted_preimage,
+                state as "state: HoldInvoiceState",
+                htlc_expiry_height, created_at, updated_at
+            FROM lightning_hold_invoices
+            WHERE payment_id = $1
+            "#,
+            payment_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No hold invoice for payment {}", payment_id)))?;
+
+        Ok(hold_invoice)
+    }
+
+    /// Hold invoices that are still open or held
+    pub async fn find_unresolved(pool: &PgPool) -> AppResult<Vec<HoldInvoice>> {
+        let hold_invoices = sqlx::query_as!(
+            HoldInvoice,
+            r#"
+            SELECT
+                payment_id, payment_hash, encrypted_preimage,
+                state as "state: HoldInvoiceState",
+                htlc_expiry_height, created_at, updated_at
+            FROM lightning_hold_invoices
+            WHERE state IN ('open', 'accepted')
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(hold_invoices)
+    }
+
+    /// Move a hold invoice to `state` if it is currently in one of `from`,
+    /// returning `None` if another worker got there first
+    pub async fn transition(
+        pool: &PgPool,
+        payment_id: Uuid,
+        from: &[HoldInvoiceState],
+        state: HoldInvoiceState,
+        htlc_expiry_height: Option<i32>,
+    ) -> AppResult<Option<HoldInvoice>> {
+        let hold_invoice = sqlx::query_as!(
+            HoldInvoice,
+            r#"
+            UPDATE lightning_hold_invoices
+            SET state = $3,
+                htlc_expiry_height = COALESCE($4, htlc_expiry_height),
+                updated_at = $5
+            WHERE payment_id = $1 AND state = ANY($2)
+            RETURNING
+                payment_id, payment_hash, encrypted_preimage,
+                state as "state: HoldInvoiceState",
+                htlc_expiry_height, created_at, updated_at
+            "#,
+            payment_id,
+            from as &[HoldInvoiceState],
+            state as HoldInvoiceState,
+            htlc_expiry_height,
+            Utc::now()
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(hold_invoice)
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateLnurlPayLinkRequest, LnurlPayLink};
+
+pub struct LnurlPayLinkRepository;
+
+impl LnurlPayLinkRepository {
+    pub async fn create(
+        pool: &PgPool,
+        request: &CreateLnurlPayLinkRequest,
+    ) -> AppResult<LnurlPayLink> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let link = sqlx::query_as!(
+            LnurlPayLink,
+            r#"
+            INSERT INTO lnurl_pay_links (
+                id, name, description, min_sendable_msat, max_sendable_msat,
+                is_active, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING
+                id, name, description, min_sendable_msat, max_sendable_msat,
+                is_active, created_at, updated_at
+            "#,
+            id,
+            request.name,
+            request.description,
+            request.min_sendable_msat,
+            request.max_sendable_msat,
+            true,
+            now,
+            now
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => AppError::Validation(
+                format!("Lightning Address name already taken: {}", request.name),
+            ),
+            e => AppError::Database(e),
+        })?;
+
+        Ok(link)
+    }
+
+    pub async fn find_active_by_name(pool: &PgPool, name: &str) -> AppResult<Option<LnurlPayLink>> {
+        let link = sqlx::query_as!(
+            LnurlPayLink,
+            r#"
+            SELECT
+                id, name, description, min_sendable_msat, max_sendable_msat,
+                is_active, created_at, updated_at
+            FROM lnurl_pay_links
+            WHERE name = $1 AND is_active = true
+            "#,
+            name
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    pub async fn list(pool: &PgPool) -> AppResult<Vec<LnurlPayLink>> {
+        let links = sqlx::query_as!(
+            LnurlPayLink,
+            r#"
+            SELECT
+                id, name, description, min_sendable_msat, max_sendable_msat,
+                is_active, created_at, updated_at
+            FROM lnurl_pay_links
+            ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(links)
+    }
+}
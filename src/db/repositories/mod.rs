@@ -2,8 +2,26 @@ pub mod payment_repo;
 pub mod transaction_repo;
 pub mod webhook_repo;
 pub mod address_repo;
+pub mod checkpoint_repo;
+pub mod lnurl_repo;
+pub mod hold_invoice_repo;
+pub mod subscription_repo;
+pub mod payment_link_repo;
+pub mod transfer_repo;
+pub mod customer_repo;
+pub mod virtual_account_repo;
+pub mod qr_code_repo;
 
 pub use payment_repo::PaymentRepository;
 pub use transaction_repo::TransactionRepository;
 pub use webhook_repo::WebhookRepository;
 pub use address_repo::AddressRepository;
+pub use checkpoint_repo::ScanCheckpointRepository;
+pub use lnurl_repo::LnurlPayLinkRepository;
+pub use hold_invoice_repo::HoldInvoiceRepository;
+pub use subscription_repo::SubscriptionRepository;
+pub use payment_link_repo::PaymentLinkRepository;
+pub use transfer_repo::TransferRepository;
+pub use customer_repo::CustomerRepository;
+pub use virtual_account_repo::VirtualAccountRepository;
+pub use qr_code_repo::QrCodeRepository;
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{PaymentLink, PaymentLinkStatus};
+
+pub struct PaymentLinkRepository;
+
+impl PaymentLinkRepository {
+    pub async fn create(
+        pool: &PgPool,
+        payment_id: Uuid,
+        razorpay_payment_link_id: &str,
+        short_url: &str,
+        expire_by: Option<DateTime<Utc>>,
+    ) -> AppResult<PaymentLink> {
+        let now = Utc::now();
+
+        let link = sqlx::query_as!(
+            PaymentLink,
+            r#"
+            INSERT INTO razorpay_payment_links (
+                payment_id, razorpay_payment_link_id, short_url, status, expire_by,
+                created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING
+                payment_id, razorpay_payment_link_id, short_url,
+                status as "status: PaymentLinkStatus",
+                expire_by, created_at, updated_at
+            "#,
+            payment_id,
+            razorpay_payment_link_id,
+            short_url,
+            PaymentLinkStatus::Created as PaymentLinkStatus,
+            expire_by,
+            now,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    pub async fn find_by_payment_id(pool: &PgPool, payment_id: Uuid) -> AppResult<PaymentLink> {
+        let link = sqlx::query_as!(
+            PaymentLink,
+            r#"
+            SELECT
+                payment_id, razorpay_payment_link_id, short_url,
+                status as "status: PaymentLinkStatus",
+                expire_by, created_at, updated_at
+            FROM razorpay_payment_links
+            WHERE payment_id = $1
+            "#,
+            payment_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No payment link for payment {}", payment_id)))?;
+
+        Ok(link)
+    }
+
+    pub async fn find_by_razorpay_id(
+        pool: &PgPool,
+        razorpay_payment_link_id: &str,
+    ) -> AppResult<Option<PaymentLink>> {
+        let link = sqlx::query_as!(
+            PaymentLink,
+            r#"
+            SELECT
+                payment_id, razorpay_payment_link_id, short_url,
+                status as "status: PaymentLinkStatus",
+                expire_by, created_at, updated_at
+            FROM razorpay_payment_links
+            WHERE razorpay_payment_link_id = $1
+            "#,
+            razorpay_payment_link_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    pub async fn update_status(
+        pool: &PgPool,
+        payment_id: Uuid,
+        status: PaymentLinkStatus,
+    ) -> AppResult<PaymentLink> {
+        let link = sqlx::query_as!(
+            PaymentLink,
+            r#"
+            UPDATE razorpay_payment_links
+            SET status = $2, updated_at = $3
+            WHERE payment_id = $1
+            RETURNING
+                payment_id, razorpay_payment_link_id, short_url,
+                status as "status: PaymentLinkStatus",
+                expire_by, created_at, updated_at
+            "#,
+            payment_id,
+            status as PaymentLinkStatus,
+            Utc::now()
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No payment link for payment {}", payment_id)))?;
+
+        Ok(link)
+    }
+}
@@ -1,10 +1,11 @@
 use chrono::Utc;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
 use crate::models::{
     CreatePaymentRequest, CurrencyType, Payment, PaymentMethod, PaymentStatus,
+    PaymentStatusChange,
 };
 
 pub struct PaymentRepository;
@@ -13,6 +14,15 @@ impl PaymentRepository {
     pub async fn create(
         pool: &PgPool,
         request: &CreatePaymentRequest,
+    ) -> AppResult<Payment> {
+        Self::create_in(&mut *pool.acquire().await?, request).await
+    }
+
+    /// [`create`](Self::create) on a caller's connection, so the payment can be
+    /// part of a larger DB transaction
+    pub async fn create_in(
+        conn: &mut PgConnection,
+        request: &CreatePaymentRequest,
     ) -> AppResult<Payment> {
         let id = Uuid::new_v4();
         let now = Utc::now();
@@ -48,7 +58,60 @@ impl PaymentRepository {
             now,
             now
         )
-        .fetch_one(pool)
+        .fetch_one(conn)
+        .await?;
+
+        Ok(payment)
+    }
+
+    /// Record a payment whose money Razorpay has already collected, such as a
+    /// subscription charge or a bank transfer, on a caller's connection so it is
+    /// written together with the record of how it arrived
+    pub async fn create_completed_in(
+        conn: &mut PgConnection,
+        request: &CreatePaymentRequest,
+        razorpay_payment_id: &str,
+        razorpay_order_id: Option<&str>,
+    ) -> AppResult<Payment> {
+        let now = Utc::now();
+
+        let payment = sqlx::query_as!(
+            Payment,
+            r#"
+            INSERT INTO payments (
+                id, amount, currency, status, method, description,
+                customer_email, customer_phone, metadata,
+                razorpay_payment_id, razorpay_order_id,
+                completed_at, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING
+                id, external_id, order_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: PaymentStatus",
+                method as "method: PaymentMethod",
+                description, customer_email, customer_phone, metadata,
+                razorpay_payment_id, razorpay_order_id, razorpay_signature,
+                crypto_tx_hash, crypto_from_address, crypto_to_address, crypto_chain,
+                lightning_invoice, lightning_payment_hash,
+                expires_at, completed_at, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            request.amount,
+            request.currency.clone() as CurrencyType,
+            PaymentStatus::Completed as PaymentStatus,
+            request.method.clone() as PaymentMethod,
+            request.description,
+            request.customer_email,
+            request.customer_phone,
+            request.metadata,
+            razorpay_payment_id,
+            razorpay_order_id,
+            now,
+            now,
+            now
+        )
+        .fetch_one(conn)
         .await?;
 
         Ok(payment)
@@ -108,6 +171,34 @@ impl PaymentRepository {
         Ok(payment)
     }
 
+    pub async fn find_by_razorpay_payment_id(
+        pool: &PgPool,
+        razorpay_payment_id: &str,
+    ) -> AppResult<Option<Payment>> {
+        let payment = sqlx::query_as!(
+            Payment,
+            r#"
+            SELECT
+                id, external_id, order_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: PaymentStatus",
+                method as "method: PaymentMethod",
+                description, customer_email, customer_phone, metadata,
+                razorpay_payment_id, razorpay_order_id, razorpay_signature,
+                crypto_tx_hash, crypto_from_address, crypto_to_address, crypto_chain,
+                lightning_invoice, lightning_payment_hash,
+                expires_at, completed_at, created_at, updated_at
+            FROM payments
+            WHERE razorpay_payment_id = $1
+            "#,
+            razorpay_payment_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(payment)
+    }
+
     pub async fn update_status(
         pool: &PgPool,
         id: Uuid,
@@ -147,6 +238,136 @@ impl PaymentRepository {
         Ok(payment)
     }
 
+    /// Move a payment that is still waiting for its money to `status` on a
+    /// caller's connection. Returns `None` if the payment had already moved on.
+    pub async fn advance_status_in(
+        conn: &mut PgConnection,
+        id: Uuid,
+        status: PaymentStatus,
+    ) -> AppResult<Option<Payment>> {
+        let now = Utc::now();
+        let completed_at = (status == PaymentStatus::Completed).then_some(now);
+
+        let payment = sqlx::query_as!(
+            Payment,
+            r#"
+            UPDATE payments
+            SET status = $2, completed_at = COALESCE($3, completed_at), updated_at = $4
+            WHERE id = $1 AND status IN ('pending', 'processing')
+            RETURNING
+                id, external_id, order_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: PaymentStatus",
+                method as "method: PaymentMethod",
+                description, customer_email, customer_phone, metadata,
+                razorpay_payment_id, razorpay_order_id, razorpay_signature,
+                crypto_tx_hash, crypto_from_address, crypto_to_address, crypto_chain,
+                lightning_invoice, lightning_payment_hash,
+                expires_at, completed_at, created_at, updated_at
+            "#,
+            id,
+            status as PaymentStatus,
+            completed_at,
+            now
+        )
+        .fetch_optional(conn)
+        .await?;
+
+        Ok(payment)
+    }
+
+    /// Move a payment back to an earlier status, recording the reason in its audit trail
+    pub async fn revert_status(
+        pool: &PgPool,
+        id: Uuid,
+        status: PaymentStatus,
+        reason: &str,
+    ) -> AppResult<Payment> {
+        let mut db_tx = pool.begin().await?;
+
+        let previous_status = sqlx::query_scalar!(
+            r#"
+            SELECT status as "status: PaymentStatus"
+            FROM payments
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Payment {} not found", id)))?;
+
+        let payment = sqlx::query_as!(
+            Payment,
+            r#"
+            UPDATE payments
+            SET status = $2, completed_at = NULL, updated_at = $3
+            WHERE id = $1
+            RETURNING
+                id, external_id, order_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: PaymentStatus",
+                method as "method: PaymentMethod",
+                description, customer_email, customer_phone, metadata,
+                razorpay_payment_id, razorpay_order_id, razorpay_signature,
+                crypto_tx_hash, crypto_from_address, crypto_to_address, crypto_chain,
+                lightning_invoice, lightning_payment_hash,
+                expires_at, completed_at, created_at, updated_at
+            "#,
+            id,
+            status.clone() as PaymentStatus,
+            Utc::now()
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO payment_status_history (
+                id, payment_id, previous_status, new_status, reason, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            Uuid::new_v4(),
+            id,
+            previous_status as PaymentStatus,
+            status as PaymentStatus,
+            reason,
+            Utc::now()
+        )
+        .execute(&mut *db_tx)
+        .await?;
+
+        db_tx.commit().await?;
+
+        Ok(payment)
+    }
+
+    pub async fn find_status_history(
+        pool: &PgPool,
+        payment_id: Uuid,
+    ) -> AppResult<Vec<PaymentStatusChange>> {
+        let history = sqlx::query_as!(
+            PaymentStatusChange,
+            r#"
+            SELECT
+                id, payment_id,
+                previous_status as "previous_status: PaymentStatus",
+                new_status as "new_status: PaymentStatus",
+                reason, created_at
+            FROM payment_status_history
+            WHERE payment_id = $1
+            ORDER BY created_at ASC
+            "#,
+            payment_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(history)
+    }
+
     pub async fn update_razorpay_details(
         pool: &PgPool,
         id: Uuid,
@@ -184,6 +405,40 @@ impl PaymentRepository {
         Ok(payment)
     }
 
+    /// Record the Razorpay payment of a payment collected without an order,
+    /// e.g. through a QR code
+    pub async fn update_razorpay_payment_id(
+        conn: &mut PgConnection,
+        id: Uuid,
+        razorpay_payment_id: &str,
+    ) -> AppResult<Payment> {
+        let payment = sqlx::query_as!(
+            Payment,
+            r#"
+            UPDATE payments
+            SET razorpay_payment_id = $2, updated_at = $3
+            WHERE id = $1
+            RETURNING
+                id, external_id, order_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: PaymentStatus",
+                method as "method: PaymentMethod",
+                description, customer_email, customer_phone, metadata,
+                razorpay_payment_id, razorpay_order_id, razorpay_signature,
+                crypto_tx_hash, crypto_from_address, crypto_to_address, crypto_chain,
+                lightning_invoice, lightning_payment_hash,
+                expires_at, completed_at, created_at, updated_at
+            "#,
+            id,
+            razorpay_payment_id,
+            Utc::now()
+        )
+        .fetch_one(conn)
+        .await?;
+
+        Ok(payment)
+    }
+
     pub async fn update_crypto_details(
         pool: &PgPool,
         id: Uuid,
@@ -260,6 +515,34 @@ impl PaymentRepository {
         Ok(payment)
     }
 
+    pub async fn find_by_lightning_payment_hash(
+        pool: &PgPool,
+        payment_hash: &str,
+    ) -> AppResult<Option<Payment>> {
+        let payment = sqlx::query_as!(
+            Payment,
+            r#"
+            SELECT
+                id, external_id, order_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: PaymentStatus",
+                method as "method: PaymentMethod",
+                description, customer_email, customer_phone, metadata,
+                razorpay_payment_id, razorpay_order_id, razorpay_signature,
+                crypto_tx_hash, crypto_from_address, crypto_to_address, crypto_chain,
+                lightning_invoice, lightning_payment_hash,
+                expires_at, completed_at, created_at, updated_at
+            FROM payments
+            WHERE lightning_payment_hash = $1
+            "#,
+            payment_hash
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(payment)
+    }
+
     pub async fn find_pending_by_crypto_address(
         pool: &PgPool,
         address: &str,
@@ -0,0 +1,321 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::PaymentRepository;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreatePaymentRequest, CurrencyType, Customer, NewQrCode, Payment, PaymentMethod,
+    PaymentStatus, QrCode, QrCodeCredit, QrCodeStatus,
+};
+
+pub struct QrCodeRepository;
+
+impl QrCodeRepository {
+    pub async fn create(pool: &PgPool, qr_code: &NewQrCode) -> AppResult<QrCode> {
+        let now = Utc::now();
+
+        let qr_code = sqlx::query_as!(
+            QrCode,
+            r#"
+            INSERT INTO razorpay_qr_codes (
+                id, payment_id, customer_id, multiple_use, payment_amount,
+                razorpay_qr_code_id, image_url, status, close_by, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING
+                id, payment_id, customer_id, multiple_use, payment_amount,
+                razorpay_qr_code_id, image_url,
+                status as "status: QrCodeStatus",
+                close_reason, close_by, closed_at, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            qr_code.payment_id,
+            qr_code.customer_id,
+            qr_code.multiple_use,
+            qr_code.payment_amount,
+            qr_code.razorpay_qr_code_id,
+            qr_code.image_url,
+            QrCodeStatus::Active as QrCodeStatus,
+            qr_code.close_by,
+            now,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(qr_code)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> AppResult<QrCode> {
+        let qr_code = sqlx::query_as!(
+            QrCode,
+            r#"
+            SELECT
+                id, payment_id, customer_id, multiple_use, payment_amount,
+                razorpay_qr_code_id, image_url,
+                status as "status: QrCodeStatus",
+                close_reason, close_by, closed_at, created_at, updated_at
+            FROM razorpay_qr_codes
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("QR code {} not found", id)))?;
+
+        Ok(qr_code)
+    }
+
+    pub async fn find_by_razorpay_id(
+        pool: &PgPool,
+        razorpay_qr_code_id: &str,
+    ) -> AppResult<Option<QrCode>> {
+        let qr_code = sqlx::query_as!(
+            QrCode,
+            r#"
+            SELECT
+                id, payment_id, customer_id, multiple_use, payment_amount,
+                razorpay_qr_code_id, image_url,
+                status as "status: QrCodeStatus",
+                close_reason, close_by, closed_at, created_at, updated_at
+            FROM razorpay_qr_codes
+            WHERE razorpay_qr_code_id = $1
+            "#,
+            razorpay_qr_code_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(qr_code)
+    }
+
+    pub async fn mark_closed(
+        pool: &PgPool,
+        id: Uuid,
+        close_reason: Option<&str>,
+        closed_at: Option<DateTime<Utc>>,
+    ) -> AppResult<QrCode> {
+        let qr_code = sqlx::query_as!(
+            QrCode,
+            r#"
+            UPDATE razorpay_qr_codes
+            SET status = $2,
+                close_reason = COALESCE($3, close_reason),
+                closed_at = COALESCE($4, closed_at, $5),
+                updated_at = $5
+            WHERE id = $1
+            RETURNING
+                id, payment_id, customer_id, multiple_use, payment_amount,
+                razorpay_qr_code_id, image_url,
+                status as "status: QrCodeStatus",
+                close_reason, close_by, closed_at, created_at, updated_at
+            "#,
+            id,
+            QrCodeStatus::Closed as QrCodeStatus,
+            close_reason,
+            closed_at,
+            Utc::now()
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("QR code {} not found", id)))?;
+
+        Ok(qr_code)
+    }
+
+    /// Record a UPI payment to the QR code. A single-use QR code completes its
+    /// payment once paid in full; a multi-use QR code turns every credit into a
+    /// completed payment. Returns `false` if the credit was already recorded.
+    pub async fn record_credit(
+        pool: &PgPool,
+        qr_code: &QrCode,
+        razorpay_payment_id: &str,
+        amount: i64,
+        customer: Option<&Customer>,
+    ) -> AppResult<(Payment, bool)> {
+        let mut db_tx = pool.begin().await?;
+
+        // Serializes concurrent reports of credits to the QR code
+        sqlx::query_scalar!(
+            r#"
+            SELECT id FROM razorpay_qr_codes
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            qr_code.id
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        let existing = sqlx::query_scalar!(
+            r#"
+            SELECT payment_id FROM razorpay_qr_code_credits
+            WHERE razorpay_payment_id = $1
+            "#,
+            razorpay_payment_id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?;
+
+        if let Some(payment_id) = existing {
+            db_tx.rollback().await?;
+            let payment = PaymentRepository::find_by_id(pool, payment_id).await?;
+            return Ok((payment, false));
+        }
+
+        let payment_id = match qr_code.payment_id {
+            Some(payment_id) => {
+                // Only a payment still waiting for its money is completed
+                if amount >= qr_code.payment_amount.unwrap_or_default()
+                    && PaymentRepository::advance_status_in(
+                        &mut db_tx,
+                        payment_id,
+                        PaymentStatus::Completed,
+                    )
+                    .await?
+                    .is_some()
+                {
+                    PaymentRepository::update_razorpay_payment_id(
+                        &mut db_tx,
+                        payment_id,
+                        razorpay_payment_id,
+                    )
+                    .await?;
+                }
+                payment_id
+            }
+            None => {
+                let request = CreatePaymentRequest {
+                    amount,
+                    currency: CurrencyType::INR,
+                    method: PaymentMethod::Upi,
+                    chain: None,
+                    description: Some(format!(
+                        "UPI payment to QR code {}",
+                        qr_code.razorpay_qr_code_id
+                    )),
+                    customer_email: customer.and_then(|c| c.email.clone()),
+                    customer_phone: customer.and_then(|c| c.contact.clone()),
+                    metadata: Some(serde_json::json!({
+                        "qr_code_id": qr_code.id,
+                        "razorpay_qr_code_id": qr_code.razorpay_qr_code_id,
+                    })),
+                    callback_url: None,
+                };
+
+                PaymentRepository::create_completed_in(
+                    &mut db_tx,
+                    &request,
+                    razorpay_payment_id,
+                    None,
+                )
+                .await?
+                .id
+            }
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO razorpay_qr_code_credits (
+                id, qr_code_id, payment_id, razorpay_payment_id, amount, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            Uuid::new_v4(),
+            qr_code.id,
+            payment_id,
+            razorpay_payment_id,
+            amount,
+            Utc::now()
+        )
+        .execute(&mut *db_tx)
+        .await?;
+
+        db_tx.commit().await?;
+
+        let payment = PaymentRepository::find_by_id(pool, payment_id).await?;
+
+        Ok((payment, true))
+    }
+
+    pub async fn find_credits(pool: &PgPool, qr_code_id: Uuid) -> AppResult<Vec<QrCodeCredit>> {
+        let credits = sqlx::query_as!(
+            QrCodeCredit,
+            r#"
+            SELECT id, qr_code_id, payment_id, razorpay_payment_id, amount, created_at
+            FROM razorpay_qr_code_credits
+            WHERE qr_code_id = $1
+            ORDER BY created_at
+            "#,
+            qr_code_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(credits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Point DATABASE_URL at a migrated database, then run
+    /// `cargo test qr_code_repo -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a migrated DATABASE_URL"]
+    async fn test_multi_use_credits_become_payments() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let qr_code = QrCodeRepository::create(
+            &pool,
+            &NewQrCode {
+                razorpay_qr_code_id: format!("qr_{}", Uuid::new_v4().simple()),
+                payment_id: None,
+                customer_id: None,
+                multiple_use: true,
+                payment_amount: None,
+                image_url: "https://rzp.io/i/qr".to_string(),
+                close_by: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let first_id = format!("pay_{}", Uuid::new_v4().simple());
+        let (first, recorded) =
+            QrCodeRepository::record_credit(&pool, &qr_code, &first_id, 25_000, None)
+                .await
+                .unwrap();
+        assert!(recorded);
+        assert_eq!(first.amount, 25_000);
+        assert_eq!(first.status, PaymentStatus::Completed);
+        assert_eq!(first.razorpay_payment_id.as_deref(), Some(first_id.as_str()));
+
+        let (second, _) = QrCodeRepository::record_credit(
+            &pool,
+            &qr_code,
+            &format!("pay_{}", Uuid::new_v4().simple()),
+            40_000,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_ne!(second.id, first.id);
+
+        // A webhook retry returns the payment it already recorded
+        let (again, recorded) =
+            QrCodeRepository::record_credit(&pool, &qr_code, &first_id, 25_000, None)
+                .await
+                .unwrap();
+        assert!(!recorded);
+        assert_eq!(again.id, first.id);
+
+        let credits = QrCodeRepository::find_credits(&pool, qr_code.id).await.unwrap();
+        assert_eq!(credits.len(), 2);
+    }
+}
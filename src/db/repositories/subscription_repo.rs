@@ -0,0 +1,259 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::PaymentRepository;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreatePaymentRequest, CurrencyType, NewSubscription, Payment, PaymentMethod, Subscription,
+    SubscriptionCharge, SubscriptionStatus, SubscriptionUpdate,
+};
+
+pub struct SubscriptionRepository;
+
+impl SubscriptionRepository {
+    pub async fn create(pool: &PgPool, subscription: &NewSubscription) -> AppResult<Subscription> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let subscription = sqlx::query_as!(
+            Subscription,
+            r#"
+            INSERT INTO subscriptions (
+                id, razorpay_subscription_id, razorpay_plan_id, status, amount, currency,
+                quantity, total_count, customer_email, customer_phone, short_url, metadata,
+                created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING
+                id, razorpay_subscription_id, razorpay_plan_id,
+                status as "status: SubscriptionStatus",
+                amount,
+                currency as "currency: CurrencyType",
+                quantity, total_count, paid_count, customer_email, customer_phone,
+                short_url, metadata, current_start, current_end, ended_at,
+                created_at, updated_at
+            "#,
+            id,
+            subscription.razorpay_subscription_id,
+            subscription.razorpay_plan_id,
+            subscription.status as SubscriptionStatus,
+            subscription.amount,
+            subscription.currency.clone() as CurrencyType,
+            subscription.quantity,
+            subscription.total_count,
+            subscription.customer_email,
+            subscription.customer_phone,
+            subscription.short_url,
+            subscription.metadata,
+            now,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> AppResult<Subscription> {
+        let subscription = sqlx::query_as!(
+            Subscription,
+            r#"
+            SELECT
+                id, razorpay_subscription_id, razorpay_plan_id,
+                status as "status: SubscriptionStatus",
+                amount,
+                currency as "currency: CurrencyType",
+                quantity, total_count, paid_count, customer_email, customer_phone,
+                short_url, metadata, current_start, current_end, ended_at,
+                created_at, updated_at
+            FROM subscriptions
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Subscription {} not found", id)))?;
+
+        Ok(subscription)
+    }
+
+    pub async fn find_by_razorpay_id(
+        pool: &PgPool,
+        razorpay_subscription_id: &str,
+    ) -> AppResult<Option<Subscription>> {
+        let subscription = sqlx::query_as!(
+            Subscription,
+            r#"
+            SELECT
+                id, razorpay_subscription_id, razorpay_plan_id,
+                status as "status: SubscriptionStatus",
+                amount,
+                currency as "currency: CurrencyType",
+                quantity, total_count, paid_count, customer_email, customer_phone,
+                short_url, metadata, current_start, current_end, ended_at,
+                created_at, updated_at
+            FROM subscriptions
+            WHERE razorpay_subscription_id = $1
+            "#,
+            razorpay_subscription_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    /// Apply the state reported by Razorpay. Webhooks can arrive out of order,
+    /// so a subscription never leaves a terminal status and its paid count
+    /// never goes down.
+    pub async fn apply_update(
+        pool: &PgPool,
+        id: Uuid,
+        update: &SubscriptionUpdate,
+    ) -> AppResult<Subscription> {
+        let subscription = sqlx::query_as!(
+            Subscription,
+            r#"
+            UPDATE subscriptions
+            SET status = CASE
+                    WHEN status IN ('cancelled', 'completed', 'expired') THEN status
+                    ELSE $2
+                END,
+                paid_count = GREATEST(paid_count, $3),
+                current_start = COALESCE($4, current_start),
+                current_end = COALESCE($5, current_end),
+                ended_at = COALESCE($6, ended_at),
+                updated_at = $7
+            WHERE id = $1
+            RETURNING
+                id, razorpay_subscription_id, razorpay_plan_id,
+                status as "status: SubscriptionStatus",
+                amount,
+                currency as "currency: CurrencyType",
+                quantity, total_count, paid_count, customer_email, customer_phone,
+                short_url, metadata, current_start, current_end, ended_at,
+                created_at, updated_at
+            "#,
+            id,
+            update.status as SubscriptionStatus,
+            update.paid_count,
+            update.current_start,
+            update.current_end,
+            update.ended_at,
+            Utc::now()
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Subscription {} not found", id)))?;
+
+        Ok(subscription)
+    }
+
+    /// Record a charge as a completed payment linked to the subscription.
+    /// Checkout verification and the `subscription.charged` webhook both
+    /// report the first charge, so a charge already recorded is returned
+    /// as is with `false`.
+    pub async fn record_charge(
+        pool: &PgPool,
+        subscription: &Subscription,
+        razorpay_payment_id: &str,
+        razorpay_order_id: Option<&str>,
+        amount: i64,
+        method: PaymentMethod,
+    ) -> AppResult<(Payment, bool)> {
+        let mut db_tx = pool.begin().await?;
+
+        // Serializes concurrent reports of the same charge
+        sqlx::query_scalar!(
+            r#"
+            SELECT id FROM subscriptions
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            subscription.id
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        let existing = sqlx::query_scalar!(
+            r#"
+            SELECT payment_id FROM subscription_charges
+            WHERE razorpay_payment_id = $1
+            "#,
+            razorpay_payment_id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?;
+
+        if let Some(payment_id) = existing {
+            db_tx.rollback().await?;
+            let payment = PaymentRepository::find_by_id(pool, payment_id).await?;
+            return Ok((payment, false));
+        }
+
+        let payment = PaymentRepository::create_completed_in(
+            &mut db_tx,
+            &CreatePaymentRequest {
+                amount,
+                currency: subscription.currency.clone(),
+                method,
+                chain: None,
+                description: Some(format!(
+                    "Subscription {}",
+                    subscription.razorpay_subscription_id
+                )),
+                customer_email: subscription.customer_email.clone(),
+                customer_phone: subscription.customer_phone.clone(),
+                metadata: Some(serde_json::json!({
+                    "subscription_id": subscription.id,
+                    "razorpay_subscription_id": subscription.razorpay_subscription_id,
+                })),
+                callback_url: None,
+            },
+            razorpay_payment_id,
+            razorpay_order_id,
+        )
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO subscription_charges (
+                payment_id, subscription_id, razorpay_payment_id, created_at
+            )
+            VALUES ($1, $2, $3, $4)
+            "#,
+            payment.id,
+            subscription.id,
+            razorpay_payment_id,
+            payment.created_at
+        )
+        .execute(&mut *db_tx)
+        .await?;
+
+        db_tx.commit().await?;
+
+        Ok((payment, true))
+    }
+
+    pub async fn find_charges(
+        pool: &PgPool,
+        subscription_id: Uuid,
+    ) -> AppResult<Vec<SubscriptionCharge>> {
+        let charges = sqlx::query_as!(
+            SubscriptionCharge,
+            r#"
+            SELECT payment_id, subscription_id, razorpay_payment_id, created_at
+            FROM subscription_charges
+            WHERE subscription_id = $1
+            ORDER BY created_at
+            "#,
+            subscription_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(charges)
+    }
+}
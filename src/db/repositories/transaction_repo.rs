@@ -1,9 +1,12 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
-use crate::models::{Transaction, TransactionStatus, TransactionType};
+use crate::models::{
+    payout_status_applies, ChainType, CryptoAddress, DepositUpdate, PayoutUpdate, Transaction,
+    TransactionStatus, TransactionType,
+};
 
 pub struct TransactionRepository;
 
@@ -32,7 +35,7 @@ impl TransactionRepository {
                 id, payment_id,
                 tx_type as "tx_type: TransactionType",
                 status as "status: TransactionStatus",
-                amount, fee, currency, tx_hash, block_number,
+                amount, fee, currency, tx_hash, block_number, block_hash,
                 confirmations, required_confirmations,
                 from_address, to_address, chain, raw_data,
                 error_message, created_at, updated_at
@@ -55,6 +58,142 @@ impl TransactionRepository {
         Ok(tx)
     }
 
+    /// Record a payment that settled off-chain, e.g. a paid Lightning invoice, in a
+    /// single insert so it is never seen half-written
+    pub async fn create_settled(
+        pool: &PgPool,
+        payment_id: Uuid,
+        amount: i64,
+        currency: &str,
+        chain: &str,
+        tx_hash: &str,
+        raw_data: serde_json::Value,
+    ) -> AppResult<Transaction> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let tx = sqlx::query_as!(
+            Transaction,
+            r#"
+            INSERT INTO transactions (
+                id, payment_id, tx_type, status, amount, currency, chain, tx_hash,
+                raw_data, confirmations, required_confirmations, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 0, 0, $10, $10)
+            RETURNING
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            "#,
+            id,
+            payment_id,
+            TransactionType::Payment as TransactionType,
+            TransactionStatus::Confirmed as TransactionStatus,
+            amount,
+            currency,
+            chain,
+            tx_hash,
+            raw_data,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(tx)
+    }
+
+    /// Record an outgoing refund or payout against a payment. The payment row is
+    /// locked so concurrent requests cannot together send back more than was paid;
+    /// failed and cancelled attempts do not count towards the total.
+    pub async fn create_outgoing(
+        pool: &PgPool,
+        payment_id: Uuid,
+        tx_type: TransactionType,
+        amount: i64,
+        currency: &str,
+        chain: &str,
+        tx_hash: &str,
+    ) -> AppResult<Transaction> {
+        let mut db_tx = pool.begin().await?;
+
+        let paid = sqlx::query_scalar!(
+            r#"
+            SELECT amount FROM payments
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            payment_id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Payment {} not found", payment_id)))?;
+
+        let sent = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(amount), 0)::BIGINT as "sent!"
+            FROM transactions
+            WHERE payment_id = $1
+              AND tx_type IN ('refund', 'withdrawal')
+              AND status NOT IN ('failed', 'cancelled')
+            "#,
+            payment_id
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        if sent + amount > paid {
+            return Err(AppError::Validation(format!(
+                "Amount {} exceeds the {} remaining on payment {}",
+                amount,
+                paid - sent,
+                payment_id
+            )));
+        }
+
+        let now = Utc::now();
+
+        let tx = sqlx::query_as!(
+            Transaction,
+            r#"
+            INSERT INTO transactions (
+                id, payment_id, tx_type, status, amount, currency, chain, tx_hash,
+                confirmations, required_confirmations, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            payment_id,
+            tx_type as TransactionType,
+            TransactionStatus::Pending as TransactionStatus,
+            amount,
+            currency,
+            chain,
+            tx_hash,
+            0_i32,
+            0_i32,
+            now,
+            now
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        db_tx.commit().await?;
+
+        Ok(tx)
+    }
+
     pub async fn find_by_id(pool: &PgPool, id: Uuid) -> AppResult<Transaction> {
         let tx = sqlx::query_as!(
             Transaction,
@@ -63,7 +202,7 @@ impl TransactionRepository {
                 id, payment_id,
                 tx_type as "tx_type: TransactionType",
                 status as "status: TransactionStatus",
-                amount, fee, currency, tx_hash, block_number,
+                amount, fee, currency, tx_hash, block_number, block_hash,
                 confirmations, required_confirmations,
                 from_address, to_address, chain, raw_data,
                 error_message, created_at, updated_at
@@ -87,7 +226,7 @@ impl TransactionRepository {
                 id, payment_id,
                 tx_type as "tx_type: TransactionType",
                 status as "status: TransactionStatus",
-                amount, fee, currency, tx_hash, block_number,
+                amount, fee, currency, tx_hash, block_number, block_hash,
                 confirmations, required_confirmations,
                 from_address, to_address, chain, raw_data,
                 error_message, created_at, updated_at
@@ -111,12 +250,42 @@ impl TransactionRepository {
                 id, payment_id,
                 tx_type as "tx_type: TransactionType",
                 status as "status: TransactionStatus",
-                amount, fee, currency, tx_hash, block_number,
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            FROM transactions
+            WHERE tx_hash = $1
+            "#,
+            tx_hash
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(tx)
+    }
+
+    /// A transaction for `tx_hash` that has not failed or been cancelled, so a
+    /// failed Lightning payment can be attempted again
+    pub async fn find_live_by_tx_hash(
+        pool: &PgPool,
+        tx_hash: &str,
+    ) -> AppResult<Option<Transaction>> {
+        let tx = sqlx::query_as!(
+            Transaction,
+            r#"
+            SELECT
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
                 confirmations, required_confirmations,
                 from_address, to_address, chain, raw_data,
                 error_message, created_at, updated_at
             FROM transactions
             WHERE tx_hash = $1
+                AND status NOT IN ('failed', 'cancelled')
+            LIMIT 1
             "#,
             tx_hash
         )
@@ -126,6 +295,36 @@ impl TransactionRepository {
         Ok(tx)
     }
 
+    /// The transaction a deposit of `tx_hash` into `to_address` was recorded on.
+    /// One transaction can pay several deposit addresses, so the hash alone is not enough.
+    pub async fn find_by_tx_hash_and_address(
+        pool: &PgPool,
+        tx_hash: &str,
+        to_address: &str,
+    ) -> AppResult<Option<Transaction>> {
+        let tx = sqlx::query_as!(
+            Transaction,
+            r#"
+            SELECT
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            FROM transactions
+            WHERE tx_hash = $1 AND to_address = $2
+            "#,
+            tx_hash,
+            to_address
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(tx)
+    }
+
     pub async fn update_status(
         pool: &PgPool,
         id: Uuid,
@@ -142,7 +341,7 @@ impl TransactionRepository {
                 id, payment_id,
                 tx_type as "tx_type: TransactionType",
                 status as "status: TransactionStatus",
-                amount, fee, currency, tx_hash, block_number,
+                amount, fee, currency, tx_hash, block_number, block_hash,
                 confirmations, required_confirmations,
                 from_address, to_address, chain, raw_data,
                 error_message, created_at, updated_at
@@ -178,7 +377,7 @@ impl TransactionRepository {
                 id, payment_id,
                 tx_type as "tx_type: TransactionType",
                 status as "status: TransactionStatus",
-                amount, fee, currency, tx_hash, block_number,
+                amount, fee, currency, tx_hash, block_number, block_hash,
                 confirmations, required_confirmations,
                 from_address, to_address, chain, raw_data,
                 error_message, created_at, updated_at
@@ -197,6 +396,322 @@ impl TransactionRepository {
         Ok(tx)
     }
 
+    pub async fn update_raw_data(
+        pool: &PgPool,
+        id: Uuid,
+        raw_data: serde_json::Value,
+    ) -> AppResult<Transaction> {
+        let tx = sqlx::query_as!(
+            Transaction,
+            r#"
+            UPDATE transactions
+            SET raw_data = $2, updated_at = $3
+            WHERE id = $1
+            RETURNING
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            "#,
+            id,
+            raw_data,
+            Utc::now()
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(tx)
+    }
+
+    /// Apply the state of a RazorpayX payout to its withdrawal transaction.
+    /// The row is locked so a webhook and a reconciliation racing each other
+    /// cannot move a final payout back; returns whether the update applied.
+    pub async fn apply_payout(
+        pool: &PgPool,
+        id: Uuid,
+        update: &PayoutUpdate,
+    ) -> AppResult<(Transaction, bool)> {
+        let mut db_tx = pool.begin().await?;
+
+        let current = sqlx::query_scalar!(
+            r#"
+            SELECT status as "status: TransactionStatus"
+            FROM transactions
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", id)))?;
+
+        if !payout_status_applies(&current, &update.status) {
+            db_tx.rollback().await?;
+            return Ok((Self::find_by_id(pool, id).await?, false));
+        }
+
+        let error_message = match update.status {
+            TransactionStatus::Failed | TransactionStatus::Cancelled => {
+                update.failure_reason.as_deref()
+            }
+            _ => None,
+        };
+        let payout_data = serde_json::json!({
+            "payout_status": update.razorpay_status,
+            "utr": update.utr,
+        });
+
+        let tx = sqlx::query_as!(
+            Transaction,
+            r#"
+            UPDATE transactions
+            SET status = $2, tx_hash = $3, to_address = $4, fee = COALESCE($5, fee),
+                raw_data = COALESCE(raw_data, '{}'::jsonb) || $6,
+                error_message = $7, updated_at = $8
+            WHERE id = $1
+            RETURNING
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            "#,
+            id,
+            update.status.clone() as TransactionStatus,
+            update.razorpay_payout_id,
+            update.fund_account_id,
+            update.fees,
+            payout_data,
+            error_message,
+            Utc::now()
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        db_tx.commit().await?;
+
+        Ok((tx, true))
+    }
+
+    /// Record a deposit to a payment's address and credit the address with it, together
+    /// so a failure part way cannot leave one without the other. The deposit claims the
+    /// payment's transaction that is still waiting for one, or is recorded as a new
+    /// transaction. The credit is added in the database rather than from a balance read
+    /// earlier, which another deposit to the same address may have changed since.
+    /// Returns `None` if the deposit was already recorded, e.g. by the chain scanner
+    /// and a client-side verification of the same transaction.
+    pub async fn record_deposit(
+        pool: &PgPool,
+        payment_id: Uuid,
+        currency: &str,
+        chain: &str,
+        address_id: Uuid,
+        required_confirmations: i32,
+        deposit: &DepositUpdate,
+    ) -> AppResult<Option<(Transaction, CryptoAddress)>> {
+        let mut db_tx = pool.begin().await?;
+        let now = Utc::now();
+
+        // Serializes deposits to the address, so each is credited once
+        sqlx::query_scalar!(
+            r#"
+            SELECT id FROM crypto_addresses
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            address_id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Address {} not found", address_id)))?;
+
+        // One transaction can pay several addresses, each credited on its own
+        let recorded = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM transactions
+            WHERE tx_hash = $1 AND to_address = $2
+            "#,
+            deposit.tx_hash,
+            deposit.to_address
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?;
+
+        if recorded.is_some() {
+            db_tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let claimed = sqlx::query_as!(
+            Transaction,
+            r#"
+            UPDATE transactions
+            SET tx_hash = $2, amount = $3, block_number = $4, block_hash = $5,
+                from_address = $6, to_address = $7, confirmations = $8, status = $9,
+                updated_at = $10
+            WHERE id = (
+                SELECT id FROM transactions
+                WHERE payment_id = $1 AND tx_type = 'payment' AND tx_hash IS NULL
+                ORDER BY created_at
+                LIMIT 1
+            )
+            AND tx_hash IS NULL
+            RETURNING
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            "#,
+            payment_id,
+            deposit.tx_hash,
+            deposit.amount,
+            deposit.block_number,
+            deposit.block_hash,
+            deposit.from_address,
+            deposit.to_address,
+            deposit.confirmations,
+            deposit.status.clone() as TransactionStatus,
+            now
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?;
+
+        let tx = match claimed {
+            Some(tx) => tx,
+            None => {
+                sqlx::query_as!(
+                    Transaction,
+                    r#"
+                    INSERT INTO transactions (
+                        id, payment_id, tx_type, status, amount, currency, chain,
+                        tx_hash, block_number, block_hash, from_address, to_address,
+                        confirmations, required_confirmations, created_at, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                    RETURNING
+                        id, payment_id,
+                        tx_type as "tx_type: TransactionType",
+                        status as "status: TransactionStatus",
+                        amount, fee, currency, tx_hash, block_number, block_hash,
+                        confirmations, required_confirmations,
+                        from_address, to_address, chain, raw_data,
+                        error_message, created_at, updated_at
+                    "#,
+                    Uuid::new_v4(),
+                    payment_id,
+                    TransactionType::Payment as TransactionType,
+                    deposit.status.clone() as TransactionStatus,
+                    deposit.amount,
+                    currency,
+                    chain,
+                    deposit.tx_hash,
+                    deposit.block_number,
+                    deposit.block_hash,
+                    deposit.from_address,
+                    deposit.to_address,
+                    deposit.confirmations,
+                    required_confirmations,
+                    now,
+                    now
+                )
+                .fetch_one(&mut *db_tx)
+                .await?
+            }
+        };
+
+        let address = sqlx::query_as!(
+            CryptoAddress,
+            r#"
+            UPDATE crypto_addresses
+            SET received_amount = COALESCE(received_amount, 0) + $2,
+                last_checked_at = $3, updated_at = $3
+            WHERE id = $1
+            RETURNING
+                id, payment_id, address,
+                chain as "chain: ChainType",
+                is_active, label, expected_amount, received_amount,
+                token_address, derivation_path, derivation_index,
+                last_checked_at, created_at, updated_at
+            "#,
+            address_id,
+            deposit.amount,
+            now
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        db_tx.commit().await?;
+
+        Ok(Some((tx, address)))
+    }
+
+    /// Undo `record_deposit` for a deposit that never made it onto the chain: fail the
+    /// transaction and take its amount back off the payment's address, which is watched
+    /// again for a replacement deposit. Returns `None` if the deposit had already failed.
+    pub async fn revert_deposit(
+        pool: &PgPool,
+        id: Uuid,
+        reason: &str,
+    ) -> AppResult<Option<Transaction>> {
+        let mut db_tx = pool.begin().await?;
+        let now = Utc::now();
+
+        let tx = sqlx::query_as!(
+            Transaction,
+            r#"
+            UPDATE transactions
+            SET status = $2, error_message = $3, updated_at = $4
+            WHERE id = $1 AND status <> $2
+            RETURNING
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            "#,
+            id,
+            TransactionStatus::Failed as TransactionStatus,
+            reason,
+            now
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?;
+
+        let Some(tx) = tx else {
+            db_tx.rollback().await?;
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE crypto_addresses
+            SET received_amount = GREATEST(COALESCE(received_amount, 0) - $2, 0),
+                is_active = true, updated_at = $3
+            WHERE payment_id = $1
+            "#,
+            tx.payment_id,
+            tx.amount,
+            now
+        )
+        .execute(&mut *db_tx)
+        .await?;
+
+        db_tx.commit().await?;
+
+        Ok(Some(tx))
+    }
+
     pub async fn update_confirmations(
         pool: &PgPool,
         id: Uuid,
@@ -213,7 +728,7 @@ impl TransactionRepository {
                 id, payment_id,
                 tx_type as "tx_type: TransactionType",
                 status as "status: TransactionStatus",
-                amount, fee, currency, tx_hash, block_number,
+                amount, fee, currency, tx_hash, block_number, block_hash,
                 confirmations, required_confirmations,
                 from_address, to_address, chain, raw_data,
                 error_message, created_at, updated_at
@@ -229,6 +744,65 @@ impl TransactionRepository {
         Ok(tx)
     }
 
+    /// Record the block a transaction was included in
+    pub async fn update_block(
+        pool: &PgPool,
+        id: Uuid,
+        block_number: Option<i64>,
+        block_hash: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET block_number = $2, block_hash = $3, updated_at = $4
+            WHERE id = $1
+            "#,
+            id,
+            block_number,
+            block_hash,
+            Utc::now()
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// On-chain transactions recent enough to still be affected by a chain reorganization.
+    /// Lightning payments and RazorpayX payouts are recorded with their own `chain`
+    /// labels and have no blocks to follow.
+    pub async fn find_reorg_candidates(
+        pool: &PgPool,
+        since: DateTime<Utc>,
+    ) -> AppResult<Vec<Transaction>> {
+        let txs = sqlx::query_as!(
+            Transaction,
+            r#"
+            SELECT
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            FROM transactions
+            WHERE status IN ('confirming', 'confirmed')
+                AND tx_hash IS NOT NULL
+                AND created_at >= $1
+                AND chain IS NOT NULL
+                AND chain NOT IN ('lightning', 'razorpayx')
+            ORDER BY created_at ASC
+            "#,
+            since
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(txs)
+    }
+
+    /// On-chain transactions still short of their required confirmations
     pub async fn find_pending_confirmations(pool: &PgPool) -> AppResult<Vec<Transaction>> {
         let txs = sqlx::query_as!(
             Transaction,
@@ -237,13 +811,15 @@ impl TransactionRepository {
                 id, payment_id,
                 tx_type as "tx_type: TransactionType",
                 status as "status: TransactionStatus",
-                amount, fee, currency, tx_hash, block_number,
+                amount, fee, currency, tx_hash, block_number, block_hash,
                 confirmations, required_confirmations,
                 from_address, to_address, chain, raw_data,
                 error_message, created_at, updated_at
             FROM transactions
             WHERE status = 'confirming'
                 AND tx_hash IS NOT NULL
+                AND chain IS NOT NULL
+                AND chain NOT IN ('lightning', 'razorpayx')
             ORDER BY created_at ASC
             "#
         )
@@ -252,4 +828,131 @@ impl TransactionRepository {
 
         Ok(txs)
     }
+
+    /// Refunds and payouts on `chain` still waiting for their outcome, oldest first
+    pub async fn find_pending_outgoing(pool: &PgPool, chain: &str) -> AppResult<Vec<Transaction>> {
+        let txs = sqlx::query_as!(
+            Transaction,
+            r#"
+            SELECT
+                id, payment_id,
+                tx_type as "tx_type: TransactionType",
+                status as "status: TransactionStatus",
+                amount, fee, currency, tx_hash, block_number, block_hash,
+                confirmations, required_confirmations,
+                from_address, to_address, chain, raw_data,
+                error_message, created_at, updated_at
+            FROM transactions
+            WHERE status = 'pending'
+                AND tx_type IN ('refund', 'withdrawal')
+                AND chain = $1
+            ORDER BY created_at ASC
+            "#,
+            chain
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(txs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repositories::{AddressRepository, PaymentRepository};
+    use crate::models::{CreatePaymentRequest, CurrencyType, PaymentMethod};
+
+    /// Point DATABASE_URL at a migrated database, then run
+    /// `cargo test transaction_repo -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a migrated DATABASE_URL"]
+    async fn test_deposit_reported_twice_is_credited_once() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let chain = ChainType::evm("ethereum");
+
+        let payment = PaymentRepository::create(
+            &pool,
+            &CreatePaymentRequest {
+                amount: 1_000_000,
+                currency: CurrencyType::ETH,
+                method: PaymentMethod::Evm,
+                chain: Some(chain.clone()),
+                description: None,
+                customer_email: None,
+                customer_phone: None,
+                metadata: None,
+                callback_url: None,
+            },
+        )
+        .await
+        .unwrap();
+        let pending = TransactionRepository::create(
+            &pool,
+            payment.id,
+            TransactionType::Payment,
+            payment.amount,
+            "ETH",
+            Some("ethereum"),
+            12,
+        )
+        .await
+        .unwrap();
+
+        let to_address = format!("0x{}", Uuid::new_v4().simple());
+        let address = AddressRepository::create(
+            &pool,
+            &to_address,
+            chain,
+            Some(payment.id),
+            Some(payment.amount),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Reported by the chain scanner and a client-side verification at once
+        let deposit = DepositUpdate {
+            tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+            amount: 1_200_000,
+            block_number: Some(300),
+            block_hash: None,
+            from_address: "0x3333333333333333333333333333333333333333".to_string(),
+            to_address,
+            confirmations: 1,
+            status: TransactionStatus::Confirming,
+        };
+        let record = || {
+            TransactionRepository::record_deposit(
+                &pool,
+                payment.id,
+                "ETH",
+                "ethereum",
+                address.id,
+                12,
+                &deposit,
+            )
+        };
+        let (first, second) = tokio::join!(record(), record());
+        let recorded: Vec<_> = [first.unwrap(), second.unwrap()]
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(recorded.len(), 1);
+
+        let (tx, address) = &recorded[0];
+        assert_eq!(tx.id, pending.id);
+        assert_eq!(tx.amount, 1_200_000);
+        assert_eq!(address.received_amount, Some(1_200_000));
+
+        let txs = TransactionRepository::find_by_payment_id(&pool, payment.id)
+            .await
+            .unwrap();
+        assert_eq!(txs.len(), 1);
+    }
 }
@@ -0,0 +1,239 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{CurrencyType, Transfer, TransferSplit, TransferStatus, TransferUpdate};
+
+pub struct TransferRepository;
+
+impl TransferRepository {
+    /// Store a split attached to a payment's order, before Razorpay creates it
+    pub async fn create_split(
+        pool: &PgPool,
+        payment_id: Uuid,
+        split: &TransferSplit,
+        currency: &CurrencyType,
+    ) -> AppResult<Transfer> {
+        let now = Utc::now();
+
+        let transfer = sqlx::query_as!(
+            Transfer,
+            r#"
+            INSERT INTO razorpay_transfers (
+                id, payment_id, linked_account_id, amount, currency, status,
+                on_hold, on_hold_until, notes, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING
+                id, payment_id, razorpay_transfer_id, linked_account_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: TransferStatus",
+                amount_reversed, on_hold, on_hold_until, notes, error_message,
+                processed_at, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            payment_id,
+            split.account,
+            split.amount,
+            currency.clone() as CurrencyType,
+            TransferStatus::Created as TransferStatus,
+            split.on_hold,
+            split.on_hold_until,
+            split.notes,
+            now,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    /// Record the state of a transfer reported by Razorpay. A transfer not
+    /// seen before takes over a matching split of the same payment that is
+    /// still waiting for its Razorpay ID, or is stored as a new transfer.
+    /// Reports older than the stored status are ignored, see
+    /// [`TransferStatus::can_become`], and the transfer is returned as stored.
+    pub async fn record(
+        pool: &PgPool,
+        payment_id: Option<Uuid>,
+        update: &TransferUpdate,
+        notes: Option<&serde_json::Value>,
+    ) -> AppResult<Transfer> {
+        // A concurrent report can insert the transfer between the lookup and
+        // the insert; the next round then finds and updates it
+        loop {
+            let mut db_tx = pool.begin().await?;
+            let now = Utc::now();
+
+            let existing = sqlx::query!(
+                r#"
+                SELECT id, status as "status: TransferStatus"
+                FROM razorpay_transfers
+                WHERE razorpay_transfer_id = $2
+                   OR (payment_id = $1
+                       AND razorpay_transfer_id IS NULL
+                       AND linked_account_id = $3
+                       AND amount = $4)
+                -- Prefer the transfer already known by its ID
+                ORDER BY razorpay_transfer_id IS NULL, created_at
+                LIMIT 1
+                FOR UPDATE
+                "#,
+                payment_id,
+                update.razorpay_transfer_id,
+                update.linked_account_id,
+                update.amount
+            )
+            .fetch_optional(&mut *db_tx)
+            .await?;
+
+            if let Some(existing) = existing {
+                if !existing.status.can_become(update.status) {
+                    db_tx.rollback().await?;
+                    return Self::find_by_id(pool, existing.id).await;
+                }
+
+                let transfer = sqlx::query_as!(
+                    Transfer,
+                    r#"
+                    UPDATE razorpay_transfers
+                    SET razorpay_transfer_id = $2,
+                        status = $3,
+                        amount_reversed = GREATEST(amount_reversed, $4),
+                        on_hold = $5,
+                        on_hold_until = $6,
+                        error_message = $7,
+                        processed_at = COALESCE($8, processed_at),
+                        updated_at = $9
+                    WHERE id = $1
+                    RETURNING
+                        id, payment_id, razorpay_transfer_id, linked_account_id, amount,
+                        currency as "currency: CurrencyType",
+                        status as "status: TransferStatus",
+                        amount_reversed, on_hold, on_hold_until, notes, error_message,
+                        processed_at, created_at, updated_at
+                    "#,
+                    existing.id,
+                    update.razorpay_transfer_id,
+                    update.status as TransferStatus,
+                    update.amount_reversed,
+                    update.on_hold,
+                    update.on_hold_until,
+                    update.error_message,
+                    update.processed_at,
+                    now
+                )
+                .fetch_one(&mut *db_tx)
+                .await?;
+
+                db_tx.commit().await?;
+                return Ok(transfer);
+            }
+
+            let transfer = sqlx::query_as!(
+                Transfer,
+                r#"
+                INSERT INTO razorpay_transfers (
+                    id, payment_id, razorpay_transfer_id, linked_account_id, amount, currency,
+                    status, amount_reversed, on_hold, on_hold_until, notes, error_message,
+                    processed_at, created_at, updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                ON CONFLICT (razorpay_transfer_id) DO NOTHING
+                RETURNING
+                    id, payment_id, razorpay_transfer_id, linked_account_id, amount,
+                    currency as "currency: CurrencyType",
+                    status as "status: TransferStatus",
+                    amount_reversed, on_hold, on_hold_until, notes, error_message,
+                    processed_at, created_at, updated_at
+                "#,
+                Uuid::new_v4(),
+                payment_id,
+                update.razorpay_transfer_id,
+                update.linked_account_id,
+                update.amount,
+                update.currency.clone() as CurrencyType,
+                update.status as TransferStatus,
+                update.amount_reversed,
+                update.on_hold,
+                update.on_hold_until,
+                notes,
+                update.error_message,
+                update.processed_at,
+                now,
+                now
+            )
+            .fetch_optional(&mut *db_tx)
+            .await?;
+
+            if let Some(transfer) = transfer {
+                db_tx.commit().await?;
+                return Ok(transfer);
+            }
+
+            db_tx.rollback().await?;
+        }
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> AppResult<Transfer> {
+        let transfer = sqlx::query_as!(
+            Transfer,
+            r#"
+            SELECT
+                id, payment_id, razorpay_transfer_id, linked_account_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: TransferStatus",
+                amount_reversed, on_hold, on_hold_until, notes, error_message,
+                processed_at, created_at, updated_at
+            FROM razorpay_transfers
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transfer {} not found", id)))?;
+
+        Ok(transfer)
+    }
+
+    pub async fn find_by_payment_id(pool: &PgPool, payment_id: Uuid) -> AppResult<Vec<Transfer>> {
+        let transfers = sqlx::query_as!(
+            Transfer,
+            r#"
+            SELECT
+                id, payment_id, razorpay_transfer_id, linked_account_id, amount,
+                currency as "currency: CurrencyType",
+                status as "status: TransferStatus",
+                amount_reversed, on_hold, on_hold_until, notes, error_message,
+                processed_at, created_at, updated_at
+            FROM razorpay_transfers
+            WHERE payment_id = $1
+            ORDER BY created_at
+            "#,
+            payment_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(transfers)
+    }
+
+    /// Amount of a payment already sent, or about to be sent, to linked accounts
+    pub async fn allocated_amount(pool: &PgPool, payment_id: Uuid) -> AppResult<i64> {
+        let allocated = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(amount - amount_reversed), 0)::BIGINT as "allocated!"
+            FROM razorpay_transfers
+            WHERE payment_id = $1 AND status <> 'failed'
+            "#,
+            payment_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(allocated)
+    }
+}
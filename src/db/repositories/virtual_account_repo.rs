@@ -0,0 +1,356 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::PaymentRepository;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    BankTransferCredit, CreatePaymentRequest, CurrencyType, Customer, NewVirtualAccount, Payment,
+    PaymentMethod, VirtualAccount, VirtualAccountCredit, VirtualAccountStatus,
+};
+
+pub struct VirtualAccountRepository;
+
+impl VirtualAccountRepository {
+    pub async fn create(pool: &PgPool, account: &NewVirtualAccount) -> AppResult<VirtualAccount> {
+        let now = Utc::now();
+
+        let account = sqlx::query_as!(
+            VirtualAccount,
+            r#"
+            INSERT INTO virtual_accounts (
+                id, razorpay_virtual_account_id, payment_id, customer_id, status,
+                amount_expected, account_number, ifsc, bank_name, beneficiary_name,
+                close_by, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING
+                id, razorpay_virtual_account_id, payment_id, customer_id,
+                status as "status: VirtualAccountStatus",
+                amount_expected, amount_paid, amount_overpaid, account_number, ifsc,
+                bank_name, beneficiary_name, close_by, closed_at, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            account.razorpay_virtual_account_id,
+            account.payment_id,
+            account.customer_id,
+            VirtualAccountStatus::Active as VirtualAccountStatus,
+            account.amount_expected,
+            account.account_number,
+            account.ifsc,
+            account.bank_name,
+            account.beneficiary_name,
+            account.close_by,
+            now,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> AppResult<VirtualAccount> {
+        let account = sqlx::query_as!(
+            VirtualAccount,
+            r#"
+            SELECT
+                id, razorpay_virtual_account_id, payment_id, customer_id,
+                status as "status: VirtualAccountStatus",
+                amount_expected, amount_paid, amount_overpaid, account_number, ifsc,
+                bank_name, beneficiary_name, close_by, closed_at, created_at, updated_at
+            FROM virtual_accounts
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Virtual account {} not found", id)))?;
+
+        Ok(account)
+    }
+
+    pub async fn find_by_razorpay_id(
+        pool: &PgPool,
+        razorpay_virtual_account_id: &str,
+    ) -> AppResult<Option<VirtualAccount>> {
+        let account = sqlx::query_as!(
+            VirtualAccount,
+            r#"
+            SELECT
+                id, razorpay_virtual_account_id, payment_id, customer_id,
+                status as "status: VirtualAccountStatus",
+                amount_expected, amount_paid, amount_overpaid, account_number, ifsc,
+                bank_name, beneficiary_name, close_by, closed_at, created_at, updated_at
+            FROM virtual_accounts
+            WHERE razorpay_virtual_account_id = $1
+            "#,
+            razorpay_virtual_account_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    pub async fn update_status(
+        pool: &PgPool,
+        id: Uuid,
+        status: VirtualAccountStatus,
+        closed_at: Option<DateTime<Utc>>,
+    ) -> AppResult<VirtualAccount> {
+        let account = sqlx::query_as!(
+            VirtualAccount,
+            r#"
+            UPDATE virtual_accounts
+            SET status = $2, closed_at = COALESCE($3, closed_at), updated_at = $4
+            WHERE id = $1
+            RETURNING
+                id, razorpay_virtual_account_id, payment_id, customer_id,
+                status as "status: VirtualAccountStatus",
+                amount_expected, amount_paid, amount_overpaid, account_number, ifsc,
+                bank_name, beneficiary_name, close_by, closed_at, created_at, updated_at
+            "#,
+            id,
+            status as VirtualAccountStatus,
+            closed_at,
+            Utc::now()
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Virtual account {} not found", id)))?;
+
+        Ok(account)
+    }
+
+    /// Record a bank transfer to the account. An account collecting a single
+    /// payment moves that payment along as credits add up to the expected
+    /// amount; an account belonging to a customer turns every credit into a
+    /// completed payment. Returns `false` if the credit was already recorded.
+    pub async fn record_credit(
+        pool: &PgPool,
+        account: &VirtualAccount,
+        credit: &BankTransferCredit,
+        customer: Option<&Customer>,
+    ) -> AppResult<(VirtualAccount, Payment, bool)> {
+        let mut db_tx = pool.begin().await?;
+
+        // Serializes concurrent reports of credits to the account
+        sqlx::query_scalar!(
+            r#"
+            SELECT id FROM virtual_accounts
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            account.id
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        let existing = sqlx::query_scalar!(
+            r#"
+            SELECT payment_id FROM virtual_account_credits
+            WHERE razorpay_payment_id = $1
+            "#,
+            credit.razorpay_payment_id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?;
+
+        if let Some(payment_id) = existing {
+            db_tx.rollback().await?;
+            let account = Self::find_by_id(pool, account.id).await?;
+            let payment = PaymentRepository::find_by_id(pool, payment_id).await?;
+            return Ok((account, payment, false));
+        }
+
+        let now = Utc::now();
+
+        let payment_id = match account.payment_id {
+            Some(payment_id) => payment_id,
+            None => {
+                let request = CreatePaymentRequest {
+                    amount: credit.amount,
+                    currency: CurrencyType::INR,
+                    method: PaymentMethod::BankTransfer,
+                    chain: None,
+                    description: Some(format!(
+                        "Bank transfer to virtual account {}",
+                        account.razorpay_virtual_account_id
+                    )),
+                    customer_email: customer.and_then(|c| c.email.clone()),
+                    customer_phone: customer.and_then(|c| c.contact.clone()),
+                    metadata: Some(serde_json::json!({
+                        "virtual_account_id": account.id,
+                        "razorpay_virtual_account_id": account.razorpay_virtual_account_id,
+                    })),
+                    callback_url: None,
+                };
+
+                PaymentRepository::create_completed_in(
+                    &mut db_tx,
+                    &request,
+                    &credit.razorpay_payment_id,
+                    None,
+                )
+                .await?
+                .id
+            }
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO virtual_account_credits (
+                id, virtual_account_id, payment_id, razorpay_payment_id, amount,
+                mode, bank_reference, payer_name, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            Uuid::new_v4(),
+            account.id,
+            payment_id,
+            credit.razorpay_payment_id,
+            credit.amount,
+            credit.mode,
+            credit.bank_reference,
+            credit.payer_name,
+            now
+        )
+        .execute(&mut *db_tx)
+        .await?;
+
+        let account = sqlx::query_as!(
+            VirtualAccount,
+            r#"
+            UPDATE virtual_accounts
+            SET
+                amount_paid = amount_paid + $2,
+                amount_overpaid = GREATEST(amount_paid + $2 - amount_expected, 0),
+                updated_at = $3
+            WHERE id = $1
+            RETURNING
+                id, razorpay_virtual_account_id, payment_id, customer_id,
+                status as "status: VirtualAccountStatus",
+                amount_expected, amount_paid, amount_overpaid, account_number, ifsc,
+                bank_name, beneficiary_name, close_by, closed_at, created_at, updated_at
+            "#,
+            account.id,
+            credit.amount,
+            now
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        // Only a payment still waiting for its money moves along
+        if let Some(collection) = account.collection() {
+            PaymentRepository::advance_status_in(
+                &mut db_tx,
+                payment_id,
+                collection.payment_status(),
+            )
+            .await?;
+        }
+
+        db_tx.commit().await?;
+
+        let payment = PaymentRepository::find_by_id(pool, payment_id).await?;
+
+        Ok((account, payment, true))
+    }
+
+    pub async fn find_credits(
+        pool: &PgPool,
+        virtual_account_id: Uuid,
+    ) -> AppResult<Vec<VirtualAccountCredit>> {
+        let credits = sqlx::query_as!(
+            VirtualAccountCredit,
+            r#"
+            SELECT
+                id, virtual_account_id, payment_id, razorpay_payment_id, amount,
+                mode, bank_reference, payer_name, created_at
+            FROM virtual_account_credits
+            WHERE virtual_account_id = $1
+            ORDER BY created_at
+            "#,
+            virtual_account_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(credits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PaymentStatus;
+
+    /// Point DATABASE_URL at a migrated database, then run
+    /// `cargo test virtual_account_repo -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a migrated DATABASE_URL"]
+    async fn test_record_credit_keeps_overpaid_excess() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let payment = PaymentRepository::create(
+            &pool,
+            &CreatePaymentRequest {
+                amount: 100_000,
+                currency: CurrencyType::INR,
+                method: PaymentMethod::BankTransfer,
+                chain: None,
+                description: None,
+                customer_email: None,
+                customer_phone: None,
+                metadata: None,
+                callback_url: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let account = VirtualAccountRepository::create(
+            &pool,
+            &NewVirtualAccount {
+                razorpay_virtual_account_id: format!("va_{}", Uuid::new_v4().simple()),
+                payment_id: Some(payment.id),
+                customer_id: None,
+                amount_expected: Some(100_000),
+                account_number: "2223330012345678".to_string(),
+                ifsc: "RATN0VAAPIS".to_string(),
+                bank_name: None,
+                beneficiary_name: None,
+                close_by: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let credit = |amount| BankTransferCredit {
+            razorpay_payment_id: format!("pay_{}", Uuid::new_v4().simple()),
+            amount,
+            mode: Some("NEFT".to_string()),
+            bank_reference: None,
+            payer_name: None,
+        };
+
+        let (account, payment, _) =
+            VirtualAccountRepository::record_credit(&pool, &account, &credit(60_000), None)
+                .await
+                .unwrap();
+        assert_eq!(account.amount_overpaid, 0);
+        assert_eq!(payment.status, PaymentStatus::Processing);
+
+        let (account, payment, _) =
+            VirtualAccountRepository::record_credit(&pool, &account, &credit(40_500), None)
+                .await
+                .unwrap();
+        assert_eq!(account.amount_paid, 100_500);
+        assert_eq!(account.amount_overpaid, 500);
+        assert_eq!(payment.status, PaymentStatus::Completed);
+    }
+}
@@ -30,6 +30,10 @@ pub enum AppError {
     #[error("Razorpay error: {0}")]
     Razorpay(String),
 
+    /// Razorpay answered with a 4xx status, so it did not act on the request
+    #[error("Razorpay rejected the request: {0}")]
+    RazorpayRejected(String),
+
     // Crypto errors
     #[error("Ethereum error: {0}")]
     Ethereum(String),
@@ -37,6 +41,9 @@ pub enum AppError {
     #[error("Solana error: {0}")]
     Solana(String),
 
+    #[error("Bitcoin error: {0}")]
+    Bitcoin(String),
+
     #[error("Lightning error: {0}")]
     Lightning(String),
 
@@ -106,9 +113,12 @@ impl IntoResponse for AppError {
             }
             AppError::Unauthorized(msg) => (StatusCode::FORBIDDEN, "UNAUTHORIZED", msg.clone()),
             AppError::Payment(msg) => (StatusCode::BAD_REQUEST, "PAYMENT_ERROR", msg.clone()),
-            AppError::Razorpay(msg) => (StatusCode::BAD_REQUEST, "RAZORPAY_ERROR", msg.clone()),
+            AppError::Razorpay(msg) | AppError::RazorpayRejected(msg) => {
+                (StatusCode::BAD_REQUEST, "RAZORPAY_ERROR", msg.clone())
+            }
             AppError::Ethereum(msg) => (StatusCode::BAD_REQUEST, "ETHEREUM_ERROR", msg.clone()),
             AppError::Solana(msg) => (StatusCode::BAD_REQUEST, "SOLANA_ERROR", msg.clone()),
+            AppError::Bitcoin(msg) => (StatusCode::BAD_REQUEST, "BITCOIN_ERROR", msg.clone()),
             AppError::Lightning(msg) => (StatusCode::BAD_REQUEST, "LIGHTNING_ERROR", msg.clone()),
             AppError::InvalidSignature(msg) => {
                 (StatusCode::BAD_REQUEST, "INVALID_SIGNATURE", msg.clone())
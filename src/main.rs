@@ -5,7 +5,7 @@ use payment_gateway::{
     api::middleware::logging::init_tracing,
     config::Config,
     db::{create_pool, run_migrations},
-    services::PaymentProcessor,
+    services::{monitor, PaymentProcessor},
     websocket::PaymentBroadcaster,
     AppState,
 };
@@ -54,6 +54,21 @@ async fn main() -> anyhow::Result<()> {
         Some(ws_broadcaster),
     );
 
+    // Start background deposit scanners, confirmation tracking and reorg detection
+    if config.monitor.enabled {
+        monitor::spawn_evm_scanners(&state);
+        monitor::spawn_bitcoin_watcher(&state);
+        monitor::spawn_confirmation_tracker(&state);
+        monitor::spawn_reorg_detector(&state);
+        monitor::spawn_invoice_watcher(&state);
+        tracing::info!("Blockchain monitors started");
+    }
+
+    // Held payments must be cancelled before their HTLCs expire, and refunds and
+    // payouts reserve funds until they resolve, so these run whenever Lightning is set up
+    monitor::spawn_hold_invoice_monitor(&state);
+    monitor::spawn_outgoing_payment_monitor(&state);
+
     // Create router
     let app = create_router(state);
 
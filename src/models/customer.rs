@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Our customer, mapped to the Razorpay customer that holds their saved cards
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Customer {
+    pub id: Uuid,
+    pub razorpay_customer_id: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub contact: Option<String>,
+    pub gstin: Option<String>,
+    pub notes: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Customer details as last reported by Razorpay
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomerDetails {
+    pub name: String,
+    pub email: Option<String>,
+    pub contact: Option<String>,
+    pub gstin: Option<String>,
+    pub notes: Option<serde_json::Value>,
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "hold_invoice_state", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum HoldInvoiceState {
+    /// Waiting for the customer to pay
+    Open,
+    /// Paid and held by the node until captured or cancelled
+    Accepted,
+    Settled,
+    Cancelled,
+}
+
+impl sqlx::postgres::PgHasArrayType for HoldInvoiceState {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_hold_invoice_state")
+    }
+}
+
+/// Hold invoice backing an escrowed Lightning payment
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct HoldInvoice {
+    pub payment_id: Uuid,
+    pub payment_hash: String,
+    /// Hex AES-256-GCM ciphertext of the preimage, never sent to clients
+    #[serde(skip_serializing)]
+    pub encrypted_preimage: String,
+    pub state: HoldInvoiceState,
+    pub htlc_expiry_height: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
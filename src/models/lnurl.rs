@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// A merchant's LNURL-pay endpoint, also reachable as the Lightning Address
+/// `name@domain` (LUD-16)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LnurlPayLink {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub min_sendable_msat: i64,
+    pub max_sendable_msat: i64,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LnurlPayLink {
+    /// LUD-06 metadata, a JSON array serialized to a string. Wallets check the
+    /// invoice's description hash against the SHA-256 of this exact string.
+    pub fn metadata(&self, domain: &str) -> String {
+        serde_json::json!([
+            ["text/plain", self.description],
+            ["text/identifier", format!("{}@{}", self.name, domain)],
+        ])
+        .to_string()
+    }
+
+    pub fn accepts(&self, amount_msat: u64) -> bool {
+        (self.min_sendable_msat as u64..=self.max_sendable_msat as u64).contains(&amount_msat)
+    }
+}
+
+/// LUD-16 usernames: lowercase letters, digits, `-`, `_` and `.`
+pub fn is_valid_lnurl_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateLnurlPayLinkRequest {
+    #[validate(length(min = 1, max = 64, message = "Invalid name length"))]
+    pub name: String,
+    #[validate(length(min = 1, max = 255, message = "Invalid description length"))]
+    pub description: String,
+    #[validate(range(min = 1000, message = "Minimum must be at least 1 sat"))]
+    pub min_sendable_msat: i64,
+    #[validate(range(min = 1000, message = "Maximum must be at least 1 sat"))]
+    pub max_sendable_msat: i64,
+}
+
+/// First LNURL-pay response, served from `/.well-known/lnurlp/:name`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LnurlPayResponse {
+    pub callback: String,
+    pub max_sendable: u64,
+    pub min_sendable: u64,
+    pub metadata: String,
+    pub tag: String,
+}
+
+/// Callback response carrying the invoice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LnurlInvoiceResponse {
+    pub pr: String,
+    pub routes: Vec<serde_json::Value>,
+}
+
+/// LNURL error body; wallets expect this shape rather than the API's error format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LnurlErrorResponse {
+    pub status: String,
+    pub reason: String,
+}
+
+impl LnurlErrorResponse {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            status: "ERROR".to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link() -> LnurlPayLink {
+        LnurlPayLink {
+            id: Uuid::new_v4(),
+            name: "coffee".to_string(),
+            description: "Coffee \"to go\"".to_string(),
+            min_sendable_msat: 1_000,
+            max_sendable_msat: 100_000_000,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_metadata() {
+        let metadata = link().metadata("pay.example.com");
+
+        assert_eq!(
+            metadata,
+            r#"[["text/plain","Coffee \"to go\""],["text/identifier","coffee@pay.example.com"]]"#
+        );
+    }
+
+    #[test]
+    fn test_accepts() {
+        let link = link();
+
+        assert!(link.accepts(1_000));
+        assert!(link.accepts(100_000_000));
+        assert!(!link.accepts(999));
+        assert!(!link.accepts(100_000_001));
+    }
+
+    #[test]
+    fn test_lnurl_name() {
+        assert!(is_valid_lnurl_name("coffee.shop-1_a"));
+        assert!(!is_valid_lnurl_name("Coffee"));
+        assert!(!is_valid_lnurl_name("coffee@shop"));
+        assert!(!is_valid_lnurl_name(""));
+    }
+}
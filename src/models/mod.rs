@@ -1,9 +1,27 @@
+pub mod customer;
+pub mod hold_invoice;
+pub mod lnurl;
 pub mod payment;
+pub mod payment_link;
+pub mod payout;
+pub mod qr_code;
+pub mod subscription;
 pub mod transaction;
+pub mod transfer;
+pub mod virtual_account;
 pub mod wallet;
 pub mod webhook_event;
 
+pub use customer::*;
+pub use hold_invoice::*;
+pub use lnurl::*;
 pub use payment::*;
+pub use payment_link::*;
+pub use payout::*;
+pub use qr_code::*;
+pub use subscription::*;
 pub use transaction::*;
+pub use transfer::*;
+pub use virtual_account::*;
 pub use wallet::*;
 pub use webhook_event::*;
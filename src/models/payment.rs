@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use super::ChainType;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "payment_status", rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
@@ -25,12 +27,33 @@ pub enum PaymentMethod {
     NetBanking,
     Wallet,
     Emi,
+    /// Any chain in the EVM registry; the chain itself is stored in `crypto_chain`
+    Evm,
+    // Per-chain methods of payments created before the EVM chain registry
     Ethereum,
     Polygon,
     Bsc,
     Arbitrum,
     Solana,
+    Bitcoin,
     Lightning,
+    /// NEFT/RTGS/IMPS transfer to a Razorpay Smart Collect virtual account
+    BankTransfer,
+}
+
+impl PaymentMethod {
+    /// Map the `method` of a Razorpay payment entity
+    pub fn from_razorpay(method: &str) -> Option<Self> {
+        match method {
+            "card" => Some(Self::Card),
+            "upi" => Some(Self::Upi),
+            "netbanking" => Some(Self::NetBanking),
+            "wallet" => Some(Self::Wallet),
+            "emi" => Some(Self::Emi),
+            "bank_transfer" => Some(Self::BankTransfer),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
@@ -49,6 +72,18 @@ pub enum CurrencyType {
     USDC,
 }
 
+impl CurrencyType {
+    /// Parse a currency code of the currencies Razorpay settles in
+    pub fn from_razorpay(currency: &str) -> Option<Self> {
+        match currency {
+            "INR" => Some(Self::INR),
+            "USD" => Some(Self::USD),
+            "EUR" => Some(Self::EUR),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Payment {
     pub id: Uuid,
@@ -77,11 +112,25 @@ pub struct Payment {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Audit record of a payment status reversal (e.g. after a chain reorganization)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PaymentStatusChange {
+    pub id: Uuid,
+    pub payment_id: Uuid,
+    pub previous_status: PaymentStatus,
+    pub new_status: PaymentStatus,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreatePaymentRequest {
     pub amount: i64,
     pub currency: CurrencyType,
     pub method: PaymentMethod,
+    /// Chain the payment settles on, required for `PaymentMethod::Evm`
+    #[serde(default)]
+    pub chain: Option<ChainType>,
     #[serde(default)]
     pub description: Option<String>,
     #[serde(default)]
@@ -106,6 +155,8 @@ pub struct PaymentResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub crypto_address: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lightning_invoice: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<DateTime<Utc>>,
@@ -122,6 +173,7 @@ impl From<Payment> for PaymentResponse {
             method: payment.method,
             razorpay_order_id: payment.razorpay_order_id,
             crypto_address: payment.crypto_to_address,
+            chain: payment.crypto_chain,
             lightning_invoice: payment.lightning_invoice,
             expires_at: payment.expires_at,
             created_at: payment.created_at,
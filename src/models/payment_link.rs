@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::PaymentStatus;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "payment_link_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentLinkStatus {
+    Created,
+    PartiallyPaid,
+    Paid,
+    Expired,
+    Cancelled,
+}
+
+impl PaymentLinkStatus {
+    /// Parse a payment link status as reported by Razorpay
+    pub fn from_razorpay(status: &str) -> Option<Self> {
+        match status {
+            "created" => Some(Self::Created),
+            "partially_paid" => Some(Self::PartiallyPaid),
+            "paid" => Some(Self::Paid),
+            "expired" => Some(Self::Expired),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// Status of the linked payment once the link reaches this state, if it
+    /// settles the payment one way or the other
+    pub fn payment_status(self) -> Option<PaymentStatus> {
+        match self {
+            Self::Paid => Some(PaymentStatus::Completed),
+            Self::Expired => Some(PaymentStatus::Expired),
+            Self::Cancelled => Some(PaymentStatus::Cancelled),
+            Self::Created | Self::PartiallyPaid => None,
+        }
+    }
+}
+
+/// Razorpay Payment Link collecting a payment
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PaymentLink {
+    pub payment_id: Uuid,
+    pub razorpay_payment_link_id: String,
+    pub short_url: String,
+    pub status: PaymentLinkStatus,
+    pub expire_by: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payment_status() {
+        assert_eq!(
+            PaymentLinkStatus::from_razorpay("paid").and_then(PaymentLinkStatus::payment_status),
+            Some(PaymentStatus::Completed)
+        );
+        assert_eq!(
+            PaymentLinkStatus::from_razorpay("expired").and_then(PaymentLinkStatus::payment_status),
+            Some(PaymentStatus::Expired)
+        );
+        assert_eq!(PaymentLinkStatus::PartiallyPaid.payment_status(), None);
+        assert_eq!(PaymentLinkStatus::from_razorpay("issued"), None);
+    }
+}
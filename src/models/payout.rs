@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+use super::{RazorpayPayoutData, TransactionStatus};
+
+/// Rail a RazorpayX payout is sent over
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PayoutMode {
+    Imps,
+    Neft,
+    Upi,
+}
+
+/// Where a RazorpayX fund account sends money: a bank account or a UPI VPA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "account_type", rename_all = "snake_case")]
+pub enum FundAccountDetails {
+    BankAccount { bank_account: BankAccountDetails },
+    Vpa { vpa: VpaDetails },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankAccountDetails {
+    /// Account holder's name
+    pub name: String,
+    pub ifsc: String,
+    pub account_number: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VpaDetails {
+    /// e.g. `customer@okbank`
+    pub address: String,
+}
+
+impl FundAccountDetails {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::BankAccount { bank_account } => {
+                if bank_account.name.trim().is_empty() || bank_account.name.len() > 120 {
+                    return Err("Invalid account holder name".to_string());
+                }
+                if !is_valid_ifsc(&bank_account.ifsc) {
+                    return Err(format!("Invalid IFSC: {}", bank_account.ifsc));
+                }
+                let account_number = &bank_account.account_number;
+                if !(5..=35).contains(&account_number.len())
+                    || !account_number.chars().all(|c| c.is_ascii_alphanumeric())
+                {
+                    return Err("Invalid account number".to_string());
+                }
+            }
+            Self::Vpa { vpa } => match vpa.address.split_once('@') {
+                Some((user, handle)) if !user.is_empty() && !handle.is_empty() => {}
+                _ => return Err(format!("Invalid VPA: {}", vpa.address)),
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// IFSC codes are 4 letters for the bank, a zero, then 6 characters for the branch
+fn is_valid_ifsc(ifsc: &str) -> bool {
+    let bytes = ifsc.as_bytes();
+    bytes.len() == 11
+        && bytes[..4].iter().all(u8::is_ascii_uppercase)
+        && bytes[4] == b'0'
+        && bytes[5..].iter().all(u8::is_ascii_alphanumeric)
+}
+
+impl PayoutMode {
+    /// Whether payouts in this mode can go to a fund account of `account_type`
+    /// as Razorpay names it: UPI only reaches VPAs, IMPS and NEFT only bank accounts
+    pub fn supports(&self, account_type: &str) -> bool {
+        match self {
+            Self::Upi => account_type == "vpa",
+            Self::Imps | Self::Neft => account_type == "bank_account",
+        }
+    }
+}
+
+/// Status of the withdrawal transaction behind a payout Razorpay reports in
+/// `status`. A processed payout can still be reversed by the bank later.
+pub fn payout_transaction_status(status: &str) -> Option<TransactionStatus> {
+    match status {
+        "queued" | "pending" | "processing" => Some(TransactionStatus::Pending),
+        "processed" => Some(TransactionStatus::Confirmed),
+        "reversed" | "failed" | "rejected" => Some(TransactionStatus::Failed),
+        "cancelled" => Some(TransactionStatus::Cancelled),
+        _ => None,
+    }
+}
+
+/// Whether an update reporting `next` applies to a payout's transaction in
+/// `current`, since webhooks can arrive out of order. Failed and cancelled
+/// payouts are final; a confirmed one can still be reversed.
+pub fn payout_status_applies(current: &TransactionStatus, next: &TransactionStatus) -> bool {
+    match current {
+        TransactionStatus::Pending | TransactionStatus::Confirming => true,
+        TransactionStatus::Confirmed => {
+            matches!(
+                next,
+                TransactionStatus::Confirmed | TransactionStatus::Failed
+            )
+        }
+        TransactionStatus::Failed | TransactionStatus::Cancelled => current == next,
+    }
+}
+
+/// State of a payout reported by Razorpay, to apply to its withdrawal transaction
+#[derive(Debug, Clone)]
+pub struct PayoutUpdate {
+    pub razorpay_payout_id: String,
+    pub fund_account_id: String,
+    pub status: TransactionStatus,
+    /// Status as Razorpay names it, e.g. `reversed` rather than `failed`
+    pub razorpay_status: String,
+    pub utr: Option<String>,
+    /// Fees including tax
+    pub fees: Option<i64>,
+    pub failure_reason: Option<String>,
+}
+
+impl TryFrom<&RazorpayPayoutData> for PayoutUpdate {
+    type Error = String;
+
+    fn try_from(data: &RazorpayPayoutData) -> Result<Self, Self::Error> {
+        Ok(Self {
+            razorpay_payout_id: data.id.clone(),
+            fund_account_id: data.fund_account_id.clone(),
+            status: payout_transaction_status(&data.status)
+                .ok_or_else(|| format!("Unknown payout status: {}", data.status))?,
+            razorpay_status: data.status.clone(),
+            utr: data.utr.clone(),
+            fees: data.fees,
+            failure_reason: data
+                .status_details
+                .as_ref()
+                .and_then(|details| details.description.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payout_status() {
+        assert_eq!(
+            payout_transaction_status("queued"),
+            Some(TransactionStatus::Pending)
+        );
+        assert_eq!(
+            payout_transaction_status("processed"),
+            Some(TransactionStatus::Confirmed)
+        );
+        assert_eq!(
+            payout_transaction_status("reversed"),
+            Some(TransactionStatus::Failed)
+        );
+        assert_eq!(payout_transaction_status("unknown"), None);
+
+        assert!(payout_status_applies(
+            &TransactionStatus::Pending,
+            &TransactionStatus::Confirmed
+        ));
+        assert!(payout_status_applies(
+            &TransactionStatus::Confirmed,
+            &TransactionStatus::Failed
+        ));
+        assert!(!payout_status_applies(
+            &TransactionStatus::Failed,
+            &TransactionStatus::Confirmed
+        ));
+    }
+
+    #[test]
+    fn test_fund_account_details() {
+        let bank: FundAccountDetails = serde_json::from_value(serde_json::json!({
+            "account_type": "bank_account",
+            "bank_account": {
+                "name": "Gaurav Kumar",
+                "ifsc": "HDFC0000053",
+                "account_number": "765432123456789"
+            }
+        }))
+        .unwrap();
+        assert!(bank.validate().is_ok());
+
+        let vpa = FundAccountDetails::Vpa {
+            vpa: VpaDetails {
+                address: "gaurav@exampleupi".to_string(),
+            },
+        };
+        assert!(vpa.validate().is_ok());
+        assert_eq!(serde_json::to_value(&vpa).unwrap()["account_type"], "vpa");
+
+        let bad_ifsc = FundAccountDetails::BankAccount {
+            bank_account: BankAccountDetails {
+                name: "Gaurav Kumar".to_string(),
+                ifsc: "HDFC1000053".to_string(),
+                account_number: "765432123456789".to_string(),
+            },
+        };
+        assert!(bad_ifsc.validate().is_err());
+
+        assert!(PayoutMode::Upi.supports("vpa"));
+        assert!(!PayoutMode::Imps.supports("vpa"));
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::PaymentStatus;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "qr_code_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum QrCodeStatus {
+    Active,
+    Closed,
+}
+
+impl QrCodeStatus {
+    /// Parse a QR code status as reported by Razorpay
+    pub fn from_razorpay(status: &str) -> Option<Self> {
+        match status {
+            "active" => Some(Self::Active),
+            "closed" => Some(Self::Closed),
+            _ => None,
+        }
+    }
+}
+
+/// Razorpay UPI QR code, e.g. shown at a store counter. It collects either one
+/// payment or, when multi-use, any number of payments.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct QrCode {
+    pub id: Uuid,
+    pub payment_id: Option<Uuid>,
+    pub customer_id: Option<Uuid>,
+    pub multiple_use: bool,
+    /// Amount every payment must be; a multi-use QR code without it takes any
+    /// amount
+    pub payment_amount: Option<i64>,
+    pub razorpay_qr_code_id: String,
+    pub image_url: String,
+    pub status: QrCodeStatus,
+    pub close_reason: Option<String>,
+    pub close_by: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewQrCode {
+    pub razorpay_qr_code_id: String,
+    pub payment_id: Option<Uuid>,
+    pub customer_id: Option<Uuid>,
+    pub multiple_use: bool,
+    pub payment_amount: Option<i64>,
+    pub image_url: String,
+    pub close_by: Option<DateTime<Utc>>,
+}
+
+/// UPI payment received by a QR code
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct QrCodeCredit {
+    pub id: Uuid,
+    pub qr_code_id: Uuid,
+    pub payment_id: Uuid,
+    pub razorpay_payment_id: String,
+    pub amount: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Status of the payment behind a QR code closed for `close_reason`, if
+/// closing settles it. A paid QR code is settled by its credit instead.
+pub fn qr_code_closed_payment_status(close_reason: Option<&str>) -> Option<PaymentStatus> {
+    match close_reason {
+        Some("paid") => None,
+        Some("on_demand") => Some(PaymentStatus::Cancelled),
+        _ => Some(PaymentStatus::Expired),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closed_payment_status() {
+        assert_eq!(qr_code_closed_payment_status(Some("paid")), None);
+        assert_eq!(
+            qr_code_closed_payment_status(Some("on_demand")),
+            Some(PaymentStatus::Cancelled)
+        );
+        assert_eq!(
+            qr_code_closed_payment_status(None),
+            Some(PaymentStatus::Expired)
+        );
+    }
+}
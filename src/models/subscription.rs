@@ -0,0 +1,220 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::{CurrencyType, RazorpaySubscriptionData};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "subscription_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    Created,
+    /// The customer completed the first checkout and authorized the mandate
+    Authenticated,
+    Active,
+    /// A charge failed and Razorpay is retrying it
+    Pending,
+    /// Every retry of a charge failed
+    Halted,
+    Paused,
+    Cancelled,
+    Completed,
+    Expired,
+}
+
+impl SubscriptionStatus {
+    /// Parse a subscription status as reported by Razorpay
+    pub fn from_razorpay(status: &str) -> Option<Self> {
+        match status {
+            "created" => Some(Self::Created),
+            "authenticated" => Some(Self::Authenticated),
+            "active" => Some(Self::Active),
+            "pending" => Some(Self::Pending),
+            "halted" => Some(Self::Halted),
+            "paused" => Some(Self::Paused),
+            "cancelled" => Some(Self::Cancelled),
+            "completed" => Some(Self::Completed),
+            "expired" => Some(Self::Expired),
+            _ => None,
+        }
+    }
+
+    /// No further charges are made once a subscription reaches these
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Cancelled | Self::Completed | Self::Expired)
+    }
+}
+
+/// A Razorpay subscription to a plan; its charges are recorded as payments
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Subscription {
+    pub id: Uuid,
+    pub razorpay_subscription_id: String,
+    pub razorpay_plan_id: String,
+    pub status: SubscriptionStatus,
+    /// Amount of each charge
+    pub amount: i64,
+    pub currency: CurrencyType,
+    pub quantity: i32,
+    pub total_count: i32,
+    pub paid_count: i32,
+    pub customer_email: Option<String>,
+    pub customer_phone: Option<String>,
+    pub short_url: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub current_start: Option<DateTime<Utc>>,
+    pub current_end: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Links a subscription charge to the payment recording it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SubscriptionCharge {
+    pub payment_id: Uuid,
+    pub subscription_id: Uuid,
+    pub razorpay_payment_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewSubscription {
+    pub razorpay_subscription_id: String,
+    pub razorpay_plan_id: String,
+    pub status: SubscriptionStatus,
+    pub amount: i64,
+    pub currency: CurrencyType,
+    pub quantity: i32,
+    pub total_count: i32,
+    pub customer_email: Option<String>,
+    pub customer_phone: Option<String>,
+    pub short_url: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Subscription state as last reported by Razorpay
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionUpdate {
+    pub status: SubscriptionStatus,
+    pub paid_count: i32,
+    pub current_start: Option<DateTime<Utc>>,
+    pub current_end: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl SubscriptionUpdate {
+    /// Build from a Razorpay status string and Unix timestamps
+    pub fn from_razorpay(
+        status: &str,
+        paid_count: i32,
+        current_start: Option<i64>,
+        current_end: Option<i64>,
+        ended_at: Option<i64>,
+    ) -> Result<Self, String> {
+        let status = SubscriptionStatus::from_razorpay(status)
+            .ok_or_else(|| format!("Unknown subscription status: {}", status))?;
+
+        Ok(Self {
+            status,
+            paid_count,
+            current_start: current_start.and_then(|t| DateTime::from_timestamp(t, 0)),
+            current_end: current_end.and_then(|t| DateTime::from_timestamp(t, 0)),
+            ended_at: ended_at.and_then(|t| DateTime::from_timestamp(t, 0)),
+        })
+    }
+}
+
+impl TryFrom<&RazorpaySubscriptionData> for SubscriptionUpdate {
+    type Error = String;
+
+    fn try_from(data: &RazorpaySubscriptionData) -> Result<Self, Self::Error> {
+        Self::from_razorpay(
+            &data.status,
+            data.paid_count,
+            data.current_start,
+            data.current_end,
+            data.ended_at,
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubscriptionResponse {
+    pub id: Uuid,
+    pub razorpay_subscription_id: String,
+    pub razorpay_plan_id: String,
+    pub status: SubscriptionStatus,
+    pub amount: i64,
+    pub currency: CurrencyType,
+    pub total_count: i32,
+    pub paid_count: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_end: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Subscription> for SubscriptionResponse {
+    fn from(subscription: Subscription) -> Self {
+        Self {
+            id: subscription.id,
+            razorpay_subscription_id: subscription.razorpay_subscription_id,
+            razorpay_plan_id: subscription.razorpay_plan_id,
+            status: subscription.status,
+            amount: subscription.amount,
+            currency: subscription.currency,
+            total_count: subscription.total_count,
+            paid_count: subscription.paid_count,
+            short_url: subscription.short_url,
+            current_end: subscription.current_end,
+            created_at: subscription.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_from_razorpay() {
+        assert_eq!(
+            SubscriptionStatus::from_razorpay("halted"),
+            Some(SubscriptionStatus::Halted)
+        );
+        assert_eq!(SubscriptionStatus::from_razorpay("unknown"), None);
+        assert!(SubscriptionStatus::Cancelled.is_terminal());
+        assert!(!SubscriptionStatus::Halted.is_terminal());
+    }
+
+    #[test]
+    fn test_update_from_webhook_entity() {
+        let data: RazorpaySubscriptionData = serde_json::from_value(serde_json::json!({
+            "id": "sub_00000000000001",
+            "entity": "subscription",
+            "plan_id": "plan_00000000000001",
+            "customer_id": null,
+            "status": "active",
+            "current_start": 1_700_000_000,
+            "current_end": 1_702_592_000,
+            "ended_at": null,
+            "quantity": 1,
+            "notes": [],
+            "charge_at": 1_702_592_000,
+            "total_count": 12,
+            "paid_count": 1,
+            "created_at": 1_699_999_000
+        }))
+        .unwrap();
+
+        let update = SubscriptionUpdate::try_from(&data).unwrap();
+
+        assert_eq!(update.status, SubscriptionStatus::Active);
+        assert_eq!(update.paid_count, 1);
+        assert_eq!(update.current_start.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(update.ended_at, None);
+    }
+}
@@ -35,6 +35,7 @@ pub struct Transaction {
     pub currency: String,
     pub tx_hash: Option<String>,
     pub block_number: Option<i64>,
+    pub block_hash: Option<String>,
     pub confirmations: i32,
     pub required_confirmations: i32,
     pub from_address: Option<String>,
@@ -87,3 +88,18 @@ pub struct VerifyTransactionRequest {
     pub tx_hash: String,
     pub chain: String,
 }
+
+/// A transfer into a deposit address, recorded on the payment transaction it settles
+#[derive(Debug, Clone)]
+pub struct DepositUpdate {
+    pub tx_hash: String,
+    /// Amount the transaction paid to the address
+    pub amount: i64,
+    /// `None` while the transaction is not in a block yet
+    pub block_number: Option<i64>,
+    pub block_hash: Option<String>,
+    pub from_address: String,
+    pub to_address: String,
+    pub confirmations: i32,
+    pub status: TransactionStatus,
+}
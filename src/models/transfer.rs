@@ -0,0 +1,254 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::{CurrencyType, RazorpayTransferData};
+
+/// Smallest transfer Razorpay Route accepts, 1 INR in paise
+pub const MIN_TRANSFER_AMOUNT: i64 = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "transfer_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    /// Requested, or for an order split, waiting for the payment to be captured
+    Created,
+    Pending,
+    Processed,
+    Failed,
+    Reversed,
+    PartiallyReversed,
+}
+
+impl TransferStatus {
+    /// Parse a transfer status as reported by Razorpay
+    pub fn from_razorpay(status: &str) -> Option<Self> {
+        match status {
+            "created" => Some(Self::Created),
+            "pending" => Some(Self::Pending),
+            "processed" => Some(Self::Processed),
+            "failed" => Some(Self::Failed),
+            "reversed" => Some(Self::Reversed),
+            "partially_reversed" => Some(Self::PartiallyReversed),
+            _ => None,
+        }
+    }
+
+    /// Whether a transfer in this status can move to `next`. Webhooks and API
+    /// responses arrive out of order, so a transfer only ever moves forward:
+    /// failed is final, a processed transfer cannot fail, and reversals only grow.
+    pub fn can_become(self, next: Self) -> bool {
+        match (self, next) {
+            (Self::Failed, next) => next == Self::Failed,
+            (current, Self::Failed) => matches!(current, Self::Created | Self::Pending),
+            (current, next) => next.stage() >= current.stage(),
+        }
+    }
+
+    fn stage(self) -> u8 {
+        match self {
+            Self::Created => 0,
+            Self::Pending => 1,
+            Self::Processed | Self::Failed => 2,
+            Self::PartiallyReversed => 3,
+            Self::Reversed => 4,
+        }
+    }
+}
+
+/// Razorpay Route transfer of funds to a linked account, either split off a
+/// payment or made directly from the account balance
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Transfer {
+    pub id: Uuid,
+    /// `None` for direct transfers
+    pub payment_id: Option<Uuid>,
+    /// `None` until Razorpay creates the transfers of an order on capture
+    pub razorpay_transfer_id: Option<String>,
+    pub linked_account_id: String,
+    pub amount: i64,
+    pub currency: CurrencyType,
+    pub status: TransferStatus,
+    pub amount_reversed: i64,
+    /// Settlement to the linked account is held
+    pub on_hold: bool,
+    pub on_hold_until: Option<DateTime<Utc>>,
+    pub notes: Option<serde_json::Value>,
+    pub error_message: Option<String>,
+    pub processed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Share of a payment sent to a linked account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferSplit {
+    /// Linked account ID (`acc_...`)
+    pub account: String,
+    pub amount: i64,
+    /// Hold settlement to the linked account, e.g. until an order is delivered
+    #[serde(default)]
+    pub on_hold: bool,
+    #[serde(default)]
+    pub on_hold_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub notes: Option<serde_json::Value>,
+}
+
+/// Check splits of a payment of `amount` before they are sent to Razorpay.
+/// `allocated` is what earlier transfers of the payment already take.
+pub fn validate_transfer_splits(
+    amount: i64,
+    allocated: i64,
+    splits: &[TransferSplit],
+) -> Result<(), String> {
+    if splits.is_empty() {
+        return Err("At least one transfer is required".to_string());
+    }
+
+    let mut total = allocated;
+    for split in splits {
+        if !split.account.starts_with("acc_") {
+            return Err(format!("Invalid linked account: {}", split.account));
+        }
+        if split.amount < MIN_TRANSFER_AMOUNT {
+            return Err(format!(
+                "Transfer amount must be at least {}",
+                MIN_TRANSFER_AMOUNT
+            ));
+        }
+        if split.on_hold_until.is_some() && !split.on_hold {
+            return Err("on_hold_until requires on_hold".to_string());
+        }
+        total = total.saturating_add(split.amount);
+    }
+
+    if total > amount {
+        return Err(format!(
+            "Transfers of {} exceed the payment amount of {}",
+            total, amount
+        ));
+    }
+
+    Ok(())
+}
+
+/// Transfer state as last reported by Razorpay
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferUpdate {
+    pub razorpay_transfer_id: String,
+    pub linked_account_id: String,
+    pub amount: i64,
+    pub currency: CurrencyType,
+    pub status: TransferStatus,
+    pub amount_reversed: i64,
+    pub on_hold: bool,
+    pub on_hold_until: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<&RazorpayTransferData> for TransferUpdate {
+    type Error = String;
+
+    fn try_from(data: &RazorpayTransferData) -> Result<Self, Self::Error> {
+        Ok(Self {
+            razorpay_transfer_id: data.id.clone(),
+            linked_account_id: data.recipient.clone(),
+            amount: data.amount,
+            currency: CurrencyType::from_razorpay(&data.currency)
+                .ok_or_else(|| format!("Unsupported transfer currency: {}", data.currency))?,
+            status: TransferStatus::from_razorpay(&data.status)
+                .ok_or_else(|| format!("Unknown transfer status: {}", data.status))?,
+            amount_reversed: data.amount_reversed,
+            on_hold: data.on_hold,
+            on_hold_until: data
+                .on_hold_until
+                .and_then(|t| DateTime::from_timestamp(t, 0)),
+            error_message: data.error.as_ref().and_then(|e| e.description.clone()),
+            processed_at: data
+                .processed_at
+                .and_then(|t| DateTime::from_timestamp(t, 0)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(account: &str, amount: i64) -> TransferSplit {
+        TransferSplit {
+            account: account.to_string(),
+            amount,
+            on_hold: false,
+            on_hold_until: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_transfer_splits() {
+        let splits = [split("acc_seller1", 6_000), split("acc_seller2", 3_000)];
+
+        assert!(validate_transfer_splits(10_000, 0, &splits).is_ok());
+        assert!(validate_transfer_splits(10_000, 1_000, &splits).is_ok());
+        assert!(validate_transfer_splits(10_000, 1_001, &splits).is_err());
+        assert!(validate_transfer_splits(10_000, 0, &[]).is_err());
+        assert!(validate_transfer_splits(10_000, 0, &[split("seller1", 6_000)]).is_err());
+        assert!(validate_transfer_splits(10_000, 0, &[split("acc_seller1", 99)]).is_err());
+    }
+
+    #[test]
+    fn test_transfer_status_only_moves_forward() {
+        use TransferStatus::*;
+
+        assert!(Created.can_become(Processed));
+        assert!(Pending.can_become(Failed));
+        assert!(Processed.can_become(PartiallyReversed));
+        assert!(PartiallyReversed.can_become(PartiallyReversed));
+        assert!(PartiallyReversed.can_become(Reversed));
+
+        assert!(!Reversed.can_become(Processed));
+        assert!(!PartiallyReversed.can_become(Processed));
+        assert!(!Processed.can_become(Pending));
+        assert!(!Processed.can_become(Failed));
+        assert!(!Failed.can_become(Processed));
+    }
+
+    #[test]
+    fn test_update_from_webhook_entity() {
+        let data: RazorpayTransferData = serde_json::from_value(serde_json::json!({
+            "id": "trf_00000000000001",
+            "entity": "transfer",
+            "status": "failed",
+            "source": "pay_00000000000001",
+            "recipient": "acc_00000000000001",
+            "amount": 5_000,
+            "currency": "INR",
+            "amount_reversed": 0,
+            "notes": [],
+            "on_hold": true,
+            "on_hold_until": null,
+            "processed_at": null,
+            "error": {
+                "code": "BAD_REQUEST_ERROR",
+                "description": "Linked account is not activated",
+                "reason": "linked_account_not_activated"
+            },
+            "created_at": 1_700_000_000
+        }))
+        .unwrap();
+
+        let update = TransferUpdate::try_from(&data).unwrap();
+
+        assert_eq!(update.status, TransferStatus::Failed);
+        assert_eq!(update.linked_account_id, "acc_00000000000001");
+        assert!(update.on_hold);
+        assert_eq!(
+            update.error_message.as_deref(),
+            Some("Linked account is not activated")
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::{PaymentStatus, RazorpayBankTransferData};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "virtual_account_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum VirtualAccountStatus {
+    Active,
+    /// The expected amount has been received
+    Paid,
+    Closed,
+}
+
+impl VirtualAccountStatus {
+    /// Parse a virtual account status as reported by Razorpay
+    pub fn from_razorpay(status: &str) -> Option<Self> {
+        match status {
+            "active" => Some(Self::Active),
+            "paid" => Some(Self::Paid),
+            "closed" => Some(Self::Closed),
+            _ => None,
+        }
+    }
+}
+
+/// Razorpay Smart Collect virtual account that customers pay into by
+/// NEFT/RTGS/IMPS. It collects either one payment or any number of payments
+/// from a customer.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct VirtualAccount {
+    pub id: Uuid,
+    pub razorpay_virtual_account_id: String,
+    pub payment_id: Option<Uuid>,
+    pub customer_id: Option<Uuid>,
+    pub status: VirtualAccountStatus,
+    pub amount_expected: Option<i64>,
+    pub amount_paid: i64,
+    /// Received beyond `amount_expected`, for the merchant to refund or keep
+    /// as credit
+    pub amount_overpaid: i64,
+    pub account_number: String,
+    pub ifsc: String,
+    pub bank_name: Option<String>,
+    pub beneficiary_name: Option<String>,
+    pub close_by: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl VirtualAccount {
+    /// How the credits received so far settle the expected amount, for an
+    /// account collecting a single payment
+    pub fn collection(&self) -> Option<Collection> {
+        self.amount_expected
+            .map(|expected| Collection::new(expected, self.amount_paid))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewVirtualAccount {
+    pub razorpay_virtual_account_id: String,
+    pub payment_id: Option<Uuid>,
+    pub customer_id: Option<Uuid>,
+    pub amount_expected: Option<i64>,
+    pub account_number: String,
+    pub ifsc: String,
+    pub bank_name: Option<String>,
+    pub beneficiary_name: Option<String>,
+    pub close_by: Option<DateTime<Utc>>,
+}
+
+/// Amount received by a virtual account against the amount expected
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Collection {
+    Unpaid,
+    Underpaid {
+        amount_due: i64,
+    },
+    Paid,
+    /// The excess is left for the merchant to refund or keep as credit
+    Overpaid {
+        excess_amount: i64,
+    },
+}
+
+impl Collection {
+    pub fn new(amount_expected: i64, amount_paid: i64) -> Self {
+        if amount_paid <= 0 {
+            Self::Unpaid
+        } else if amount_paid < amount_expected {
+            Self::Underpaid {
+                amount_due: amount_expected - amount_paid,
+            }
+        } else if amount_paid == amount_expected {
+            Self::Paid
+        } else {
+            Self::Overpaid {
+                excess_amount: amount_paid - amount_expected,
+            }
+        }
+    }
+
+    /// Status of the payment collected by the account
+    pub fn payment_status(self) -> PaymentStatus {
+        match self {
+            Self::Unpaid => PaymentStatus::Pending,
+            Self::Underpaid { .. } => PaymentStatus::Processing,
+            Self::Paid | Self::Overpaid { .. } => PaymentStatus::Completed,
+        }
+    }
+}
+
+/// Bank transfer received by a virtual account
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct VirtualAccountCredit {
+    pub id: Uuid,
+    pub virtual_account_id: Uuid,
+    pub payment_id: Uuid,
+    pub razorpay_payment_id: String,
+    pub amount: i64,
+    pub mode: Option<String>,
+    pub bank_reference: Option<String>,
+    pub payer_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Bank transfer reported by Razorpay, before it is recorded
+#[derive(Debug, Clone, PartialEq)]
+pub struct BankTransferCredit {
+    pub razorpay_payment_id: String,
+    pub amount: i64,
+    pub mode: Option<String>,
+    pub bank_reference: Option<String>,
+    pub payer_name: Option<String>,
+}
+
+impl From<&RazorpayBankTransferData> for BankTransferCredit {
+    fn from(data: &RazorpayBankTransferData) -> Self {
+        Self {
+            razorpay_payment_id: data.payment_id.clone(),
+            amount: data.amount,
+            mode: data.mode.clone(),
+            bank_reference: data.bank_reference.clone(),
+            payer_name: data
+                .payer_bank_account
+                .as_ref()
+                .and_then(|account| account.name.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_against_expected_amount() {
+        assert_eq!(Collection::new(100_000, 0), Collection::Unpaid);
+        assert_eq!(
+            Collection::new(100_000, 40_000),
+            Collection::Underpaid { amount_due: 60_000 }
+        );
+        assert_eq!(Collection::new(100_000, 100_000), Collection::Paid);
+        assert_eq!(
+            Collection::new(100_000, 100_500),
+            Collection::Overpaid { excess_amount: 500 }
+        );
+
+        assert_eq!(
+            Collection::new(100_000, 40_000).payment_status(),
+            PaymentStatus::Processing
+        );
+        assert_eq!(
+            Collection::new(100_000, 100_500).payment_status(),
+            PaymentStatus::Completed
+        );
+    }
+}
@@ -3,25 +3,41 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
-#[sqlx(type_name = "chain_type", rename_all = "snake_case")]
-#[serde(rename_all = "snake_case")]
+/// Blockchain a payment settles on. EVM chains come from the configured chain
+/// registry and are identified by their registry name, e.g. `Evm("base")`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
 pub enum ChainType {
-    Ethereum,
-    Polygon,
-    Bsc,
-    Arbitrum,
+    Evm(String),
     Solana,
     Bitcoin,
 }
 
+impl ChainType {
+    pub fn evm(name: &str) -> Self {
+        ChainType::Evm(name.to_lowercase())
+    }
+
+    pub fn is_evm(&self) -> bool {
+        matches!(self, ChainType::Evm(_))
+    }
+
+    /// Sequence HD derivation indices are reserved from. Every EVM chain derives
+    /// the same address for an index, so they share one sequence and an address
+    /// is never handed out on two chains at once.
+    pub fn derivation_scope(&self) -> &'static str {
+        match self {
+            ChainType::Evm(_) => "evm",
+            ChainType::Solana => "solana",
+            ChainType::Bitcoin => "bitcoin",
+        }
+    }
+}
+
 impl std::fmt::Display for ChainType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ChainType::Ethereum => write!(f, "ethereum"),
-            ChainType::Polygon => write!(f, "polygon"),
-            ChainType::Bsc => write!(f, "bsc"),
-            ChainType::Arbitrum => write!(f, "arbitrum"),
+            ChainType::Evm(name) => write!(f, "{}", name),
             ChainType::Solana => write!(f, "solana"),
             ChainType::Bitcoin => write!(f, "bitcoin"),
         }
@@ -31,19 +47,73 @@ impl std::fmt::Display for ChainType {
 impl std::str::FromStr for ChainType {
     type Err = String;
 
+    /// Any well-formed name parses as an EVM chain; whether it is actually
+    /// registered is checked against the chain registry by the caller
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "ethereum" | "eth" => Ok(ChainType::Ethereum),
-            "polygon" | "matic" => Ok(ChainType::Polygon),
-            "bsc" | "bnb" => Ok(ChainType::Bsc),
-            "arbitrum" | "arb" => Ok(ChainType::Arbitrum),
+        let name = s.trim().to_lowercase();
+
+        match name.as_str() {
             "solana" | "sol" => Ok(ChainType::Solana),
             "bitcoin" | "btc" => Ok(ChainType::Bitcoin),
+            "eth" => Ok(ChainType::evm("ethereum")),
+            "matic" => Ok(ChainType::evm("polygon")),
+            "bnb" => Ok(ChainType::evm("bsc")),
+            "arb" => Ok(ChainType::evm("arbitrum")),
+            // Off-chain; Lightning payments are tracked through the node, not a chain
+            "lightning" | "ln" => Err(format!("Not an on-chain network: {}", s)),
+            _ if is_valid_chain_name(&name) => Ok(ChainType::Evm(name)),
             _ => Err(format!("Unknown chain: {}", s)),
         }
     }
 }
 
+impl TryFrom<String> for ChainType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ChainType> for String {
+    fn from(chain: ChainType) -> Self {
+        chain.to_string()
+    }
+}
+
+/// Registry names are lowercase slugs so they are stable as database and API keys
+pub fn is_valid_chain_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 50
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+// Stored as text so registering a chain needs no schema change
+impl sqlx::Type<sqlx::Postgres> for ChainType {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <String as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <String as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl<'q> sqlx::Encode<'q, sqlx::Postgres> for ChainType {
+    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+        <String as sqlx::Encode<'q, sqlx::Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for ChainType {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let name = <&str as sqlx::Decode<'r, sqlx::Postgres>>::decode(value)?;
+        Ok(name.parse()?)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct CryptoAddress {
     pub id: Uuid,
@@ -55,6 +125,8 @@ pub struct CryptoAddress {
     pub expected_amount: Option<i64>,
     pub received_amount: Option<i64>,
     pub token_address: Option<String>,
+    pub derivation_path: Option<String>,
+    pub derivation_index: Option<i32>,
     pub last_checked_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
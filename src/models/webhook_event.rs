@@ -75,6 +75,13 @@ pub struct RazorpayWebhookPayload {
 pub struct RazorpayPaymentPayload {
     pub payment: Option<RazorpayPaymentEntity>,
     pub order: Option<RazorpayOrderEntity>,
+    pub subscription: Option<RazorpaySubscriptionEntity>,
+    pub payment_link: Option<RazorpayPaymentLinkEntity>,
+    pub transfer: Option<RazorpayTransferEntity>,
+    pub virtual_account: Option<RazorpayVirtualAccountEntity>,
+    pub bank_transfer: Option<RazorpayBankTransferEntity>,
+    pub qr_code: Option<RazorpayQrCodeEntity>,
+    pub payout: Option<RazorpayPayoutEntity>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,3 +122,164 @@ pub struct RazorpayOrderData {
     pub status: String,
     pub created_at: i64,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpaySubscriptionEntity {
+    pub entity: RazorpaySubscriptionData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpaySubscriptionData {
+    pub id: String,
+    pub entity: String,
+    pub plan_id: String,
+    pub status: String,
+    pub current_start: Option<i64>,
+    pub current_end: Option<i64>,
+    pub ended_at: Option<i64>,
+    pub total_count: i32,
+    pub paid_count: i32,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayPaymentLinkEntity {
+    pub entity: RazorpayPaymentLinkData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayPaymentLinkData {
+    pub id: String,
+    pub amount: i64,
+    pub amount_paid: i64,
+    pub currency: String,
+    pub status: String,
+    pub order_id: Option<String>,
+    pub reference_id: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayTransferEntity {
+    pub entity: RazorpayTransferData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayTransferData {
+    pub id: String,
+    pub entity: String,
+    pub status: String,
+    /// Payment, order or account the funds are transferred from
+    pub source: String,
+    pub recipient: String,
+    pub amount: i64,
+    pub currency: String,
+    pub amount_reversed: i64,
+    pub on_hold: bool,
+    pub on_hold_until: Option<i64>,
+    pub processed_at: Option<i64>,
+    pub error: Option<RazorpayTransferError>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayTransferError {
+    pub code: Option<String>,
+    pub description: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayVirtualAccountEntity {
+    pub entity: RazorpayVirtualAccountData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayVirtualAccountData {
+    pub id: String,
+    pub entity: String,
+    pub status: String,
+    pub amount_expected: Option<i64>,
+    pub amount_paid: i64,
+    pub customer_id: Option<String>,
+    pub closed_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayBankTransferEntity {
+    pub entity: RazorpayBankTransferData,
+}
+
+/// Bank transfer credited to a virtual account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayBankTransferData {
+    pub id: String,
+    pub entity: String,
+    pub payment_id: String,
+    /// NEFT, RTGS or IMPS
+    pub mode: Option<String>,
+    pub bank_reference: Option<String>,
+    pub amount: i64,
+    pub payer_bank_account: Option<RazorpayPayerBankAccount>,
+    pub virtual_account_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayPayerBankAccount {
+    pub ifsc: Option<String>,
+    pub bank_name: Option<String>,
+    pub name: Option<String>,
+    pub account_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayQrCodeEntity {
+    pub entity: RazorpayQrCodeData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayQrCodeData {
+    pub id: String,
+    pub entity: String,
+    pub status: String,
+    pub usage: String,
+    pub fixed_amount: bool,
+    pub payment_amount: Option<i64>,
+    pub payments_amount_received: i64,
+    pub payments_count_received: i64,
+    pub close_reason: Option<String>,
+    pub closed_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayPayoutEntity {
+    pub entity: RazorpayPayoutData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayPayoutData {
+    pub id: String,
+    pub entity: String,
+    pub fund_account_id: String,
+    pub amount: i64,
+    pub currency: String,
+    pub status: String,
+    pub mode: Option<String>,
+    /// Bank reference of the transfer, once the bank has processed it
+    pub utr: Option<String>,
+    pub fees: Option<i64>,
+    pub tax: Option<i64>,
+    /// Our transaction ID
+    pub reference_id: Option<String>,
+    pub status_details: Option<RazorpayPayoutStatusDetails>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayPayoutStatusDetails {
+    pub reason: Option<String>,
+    pub description: Option<String>,
+    pub source: Option<String>,
+}
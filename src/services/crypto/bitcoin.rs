@@ -0,0 +1,408 @@
+use bitcoin::{Address, Network};
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::str::FromStr;
+
+use crate::config::BitcoinConfig;
+use crate::error::{AppError, AppResult};
+
+/// On-chain Bitcoin payments backed by an Esplora-compatible REST API
+/// (Blockstream, mempool.space, or a local electrs for regtest)
+#[derive(Clone)]
+pub struct BitcoinService {
+    http_client: Client,
+    esplora_url: String,
+    network: Network,
+}
+
+impl BitcoinService {
+    pub fn new(config: &BitcoinConfig) -> AppResult<Self> {
+        let network = Network::from_str(&config.network)
+            .map_err(|e| AppError::Config(format!("Invalid BITCOIN_NETWORK: {}", e)))?;
+
+        let http_client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| AppError::Config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            http_client,
+            esplora_url: config.esplora_url.trim_end_matches('/').to_string(),
+            network,
+        })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Get the current chain tip height
+    pub async fn get_block_height(&self) -> AppResult<u64> {
+        let body = self.get_text("/blocks/tip/height").await?;
+
+        body.trim()
+            .parse()
+            .map_err(|e| AppError::Bitcoin(format!("Invalid tip height: {}", e)))
+    }
+
+    /// Get confirmed and unconfirmed balance of an address in satoshis
+    pub async fn get_balance(&self, address: &str) -> AppResult<AddressBalance> {
+        let address = self.parse_address(address)?;
+        let info: EsploraAddress = self.get_json(&format!("/address/{}", address)).await?;
+
+        Ok(AddressBalance {
+            confirmed_sats: info.chain_stats.balance(),
+            unconfirmed_sats: info.mempool_stats.balance(),
+        })
+    }
+
+    /// Get transaction by txid
+    pub async fn get_transaction(&self, txid: &str) -> AppResult<Option<EsploraTransaction>> {
+        Self::validate_txid(txid)?;
+
+        match self.get_json(&format!("/tx/{}", txid)).await {
+            Ok(tx) => Ok(Some(tx)),
+            Err(AppError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get recent transactions (mempool and confirmed) paying or spending an address
+    pub async fn get_address_transactions(
+        &self,
+        address: &str,
+    ) -> AppResult<Vec<EsploraTransaction>> {
+        let address = self.parse_address(address)?;
+        self.get_json(&format!("/address/{}/txs", address)).await
+    }
+
+    /// Get number of confirmations for a transaction
+    pub async fn get_confirmations(&self, txid: &str) -> AppResult<u64> {
+        match self.get_transaction(txid).await? {
+            Some(tx) => self.confirmations_for(&tx).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Verify a payment transaction
+    pub async fn verify_payment(
+        &self,
+        txid: &str,
+        expected_to: &str,
+        expected_amount_sats: u64,
+    ) -> AppResult<BitcoinPaymentVerification> {
+        let expected_to = self.parse_address(expected_to)?.to_string();
+
+        let tx = self
+            .get_transaction(txid)
+            .await?
+            .ok_or_else(|| AppError::Bitcoin("Transaction not found".to_string()))?;
+
+        let actual_amount = tx.amount_to(&expected_to);
+        let to_matches = actual_amount > 0;
+        let amount_matches = actual_amount >= expected_amount_sats;
+        let confirmations = self.confirmations_for(&tx).await?;
+
+        Ok(BitcoinPaymentVerification {
+            is_valid: to_matches && amount_matches,
+            to_matches,
+            amount_matches,
+            confirmations,
+            from_address: tx.input_addresses().into_iter().next(),
+            actual_amount,
+            block_height: tx.status.block_height,
+            block_hash: tx.status.block_hash.clone(),
+        })
+    }
+
+    /// Find the confirmed transactions paying an address, for deposit watching.
+    /// Mempool transactions are left out until they are mined, since they can
+    /// still be replaced.
+    pub async fn find_deposits(&self, address: &str) -> AppResult<Vec<EsploraTransaction>> {
+        let address_str = self.parse_address(address)?.to_string();
+        let txs = self.get_address_transactions(address).await?;
+
+        Ok(txs
+            .into_iter()
+            .filter(|tx| tx.status.confirmed && tx.status.block_height.is_some())
+            .filter(|tx| tx.amount_to(&address_str) > 0)
+            .collect())
+    }
+
+    /// Validate an address for the configured network
+    pub fn validate_address(&self, address: &str) -> bool {
+        self.parse_address(address).is_ok()
+    }
+
+    /// Convert satoshis to BTC
+    pub fn sats_to_btc(sats: u64) -> String {
+        let btc = sats as f64 / 100_000_000.0;
+        format!("{:.8}", btc)
+    }
+
+    /// Get required confirmations based on amount
+    pub fn get_required_confirmations(amount_sats: u64) -> i32 {
+        let btc = amount_sats as f64 / 100_000_000.0;
+
+        if btc > 1.0 {
+            6
+        } else if btc > 0.1 {
+            3
+        } else {
+            1
+        }
+    }
+
+    /// Confirmations of a transaction mined at `height` when the tip is at `tip`.
+    /// The block containing the transaction counts as the first confirmation.
+    pub fn confirmations_at(tip: u64, height: u64) -> u64 {
+        tip.saturating_sub(height) + 1
+    }
+
+    async fn confirmations_for(&self, tx: &EsploraTransaction) -> AppResult<u64> {
+        match tx.status.block_height {
+            Some(height) if tx.status.confirmed => {
+                let tip = self.get_block_height().await?;
+                Ok(Self::confirmations_at(tip, height))
+            }
+            _ => Ok(0),
+        }
+    }
+
+    fn parse_address(&self, address: &str) -> AppResult<Address> {
+        Address::from_str(address)
+            .map_err(|e| AppError::InvalidAddress(format!("Invalid Bitcoin address: {}", e)))?
+            .require_network(self.network)
+            .map_err(|e| AppError::InvalidAddress(format!("Wrong Bitcoin network: {}", e)))
+    }
+
+    fn validate_txid(txid: &str) -> AppResult<()> {
+        if txid.len() == 64 && txid.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(())
+        } else {
+            Err(AppError::Bitcoin("Invalid transaction id".to_string()))
+        }
+    }
+
+    async fn get_text(&self, endpoint: &str) -> AppResult<String> {
+        let url = format!("{}{}", self.esplora_url, endpoint);
+        let response = self.http_client.get(&url).send().await?;
+        let status = response.status();
+
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::NotFound(format!("Esplora resource {}", endpoint)));
+        }
+
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            tracing::error!("Esplora API error: {} - {}", status, body);
+            return Err(AppError::Bitcoin(format!("Esplora API error: {}", status)));
+        }
+
+        Ok(response.text().await?)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, endpoint: &str) -> AppResult<T> {
+        let body = self.get_text(endpoint).await?;
+
+        serde_json::from_str(&body)
+            .map_err(|e| AppError::Bitcoin(format!("Failed to parse Esplora response: {}", e)))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AddressBalance {
+    pub confirmed_sats: u64,
+    pub unconfirmed_sats: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BitcoinPaymentVerification {
+    pub is_valid: bool,
+    pub to_matches: bool,
+    pub amount_matches: bool,
+    pub confirmations: u64,
+    pub from_address: Option<String>,
+    pub actual_amount: u64,
+    pub block_height: Option<u64>,
+    pub block_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EsploraTransaction {
+    pub txid: String,
+    pub status: EsploraTxStatus,
+    #[serde(default)]
+    pub vin: Vec<EsploraInput>,
+    #[serde(default)]
+    pub vout: Vec<EsploraOutput>,
+    #[serde(default)]
+    pub fee: Option<u64>,
+}
+
+impl EsploraTransaction {
+    /// Total value of outputs paying `address`, in satoshis
+    pub fn amount_to(&self, address: &str) -> u64 {
+        self.vout
+            .iter()
+            .filter(|out| out.scriptpubkey_address.as_deref() == Some(address))
+            .map(|out| out.value)
+            .sum()
+    }
+
+    pub fn input_addresses(&self) -> Vec<String> {
+        self.vin
+            .iter()
+            .filter_map(|input| input.prevout.as_ref())
+            .filter_map(|prevout| prevout.scriptpubkey_address.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EsploraTxStatus {
+    pub confirmed: bool,
+    #[serde(default)]
+    pub block_height: Option<u64>,
+    #[serde(default)]
+    pub block_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EsploraInput {
+    #[serde(default)]
+    pub prevout: Option<EsploraOutput>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EsploraOutput {
+    #[serde(default)]
+    pub scriptpubkey_address: Option<String>,
+    pub value: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraAddress {
+    chain_stats: EsploraAddressStats,
+    mempool_stats: EsploraAddressStats,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraAddressStats {
+    funded_txo_sum: u64,
+    spent_txo_sum: u64,
+}
+
+impl EsploraAddressStats {
+    fn balance(&self) -> u64 {
+        self.funded_txo_sum.saturating_sub(self.spent_txo_sum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const DEPOSIT: &str = "bcrt1q6rz28mcfaxtmd6v789l9rrlrusdprr9pz3cppk";
+    const TXID: &str = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+    fn regtest_service(url: &str) -> BitcoinService {
+        BitcoinService::new(&BitcoinConfig {
+            esplora_url: url.to_string(),
+            network: "regtest".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_payment_counts_confirmations() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path(format!("/tx/{}", TXID)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "txid": TXID,
+                "status": { "confirmed": true, "block_height": 100, "block_hash": "00ab" },
+                "vin": [{ "prevout": { "scriptpubkey_address": "bcrt1qsender", "value": 90000 } }],
+                "vout": [
+                    { "scriptpubkey_address": DEPOSIT, "value": 50000 },
+                    { "scriptpubkey_address": "bcrt1qchange", "value": 39000 }
+                ],
+                "fee": 1000
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/blocks/tip/height"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("102"))
+            .mount(&server)
+            .await;
+
+        let service = regtest_service(&server.uri());
+
+        let verification = service.verify_payment(TXID, DEPOSIT, 50000).await.unwrap();
+        assert!(verification.is_valid);
+        assert_eq!(verification.actual_amount, 50000);
+        assert_eq!(verification.confirmations, 3);
+        assert_eq!(verification.from_address.as_deref(), Some("bcrt1qsender"));
+
+        let underpaid = service.verify_payment(TXID, DEPOSIT, 60000).await.unwrap();
+        assert!(!underpaid.is_valid);
+        assert!(underpaid.to_matches);
+    }
+
+    #[tokio::test]
+    async fn test_find_deposits_skips_mempool_and_other_addresses() {
+        let server = MockServer::start().await;
+        let other_txid = "ff".repeat(32);
+        let mempool_txid = "ee".repeat(32);
+
+        Mock::given(method("GET"))
+            .and(path(format!("/address/{}/txs", DEPOSIT)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {
+                    "txid": mempool_txid,
+                    "status": { "confirmed": false },
+                    "vout": [{ "scriptpubkey_address": DEPOSIT, "value": 20000 }]
+                },
+                {
+                    "txid": other_txid,
+                    "status": { "confirmed": true, "block_height": 101, "block_hash": "00cd" },
+                    "vin": [{ "prevout": { "scriptpubkey_address": DEPOSIT, "value": 50000 } }],
+                    "vout": [{ "scriptpubkey_address": "bcrt1qelsewhere", "value": 49000 }]
+                },
+                {
+                    "txid": TXID,
+                    "status": { "confirmed": true, "block_height": 100, "block_hash": "00ab" },
+                    "vout": [{ "scriptpubkey_address": DEPOSIT, "value": 50000 }]
+                }
+            ])))
+            .mount(&server)
+            .await;
+
+        let service = regtest_service(&server.uri());
+
+        let deposits = service.find_deposits(DEPOSIT).await.unwrap();
+        assert_eq!(deposits.len(), 1);
+        assert_eq!(deposits[0].txid, TXID);
+        assert_eq!(deposits[0].amount_to(DEPOSIT), 50000);
+    }
+
+    #[test]
+    fn test_rejects_wrong_network_address() {
+        let service = regtest_service("http://localhost:3002");
+
+        assert!(service.validate_address(DEPOSIT));
+        assert!(!service.validate_address("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"));
+    }
+
+    #[test]
+    fn test_required_confirmations() {
+        assert_eq!(BitcoinService::get_required_confirmations(50_000), 1);
+        assert_eq!(BitcoinService::get_required_confirmations(20_000_000), 3);
+        assert_eq!(BitcoinService::get_required_confirmations(200_000_000), 6);
+    }
+}
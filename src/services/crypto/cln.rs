@@ -0,0 +1,616 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::time::Duration;
+
+use super::lightning::{
+    node_http_client, CreateInvoiceResponse, InvoiceLookup, LightningBackend, NodeInfo,
+    OutgoingPayment, OutgoingStatus, PaymentStatus, SettlementStream,
+};
+use crate::config::LightningConfig;
+use crate::error::{AppError, AppResult};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Payments can take a while to find a route
+const PAYMENT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Seconds `waitanyinvoice` blocks before giving up, so dead connections are noticed
+const WAIT_TIMEOUT_SECS: u64 = 60;
+
+/// `waitanyinvoice` error code for a timeout without any payment
+const WAIT_TIMED_OUT: i64 = 904;
+
+/// Core Lightning client for the clnrest plugin, authenticated with a commando rune.
+/// Hold invoices need a separate plugin in Core Lightning and are not supported.
+#[derive(Clone)]
+pub struct ClnClient {
+    http_client: Client,
+    node_url: String,
+    rune: String,
+}
+
+impl ClnClient {
+    pub fn new(config: &LightningConfig) -> AppResult<Self> {
+        let rune = config.rune.clone().ok_or_else(|| {
+            AppError::Config("LIGHTNING_RUNE is required for the cln backend".to_string())
+        })?;
+
+        Ok(Self {
+            http_client: node_http_client(config)?,
+            node_url: config.node_url.trim_end_matches('/').to_string(),
+            rune,
+        })
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> AppResult<T> {
+        self.call_rpc(method, params, timeout).await?.map_err(|e| {
+            tracing::error!("CLN {} failed: {} - {}", method, e.code, e.message);
+            AppError::Lightning(format!("CLN {} failed: {}", method, e.message))
+        })
+    }
+
+    /// Transport failures in the outer result, JSON-RPC errors from the node in the inner one
+    async fn call_rpc<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> AppResult<Result<T, ClnError>> {
+        let response = self
+            .http_client
+            .post(format!("{}/v1/{}", self.node_url, method))
+            .header("Rune", &self.rune)
+            .timeout(timeout)
+            .json(&params)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return match serde_json::from_str::<ClnError>(&body) {
+                Ok(error) => Ok(Err(error)),
+                Err(_) => Err(AppError::Lightning(format!("CLN API error: {} - {}", status, body))),
+            };
+        }
+
+        serde_json::from_str(&body)
+            .map(Ok)
+            .map_err(|e| AppError::Lightning(format!("Failed to parse CLN response: {}", e)))
+    }
+
+    /// Next invoice paid after `lastpay_index`, `None` if the wait timed out
+    async fn wait_any_invoice(&self, lastpay_index: u64) -> AppResult<Option<ClnInvoice>> {
+        let params = serde_json::json!({
+            "lastpay_index": lastpay_index,
+            "timeout": WAIT_TIMEOUT_SECS,
+        });
+        let timeout = Duration::from_secs(WAIT_TIMEOUT_SECS) + REQUEST_TIMEOUT;
+
+        match self.call_rpc("waitanyinvoice", params, timeout).await? {
+            Ok(invoice) => Ok(Some(invoice)),
+            Err(e) if e.code == WAIT_TIMED_OUT => Ok(None),
+            Err(e) => Err(AppError::Lightning(format!("CLN waitanyinvoice failed: {}", e.message))),
+        }
+    }
+}
+
+#[async_trait]
+impl LightningBackend for ClnClient {
+    fn name(&self) -> &'static str {
+        "cln"
+    }
+
+    async fn create_invoice(
+        &self,
+        amount_sat: u64,
+        description: &str,
+        expiry_seconds: u32,
+    ) -> AppResult<CreateInvoiceResponse> {
+        // Labels must be unique per node; nothing else refers to them
+        let params = serde_json::json!({
+            "amount_msat": amount_sat * 1000,
+            "label": uuid::Uuid::new_v4().to_string(),
+            "description": description,
+            "expiry": expiry_seconds,
+        });
+
+        let response: ClnInvoiceResponse = self.call("invoice", params, REQUEST_TIMEOUT).await?;
+
+        Ok(CreateInvoiceResponse {
+            payment_request: response.bolt11,
+            payment_hash: response.payment_hash,
+            add_index: response.created_index,
+        })
+    }
+
+    /// `deschashonly` puts only the hash of the description in the invoice
+    async fn create_hashed_invoice(
+        &self,
+        amount_msat: u64,
+        description: &str,
+        expiry_seconds: u32,
+    ) -> AppResult<CreateInvoiceResponse> {
+        let params = serde_json::json!({
+            "amount_msat": amount_msat,
+            "label": uuid::Uuid::new_v4().to_string(),
+            "description": description,
+            "expiry": expiry_seconds,
+            "deschashonly": true,
+        });
+
+        let response: ClnInvoiceResponse = self.call("invoice", params, REQUEST_TIMEOUT).await?;
+
+        Ok(CreateInvoiceResponse {
+            payment_request: response.bolt11,
+            payment_hash: response.payment_hash,
+            add_index: response.created_index,
+        })
+    }
+
+    async fn lookup_invoice(&self, payment_hash: &str) -> AppResult<Option<InvoiceLookup>> {
+        let params = serde_json::json!({ "payment_hash": payment_hash });
+        let response: ClnListInvoices = self.call("listinvoices", params, REQUEST_TIMEOUT).await?;
+
+        Ok(response.invoices.into_iter().next().map(ClnInvoice::into_lookup))
+    }
+
+    /// Repeated `waitanyinvoice` long-polls; CLN's pay index plays the role of the settle index
+    async fn subscribe_settlements(&self, settle_index: u64) -> AppResult<SettlementStream> {
+        let client = self.clone();
+
+        let settlements = futures::stream::unfold(Some(settle_index), move |state| {
+            let client = client.clone();
+            async move {
+                let mut lastpay_index = state?;
+
+                loop {
+                    match client.wait_any_invoice(lastpay_index).await {
+                        Ok(Some(invoice)) => {
+                            lastpay_index = invoice.pay_index.unwrap_or(lastpay_index);
+                            return Some((Ok(invoice.into_lookup()), Some(lastpay_index)));
+                        }
+                        Ok(None) => continue,
+                        Err(e) => return Some((Err(e), None)),
+                    }
+                }
+            }
+        });
+
+        Ok(settlements.boxed())
+    }
+
+    async fn pay_invoice(
+        &self,
+        payment_request: &str,
+        max_fee_sat: u64,
+    ) -> AppResult<OutgoingStatus> {
+        let params = serde_json::json!({
+            "bolt11": payment_request,
+            "maxfee": max_fee_sat * 1000,
+        });
+
+        let response: ClnPayResponse = self.call("pay", params, PAYMENT_TIMEOUT).await?;
+
+        match response.status.as_str() {
+            "complete" => Ok(OutgoingStatus::Succeeded(OutgoingPayment {
+                payment_hash: response.payment_hash,
+                preimage: response.payment_preimage.unwrap_or_default(),
+                amount_msat: response.amount_msat,
+                fee_msat: response.amount_sent_msat.saturating_sub(response.amount_msat),
+            })),
+            // `pay` stopped waiting while HTLCs are still out; `listpays` settles it later
+            "pending" => Ok(OutgoingStatus::InFlight),
+            status => Err(AppError::Lightning(format!("Payment {}", status))),
+        }
+    }
+
+    /// `listpays`, which groups the attempts made to pay one hash
+    async fn lookup_payment(&self, payment_hash: &str) -> AppResult<Option<OutgoingStatus>> {
+        let params = serde_json::json!({ "payment_hash": payment_hash });
+        let response: ClnListPays = self.call("listpays", params, REQUEST_TIMEOUT).await?;
+
+        Ok(response.into_status())
+    }
+
+    async fn node_info(&self) -> AppResult<NodeInfo> {
+        let info: ClnGetInfo = self
+            .call("getinfo", serde_json::json!({}), REQUEST_TIMEOUT)
+            .await?;
+
+        Ok(NodeInfo {
+            pubkey: info.id,
+            alias: info.alias,
+            block_height: info.blockheight,
+            // getinfo only carries these warnings while still syncing
+            synced_to_chain: info.warning_bitcoind_sync.is_none()
+                && info.warning_lightningd_sync.is_none(),
+            num_active_channels: info.num_active_channels,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClnError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClnInvoiceResponse {
+    bolt11: String,
+    payment_hash: String,
+    #[serde(default)]
+    created_index: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClnListInvoices {
+    invoices: Vec<ClnInvoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClnInvoice {
+    payment_hash: String,
+    #[serde(default)]
+    bolt11: String,
+    status: String,
+    #[serde(default)]
+    amount_msat: u64,
+    #[serde(default)]
+    amount_received_msat: u64,
+    pay_index: Option<u64>,
+    paid_at: Option<i64>,
+    payment_preimage: Option<String>,
+}
+
+impl ClnInvoice {
+    fn into_lookup(self) -> InvoiceLookup {
+        let status = match self.status.as_str() {
+            "unpaid" => PaymentStatus::Pending,
+            "paid" => PaymentStatus::Settled,
+            "expired" => PaymentStatus::Cancelled,
+            _ => PaymentStatus::Unknown,
+        };
+
+        InvoiceLookup {
+            payment_hash: self.payment_hash,
+            payment_request: self.bolt11,
+            status,
+            amount_sat: self.amount_msat / 1000,
+            amount_paid_msat: self.amount_received_msat,
+            settle_index: self.pay_index.unwrap_or(0),
+            settled_at: self.paid_at,
+            preimage: self.payment_preimage,
+            htlc_expiry_height: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClnPayResponse {
+    #[serde(default)]
+    payment_hash: String,
+    payment_preimage: Option<String>,
+    #[serde(default)]
+    amount_msat: u64,
+    #[serde(default)]
+    amount_sent_msat: u64,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClnListPays {
+    pays: Vec<ClnPay>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClnPay {
+    payment_hash: String,
+    status: String,
+    preimage: Option<String>,
+    #[serde(default)]
+    amount_msat: u64,
+    #[serde(default)]
+    amount_sent_msat: u64,
+}
+
+impl ClnListPays {
+    /// A hash can be retried after a failed attempt, so any completed attempt
+    /// wins and the payment has only failed once no attempt is left pending
+    fn into_status(self) -> Option<OutgoingStatus> {
+        if self.pays.is_empty() {
+            return None;
+        }
+
+        if let Some(pay) = self.pays.iter().find(|pay| pay.status == "complete") {
+            return Some(OutgoingStatus::Succeeded(OutgoingPayment {
+                payment_hash: pay.payment_hash.clone(),
+                preimage: pay.preimage.clone().unwrap_or_default(),
+                amount_msat: pay.amount_msat,
+                fee_msat: pay.amount_sent_msat.saturating_sub(pay.amount_msat),
+            }));
+        }
+
+        if self.pays.iter().any(|pay| pay.status == "pending") {
+            return Some(OutgoingStatus::InFlight);
+        }
+
+        Some(OutgoingStatus::Failed("Payment failed".to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClnGetInfo {
+    id: String,
+    #[serde(default)]
+    alias: String,
+    #[serde(default)]
+    blockheight: u32,
+    #[serde(default)]
+    num_active_channels: u32,
+    warning_bitcoind_sync: Option<String>,
+    warning_lightningd_sync: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const HASH_HEX: &str = "0001020304050607080910111213141516171819202122232425262728293031";
+
+    fn cln_client(url: &str) -> ClnClient {
+        ClnClient::new(&LightningConfig {
+            backend: "cln".to_string(),
+            node_url: url.to_string(),
+            macaroon_path: None,
+            tls_cert_path: None,
+            rune: Some("test-rune".to_string()),
+            lnurl_base_url: None,
+            network: "regtest".to_string(),
+            max_fee_ppm: 5_000,
+            min_fee_limit_sat: 10,
+            hold_cltv_expiry: 144,
+            hold_cancel_margin_blocks: 12,
+        })
+        .unwrap()
+    }
+
+    fn paid_invoice(pay_index: u64) -> serde_json::Value {
+        serde_json::json!({
+            "label": "a",
+            "bolt11": "lnbcrt15u1pexample",
+            "payment_hash": HASH_HEX,
+            "status": "paid",
+            "amount_msat": 1_500_000,
+            "amount_received_msat": 1_500_000,
+            "pay_index": pay_index,
+            "paid_at": 1_700_000_000,
+            "payment_preimage": HASH_HEX
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_invoice_sends_rune() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/invoice"))
+            .and(header("Rune", "test-rune"))
+            .and(body_partial_json(serde_json::json!({ "amount_msat": 1_500_000, "expiry": 3600 })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "bolt11": "lnbcrt15u1pexample",
+                "payment_hash": HASH_HEX,
+                "expires_at": 1_700_003_600,
+                "created_index": 4
+            })))
+            .mount(&server)
+            .await;
+
+        let invoice = cln_client(&server.uri())
+            .create_invoice(1500, "Order 42", 3600)
+            .await
+            .unwrap();
+
+        assert_eq!(invoice.payment_hash, HASH_HEX);
+        assert_eq!(invoice.add_index, 4);
+    }
+
+    #[tokio::test]
+    async fn test_create_hashed_invoice() {
+        let server = MockServer::start().await;
+        let metadata = r#"[["text/plain","Coffee"]]"#;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/invoice"))
+            .and(body_partial_json(serde_json::json!({
+                "amount_msat": 21_000,
+                "description": metadata,
+                "deschashonly": true
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "bolt11": "lnbcrt210n1pexample",
+                "payment_hash": HASH_HEX,
+                "expires_at": 1_700_003_600,
+                "created_index": 5
+            })))
+            .mount(&server)
+            .await;
+
+        let invoice = cln_client(&server.uri())
+            .create_hashed_invoice(21_000, metadata, 3600)
+            .await
+            .unwrap();
+
+        assert_eq!(invoice.payment_request, "lnbcrt210n1pexample");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_invoice() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/listinvoices"))
+            .and(body_partial_json(serde_json::json!({ "payment_hash": HASH_HEX })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "invoices": [paid_invoice(2)]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/listinvoices"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "invoices": []
+            })))
+            .mount(&server)
+            .await;
+
+        let client = cln_client(&server.uri());
+
+        let invoice = client.lookup_invoice(HASH_HEX).await.unwrap().unwrap();
+        assert!(matches!(invoice.status, PaymentStatus::Settled));
+        assert_eq!(invoice.amount_sat, 1500);
+        assert_eq!(invoice.settle_index, 2);
+
+        assert!(client.lookup_invoice(&"ff".repeat(32)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pay_invoice() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pay"))
+            .and(body_partial_json(serde_json::json!({ "bolt11": "lnbc1ok" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "payment_hash": HASH_HEX,
+                "payment_preimage": "ab".repeat(32),
+                "amount_msat": 1_500_000,
+                "amount_sent_msat": 1_502_000,
+                "status": "complete"
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pay"))
+            .and(body_partial_json(serde_json::json!({ "bolt11": "lnbc1slow" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "status": "pending"
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pay"))
+            .and(body_partial_json(serde_json::json!({ "bolt11": "lnbc1noroute" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "status": "failed"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = cln_client(&server.uri());
+
+        match client.pay_invoice("lnbc1ok", 10).await.unwrap() {
+            OutgoingStatus::Succeeded(payment) => {
+                assert_eq!(payment.preimage, "ab".repeat(32));
+                assert_eq!(payment.fee_msat, 2000);
+            }
+            other => panic!("unexpected payment state {:?}", other),
+        }
+
+        // Still routing, so it is left for `listpays` rather than failed
+        assert!(matches!(
+            client.pay_invoice("lnbc1slow", 10).await.unwrap(),
+            OutgoingStatus::InFlight
+        ));
+
+        assert!(client.pay_invoice("lnbc1noroute", 10).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_payment() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/listpays"))
+            .and(body_partial_json(serde_json::json!({ "payment_hash": HASH_HEX })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "pays": [
+                    { "payment_hash": HASH_HEX, "status": "failed" },
+                    {
+                        "payment_hash": HASH_HEX,
+                        "status": "complete",
+                        "preimage": "ab".repeat(32),
+                        "amount_msat": 1_500_000,
+                        "amount_sent_msat": 1_502_000
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/listpays"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "pays": []
+            })))
+            .mount(&server)
+            .await;
+
+        let client = cln_client(&server.uri());
+
+        match client.lookup_payment(HASH_HEX).await.unwrap() {
+            Some(OutgoingStatus::Succeeded(payment)) => {
+                assert_eq!(payment.preimage, "ab".repeat(32));
+                assert_eq!(payment.fee_msat, 2000);
+            }
+            other => panic!("unexpected payment state {:?}", other),
+        }
+
+        assert!(client.lookup_payment(&"ff".repeat(32)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_settlements_resumes_after_timeout() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/waitanyinvoice"))
+            .and(body_partial_json(serde_json::json!({ "lastpay_index": 5 })))
+            .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                "code": WAIT_TIMED_OUT,
+                "message": "Timed out"
+            })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/waitanyinvoice"))
+            .and(body_partial_json(serde_json::json!({ "lastpay_index": 5 })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(paid_invoice(6)))
+            .mount(&server)
+            .await;
+
+        let mut settlements = cln_client(&server.uri())
+            .subscribe_settlements(5)
+            .await
+            .unwrap();
+
+        let invoice = settlements.next().await.unwrap().unwrap();
+        assert_eq!(invoice.payment_hash, HASH_HEX);
+        assert_eq!(invoice.settle_index, 6);
+    }
+}
@@ -1,42 +1,61 @@
 use ethers::{
     prelude::*,
-    providers::{Http, Provider, Ws},
-    types::{Address, TransactionReceipt, H256, U256},
-    utils::{format_ether, parse_ether},
+    providers::{Provider, Ws},
+    types::{transaction::eip2718::TypedTransaction, Address, TransactionReceipt, H256, U256},
+    utils::{format_ether, format_units, parse_ether},
 };
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::config::{ChainConfig, EthereumConfig};
+use super::rpc_pool::RpcPool;
+use crate::config::{ConfirmationPolicy, EvmChainConfig, TokenConfig};
 use crate::error::{AppError, AppResult};
-use crate::models::ChainType;
+use crate::models::{ChainType, CurrencyType};
+
+/// keccak256("Transfer(address,address,uint256)")
+pub const ERC20_TRANSFER_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// Stablecoin payment amounts are stored in micro-units (6 decimals) regardless
+/// of the token's on-chain decimals, so an i64 can hold any realistic amount
+pub const STABLECOIN_AMOUNT_DECIMALS: u8 = 6;
+
+/// Per-request limit for JSON-RPC calls, so a hung endpoint fails over instead of
+/// stalling the scanner
+const RPC_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Clone)]
 pub struct EthereumService {
-    provider: Arc<Provider<Http>>,
+    rpc: Arc<RpcPool>,
+    ws_url: Option<String>,
     chain_id: u64,
     chain_type: ChainType,
+    native_currency: CurrencyType,
+    native_decimals: u8,
+    confirmations: ConfirmationPolicy,
+    tokens: Vec<Erc20Token>,
 }
 
 impl EthereumService {
-    pub async fn new(config: &EthereumConfig) -> AppResult<Self> {
-        let provider = Provider::<Http>::try_from(&config.rpc_url)
-            .map_err(|e| AppError::Ethereum(format!("Failed to create provider: {}", e)))?;
+    /// Connect to one chain of the EVM registry
+    pub async fn new(config: &EvmChainConfig) -> AppResult<Self> {
+        let rpc = RpcPool::new(
+            &config.rpc_urls,
+            config.hedge_after_ms.map(Duration::from_millis),
+            config.quorum,
+            config.max_head_lag,
+            RPC_REQUEST_TIMEOUT,
+        )?;
 
         Ok(Self {
-            provider: Arc::new(provider),
+            rpc: Arc::new(rpc),
+            ws_url: config.ws_url.clone().filter(|url| !url.is_empty()),
             chain_id: config.chain_id,
-            chain_type: ChainType::Ethereum,
-        })
-    }
-
-    pub async fn new_for_chain(config: &ChainConfig, chain_type: ChainType) -> AppResult<Self> {
-        let provider = Provider::<Http>::try_from(&config.rpc_url)
-            .map_err(|e| AppError::Ethereum(format!("Failed to create provider: {}", e)))?;
-
-        Ok(Self {
-            provider: Arc::new(provider),
-            chain_id: config.chain_id,
-            chain_type,
+            chain_type: ChainType::evm(&config.name),
+            native_currency: config.native_currency.clone(),
+            native_decimals: config.native_decimals,
+            confirmations: config.confirmations.clone(),
+            tokens: config.tokens.iter().map(Erc20Token::from).collect(),
         })
     }
 
@@ -48,12 +67,33 @@ impl EthereumService {
         self.chain_id
     }
 
-    /// Get the current block number
+    pub fn native_currency(&self) -> &CurrencyType {
+        &self.native_currency
+    }
+
+    /// Whether a WebSocket endpoint is configured for push subscriptions
+    pub fn has_ws(&self) -> bool {
+        self.ws_url.is_some()
+    }
+
+    /// Open a WebSocket connection for `newHeads` and `logs` subscriptions.
+    /// Returns `None` when the chain only has an HTTP endpoint.
+    pub async fn connect_ws(&self) -> AppResult<Option<Provider<Ws>>> {
+        let Some(ref url) = self.ws_url else {
+            return Ok(None);
+        };
+
+        Provider::<Ws>::connect(url.as_str())
+            .await
+            .map(Some)
+            .map_err(|e| AppError::Ethereum(format!("Failed to connect WebSocket: {}", e)))
+    }
+
+    /// Get the current block number, agreed by the RPC quorum when one is configured
     pub async fn get_block_number(&self) -> AppResult<u64> {
-        self.provider
-            .get_block_number()
+        self.rpc
+            .block_number()
             .await
-            .map(|n| n.as_u64())
             .map_err(|e| AppError::Ethereum(format!("Failed to get block number: {}", e)))
     }
 
@@ -63,8 +103,8 @@ impl EthereumService {
             .parse()
             .map_err(|e| AppError::InvalidAddress(format!("Invalid Ethereum address: {}", e)))?;
 
-        self.provider
-            .get_balance(address, None)
+        self.rpc
+            .request(|provider| async move { provider.get_balance(address, None).await })
             .await
             .map_err(|e| AppError::Ethereum(format!("Failed to get balance: {}", e)))
     }
@@ -81,13 +121,14 @@ impl EthereumService {
             .parse()
             .map_err(|e| AppError::Ethereum(format!("Invalid transaction hash: {}", e)))?;
 
-        self.provider
-            .get_transaction(hash)
+        self.rpc
+            .request(|provider| async move { provider.get_transaction(hash).await })
             .await
             .map_err(|e| AppError::Ethereum(format!("Failed to get transaction: {}", e)))
     }
 
-    /// Get transaction receipt
+    /// Get transaction receipt. Receipts decide whether a payment completes, so
+    /// in quorum mode the endpoints must agree on its block, status and logs.
     pub async fn get_transaction_receipt(
         &self,
         tx_hash: &str,
@@ -96,12 +137,66 @@ impl EthereumService {
             .parse()
             .map_err(|e| AppError::Ethereum(format!("Invalid transaction hash: {}", e)))?;
 
-        self.provider
-            .get_transaction_receipt(hash)
+        self.rpc
+            .quorum_request(
+                |provider| async move { provider.get_transaction_receipt(hash).await },
+                |receipt| {
+                    receipt
+                        .as_ref()
+                        .map(|r| (r.block_hash, r.block_number, r.status, r.logs.clone()))
+                },
+            )
             .await
             .map_err(|e| AppError::Ethereum(format!("Failed to get receipt: {}", e)))
     }
 
+    /// Get a block with its full transactions
+    pub async fn get_block_with_transactions(
+        &self,
+        block_number: u64,
+    ) -> AppResult<Option<Block<Transaction>>> {
+        self.rpc
+            .request(|provider| async move { provider.get_block_with_txs(block_number).await })
+            .await
+            .map_err(|e| AppError::Ethereum(format!("Failed to get block {}: {}", block_number, e)))
+    }
+
+    /// Get ERC-20 `Transfer` logs of `tokens` paying any of `recipients` in a block range
+    pub async fn get_token_transfer_logs(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        tokens: Vec<Address>,
+        recipients: Vec<Address>,
+    ) -> AppResult<Vec<Log>> {
+        let filter = Self::token_transfer_filter(tokens, recipients)?
+            .from_block(from_block)
+            .to_block(to_block);
+
+        self.rpc
+            .request(|provider| {
+                let filter = filter.clone();
+                async move { provider.get_logs(&filter).await }
+            })
+            .await
+            .map_err(|e| AppError::Ethereum(format!("Failed to get logs: {}", e)))
+    }
+
+    /// Filter matching ERC-20 `Transfer` logs of `tokens` paying any of `recipients`
+    pub fn token_transfer_filter(
+        tokens: Vec<Address>,
+        recipients: Vec<Address>,
+    ) -> AppResult<Filter> {
+        let transfer_topic: H256 = ERC20_TRANSFER_TOPIC
+            .parse()
+            .map_err(|e| AppError::Ethereum(format!("Invalid transfer topic: {}", e)))?;
+
+        Ok(Filter::new()
+            .address(tokens)
+            .topic0(transfer_topic)
+            .topic2(recipients.into_iter().map(H256::from).collect::<Vec<_>>()))
+    }
+
     /// Get number of confirmations for a transaction
     pub async fn get_confirmations(&self, tx_hash: &str) -> AppResult<u64> {
         let receipt = self.get_transaction_receipt(tx_hash).await?;
@@ -165,7 +260,9 @@ impl EthereumService {
             from_address: tx.from.to_string(),
             to_address: tx.to.map(|a| a.to_string()),
             actual_amount: tx.value,
-            block_number: receipt.and_then(|r| r.block_number.map(|b| b.as_u64())),
+            block_number: receipt.as_ref().and_then(|r| r.block_number.map(|b| b.as_u64())),
+            block_hash: receipt.and_then(|r| r.block_hash.map(|h| format!("{:?}", h))),
+            token_address: None,
         })
     }
 
@@ -216,39 +313,28 @@ impl EthereumService {
     /// Get required confirmations based on chain and amount
     pub fn get_required_confirmations(&self, amount_wei: U256) -> i32 {
         // Higher amounts require more confirmations
-        let eth_amount = Self::wei_to_eth(amount_wei)
-            .parse::<f64>()
+        let native_amount = format_units(amount_wei, self.native_decimals as u32)
+            .ok()
+            .and_then(|amount| amount.parse::<f64>().ok())
             .unwrap_or(0.0);
 
-        match self.chain_type {
-            ChainType::Ethereum => {
-                if eth_amount > 10.0 {
-                    12
-                } else if eth_amount > 1.0 {
-                    6
-                } else {
-                    3
-                }
-            }
-            ChainType::Polygon | ChainType::Bsc => {
-                // Faster block times, need more confirmations
-                if eth_amount > 10.0 {
-                    50
-                } else if eth_amount > 1.0 {
-                    25
-                } else {
-                    10
-                }
-            }
-            ChainType::Arbitrum => {
-                // L2, faster finality
-                if eth_amount > 10.0 {
-                    20
-                } else {
-                    5
-                }
-            }
-            _ => 3,
+        self.confirmations_for_tier(native_amount > 10.0, native_amount > 1.0)
+    }
+
+    /// Get required confirmations for a stablecoin amount in micro-units
+    pub fn get_required_token_confirmations(&self, amount_micro: i64) -> i32 {
+        let usd_amount = amount_micro as f64 / 1_000_000.0;
+
+        self.confirmations_for_tier(usd_amount > 25_000.0, usd_amount > 2_500.0)
+    }
+
+    fn confirmations_for_tier(&self, large: bool, medium: bool) -> i32 {
+        if large {
+            self.confirmations.large
+        } else if medium {
+            self.confirmations.medium
+        } else {
+            self.confirmations.small
         }
     }
 }
@@ -264,10 +350,167 @@ pub struct PaymentVerification {
     pub to_address: Option<String>,
     pub actual_amount: U256,
     pub block_number: Option<u64>,
+    pub block_hash: Option<String>,
+    /// Token contract for ERC-20 payments, `None` for native transfers
+    pub token_address: Option<String>,
+}
+
+/// An ERC-20 token contract registered for a specific chain
+#[derive(Debug, Clone)]
+pub struct Erc20Token {
+    pub symbol: String,
+    pub address: String,
+    pub decimals: u8,
+}
+
+impl From<&TokenConfig> for Erc20Token {
+    fn from(config: &TokenConfig) -> Self {
+        Self {
+            symbol: config.symbol.to_uppercase(),
+            address: config.address.clone(),
+            decimals: config.decimals,
+        }
+    }
+}
+
+impl Erc20Token {
+    /// Convert the token's base units to a stablecoin amount in micro-units
+    pub fn base_units_to_amount(&self, base_units: U256) -> i64 {
+        let amount = if self.decimals >= STABLECOIN_AMOUNT_DECIMALS {
+            base_units / U256::exp10((self.decimals - STABLECOIN_AMOUNT_DECIMALS) as usize)
+        } else {
+            base_units * U256::exp10((STABLECOIN_AMOUNT_DECIMALS - self.decimals) as usize)
+        };
+
+        if amount > U256::from(i64::MAX as u64) {
+            i64::MAX
+        } else {
+            amount.as_u64() as i64
+        }
+    }
+
+    /// Convert a stablecoin amount in micro-units to the token's base units
+    pub fn amount_to_base_units(&self, amount_micro: i64) -> U256 {
+        let amount = U256::from(amount_micro.max(0) as u64);
+
+        if self.decimals >= STABLECOIN_AMOUNT_DECIMALS {
+            amount * U256::exp10((self.decimals - STABLECOIN_AMOUNT_DECIMALS) as usize)
+        } else {
+            amount / U256::exp10((STABLECOIN_AMOUNT_DECIMALS - self.decimals) as usize)
+        }
+    }
 }
 
 /// ERC20 Token interactions
 impl EthereumService {
+    /// Look up the token contract for a stablecoin currency on this chain
+    pub fn token_for_currency(&self, currency: &CurrencyType) -> Option<Erc20Token> {
+        let symbol = match currency {
+            CurrencyType::USDT => "USDT",
+            CurrencyType::USDC => "USDC",
+            _ => return None,
+        };
+
+        self.tokens
+            .iter()
+            .find(|token| token.symbol == symbol)
+            .cloned()
+    }
+
+    /// Look up a registered token by its contract address on this chain
+    pub fn token_by_address(&self, address: &str) -> Option<Erc20Token> {
+        self.tokens
+            .iter()
+            .find(|token| token.address.eq_ignore_ascii_case(address))
+            .cloned()
+    }
+
+    /// Verify an ERC-20 payment by decoding `Transfer` logs from the receipt
+    pub async fn verify_token_payment(
+        &self,
+        tx_hash: &str,
+        token: &Erc20Token,
+        expected_to: &str,
+        expected_amount: U256,
+    ) -> AppResult<PaymentVerification> {
+        let receipt = self
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| AppError::Ethereum("Transaction receipt not found".to_string()))?;
+
+        let token_address: Address = token
+            .address
+            .parse()
+            .map_err(|e| AppError::InvalidAddress(format!("Invalid token address: {}", e)))?;
+
+        let to_address: Address = expected_to
+            .parse()
+            .map_err(|e| AppError::InvalidAddress(format!("Invalid address: {}", e)))?;
+
+        let (actual_amount, from_address) =
+            Self::sum_token_transfers(&receipt.logs, token_address, to_address);
+
+        let to_matches = from_address.is_some();
+        let amount_matches = to_matches && actual_amount >= expected_amount;
+        let is_successful = receipt.status == Some(1.into());
+
+        let confirmations = if let Some(block_number) = receipt.block_number {
+            let current_block = self.get_block_number().await?;
+            current_block.saturating_sub(block_number.as_u64())
+        } else {
+            0
+        };
+
+        Ok(PaymentVerification {
+            is_valid: to_matches && amount_matches && is_successful,
+            to_matches,
+            amount_matches,
+            is_successful,
+            confirmations,
+            from_address: format!("{:?}", from_address.unwrap_or(receipt.from)),
+            to_address: Some(format!("{:?}", to_address)),
+            actual_amount,
+            block_number: receipt.block_number.map(|b| b.as_u64()),
+            block_hash: receipt.block_hash.map(|h| format!("{:?}", h)),
+            token_address: Some(token.address.to_string()),
+        })
+    }
+
+    /// Amount of an ERC-20 `Transfer` log, which is a single 32-byte word. Logs
+    /// from non-standard or malicious tokens with any other data are rejected.
+    pub fn transfer_amount(log: &Log) -> Option<U256> {
+        (log.data.len() == 32).then(|| U256::from_big_endian(&log.data))
+    }
+
+    /// Sum `Transfer` events of `token` paying `to`, returning the total and the first sender
+    pub fn sum_token_transfers(
+        logs: &[Log],
+        token: Address,
+        to: Address,
+    ) -> (U256, Option<Address>) {
+        let transfer_topic: H256 = ERC20_TRANSFER_TOPIC.parse().unwrap_or_default();
+        let mut total = U256::zero();
+        let mut sender = None;
+
+        for log in logs {
+            if log.address != token
+                || log.topics.len() != 3
+                || log.topics[0] != transfer_topic
+                || Address::from(log.topics[2]) != to
+            {
+                continue;
+            }
+
+            let Some(amount) = Self::transfer_amount(log) else {
+                continue;
+            };
+            total = total.saturating_add(amount);
+            sender.get_or_insert(Address::from(log.topics[1]));
+        }
+
+        (total, sender)
+    }
+
     /// Get ERC20 token balance
     pub async fn get_token_balance(
         &self,
@@ -298,9 +541,13 @@ impl EthereumService {
                 AppError::Ethereum(format!("Failed to encode call data: {}", e))
             })?);
 
+        let call: TypedTransaction = call.into();
         let result = self
-            .provider
-            .call(&call.into(), None)
+            .rpc
+            .request(|provider| {
+                let call = call.clone();
+                async move { provider.call(&call, None).await }
+            })
             .await
             .map_err(|e| AppError::Ethereum(format!("Failed to call token contract: {}", e)))?;
 
@@ -311,3 +558,77 @@ impl EthereumService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer_log(token: Address, from: Address, to: Address, amount: u64) -> Log {
+        let mut data = [0u8; 32];
+        U256::from(amount).to_big_endian(&mut data);
+
+        Log {
+            address: token,
+            topics: vec![
+                ERC20_TRANSFER_TOPIC.parse().unwrap(),
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: data.to_vec().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sum_token_transfers() {
+        let token = Address::repeat_byte(0x11);
+        let other_token = Address::repeat_byte(0x22);
+        let sender = Address::repeat_byte(0x33);
+        let deposit = Address::repeat_byte(0x44);
+        let someone_else = Address::repeat_byte(0x55);
+
+        let logs = vec![
+            transfer_log(token, sender, deposit, 1_500_000),
+            transfer_log(token, sender, someone_else, 9_000_000),
+            transfer_log(other_token, sender, deposit, 7_000_000),
+            transfer_log(token, sender, deposit, 500_000),
+        ];
+
+        let (total, from) = EthereumService::sum_token_transfers(&logs, token, deposit);
+        assert_eq!(total, U256::from(2_000_000u64));
+        assert_eq!(from, Some(sender));
+
+        let (total, from) =
+            EthereumService::sum_token_transfers(&logs, token, Address::repeat_byte(0x66));
+        assert!(total.is_zero());
+        assert!(from.is_none());
+
+        // Data longer than one word is skipped rather than panicking
+        let mut oversized = transfer_log(token, sender, deposit, 1_000_000);
+        oversized.data = vec![0x01; 64].into();
+        let (total, _) = EthereumService::sum_token_transfers(&[oversized], token, deposit);
+        assert!(total.is_zero());
+    }
+
+    #[test]
+    fn test_stablecoin_base_units() {
+        let usdc = Erc20Token {
+            symbol: "USDC".to_string(),
+            address: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+            decimals: 6,
+        };
+        assert_eq!(usdc.amount_to_base_units(12_500_000), U256::from(12_500_000u64));
+
+        let bsc_usdt = Erc20Token {
+            symbol: "USDT".to_string(),
+            address: "0x55d398326f99059fF775485246999027B3197955".to_string(),
+            decimals: 18,
+        };
+        assert_eq!(
+            bsc_usdt.amount_to_base_units(1_000_000),
+            U256::exp10(18)
+        );
+        assert_eq!(bsc_usdt.base_units_to_amount(U256::exp10(18)), 1_000_000);
+        assert_eq!(bsc_usdt.base_units_to_amount(U256::MAX), i64::MAX);
+    }
+}
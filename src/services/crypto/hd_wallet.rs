@@ -0,0 +1,373 @@
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv, Xpub};
+use bitcoin::secp256k1::{PublicKey, Secp256k1};
+use bitcoin::{Address as BitcoinAddress, CompressedPublicKey, Network, NetworkKind};
+use ed25519_dalek::SigningKey;
+use ethers::types::Address;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::config::HdWalletConfig;
+use crate::crypto_utils::checksum::keccak256;
+use crate::crypto_utils::encryption;
+use crate::error::{AppError, AppResult};
+use crate::models::ChainType;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// BIP44 account path for EVM chains (coin type 60)
+const EVM_ACCOUNT_PATH: &str = "m/44'/60'/0'";
+
+/// SLIP-10 master key HMAC key for the ed25519 curve
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// HD wallet used to derive a unique deposit address per payment.
+///
+/// The master seed is kept encrypted with `SecurityConfig::encryption_key` and
+/// only decrypted for the duration of a single derivation. When an account-level
+/// xpub is configured it is preferred, so the seed is never touched for EVM chains.
+/// Solana addresses always need the seed since SLIP-10 ed25519 is hardened-only.
+#[derive(Clone)]
+pub struct HdWallet {
+    encrypted_seed: Option<Vec<u8>>,
+    encryption_key: Vec<u8>,
+    evm_xpub: Option<Xpub>,
+    bitcoin_xpub: Option<Xpub>,
+}
+
+impl HdWallet {
+    pub fn new(config: &HdWalletConfig, encryption_key: &str) -> AppResult<Self> {
+        let encrypted_seed = match config.encrypted_seed.as_deref() {
+            Some(seed_hex) if !seed_hex.is_empty() => Some(hex::decode(seed_hex).map_err(|e| {
+                AppError::Config(format!("Invalid HD_WALLET_ENCRYPTED_SEED: {}", e))
+            })?),
+            _ => None,
+        };
+
+        let evm_xpub = match config.evm_xpub.as_deref() {
+            Some(xpub) if !xpub.is_empty() => Some(
+                Xpub::from_str(xpub)
+                    .map_err(|e| AppError::Config(format!("Invalid HD_WALLET_EVM_XPUB: {}", e)))?,
+            ),
+            _ => None,
+        };
+
+        let bitcoin_xpub = match config.bitcoin_xpub.as_deref() {
+            Some(xpub) if !xpub.is_empty() => Some(Xpub::from_str(xpub).map_err(|e| {
+                AppError::Config(format!("Invalid HD_WALLET_BITCOIN_XPUB: {}", e))
+            })?),
+            _ => None,
+        };
+
+        let wallet = Self {
+            encrypted_seed,
+            encryption_key: encryption_key.as_bytes().to_vec(),
+            evm_xpub,
+            bitcoin_xpub,
+        };
+
+        // Fail fast on a seed that cannot be decrypted with the configured key
+        if wallet.encrypted_seed.is_some() {
+            wallet.decrypt_seed()?;
+        }
+
+        Ok(wallet)
+    }
+
+    /// Encrypt a raw BIP39 seed for use as `HD_WALLET_ENCRYPTED_SEED`
+    pub fn encrypt_seed(seed: &[u8], encryption_key: &str) -> AppResult<String> {
+        let ciphertext = encryption::encrypt(seed, encryption_key.as_bytes())?;
+        Ok(hex::encode(ciphertext))
+    }
+
+    pub fn is_configured(&self, chain: &ChainType) -> bool {
+        match chain {
+            ChainType::Evm(_) => {
+                self.evm_xpub.is_some() || self.encrypted_seed.is_some()
+            }
+            // ed25519 only supports hardened derivation, so the seed is required
+            ChainType::Solana => self.encrypted_seed.is_some(),
+            ChainType::Bitcoin => self.bitcoin_xpub.is_some() || self.encrypted_seed.is_some(),
+        }
+    }
+
+    /// Derive an EIP-55 checksummed address at m/44'/60'/0'/0/index
+    pub fn derive_evm_address(&self, index: u32) -> AppResult<DerivedAddress> {
+        let secp = Secp256k1::new();
+        let path = format!("{}/0/{}", EVM_ACCOUNT_PATH, index);
+
+        let public_key = if let Some(ref xpub) = self.evm_xpub {
+            let children = [normal_child(0)?, normal_child(index)?];
+            xpub.derive_pub(&secp, &children)
+                .map_err(|e| AppError::Internal(format!("Key derivation failed: {}", e)))?
+                .public_key
+        } else {
+            let seed = self.decrypt_seed()?;
+            let master = Xpriv::new_master(NetworkKind::Main, &seed)
+                .map_err(|e| AppError::Internal(format!("Invalid HD wallet seed: {}", e)))?;
+            let derivation_path = DerivationPath::from_str(&path)
+                .map_err(|e| AppError::Internal(format!("Invalid derivation path: {}", e)))?;
+            let child = master
+                .derive_priv(&secp, &derivation_path)
+                .map_err(|e| AppError::Internal(format!("Key derivation failed: {}", e)))?;
+            Xpub::from_priv(&secp, &child).public_key
+        };
+
+        Ok(DerivedAddress {
+            address: evm_address(&public_key),
+            derivation_path: path,
+            index,
+        })
+    }
+
+    /// Derive a Solana address at m/44'/501'/index'/0' using SLIP-10 (ed25519)
+    ///
+    /// This matches the default account layout used by Phantom and Solflare.
+    pub fn derive_solana_address(&self, index: u32) -> AppResult<DerivedAddress> {
+        if index >= HARDENED_OFFSET {
+            return Err(AppError::Internal(format!("Invalid child index: {}", index)));
+        }
+
+        let seed = self.decrypt_seed()?;
+        let secret = slip10_ed25519_derive(&seed, &[44, 501, index, 0])?;
+        let public_key = SigningKey::from_bytes(&secret).verifying_key();
+
+        Ok(DerivedAddress {
+            address: Pubkey::new_from_array(public_key.to_bytes()).to_string(),
+            derivation_path: format!("m/44'/501'/{}'/0'", index),
+            index,
+        })
+    }
+
+    /// Derive a bech32 P2WPKH address at m/84'/coin'/0'/0/index (BIP84)
+    pub fn derive_bitcoin_address(
+        &self,
+        network: Network,
+        index: u32,
+    ) -> AppResult<DerivedAddress> {
+        let secp = Secp256k1::new();
+        let coin_type = if network == Network::Bitcoin { 0 } else { 1 };
+        let path = format!("m/84'/{}'/0'/0/{}", coin_type, index);
+
+        let public_key = if let Some(ref xpub) = self.bitcoin_xpub {
+            let children = [normal_child(0)?, normal_child(index)?];
+            xpub.derive_pub(&secp, &children)
+                .map_err(|e| AppError::Internal(format!("Key derivation failed: {}", e)))?
+                .public_key
+        } else {
+            let seed = self.decrypt_seed()?;
+            let master = Xpriv::new_master(NetworkKind::from(network), &seed)
+                .map_err(|e| AppError::Internal(format!("Invalid HD wallet seed: {}", e)))?;
+            let derivation_path = DerivationPath::from_str(&path)
+                .map_err(|e| AppError::Internal(format!("Invalid derivation path: {}", e)))?;
+            let child = master
+                .derive_priv(&secp, &derivation_path)
+                .map_err(|e| AppError::Internal(format!("Key derivation failed: {}", e)))?;
+            Xpub::from_priv(&secp, &child).public_key
+        };
+
+        let address = BitcoinAddress::p2wpkh(&CompressedPublicKey(public_key), network);
+
+        Ok(DerivedAddress {
+            address: address.to_string(),
+            derivation_path: path,
+            index,
+        })
+    }
+
+    fn decrypt_seed(&self) -> AppResult<Vec<u8>> {
+        let encrypted = self.encrypted_seed.as_ref().ok_or_else(|| {
+            AppError::Payment(
+                "HD wallet not configured. Set HD_WALLET_EVM_XPUB or HD_WALLET_ENCRYPTED_SEED"
+                    .to_string(),
+            )
+        })?;
+
+        encryption::decrypt(encrypted, &self.encryption_key)
+            .map_err(|_| AppError::Config("Failed to decrypt HD wallet seed".to_string()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DerivedAddress {
+    pub address: String,
+    pub derivation_path: String,
+    pub index: u32,
+}
+
+/// SLIP-10 ed25519 private key derivation; every path element is hardened
+fn slip10_ed25519_derive(seed: &[u8], path: &[u32]) -> AppResult<[u8; 32]> {
+    let (mut key, mut chain_code) = slip10_hmac(ED25519_SEED_KEY, &[seed])?;
+
+    for index in path {
+        let hardened = (index | HARDENED_OFFSET).to_be_bytes();
+        (key, chain_code) = slip10_hmac(&chain_code, &[&[0u8], &key, &hardened])?;
+    }
+
+    Ok(key)
+}
+
+fn slip10_hmac(key: &[u8], parts: &[&[u8]]) -> AppResult<([u8; 32], [u8; 32])> {
+    let mut mac = HmacSha512::new_from_slice(key)
+        .map_err(|e| AppError::Internal(format!("HMAC initialization failed: {}", e)))?;
+
+    for part in parts {
+        mac.update(part);
+    }
+
+    let result = mac.finalize().into_bytes();
+    let mut secret = [0u8; 32];
+    let mut chain_code = [0u8; 32];
+    secret.copy_from_slice(&result[..32]);
+    chain_code.copy_from_slice(&result[32..]);
+
+    Ok((secret, chain_code))
+}
+
+fn normal_child(index: u32) -> AppResult<ChildNumber> {
+    ChildNumber::from_normal_idx(index)
+        .map_err(|e| AppError::Internal(format!("Invalid child index {}: {}", index, e)))
+}
+
+/// Ethereum address from a secp256k1 public key: last 20 bytes of keccak256(x || y)
+fn evm_address(public_key: &PublicKey) -> String {
+    let uncompressed = public_key.serialize_uncompressed();
+    let hash = keccak256(&uncompressed[1..]);
+    ethers::utils::to_checksum(&Address::from_slice(&hash[12..]), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP39 seed of "abandon abandon ... about" with an empty passphrase
+    const TEST_SEED: &str = "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4";
+    const TEST_XPUB: &str = "xpub6DCoCpSuQZB2jawqnGMEPS63ePKWkwWPH4TU45Q7LPXWuNd8TMtVxRrgjtEshuqpK3mdhaWHPFsBngh5GFZaM6si3yZdUsT8ddYM3PwnATt";
+    const TEST_BITCOIN_XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+    const TEST_KEY: &str = "0123456789abcdef0123456789abcdef";
+
+    fn seed_wallet() -> HdWallet {
+        let seed = hex::decode(TEST_SEED).unwrap();
+        let config = HdWalletConfig {
+            encrypted_seed: Some(HdWallet::encrypt_seed(&seed, TEST_KEY).unwrap()),
+            evm_xpub: None,
+            bitcoin_xpub: None,
+        };
+        HdWallet::new(&config, TEST_KEY).unwrap()
+    }
+
+    #[test]
+    fn test_derive_evm_address_from_seed() {
+        let wallet = seed_wallet();
+
+        let first = wallet.derive_evm_address(0).unwrap();
+        assert_eq!(first.address, "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+        assert_eq!(first.derivation_path, "m/44'/60'/0'/0/0");
+
+        let second = wallet.derive_evm_address(1).unwrap();
+        assert_eq!(second.address, "0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0");
+    }
+
+    #[test]
+    fn test_xpub_matches_seed_derivation() {
+        let config = HdWalletConfig {
+            encrypted_seed: None,
+            evm_xpub: Some(TEST_XPUB.to_string()),
+            bitcoin_xpub: None,
+        };
+        let wallet = HdWallet::new(&config, TEST_KEY).unwrap();
+
+        for index in 0..3 {
+            assert_eq!(
+                wallet.derive_evm_address(index).unwrap().address,
+                seed_wallet().derive_evm_address(index).unwrap().address
+            );
+        }
+    }
+
+    #[test]
+    fn test_slip10_ed25519_vector() {
+        // SLIP-0010 test vector 1 for ed25519, chain m/0H
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let secret = slip10_ed25519_derive(&seed, &[0]).unwrap();
+
+        assert_eq!(
+            hex::encode(secret),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        assert_eq!(
+            hex::encode(SigningKey::from_bytes(&secret).verifying_key().to_bytes()),
+            "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c"
+        );
+    }
+
+    #[test]
+    fn test_derive_solana_address_from_seed() {
+        let wallet = seed_wallet();
+
+        let first = wallet.derive_solana_address(0).unwrap();
+        assert_eq!(first.address, "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk");
+        assert_eq!(first.derivation_path, "m/44'/501'/0'/0'");
+
+        let second = wallet.derive_solana_address(1).unwrap();
+        assert_eq!(second.address, "Hh8QwFUA6MtVu1qAoq12ucvFHNwCcVTV7hpWjeY1Hztb");
+    }
+
+    #[test]
+    fn test_derive_bitcoin_address() {
+        // BIP84 test vector for the same mnemonic
+        let first = seed_wallet()
+            .derive_bitcoin_address(Network::Bitcoin, 0)
+            .unwrap();
+        assert_eq!(first.address, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        assert_eq!(first.derivation_path, "m/84'/0'/0'/0/0");
+
+        let config = HdWalletConfig {
+            encrypted_seed: None,
+            evm_xpub: None,
+            bitcoin_xpub: Some(TEST_BITCOIN_XPUB.to_string()),
+        };
+        let wallet = HdWallet::new(&config, TEST_KEY).unwrap();
+        assert_eq!(
+            wallet.derive_bitcoin_address(Network::Bitcoin, 1).unwrap().address,
+            "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+        );
+
+        let regtest = seed_wallet()
+            .derive_bitcoin_address(Network::Regtest, 0)
+            .unwrap();
+        assert!(regtest.address.starts_with("bcrt1q"));
+        assert_eq!(regtest.derivation_path, "m/84'/1'/0'/0/0");
+    }
+
+    #[test]
+    fn test_wrong_encryption_key_rejected() {
+        let seed = hex::decode(TEST_SEED).unwrap();
+        let config = HdWalletConfig {
+            encrypted_seed: Some(HdWallet::encrypt_seed(&seed, TEST_KEY).unwrap()),
+            evm_xpub: None,
+            bitcoin_xpub: None,
+        };
+
+        assert!(HdWallet::new(&config, "fedcba9876543210fedcba9876543210").is_err());
+    }
+
+    #[test]
+    fn test_unconfigured_wallet() {
+        let config = HdWalletConfig {
+            encrypted_seed: None,
+            evm_xpub: None,
+            bitcoin_xpub: None,
+        };
+        let wallet = HdWallet::new(&config, TEST_KEY).unwrap();
+
+        assert!(!wallet.is_configured(&ChainType::evm("ethereum")));
+        assert!(!wallet.is_configured(&ChainType::Bitcoin));
+        assert!(!wallet.is_configured(&ChainType::Solana));
+        assert!(wallet.derive_evm_address(0).is_err());
+        assert!(wallet.derive_solana_address(0).is_err());
+    }
+}
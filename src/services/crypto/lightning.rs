@@ -1,29 +1,197 @@
-use lightning_invoice::Bolt11Invoice;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use lightning_invoice::{Bolt11Invoice, Currency};
+use reqwest::{Certificate, Client};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
+use super::cln::ClnClient;
+use super::lnd::LndClient;
 use crate::config::LightningConfig;
 use crate::error::{AppError, AppResult};
 
+/// Settled invoices in settlement order, ending when the node connection drops
+pub type SettlementStream = BoxStream<'static, AppResult<InvoiceLookup>>;
+
+/// Operations the gateway needs from a Lightning node, independent of the implementation
+#[async_trait]
+pub trait LightningBackend: Send + Sync {
+    /// Short name of the implementation, for logs
+    fn name(&self) -> &'static str;
+
+    async fn create_invoice(
+        &self,
+        amount_sat: u64,
+        description: &str,
+        expiry_seconds: u32,
+    ) -> AppResult<CreateInvoiceResponse>;
+
+    /// Create an invoice that commits to `description` only by its SHA-256 hash,
+    /// as LNURL-pay (LUD-06) requires for the metadata
+    async fn create_hashed_invoice(
+        &self,
+        amount_msat: u64,
+        description: &str,
+        expiry_seconds: u32,
+    ) -> AppResult<CreateInvoiceResponse>;
+
+    /// Look up an invoice by hex payment hash, `None` if the node does not know it
+    async fn lookup_invoice(&self, payment_hash: &str) -> AppResult<Option<InvoiceLookup>>;
+
+    /// Invoices settled after `settle_index`, replaying any the caller missed
+    async fn subscribe_settlements(&self, settle_index: u64) -> AppResult<SettlementStream>;
+
+    /// Pay a BOLT11 invoice, spending at most `max_fee_sat` on routing. A payment
+    /// the node is still routing when it answers is `InFlight`, to be settled
+    /// through [`lookup_payment`](Self::lookup_payment).
+    async fn pay_invoice(&self, payment_request: &str, max_fee_sat: u64)
+        -> AppResult<OutgoingStatus>;
+
+    /// State of a payment this node sent, by hex payment hash, `None` if the
+    /// node never started paying it
+    async fn lookup_payment(&self, payment_hash: &str) -> AppResult<Option<OutgoingStatus>>;
+
+    /// Create a hold invoice for a hex payment hash whose preimage the caller
+    /// keeps. Incoming HTLCs are held, not settled, until
+    /// [`settle_hold_invoice`](Self::settle_hold_invoice) or
+    /// [`cancel_hold_invoice`](Self::cancel_hold_invoice).
+    async fn create_hold_invoice(
+        &self,
+        _payment_hash: &str,
+        _amount_sat: u64,
+        _description: &str,
+        _expiry_seconds: u32,
+        _cltv_expiry: u32,
+    ) -> AppResult<CreateInvoiceResponse> {
+        Err(hold_invoices_unsupported(self.name()))
+    }
+
+    /// Settle a held invoice by revealing its hex preimage
+    async fn settle_hold_invoice(&self, _preimage: &str) -> AppResult<()> {
+        Err(hold_invoices_unsupported(self.name()))
+    }
+
+    /// Cancel an invoice, failing any held HTLCs back to the payer
+    async fn cancel_hold_invoice(&self, _payment_hash: &str) -> AppResult<()> {
+        Err(hold_invoices_unsupported(self.name()))
+    }
+
+    /// Node identity and sync state, used for health checks
+    async fn node_info(&self) -> AppResult<NodeInfo>;
+}
+
+fn hold_invoices_unsupported(backend: &str) -> AppError {
+    AppError::Lightning(format!("Hold invoices are not supported by the {} backend", backend))
+}
+
+/// HTTP client for a node's REST API. Nodes serve self-signed certificates, so a
+/// configured certificate is pinned instead of trusting the system roots. No
+/// overall timeout is set because settlement subscriptions are long-lived;
+/// callers set one per request.
+pub(super) fn node_http_client(config: &LightningConfig) -> AppResult<Client> {
+    let mut builder = Client::builder().connect_timeout(Duration::from_secs(10));
+
+    if let Some(path) = &config.tls_cert_path {
+        let pem = std::fs::read(path).map_err(|e| {
+            AppError::Config(format!("Failed to read LIGHTNING_TLS_CERT_PATH: {}", e))
+        })?;
+        let cert = Certificate::from_pem(&pem)
+            .map_err(|e| AppError::Config(format!("Invalid Lightning node TLS certificate: {}", e)))?;
+
+        builder = builder
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(cert);
+    }
+
+    builder
+        .build()
+        .map_err(|e| AppError::Config(format!("Failed to create HTTP client: {}", e)))
+}
+
 #[derive(Clone)]
 pub struct LightningService {
-    node_url: String,
-    #[allow(dead_code)]
-    macaroon_path: Option<String>,
-    #[allow(dead_code)]
-    tls_cert_path: Option<String>,
+    /// `None` when no node is configured
+    backend: Option<Arc<dyn LightningBackend>>,
+    /// Network outgoing invoices must be for: bitcoin, testnet, signet or regtest
+    network: String,
+    max_fee_ppm: u64,
+    min_fee_limit_sat: u64,
 }
 
 impl LightningService {
-    pub fn new(config: &LightningConfig) -> Self {
+    pub fn new(config: &LightningConfig) -> AppResult<Self> {
+        if config.node_url.is_empty() {
+            return Ok(Self {
+                backend: None,
+                network: config.network.clone(),
+                max_fee_ppm: config.max_fee_ppm,
+                min_fee_limit_sat: config.min_fee_limit_sat,
+            });
+        }
+
+        let backend: Arc<dyn LightningBackend> = match config.backend.as_str() {
+            "cln" => Arc::new(ClnClient::new(config)?),
+            "lnd" => Arc::new(LndClient::new(config)?),
+            other => {
+                return Err(AppError::Config(format!(
+                    "Unsupported LIGHTNING_BACKEND '{}', expected lnd or cln",
+                    other
+                )))
+            }
+        };
+        tracing::info!("Lightning backend: {} at {}", backend.name(), config.node_url);
+
+        Ok(Self::with_backend(config, backend))
+    }
+
+    pub fn with_backend(config: &LightningConfig, backend: Arc<dyn LightningBackend>) -> Self {
         Self {
-            node_url: config.node_url.clone(),
-            macaroon_path: config.macaroon_path.clone(),
-            tls_cert_path: config.tls_cert_path.clone(),
+            backend: Some(backend),
+            network: config.network.clone(),
+            max_fee_ppm: config.max_fee_ppm,
+            min_fee_limit_sat: config.min_fee_limit_sat,
+        }
+    }
+
+    /// Whether a node is configured
+    pub fn is_configured(&self) -> bool {
+        self.backend.is_some()
+    }
+
+    fn node(&self) -> AppResult<&dyn LightningBackend> {
+        self.backend.as_deref().ok_or_else(|| {
+            AppError::Lightning(
+                "Lightning node connection not configured. Set LIGHTNING_NODE_URL in environment.".to_string(),
+            )
+        })
+    }
+
+    /// Parse a BOLT11 invoice we are asked to pay, rejecting invoices that
+    /// are expired or for another network
+    pub fn parse_invoice(&self, invoice_str: &str) -> AppResult<InvoiceInfo> {
+        let info = Self::decode_invoice(invoice_str.trim())?;
+        self.check_payable(&info)?;
+        Ok(info)
+    }
+
+    fn check_payable(&self, info: &InvoiceInfo) -> AppResult<()> {
+        if info.network != self.network {
+            return Err(AppError::Lightning(format!(
+                "Invoice is for {}, expected {}",
+                info.network, self.network
+            )));
+        }
+
+        if info.is_expired {
+            return Err(AppError::Lightning("Invoice has expired".to_string()));
         }
+
+        Ok(())
     }
 
-    /// Parse and validate a BOLT11 invoice
-    pub fn parse_invoice(invoice_str: &str) -> AppResult<InvoiceInfo> {
+    /// Decode a BOLT11 invoice
+    fn decode_invoice(invoice_str: &str) -> AppResult<InvoiceInfo> {
         let invoice = Bolt11Invoice::from_str(invoice_str)
             .map_err(|e| AppError::Lightning(format!("Invalid invoice: {}", e)))?;
 
@@ -35,8 +203,18 @@ impl LightningService {
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
+        // Named like BITCOIN_NETWORK
+        let network = match invoice.currency() {
+            Currency::Bitcoin => "bitcoin",
+            Currency::BitcoinTestnet => "testnet",
+            Currency::Signet => "signet",
+            Currency::Regtest => "regtest",
+            Currency::Simnet => "simnet",
+        };
+
         Ok(InvoiceInfo {
             payment_hash,
+            network: network.to_string(),
             amount_msat,
             amount_sat: amount_msat.map(|m| m / 1000),
             description,
@@ -46,50 +224,108 @@ impl LightningService {
         })
     }
 
-    /// Create a new invoice (requires LND/CLN node connection)
+    /// Create a new invoice on the configured node
     pub async fn create_invoice(
         &self,
         amount_sat: u64,
         description: &str,
         expiry_seconds: u32,
     ) -> AppResult<CreateInvoiceResponse> {
-        // This would connect to your Lightning node (LND, CLN, etc.)
-        // Using the node_url and authentication
-
-        // For now, return a placeholder - in production, this would make
-        // an API call to your Lightning node
-        let _request = CreateInvoiceRequest {
-            amount_sat,
-            description: description.to_string(),
-            expiry_seconds,
-        };
+        self.node()?
+            .create_invoice(amount_sat, description, expiry_seconds)
+            .await
+    }
 
-        // Example implementation for LND REST API:
-        // let client = reqwest::Client::new();
-        // let response = client
-        //     .post(&format!("{}/v1/invoices", self.node_url))
-        //     .header("Grpc-Metadata-macaroon", macaroon_hex)
-        //     .json(&request)
-        //     .send()
-        //     .await?;
+    /// Create an invoice carrying only the SHA-256 hash of `description`
+    pub async fn create_hashed_invoice(
+        &self,
+        amount_msat: u64,
+        description: &str,
+        expiry_seconds: u32,
+    ) -> AppResult<CreateInvoiceResponse> {
+        self.node()?
+            .create_hashed_invoice(amount_msat, description, expiry_seconds)
+            .await
+    }
 
-        Err(AppError::Lightning(
-            "Lightning node connection not configured. Set LIGHTNING_NODE_URL in environment.".to_string()
-        ))
+    /// Look up an invoice by payment hash, `None` if the node does not know it
+    pub async fn lookup_invoice(&self, payment_hash: &str) -> AppResult<Option<InvoiceLookup>> {
+        if !Self::validate_payment_hash(payment_hash) {
+            return Err(AppError::Lightning("Invalid payment hash".to_string()));
+        }
+
+        self.node()?.lookup_invoice(payment_hash).await
     }
 
     /// Check if an invoice has been paid
     pub async fn check_payment(&self, payment_hash: &str) -> AppResult<PaymentStatus> {
-        // This would query your Lightning node to check payment status
+        Ok(self
+            .lookup_invoice(payment_hash)
+            .await?
+            .map(|invoice| invoice.status)
+            .unwrap_or(PaymentStatus::Unknown))
+    }
+
+    /// Invoices settled after `settle_index`, see [`LightningBackend::subscribe_settlements`]
+    pub async fn subscribe_settlements(&self, settle_index: u64) -> AppResult<SettlementStream> {
+        self.node()?.subscribe_settlements(settle_index).await
+    }
+
+    /// Routing fee budget for paying `amount_sat`: `LIGHTNING_MAX_FEE_PPM` of
+    /// the amount, but never less than `LIGHTNING_MIN_FEE_LIMIT_SAT`
+    pub fn fee_limit_sat(&self, amount_sat: u64) -> u64 {
+        (amount_sat.saturating_mul(self.max_fee_ppm) / 1_000_000).max(self.min_fee_limit_sat)
+    }
+
+    /// Create a hold invoice, see [`LightningBackend::create_hold_invoice`]
+    pub async fn create_hold_invoice(
+        &self,
+        payment_hash: &str,
+        amount_sat: u64,
+        description: &str,
+        expiry_seconds: u32,
+        cltv_expiry: u32,
+    ) -> AppResult<CreateInvoiceResponse> {
+        if !Self::validate_payment_hash(payment_hash) {
+            return Err(AppError::Lightning("Invalid payment hash".to_string()));
+        }
+
+        self.node()?
+            .create_hold_invoice(payment_hash, amount_sat, description, expiry_seconds, cltv_expiry)
+            .await
+    }
+
+    /// Settle a held invoice with its hex preimage
+    pub async fn settle_hold_invoice(&self, preimage: &str) -> AppResult<()> {
+        self.node()?.settle_hold_invoice(preimage).await
+    }
+
+    /// Cancel a held or unpaid invoice
+    pub async fn cancel_hold_invoice(&self, payment_hash: &str) -> AppResult<()> {
+        self.node()?.cancel_hold_invoice(payment_hash).await
+    }
 
-        // For LND REST API:
-        // GET /v1/invoice/{payment_hash}
+    /// Pay a BOLT11 invoice from the node's channels
+    pub async fn pay_invoice(
+        &self,
+        payment_request: &str,
+        max_fee_sat: u64,
+    ) -> AppResult<OutgoingStatus> {
+        self.node()?.pay_invoice(payment_request, max_fee_sat).await
+    }
 
-        let _ = payment_hash;
+    /// State of an outgoing payment, see [`LightningBackend::lookup_payment`]
+    pub async fn lookup_payment(&self, payment_hash: &str) -> AppResult<Option<OutgoingStatus>> {
+        if !Self::validate_payment_hash(payment_hash) {
+            return Err(AppError::Lightning("Invalid payment hash".to_string()));
+        }
 
-        Err(AppError::Lightning(
-            "Lightning node connection not configured".to_string()
-        ))
+        self.node()?.lookup_payment(payment_hash).await
+    }
+
+    /// Node identity and sync state, used for health checks
+    pub async fn node_info(&self) -> AppResult<NodeInfo> {
+        self.node()?.node_info().await
     }
 
     /// Validate a payment hash format
@@ -117,6 +353,8 @@ impl LightningService {
 #[derive(Debug, Clone)]
 pub struct InvoiceInfo {
     pub payment_hash: String,
+    /// Network the invoice is payable on, named like `BITCOIN_NETWORK`
+    pub network: String,
     pub amount_msat: Option<u64>,
     pub amount_sat: Option<u64>,
     pub description: String,
@@ -125,13 +363,6 @@ pub struct InvoiceInfo {
     pub is_expired: bool,
 }
 
-#[derive(Debug, Clone)]
-pub struct CreateInvoiceRequest {
-    pub amount_sat: u64,
-    pub description: String,
-    pub expiry_seconds: u32,
-}
-
 #[derive(Debug, Clone)]
 pub struct CreateInvoiceResponse {
     pub payment_request: String,
@@ -142,11 +373,62 @@ pub struct CreateInvoiceResponse {
 #[derive(Debug, Clone)]
 pub enum PaymentStatus {
     Pending,
+    /// A hold invoice whose HTLCs are held, waiting to be settled or cancelled
+    Accepted,
     Settled,
     Cancelled,
     Unknown,
 }
 
+/// Node-side state of an invoice
+#[derive(Debug, Clone)]
+pub struct InvoiceLookup {
+    /// Hex-encoded payment hash
+    pub payment_hash: String,
+    pub payment_request: String,
+    pub status: PaymentStatus,
+    pub amount_sat: u64,
+    pub amount_paid_msat: u64,
+    /// Position in the node's settlement stream, 0 while unsettled
+    pub settle_index: u64,
+    /// Unix timestamp of settlement
+    pub settled_at: Option<i64>,
+    /// Hex-encoded preimage, known once the invoice is settled
+    pub preimage: Option<String>,
+    /// Earliest CLTV expiry height of the HTLCs held for an accepted hold invoice
+    pub htlc_expiry_height: Option<u32>,
+}
+
+/// Result of a successful outgoing payment
+#[derive(Debug, Clone)]
+pub struct OutgoingPayment {
+    /// Hex-encoded payment hash
+    pub payment_hash: String,
+    /// Hex-encoded preimage, the proof of payment
+    pub preimage: String,
+    pub amount_msat: u64,
+    pub fee_msat: u64,
+}
+
+/// Node-side state of an outgoing payment
+#[derive(Debug, Clone)]
+pub enum OutgoingStatus {
+    /// Still looking for a route or waiting on HTLCs
+    InFlight,
+    Succeeded(OutgoingPayment),
+    /// The payment can no longer complete, with the node's reason
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeInfo {
+    pub pubkey: String,
+    pub alias: String,
+    pub block_height: u32,
+    pub synced_to_chain: bool,
+    pub num_active_channels: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +448,54 @@ mod tests {
         assert!(!LightningService::validate_payment_hash(invalid_hash));
     }
 
+    fn service() -> LightningService {
+        LightningService::new(&LightningConfig {
+            backend: "lnd".to_string(),
+            node_url: String::new(),
+            macaroon_path: None,
+            tls_cert_path: None,
+            rune: None,
+            lnurl_base_url: None,
+            network: "regtest".to_string(),
+            max_fee_ppm: 5_000,
+            min_fee_limit_sat: 10,
+            hold_cltv_expiry: 144,
+            hold_cancel_margin_blocks: 12,
+        })
+        .unwrap()
+    }
+
+    fn invoice_info(network: &str, is_expired: bool) -> InvoiceInfo {
+        InvoiceInfo {
+            payment_hash: "00".repeat(32),
+            network: network.to_string(),
+            amount_msat: Some(1_500_000),
+            amount_sat: Some(1_500),
+            description: String::new(),
+            expiry_seconds: 3600,
+            timestamp: 1_700_000_000,
+            is_expired,
+        }
+    }
+
+    #[test]
+    fn test_check_payable() {
+        let service = service();
+
+        assert!(service.check_payable(&invoice_info("regtest", false)).is_ok());
+        assert!(service.check_payable(&invoice_info("bitcoin", false)).is_err());
+        assert!(service.check_payable(&invoice_info("regtest", true)).is_err());
+    }
+
+    #[test]
+    fn test_fee_limit() {
+        let service = service();
+
+        // 0.5% of the amount, with a 10 sat floor
+        assert_eq!(service.fee_limit_sat(1_000_000), 5_000);
+        assert_eq!(service.fee_limit_sat(1_000), 10);
+    }
+
     #[test]
     fn test_sats_conversion() {
         assert_eq!(LightningService::btc_to_sats(1.0), 100_000_000);
@@ -0,0 +1,896 @@
+use async_trait::async_trait;
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE};
+use base64::Engine;
+use futures::StreamExt;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+use super::lightning::{
+    node_http_client, CreateInvoiceResponse, InvoiceLookup, LightningBackend, NodeInfo,
+    OutgoingPayment, OutgoingStatus, PaymentStatus, SettlementStream,
+};
+use crate::config::LightningConfig;
+use crate::error::{AppError, AppResult};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Payments can take a while to find a route
+const PAYMENT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// LND REST API client, authenticated with a macaroon and, when a TLS
+/// certificate is configured, trusting only that certificate
+#[derive(Clone)]
+pub struct LndClient {
+    http_client: Client,
+    node_url: String,
+    macaroon_hex: Option<String>,
+}
+
+impl LndClient {
+    pub fn new(config: &LightningConfig) -> AppResult<Self> {
+        let macaroon_hex = match &config.macaroon_path {
+            Some(path) => Some(hex::encode(std::fs::read(path).map_err(|e| {
+                AppError::Config(format!("Failed to read LIGHTNING_MACAROON_PATH: {}", e))
+            })?)),
+            None => None,
+        };
+
+        Ok(Self {
+            http_client: node_http_client(config)?,
+            node_url: config.node_url.trim_end_matches('/').to_string(),
+            macaroon_hex,
+        })
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}{}", self.node_url, endpoint)
+    }
+
+    fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.macaroon_hex {
+            Some(macaroon) => request.header("Grpc-Metadata-macaroon", macaroon),
+            None => request,
+        }
+    }
+
+    /// `POST /v1/invoices`
+    async fn add_invoice(&self, request: serde_json::Value) -> AppResult<CreateInvoiceResponse> {
+        let response: LndAddInvoiceResponse = self
+            .send(self.http_client.post(self.url("/v1/invoices")).json(&request))
+            .await?;
+
+        Ok(CreateInvoiceResponse {
+            payment_request: response.payment_request,
+            payment_hash: decode_hash(&response.r_hash)?,
+            add_index: response.add_index,
+        })
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> AppResult<T> {
+        read_json(self.execute(request.timeout(REQUEST_TIMEOUT)).await?).await
+    }
+
+    async fn execute(&self, request: RequestBuilder) -> AppResult<Response> {
+        let response = self.authenticated(request).send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<LndError>(&body)
+            .map(|e| e.message)
+            .unwrap_or(body);
+
+        // Older LND versions answer unknown invoices with a 500
+        if status == reqwest::StatusCode::NOT_FOUND || message.contains("unable to locate invoice") {
+            return Err(AppError::NotFound(format!("LND: {}", message)));
+        }
+
+        tracing::error!("LND API error: {} - {}", status, message);
+        Err(AppError::Lightning(format!("LND API error: {} - {}", status, message)))
+    }
+}
+
+#[async_trait]
+impl LightningBackend for LndClient {
+    fn name(&self) -> &'static str {
+        "lnd"
+    }
+
+    async fn create_invoice(
+        &self,
+        amount_sat: u64,
+        description: &str,
+        expiry_seconds: u32,
+    ) -> AppResult<CreateInvoiceResponse> {
+        self.add_invoice(serde_json::json!({
+            "value": amount_sat.to_string(),
+            "memo": description,
+            "expiry": expiry_seconds.to_string(),
+        }))
+        .await
+    }
+
+    async fn create_hashed_invoice(
+        &self,
+        amount_msat: u64,
+        description: &str,
+        expiry_seconds: u32,
+    ) -> AppResult<CreateInvoiceResponse> {
+        let description_hash = Sha256::digest(description.as_bytes());
+
+        self.add_invoice(serde_json::json!({
+            "value_msat": amount_msat.to_string(),
+            "description_hash": BASE64.encode(description_hash),
+            "expiry": expiry_seconds.to_string(),
+        }))
+        .await
+    }
+
+    /// `GET /v1/invoice/{r_hash_str}`
+    async fn lookup_invoice(&self, payment_hash: &str) -> AppResult<Option<InvoiceLookup>> {
+        let request = self
+            .http_client
+            .get(self.url(&format!("/v1/invoice/{}", payment_hash)));
+
+        let invoice: LndInvoice = match self.send(request).await {
+            Ok(invoice) => invoice,
+            Err(AppError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Some(invoice.into_lookup()?))
+    }
+
+    /// `GET /v1/invoices/subscribe`, a newline-delimited JSON stream of invoice
+    /// updates. LND first replays invoices settled after `settle_index`.
+    async fn subscribe_settlements(&self, settle_index: u64) -> AppResult<SettlementStream> {
+        let request = self
+            .http_client
+            .get(self.url("/v1/invoices/subscribe"))
+            .query(&[("settle_index", settle_index.to_string())]);
+        let response = self.execute(request).await?;
+
+        let updates = futures::stream::unfold(Some((response, Vec::new())), |state| async move {
+            let (mut response, mut buffer) = state?;
+
+            loop {
+                if let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=end).collect();
+                    match parse_stream_update(&line) {
+                        Ok(Some(invoice)) => return Some((Ok(invoice), Some((response, buffer)))),
+                        Ok(None) => continue,
+                        Err(e) => return Some((Err(e), None)),
+                    }
+                }
+
+                match response.chunk().await {
+                    Ok(Some(chunk)) => buffer.extend_from_slice(&chunk),
+                    Ok(None) => return None,
+                    Err(e) => return Some((Err(e.into()), None)),
+                }
+            }
+        });
+
+        Ok(updates.boxed())
+    }
+
+    /// `POST /v1/channels/transactions`, which returns once the payment completes or fails
+    async fn pay_invoice(
+        &self,
+        payment_request: &str,
+        max_fee_sat: u64,
+    ) -> AppResult<OutgoingStatus> {
+        let request = serde_json::json!({
+            "payment_request": payment_request,
+            "fee_limit": { "fixed": max_fee_sat.to_string() },
+        });
+
+        let request = self
+            .http_client
+            .post(self.url("/v1/channels/transactions"))
+            .timeout(PAYMENT_TIMEOUT)
+            .json(&request);
+        let response: LndSendResponse = read_json(self.execute(request).await?).await?;
+
+        if !response.payment_error.is_empty() {
+            return Err(AppError::Lightning(format!(
+                "Payment failed: {}",
+                response.payment_error
+            )));
+        }
+
+        let route = response.payment_route.unwrap_or_default();
+        let preimage = BASE64
+            .decode(&response.payment_preimage)
+            .map(hex::encode)
+            .map_err(|e| AppError::Lightning(format!("Invalid preimage from LND: {}", e)))?;
+
+        // The synchronous endpoint only answers once the payment has settled
+        Ok(OutgoingStatus::Succeeded(OutgoingPayment {
+            payment_hash: decode_hash(&response.payment_hash)?,
+            preimage,
+            amount_msat: route.total_amt_msat.saturating_sub(route.total_fees_msat),
+            fee_msat: route.total_fees_msat,
+        }))
+    }
+
+    /// First update of `GET /v2/router/track/{payment_hash}`, which streams the
+    /// payment's current state before any later changes
+    async fn lookup_payment(&self, payment_hash: &str) -> AppResult<Option<OutgoingStatus>> {
+        // The hash is a path segment here, so URL-safe base64
+        let hash = hex::decode(payment_hash)
+            .map(|bytes| URL_SAFE.encode(bytes))
+            .map_err(|e| AppError::Lightning(format!("Invalid hex value: {}", e)))?;
+        let request = self
+            .http_client
+            .get(self.url(&format!("/v2/router/track/{}", hash)))
+            .timeout(REQUEST_TIMEOUT);
+
+        let mut response = match self.execute(request).await {
+            Ok(response) => response,
+            Err(AppError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let mut buffer = Vec::new();
+        while !buffer.contains(&b'\n') {
+            match response.chunk().await? {
+                Some(chunk) => buffer.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+
+        parse_track_update(&buffer)
+    }
+
+    /// `POST /v2/invoices/hodl`
+    async fn create_hold_invoice(
+        &self,
+        payment_hash: &str,
+        amount_sat: u64,
+        description: &str,
+        expiry_seconds: u32,
+        cltv_expiry: u32,
+    ) -> AppResult<CreateInvoiceResponse> {
+        let request = serde_json::json!({
+            "hash": encode_hash(payment_hash)?,
+            "value": amount_sat.to_string(),
+            "memo": description,
+            "expiry": expiry_seconds.to_string(),
+            "cltv_expiry": cltv_expiry.to_string(),
+        });
+
+        let response: LndHoldInvoiceResponse = self
+            .send(self.http_client.post(self.url("/v2/invoices/hodl")).json(&request))
+            .await?;
+
+        Ok(CreateInvoiceResponse {
+            payment_request: response.payment_request,
+            payment_hash: payment_hash.to_string(),
+            add_index: response.add_index,
+        })
+    }
+
+    /// `POST /v2/invoices/settle`
+    async fn settle_hold_invoice(&self, preimage: &str) -> AppResult<()> {
+        let request = serde_json::json!({ "preimage": encode_hash(preimage)? });
+
+        let _: serde_json::Value = self
+            .send(self.http_client.post(self.url("/v2/invoices/settle")).json(&request))
+            .await?;
+
+        Ok(())
+    }
+
+    /// `POST /v2/invoices/cancel`
+    async fn cancel_hold_invoice(&self, payment_hash: &str) -> AppResult<()> {
+        let request = serde_json::json!({ "payment_hash": encode_hash(payment_hash)? });
+
+        let _: serde_json::Value = self
+            .send(self.http_client.post(self.url("/v2/invoices/cancel")).json(&request))
+            .await?;
+
+        Ok(())
+    }
+
+    /// `GET /v1/getinfo`
+    async fn node_info(&self) -> AppResult<NodeInfo> {
+        let info: LndGetInfoResponse = self
+            .send(self.http_client.get(self.url("/v1/getinfo")))
+            .await?;
+
+        Ok(NodeInfo {
+            pubkey: info.identity_pubkey,
+            alias: info.alias,
+            block_height: info.block_height,
+            synced_to_chain: info.synced_to_chain,
+            num_active_channels: info.num_active_channels,
+        })
+    }
+}
+
+async fn read_json<T: DeserializeOwned>(response: Response) -> AppResult<T> {
+    let body = response.text().await?;
+
+    serde_json::from_str(&body)
+        .map_err(|e| AppError::Lightning(format!("Failed to parse LND response: {}", e)))
+}
+
+/// One line of the subscription stream; only settlements are of interest
+fn parse_stream_update(line: &[u8]) -> AppResult<Option<InvoiceLookup>> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+
+    let update: LndStreamUpdate = serde_json::from_slice(line)
+        .map_err(|e| AppError::Lightning(format!("Failed to parse LND invoice update: {}", e)))?;
+
+    if let Some(error) = update.error {
+        return Err(AppError::Lightning(format!("LND invoice subscription: {}", error.message)));
+    }
+
+    match update.result {
+        Some(invoice) if invoice.state == "SETTLED" => Ok(Some(invoice.into_lookup()?)),
+        _ => Ok(None),
+    }
+}
+
+/// The first line of a payment tracking stream
+fn parse_track_update(buffer: &[u8]) -> AppResult<Option<OutgoingStatus>> {
+    let line = buffer.split(|b| *b == b'\n').next().unwrap_or_default();
+
+    let update: LndTrackUpdate = serde_json::from_slice(line)
+        .map_err(|e| AppError::Lightning(format!("Failed to parse LND payment update: {}", e)))?;
+
+    if let Some(error) = update.error {
+        // Streaming errors arrive in the body, after a successful status
+        if error.message.contains("isn't initiated") {
+            return Ok(None);
+        }
+        return Err(AppError::Lightning(format!("LND payment tracking: {}", error.message)));
+    }
+
+    update
+        .result
+        .map(|payment| Some(payment.into_status()))
+        .ok_or_else(|| AppError::Lightning("Empty LND payment update".to_string()))
+}
+
+/// Payment hashes travel base64-encoded in LND's JSON, hex everywhere else
+fn decode_hash(r_hash: &str) -> AppResult<String> {
+    BASE64
+        .decode(r_hash)
+        .map(hex::encode)
+        .map_err(|e| AppError::Lightning(format!("Invalid payment hash from LND: {}", e)))
+}
+
+/// Hex hash or preimage to the base64 LND expects
+fn encode_hash(hex_value: &str) -> AppResult<String> {
+    hex::decode(hex_value)
+        .map(|bytes| BASE64.encode(bytes))
+        .map_err(|e| AppError::Lightning(format!("Invalid hex value: {}", e)))
+}
+
+/// LND renders 64-bit integers as JSON strings
+fn u64_from_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(u64),
+    }
+
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.parse().map_err(serde::de::Error::custom),
+        StringOrNumber::Number(n) => Ok(n),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LndAddInvoiceResponse {
+    r_hash: String,
+    payment_request: String,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    add_index: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LndHoldInvoiceResponse {
+    payment_request: String,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    add_index: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LndInvoice {
+    r_hash: String,
+    #[serde(default)]
+    payment_request: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    r_preimage: String,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    value: u64,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    amt_paid_msat: u64,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    settle_date: u64,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    settle_index: u64,
+    #[serde(default)]
+    htlcs: Vec<LndInvoiceHtlc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LndInvoiceHtlc {
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    expiry_height: u32,
+}
+
+impl LndInvoice {
+    fn into_lookup(self) -> AppResult<InvoiceLookup> {
+        let status = match self.state.as_str() {
+            // ACCEPTED is a held HTLC that has not been settled yet
+            "OPEN" => PaymentStatus::Pending,
+            "ACCEPTED" => PaymentStatus::Accepted,
+            "SETTLED" => PaymentStatus::Settled,
+            "CANCELED" => PaymentStatus::Cancelled,
+            _ => PaymentStatus::Unknown,
+        };
+        let preimage = match status {
+            PaymentStatus::Settled => BASE64.decode(&self.r_preimage).ok().map(hex::encode),
+            _ => None,
+        };
+        let htlc_expiry_height = self
+            .htlcs
+            .iter()
+            .filter(|htlc| htlc.state == "ACCEPTED")
+            .map(|htlc| htlc.expiry_height)
+            .min();
+
+        Ok(InvoiceLookup {
+            payment_hash: decode_hash(&self.r_hash)?,
+            payment_request: self.payment_request,
+            status,
+            amount_sat: self.value,
+            amount_paid_msat: self.amt_paid_msat,
+            settle_index: self.settle_index,
+            settled_at: (self.settle_date > 0).then_some(self.settle_date as i64),
+            preimage,
+            htlc_expiry_height,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LndStreamUpdate {
+    result: Option<LndInvoice>,
+    error: Option<LndError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LndSendResponse {
+    #[serde(default)]
+    payment_error: String,
+    #[serde(default)]
+    payment_preimage: String,
+    #[serde(default)]
+    payment_hash: String,
+    payment_route: Option<LndRoute>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LndTrackUpdate {
+    result: Option<LndPayment>,
+    error: Option<LndError>,
+}
+
+/// A sent payment; unlike invoices, its hash and preimage are hex
+#[derive(Debug, Deserialize)]
+struct LndPayment {
+    payment_hash: String,
+    #[serde(default)]
+    payment_preimage: String,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    value_msat: u64,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    fee_msat: u64,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    failure_reason: String,
+}
+
+impl LndPayment {
+    fn into_status(self) -> OutgoingStatus {
+        match self.status.as_str() {
+            "SUCCEEDED" => OutgoingStatus::Succeeded(OutgoingPayment {
+                payment_hash: self.payment_hash,
+                preimage: self.payment_preimage,
+                amount_msat: self.value_msat,
+                fee_msat: self.fee_msat,
+            }),
+            "FAILED" => OutgoingStatus::Failed(format!("Payment failed: {}", self.failure_reason)),
+            // INITIATED and IN_FLIGHT can still go either way
+            _ => OutgoingStatus::InFlight,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LndRoute {
+    #[serde(default, deserialize_with = "u64_from_string")]
+    total_fees_msat: u64,
+    #[serde(default, deserialize_with = "u64_from_string")]
+    total_amt_msat: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LndGetInfoResponse {
+    identity_pubkey: String,
+    #[serde(default)]
+    alias: String,
+    #[serde(default)]
+    block_height: u32,
+    #[serde(default)]
+    synced_to_chain: bool,
+    #[serde(default)]
+    num_active_channels: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct LndError {
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const HASH_HEX: &str = "0001020304050607080910111213141516171819202122232425262728293031";
+
+    fn lnd_client(url: &str, macaroon_path: Option<String>) -> LndClient {
+        LndClient::new(&LightningConfig {
+            backend: "lnd".to_string(),
+            node_url: url.to_string(),
+            macaroon_path,
+            tls_cert_path: None,
+            rune: None,
+            lnurl_base_url: None,
+            network: "regtest".to_string(),
+            max_fee_ppm: 5_000,
+            min_fee_limit_sat: 10,
+            hold_cltv_expiry: 144,
+            hold_cancel_margin_blocks: 12,
+        })
+        .unwrap()
+    }
+
+    fn hash_base64() -> String {
+        BASE64.encode(hex::decode(HASH_HEX).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_create_invoice_sends_macaroon() {
+        let server = MockServer::start().await;
+        let macaroon_path = std::env::temp_dir().join(format!("lnd-{}.macaroon", uuid::Uuid::new_v4()));
+        std::fs::write(&macaroon_path, [0x02, 0x01, 0xab]).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/v1/invoices"))
+            .and(header("Grpc-Metadata-macaroon", "0201ab"))
+            .and(body_partial_json(serde_json::json!({ "value": "1500", "expiry": "3600" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "r_hash": hash_base64(),
+                "payment_request": "lnbcrt15u1pexample",
+                "add_index": "7"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = lnd_client(&server.uri(), Some(macaroon_path.to_string_lossy().into_owned()));
+        let invoice = client.create_invoice(1500, "Order 42", 3600).await.unwrap();
+        std::fs::remove_file(&macaroon_path).ok();
+
+        assert_eq!(invoice.payment_hash, HASH_HEX);
+        assert_eq!(invoice.payment_request, "lnbcrt15u1pexample");
+        assert_eq!(invoice.add_index, 7);
+    }
+
+    #[tokio::test]
+    async fn test_create_hashed_invoice() {
+        let server = MockServer::start().await;
+        let metadata = r#"[["text/plain","Coffee"]]"#;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/invoices"))
+            .and(body_partial_json(serde_json::json!({
+                "value_msat": "21000",
+                "description_hash": BASE64.encode(Sha256::digest(metadata.as_bytes()))
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "r_hash": hash_base64(),
+                "payment_request": "lnbcrt210n1pexample",
+                "add_index": "8"
+            })))
+            .mount(&server)
+            .await;
+
+        let invoice = lnd_client(&server.uri(), None)
+            .create_hashed_invoice(21_000, metadata, 3600)
+            .await
+            .unwrap();
+
+        assert_eq!(invoice.payment_hash, HASH_HEX);
+        assert_eq!(invoice.payment_request, "lnbcrt210n1pexample");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_invoice() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/invoice/{}", HASH_HEX)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "r_hash": hash_base64(),
+                "payment_request": "lnbcrt15u1pexample",
+                "value": "1500",
+                "state": "SETTLED",
+                "amt_paid_msat": "1500000",
+                "settle_date": "1700000000",
+                "settle_index": "3",
+                "r_preimage": hash_base64()
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/invoice/ff"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "code": 5,
+                "message": "unable to locate invoice"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = lnd_client(&server.uri(), None);
+
+        let invoice = client.lookup_invoice(HASH_HEX).await.unwrap().unwrap();
+        assert!(matches!(invoice.status, PaymentStatus::Settled));
+        assert_eq!(invoice.amount_paid_msat, 1_500_000);
+        assert_eq!(invoice.settle_index, 3);
+        assert_eq!(invoice.settled_at, Some(1_700_000_000));
+        assert_eq!(invoice.preimage.as_deref(), Some(HASH_HEX));
+
+        assert!(client.lookup_invoice("ff").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hold_invoice_lifecycle() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v2/invoices/hodl"))
+            .and(body_partial_json(serde_json::json!({
+                "hash": hash_base64(),
+                "value": "1500",
+                "cltv_expiry": "144"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "payment_request": "lnbcrt15u1phold",
+                "add_index": "9"
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/invoice/{}", HASH_HEX)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "r_hash": hash_base64(),
+                "payment_request": "lnbcrt15u1phold",
+                "value": "1500",
+                "state": "ACCEPTED",
+                "htlcs": [
+                    { "state": "ACCEPTED", "expiry_height": 950 },
+                    { "state": "ACCEPTED", "expiry_height": 940 },
+                    { "state": "CANCELED", "expiry_height": 900 }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v2/invoices/settle"))
+            .and(body_partial_json(serde_json::json!({ "preimage": hash_base64() })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = lnd_client(&server.uri(), None);
+
+        let invoice = client
+            .create_hold_invoice(HASH_HEX, 1500, "Order 42", 3600, 144)
+            .await
+            .unwrap();
+        assert_eq!(invoice.payment_hash, HASH_HEX);
+        assert_eq!(invoice.add_index, 9);
+
+        let held = client.lookup_invoice(HASH_HEX).await.unwrap().unwrap();
+        assert!(matches!(held.status, PaymentStatus::Accepted));
+        assert_eq!(held.htlc_expiry_height, Some(940));
+        assert!(held.preimage.is_none());
+
+        client.settle_hold_invoice(HASH_HEX).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_settlements_skips_unsettled_updates() {
+        let server = MockServer::start().await;
+        let update = |state: &str, settle_index: u64| {
+            serde_json::json!({
+                "result": {
+                    "r_hash": hash_base64(),
+                    "value": "1500",
+                    "state": state,
+                    "amt_paid_msat": "1500000",
+                    "settle_index": settle_index.to_string()
+                }
+            })
+            .to_string()
+        };
+        let body = format!("{}\n{}\n", update("OPEN", 0), update("SETTLED", 9));
+
+        Mock::given(method("GET"))
+            .and(path("/v1/invoices/subscribe"))
+            .and(query_param("settle_index", "8"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(&server)
+            .await;
+
+        let client = lnd_client(&server.uri(), None);
+        let settlements: Vec<_> = client.subscribe_settlements(8).await.unwrap().collect().await;
+
+        assert_eq!(settlements.len(), 1);
+        let invoice = settlements[0].as_ref().unwrap();
+        assert_eq!(invoice.payment_hash, HASH_HEX);
+        assert_eq!(invoice.settle_index, 9);
+    }
+
+    #[tokio::test]
+    async fn test_pay_invoice_reports_payment_error() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/channels/transactions"))
+            .and(body_partial_json(serde_json::json!({ "payment_request": "lnbc1ok" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "payment_error": "",
+                "payment_preimage": hash_base64(),
+                "payment_hash": hash_base64(),
+                "payment_route": { "total_fees_msat": "2000", "total_amt_msat": "1502000" }
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/channels/transactions"))
+            .and(body_partial_json(serde_json::json!({ "payment_request": "lnbc1noroute" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "payment_error": "unable to find a path to destination"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = lnd_client(&server.uri(), None);
+
+        let OutgoingStatus::Succeeded(payment) = client.pay_invoice("lnbc1ok", 10).await.unwrap()
+        else {
+            panic!("expected a settled payment");
+        };
+        assert_eq!(payment.preimage, HASH_HEX);
+        assert_eq!(payment.amount_msat, 1_500_000);
+        assert_eq!(payment.fee_msat, 2000);
+
+        assert!(client.pay_invoice("lnbc1noroute", 10).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_payment() {
+        let server = MockServer::start().await;
+        let hash = URL_SAFE.encode(hex::decode(HASH_HEX).unwrap());
+        let unknown = URL_SAFE.encode([0xff; 32]);
+
+        let update = serde_json::json!({
+            "result": {
+                "payment_hash": HASH_HEX,
+                "payment_preimage": "ab".repeat(32),
+                "value_msat": "1500000",
+                "fee_msat": "2000",
+                "status": "SUCCEEDED",
+                "failure_reason": "FAILURE_REASON_NONE"
+            }
+        });
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/router/track/{}", hash)))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!("{}\n", update)))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/router/track/{}", unknown)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "error": { "code": 5, "message": "payment isn't initiated" }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = lnd_client(&server.uri(), None);
+
+        match client.lookup_payment(HASH_HEX).await.unwrap() {
+            Some(OutgoingStatus::Succeeded(payment)) => {
+                assert_eq!(payment.preimage, "ab".repeat(32));
+                assert_eq!(payment.amount_msat, 1_500_000);
+                assert_eq!(payment.fee_msat, 2000);
+            }
+            other => panic!("unexpected payment state {:?}", other),
+        }
+
+        assert!(client.lookup_payment(&"ff".repeat(32)).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_track_update_states() {
+        let update = |status: &str| {
+            serde_json::json!({
+                "result": {
+                    "payment_hash": HASH_HEX,
+                    "status": status,
+                    "failure_reason": "FAILURE_REASON_NO_ROUTE"
+                }
+            })
+            .to_string()
+        };
+
+        assert!(matches!(
+            parse_track_update(update("IN_FLIGHT").as_bytes()).unwrap(),
+            Some(OutgoingStatus::InFlight)
+        ));
+        assert!(matches!(
+            parse_track_update(update("FAILED").as_bytes()).unwrap(),
+            Some(OutgoingStatus::Failed(reason)) if reason.contains("NO_ROUTE")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_node_info() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/getinfo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "identity_pubkey": "02abc",
+                "alias": "gateway",
+                "block_height": 812000,
+                "synced_to_chain": true,
+                "num_active_channels": 4
+            })))
+            .mount(&server)
+            .await;
+
+        let info = lnd_client(&server.uri(), None).node_info().await.unwrap();
+        assert_eq!(info.pubkey, "02abc");
+        assert_eq!(info.block_height, 812000);
+        assert!(info.synced_to_chain);
+    }
+}
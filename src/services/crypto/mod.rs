@@ -1,9 +1,16 @@
+pub mod bitcoin;
+pub mod cln;
 pub mod ethereum;
+pub mod hd_wallet;
 pub mod solana;
 pub mod lightning;
+pub mod lnd;
+pub mod rpc_pool;
 pub mod wallet_connect;
 
+pub use self::bitcoin::BitcoinService;
 pub use ethereum::EthereumService;
+pub use hd_wallet::HdWallet;
 pub use solana::SolanaService;
-pub use lightning::LightningService;
+pub use lightning::{LightningBackend, LightningService};
 pub use wallet_connect::WalletConnectVerifier;
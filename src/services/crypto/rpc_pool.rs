@@ -0,0 +1,535 @@
+use ethers::providers::{Http, Middleware, Provider, ProviderError};
+use futures::stream::{FuturesUnordered, StreamExt};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::error::{AppError, AppResult};
+
+/// Consecutive failures before an endpoint is benched
+const FAILURE_THRESHOLD: u32 = 3;
+const BASE_COOLDOWN: Duration = Duration::from_secs(5);
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+/// Weight of the newest sample in the latency moving average
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// HTTP JSON-RPC endpoints of one chain, ranked by health.
+///
+/// Requests go to the healthiest endpoint and fail over to the next one on
+/// error. With `hedge_after` set, a slow request is raced against the next
+/// endpoint. Quorum reads ask several endpoints and only return a value that
+/// at least `quorum` of them agree on.
+pub struct RpcPool {
+    endpoints: Vec<Endpoint>,
+    hedge_after: Option<Duration>,
+    quorum: usize,
+    max_head_lag: u64,
+}
+
+struct Endpoint {
+    url: String,
+    provider: Provider<Http>,
+    health: Mutex<Health>,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    /// Moving average, `None` until the first success
+    latency_ms: Option<f64>,
+    benched_until: Option<Instant>,
+    head: u64,
+}
+
+impl Health {
+    fn is_benched(&self, now: Instant) -> bool {
+        self.benched_until.is_some_and(|until| until > now)
+    }
+}
+
+impl RpcPool {
+    pub fn new(
+        urls: &[String],
+        hedge_after: Option<Duration>,
+        quorum: usize,
+        max_head_lag: u64,
+        request_timeout: Duration,
+    ) -> AppResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(request_timeout)
+            .build()
+            .map_err(|e| AppError::Config(format!("Failed to create HTTP client: {}", e)))?;
+
+        let endpoints = urls
+            .iter()
+            .filter(|url| !url.is_empty())
+            .map(|url| {
+                let parsed = reqwest::Url::parse(url)
+                    .map_err(|e| AppError::Ethereum(format!("Failed to create provider: {}", e)))?;
+                let provider = Provider::new(Http::new_with_client(parsed, client.clone()));
+
+                Ok(Endpoint {
+                    url: url.clone(),
+                    provider,
+                    health: Mutex::new(Health::default()),
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        if endpoints.is_empty() {
+            return Err(AppError::Config("No RPC endpoints configured".to_string()));
+        }
+
+        Ok(Self {
+            quorum: quorum.clamp(1, endpoints.len()),
+            endpoints,
+            hedge_after,
+            max_head_lag,
+        })
+    }
+
+    /// Endpoint indices, best first. Benched endpoints go last so they are
+    /// still tried when everything else has failed.
+    fn ranked(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut ranked: Vec<(usize, bool, u32, f64)> = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(idx, endpoint)| {
+                let health = endpoint.health.lock();
+                (
+                    idx,
+                    health.is_benched(now),
+                    health.consecutive_failures,
+                    health.latency_ms.unwrap_or(0.0),
+                )
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            (a.1, a.2)
+                .cmp(&(b.1, b.2))
+                .then(a.3.total_cmp(&b.3))
+                .then(a.0.cmp(&b.0))
+        });
+
+        ranked.into_iter().map(|(idx, ..)| idx).collect()
+    }
+
+    fn record_success(&self, idx: usize, latency: Duration) {
+        let mut health = self.endpoints[idx].health.lock();
+        let sample = latency.as_secs_f64() * 1000.0;
+
+        health.consecutive_failures = 0;
+        health.benched_until = None;
+        health.latency_ms = Some(match health.latency_ms {
+            Some(avg) => avg + LATENCY_SMOOTHING * (sample - avg),
+            None => sample,
+        });
+    }
+
+    fn record_failure(&self, idx: usize, reason: &str) {
+        let endpoint = &self.endpoints[idx];
+        let mut health = endpoint.health.lock();
+        health.consecutive_failures += 1;
+
+        if health.consecutive_failures >= FAILURE_THRESHOLD {
+            let exponent = (health.consecutive_failures - FAILURE_THRESHOLD).min(6);
+            let cooldown = (BASE_COOLDOWN * 2u32.pow(exponent)).min(MAX_COOLDOWN);
+            health.benched_until = Some(Instant::now() + cooldown);
+
+            tracing::warn!(
+                url = %endpoint.url,
+                failures = health.consecutive_failures,
+                cooldown_secs = cooldown.as_secs(),
+                "RPC endpoint benched: {}",
+                reason
+            );
+        }
+    }
+
+    /// Record a reported head, returning false (and penalizing the endpoint)
+    /// when it lags the best head seen across the pool
+    fn observe_head(&self, idx: usize, head: u64) -> bool {
+        let best = self
+            .endpoints
+            .iter()
+            .map(|endpoint| endpoint.health.lock().head)
+            .max()
+            .unwrap_or(0);
+
+        self.endpoints[idx].health.lock().head = head;
+
+        if head.saturating_add(self.max_head_lag) < best {
+            self.record_failure(idx, &format!("stale head {} (best {})", head, best));
+            return false;
+        }
+
+        true
+    }
+
+    /// Run `call` against the healthiest endpoint, failing over on error
+    pub async fn request<T, F, Fut>(&self, call: F) -> Result<T, ProviderError>
+    where
+        F: Fn(Provider<Http>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.request_from(&self.ranked(), call)
+            .await
+            .map(|(_, value)| value)
+    }
+
+    /// Try `order` in turn, hedging slow requests, and return the first
+    /// success together with the endpoint that served it
+    async fn request_from<T, F, Fut>(
+        &self,
+        order: &[usize],
+        call: F,
+    ) -> Result<(usize, T), ProviderError>
+    where
+        F: Fn(Provider<Http>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let start = |idx: usize| {
+            let request = call(self.endpoints[idx].provider.clone());
+            async move {
+                let started = Instant::now();
+                let result = request.await;
+                (idx, started.elapsed(), result)
+            }
+        };
+
+        let mut in_flight = FuturesUnordered::new();
+        in_flight.push(start(order[0]));
+        let mut next = 1;
+
+        loop {
+            let hedge = async {
+                match self.hedge_after {
+                    Some(delay) if next < order.len() => tokio::time::sleep(delay).await,
+                    _ => std::future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                Some((idx, latency, result)) = in_flight.next() => match result {
+                    Ok(value) => {
+                        self.record_success(idx, latency);
+                        return Ok((idx, value));
+                    }
+                    Err(e) => {
+                        self.record_failure(idx, &e.to_string());
+
+                        if next < order.len() {
+                            in_flight.push(start(order[next]));
+                            next += 1;
+                        } else if in_flight.is_empty() {
+                            return Err(e);
+                        }
+                    }
+                },
+                _ = hedge => {
+                    in_flight.push(start(order[next]));
+                    next += 1;
+                }
+            }
+        }
+    }
+
+    /// Current head. A head lagging the rest of the pool counts as a failure
+    /// and is retried elsewhere; in quorum mode this is the highest block that
+    /// `quorum` endpoints have all reached, ignoring any lagging the best head.
+    pub async fn block_number(&self) -> Result<u64, ProviderError> {
+        if self.quorum > 1 {
+            let mut reported: Vec<u64> = Vec::new();
+            let head = self
+                .query_until(
+                    |provider| async move { provider.get_block_number().await },
+                    |idx, head| {
+                        let head = head.as_u64();
+                        if self.observe_head(idx, head) {
+                            reported.push(head);
+                        }
+                        // An endpoint that answered before the pool knew better
+                        // may lag the heads reported after it
+                        let best = reported.iter().copied().max()?;
+                        let mut current: Vec<u64> = reported
+                            .iter()
+                            .copied()
+                            .filter(|head| head.saturating_add(self.max_head_lag) >= best)
+                            .collect();
+                        current.sort_unstable_by(|a, b| b.cmp(a));
+                        current.get(self.quorum - 1).copied()
+                    },
+                )
+                .await;
+
+            return head.ok_or_else(|| {
+                ProviderError::CustomError(format!(
+                    "RPC quorum not reached: {} of {} endpoints answered",
+                    reported.len(),
+                    self.quorum
+                ))
+            });
+        }
+
+        let mut order = self.ranked();
+        loop {
+            let (idx, head) = self
+                .request_from(&order, |provider| async move {
+                    provider.get_block_number().await
+                })
+                .await?;
+
+            if self.observe_head(idx, head.as_u64()) || order.len() == 1 {
+                return Ok(head.as_u64());
+            }
+
+            order.retain(|&other| other != idx);
+        }
+    }
+
+    /// Run `call` against every endpoint and return the first value that
+    /// `quorum` of them agree on, as judged by `key`. Without quorum mode
+    /// this is a plain failover request.
+    pub async fn quorum_request<T, K, F, Fut>(
+        &self,
+        call: F,
+        key: impl Fn(&T) -> K,
+    ) -> Result<T, ProviderError>
+    where
+        K: PartialEq,
+        F: Fn(Provider<Http>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        if self.quorum <= 1 {
+            return self.request(call).await;
+        }
+
+        let mut answered = 0;
+        let mut groups: Vec<(K, T, usize)> = Vec::new();
+        let agreed = self
+            .query_until(call, |_, value| {
+                answered += 1;
+
+                let value_key = key(&value);
+                match groups.iter_mut().find(|(k, _, _)| *k == value_key) {
+                    Some(group) => group.2 += 1,
+                    None => groups.push((value_key, value, 1)),
+                }
+
+                let agreed = groups
+                    .iter()
+                    .position(|(_, _, votes)| *votes >= self.quorum)?;
+                Some(groups.swap_remove(agreed).1)
+            })
+            .await;
+
+        agreed.ok_or_else(|| {
+            ProviderError::CustomError(format!(
+                "RPC quorum of {} not reached ({} endpoints answered)",
+                self.quorum, answered
+            ))
+        })
+    }
+
+    /// Query every endpoint concurrently, handing each successful answer to
+    /// `accept` as it arrives. Returns as soon as `accept` produces a result,
+    /// dropping the requests still in flight, or `None` once all have answered.
+    async fn query_until<T, R, F, Fut>(
+        &self,
+        call: F,
+        mut accept: impl FnMut(usize, T) -> Option<R>,
+    ) -> Option<R>
+    where
+        F: Fn(Provider<Http>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let mut in_flight: FuturesUnordered<_> = self
+            .ranked()
+            .into_iter()
+            .map(|idx| {
+                let request = call(self.endpoints[idx].provider.clone());
+                async move {
+                    let started = Instant::now();
+                    let result = request.await;
+                    (idx, started.elapsed(), result)
+                }
+            })
+            .collect();
+
+        while let Some((idx, latency, result)) = in_flight.next().await {
+            match result {
+                Ok(value) => {
+                    self.record_success(idx, latency);
+                    if let Some(accepted) = accept(idx, value) {
+                        return Some(accepted);
+                    }
+                }
+                Err(e) => self.record_failure(idx, &e.to_string()),
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    async fn rpc_server(result: serde_json::Value) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": result,
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    async fn slow_server(result: serde_json::Value) -> MockServer {
+        delayed_server(result, Duration::from_secs(5)).await
+    }
+
+    async fn delayed_server(result: serde_json::Value, delay: Duration) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+                    .set_delay(delay),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    async fn failing_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_healthy_endpoint() {
+        let down = failing_server().await;
+        let up = rpc_server(json!("0x64")).await;
+        let pool = RpcPool::new(&[down.uri(), up.uri()], None, 1, 5, TIMEOUT).unwrap();
+
+        assert_eq!(pool.block_number().await.unwrap(), 100);
+
+        // The failing endpoint drops behind the healthy one
+        assert_eq!(pool.ranked(), vec![1, 0]);
+        assert_eq!(pool.endpoints[0].health.lock().consecutive_failures, 1);
+
+        for _ in 0..FAILURE_THRESHOLD {
+            pool.record_failure(0, "rate limited");
+        }
+        assert!(pool.endpoints[0].health.lock().is_benched(Instant::now()));
+    }
+
+    #[tokio::test]
+    async fn test_hedges_slow_endpoint() {
+        let slow = slow_server(json!("0x1")).await;
+        let fast = rpc_server(json!("0x2")).await;
+
+        let pool = RpcPool::new(
+            &[slow.uri(), fast.uri()],
+            Some(Duration::from_millis(50)),
+            1,
+            5,
+            TIMEOUT,
+        )
+        .unwrap();
+
+        let started = Instant::now();
+        let head = pool
+            .request(|provider| async move { provider.get_block_number().await })
+            .await
+            .unwrap();
+        assert_eq!(head.as_u64(), 2);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_quorum_ignores_lagging_endpoint_that_answers_first() {
+        let stale = rpc_server(json!("0x10")).await;
+        let a = delayed_server(json!("0x64"), Duration::from_millis(200)).await;
+        let b = delayed_server(json!("0x64"), Duration::from_millis(200)).await;
+        let pool = RpcPool::new(&[stale.uri(), a.uri(), b.uri()], None, 2, 5, TIMEOUT).unwrap();
+
+        assert_eq!(pool.block_number().await.unwrap(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_quorum_outvotes_lagging_endpoint() {
+        let a = rpc_server(json!("0x64")).await;
+        let b = rpc_server(json!("0x64")).await;
+        let stale = rpc_server(json!("0x10")).await;
+        let pool = RpcPool::new(&[a.uri(), b.uri(), stale.uri()], None, 2, 5, TIMEOUT).unwrap();
+
+        assert_eq!(pool.block_number().await.unwrap(), 100);
+
+        let chain_id = pool
+            .quorum_request(
+                |provider| async move { provider.get_chainid().await },
+                |id| *id,
+            )
+            .await
+            .unwrap();
+        assert_eq!(chain_id.as_u64(), 100);
+
+        let split = RpcPool::new(&[a.uri(), stale.uri()], None, 2, 5, TIMEOUT).unwrap();
+        assert!(split
+            .quorum_request(
+                |provider| async move { provider.get_chainid().await },
+                |id| *id,
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_quorum_does_not_wait_for_slow_endpoint() {
+        let a = rpc_server(json!("0x64")).await;
+        let b = rpc_server(json!("0x64")).await;
+        let slow = slow_server(json!("0x64")).await;
+        let pool = RpcPool::new(&[a.uri(), b.uri(), slow.uri()], None, 2, 5, TIMEOUT).unwrap();
+
+        let started = Instant::now();
+        assert_eq!(pool.block_number().await.unwrap(), 100);
+        let chain_id = pool
+            .quorum_request(
+                |provider| async move { provider.get_chainid().await },
+                |id| *id,
+            )
+            .await
+            .unwrap();
+        assert_eq!(chain_id.as_u64(), 100);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_request_times_out() {
+        let slow = slow_server(json!("0x64")).await;
+        let pool = RpcPool::new(&[slow.uri()], None, 1, 5, Duration::from_millis(200)).unwrap();
+
+        let started = Instant::now();
+        assert!(pool.block_number().await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(pool.endpoints[0].health.lock().consecutive_failures, 1);
+    }
+}
@@ -1,64 +1,130 @@
-use solana_client::rpc_client::RpcClient;
+use serde_json::Value;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     pubkey::Pubkey,
     signature::Signature,
 };
+use solana_transaction_status::UiTransactionEncoding;
+use std::collections::HashMap;
+use std::future::Future;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::config::SolanaConfig;
 use crate::error::{AppError, AppResult};
 
+/// Confirmation count reported for finalized transactions, the maximum lockout depth
+pub const FINALIZED_CONFIRMATIONS: u64 = 32;
+
+/// Delay before the first retry of a failed RPC call, doubled on each attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
 #[derive(Clone)]
 pub struct SolanaService {
     client: Arc<RpcClient>,
+    commitment: CommitmentConfig,
+    max_retries: u32,
+    usdc_mint: String,
 }
 
 impl SolanaService {
     pub fn new(config: &SolanaConfig) -> Self {
-        let client = RpcClient::new_with_commitment(
+        let commitment = commitment_config(&config.commitment);
+        let client = RpcClient::new_with_timeout_and_commitment(
             config.rpc_url.clone(),
-            CommitmentConfig::confirmed(),
+            Duration::from_secs(config.timeout_secs),
+            commitment,
         );
 
         Self {
             client: Arc::new(client),
+            commitment,
+            max_retries: config.max_retries,
+            usdc_mint: config.usdc_mint.clone(),
+        }
+    }
+
+    /// Run an RPC call, retrying transport failures (timeouts, dropped connections)
+    /// with exponential backoff. RPC errors returned by the node are not retried.
+    async fn retry<T, F, Fut>(&self, context: &str, call: F) -> AppResult<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_retries && is_transient(&e) => {
+                    let delay = RETRY_BASE_DELAY * 2u32.pow(attempt.min(6));
+                    tracing::debug!(
+                        "Solana RPC call failed ({}), retrying in {:?}: {}",
+                        context,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(AppError::Solana(format!("{}: {}", context, e))),
+            }
         }
     }
 
+    /// `getTransaction` rejects `processed`, so transaction lookups use at least `confirmed`
+    fn transaction_config(&self, encoding: UiTransactionEncoding) -> RpcTransactionConfig {
+        let commitment = if self.commitment == CommitmentConfig::processed() {
+            CommitmentConfig::confirmed()
+        } else {
+            self.commitment
+        };
+
+        RpcTransactionConfig {
+            encoding: Some(encoding),
+            commitment: Some(commitment),
+            max_supported_transaction_version: Some(0),
+        }
+    }
+
+    /// SPL mint accepted for USDC payments
+    pub fn usdc_mint(&self) -> &str {
+        &self.usdc_mint
+    }
+
     /// Get the current slot (similar to block number)
-    pub fn get_slot(&self) -> AppResult<u64> {
-        self.client
-            .get_slot()
-            .map_err(|e| AppError::Solana(format!("Failed to get slot: {}", e)))
+    pub async fn get_slot(&self) -> AppResult<u64> {
+        self.retry("Failed to get slot", || self.client.get_slot()).await
     }
 
     /// Get balance of an address in lamports (1 SOL = 1_000_000_000 lamports)
-    pub fn get_balance(&self, address: &str) -> AppResult<u64> {
+    pub async fn get_balance(&self, address: &str) -> AppResult<u64> {
         let pubkey = Pubkey::from_str(address)
             .map_err(|e| AppError::InvalidAddress(format!("Invalid Solana address: {}", e)))?;
 
-        self.client
-            .get_balance(&pubkey)
-            .map_err(|e| AppError::Solana(format!("Failed to get balance: {}", e)))
+        self.retry("Failed to get balance", || self.client.get_balance(&pubkey)).await
     }
 
     /// Get balance formatted as SOL string
-    pub fn get_balance_sol(&self, address: &str) -> AppResult<String> {
-        let lamports = self.get_balance(address)?;
+    pub async fn get_balance_sol(&self, address: &str) -> AppResult<String> {
+        let lamports = self.get_balance(address).await?;
         Ok(Self::lamports_to_sol(lamports))
     }
 
     /// Get transaction status
-    pub fn get_transaction_status(&self, signature: &str) -> AppResult<Option<SolanaTransactionStatus>> {
+    pub async fn get_transaction_status(&self, signature: &str) -> AppResult<Option<SolanaTransactionStatus>> {
         let sig = Signature::from_str(signature)
             .map_err(|e| AppError::Solana(format!("Invalid signature: {}", e)))?;
 
         let status = self
-            .client
-            .get_signature_status(&sig)
-            .map_err(|e| AppError::Solana(format!("Failed to get transaction status: {}", e)))?;
+            .retry("Failed to get transaction status", || {
+                self.client.get_signature_status(&sig)
+            })
+            .await?;
 
         match status {
             Some(result) => {
@@ -78,16 +144,19 @@ impl SolanaService {
     }
 
     /// Get transaction details
-    pub fn get_transaction(
+    pub async fn get_transaction(
         &self,
         signature: &str,
     ) -> AppResult<Option<SolanaTransactionInfo>> {
         let sig = Signature::from_str(signature)
             .map_err(|e| AppError::Solana(format!("Invalid signature: {}", e)))?;
 
+        let config = self.transaction_config(UiTransactionEncoding::Json);
         let tx = self
-            .client
-            .get_transaction(&sig, solana_transaction_status::UiTransactionEncoding::Json)
+            .retry("Failed to get transaction", || {
+                self.client.get_transaction_with_config(&sig, config)
+            })
+            .await
             .ok();
 
         match tx {
@@ -109,52 +178,122 @@ impl SolanaService {
         }
     }
 
-    /// Get number of confirmations for a transaction
-    pub fn get_confirmations(&self, signature: &str) -> AppResult<u64> {
+    /// Get number of confirmations for a transaction, 0 until it reaches the
+    /// configured commitment level. Searches the ledger history, since the node's
+    /// status cache only covers recent slots and forgets finalized signatures.
+    pub async fn get_confirmations(&self, signature: &str) -> AppResult<u64> {
         let sig = Signature::from_str(signature)
             .map_err(|e| AppError::Solana(format!("Invalid signature: {}", e)))?;
 
+        let sigs = [sig];
         let statuses = self
-            .client
-            .get_signature_statuses(&[sig])
-            .map_err(|e| AppError::Solana(format!("Failed to get signature status: {}", e)))?;
-
-        if let Some(Some(status)) = statuses.value.first() {
-            Ok(status.confirmations.unwrap_or(0) as u64)
-        } else {
-            Ok(0)
+            .retry("Failed to get signature status", || {
+                self.client.get_signature_statuses_with_history(&sigs)
+            })
+            .await?;
+
+        match statuses.value.first() {
+            // `confirmations` is None once the block is rooted (finalized)
+            Some(Some(status)) if status.satisfies_commitment(self.commitment) => Ok(status
+                .confirmations
+                .map(|c| c as u64)
+                .unwrap_or(FINALIZED_CONFIRMATIONS)),
+            _ => Ok(0),
         }
     }
 
-    /// Verify a payment transaction
-    pub fn verify_payment(
+    /// Get the slot a transaction landed in, searching the ledger history once it has
+    /// left the status cache. `None` means the node has no record of it, which a node
+    /// without full history also reports for old transactions.
+    pub async fn get_signature_slot(&self, signature: &str) -> AppResult<Option<u64>> {
+        let sig = Signature::from_str(signature)
+            .map_err(|e| AppError::Solana(format!("Invalid signature: {}", e)))?;
+
+        let sigs = [sig];
+        let statuses = self
+            .retry("Failed to get signature status", || {
+                self.client.get_signature_statuses_with_history(&sigs)
+            })
+            .await?;
+
+        Ok(statuses
+            .value
+            .into_iter()
+            .next()
+            .flatten()
+            .map(|status| status.slot))
+    }
+
+    /// Verify a native SOL payment by parsing system `transfer` instructions
+    pub async fn verify_payment(
         &self,
         signature: &str,
         expected_to: &str,
         expected_amount_lamports: u64,
     ) -> AppResult<SolanaPaymentVerification> {
+        let (slot, tx) = self.get_parsed_transaction(signature).await?;
+        let transfers = sol_transfers_to(&tx, expected_to);
+
+        self.build_verification(signature, slot, &tx, transfers, expected_amount_lamports)
+            .await
+    }
+
+    /// Verify an SPL token payment (e.g. USDC) to the token accounts owned by `expected_to`
+    pub async fn verify_token_payment(
+        &self,
+        signature: &str,
+        expected_to: &str,
+        mint: &str,
+        expected_amount: u64,
+    ) -> AppResult<SolanaPaymentVerification> {
+        let (slot, tx) = self.get_parsed_transaction(signature).await?;
+        let transfers = token_transfers_to(&tx, expected_to, mint);
+
+        self.build_verification(signature, slot, &tx, transfers, expected_amount)
+            .await
+    }
+
+    async fn get_parsed_transaction(&self, signature: &str) -> AppResult<(u64, Value)> {
         let sig = Signature::from_str(signature)
             .map_err(|e| AppError::Solana(format!("Invalid signature: {}", e)))?;
 
+        let config = self.transaction_config(UiTransactionEncoding::JsonParsed);
         let tx = self
-            .client
-            .get_transaction(&sig, solana_transaction_status::UiTransactionEncoding::JsonParsed)
-            .map_err(|e| AppError::Solana(format!("Transaction not found: {}", e)))?;
+            .retry("Transaction not found", || {
+                self.client.get_transaction_with_config(&sig, config)
+            })
+            .await?;
+
+        // Parsed instructions are untyped JSON, so walk the whole transaction as JSON
+        let value = serde_json::to_value(&tx.transaction)
+            .map_err(|e| AppError::Solana(format!("Failed to read transaction: {}", e)))?;
+
+        Ok((tx.slot, value))
+    }
 
-        let is_successful = tx.transaction.meta
-            .as_ref()
-            .map(|m| m.err.is_none())
-            .unwrap_or(false);
+    async fn build_verification(
+        &self,
+        signature: &str,
+        slot: u64,
+        tx: &Value,
+        transfers: TransferSummary,
+        expected_amount: u64,
+    ) -> AppResult<SolanaPaymentVerification> {
+        let is_successful = tx["meta"].is_object() && tx["meta"]["err"].is_null();
+        let to_matches = transfers.amount > 0;
+        let amount_matches = transfers.amount >= expected_amount;
 
-        let confirmations = self.get_confirmations(signature)?;
+        let confirmations = self.get_confirmations(signature).await?;
 
-        // For full payment verification, you'd parse the transaction instructions
-        // This is a simplified version
         Ok(SolanaPaymentVerification {
-            is_valid: is_successful,
+            is_valid: is_successful && to_matches && amount_matches,
             is_successful,
+            to_matches,
+            amount_matches,
             confirmations,
-            slot: tx.slot,
+            from_address: transfers.from,
+            actual_amount: transfers.amount,
+            slot,
         })
     }
 
@@ -202,6 +341,19 @@ impl SolanaService {
         Ok(verifying_key.verify(message, &sig).is_ok())
     }
 
+    /// Get required confirmations for a USDC amount in base units (6 decimals)
+    pub fn get_required_token_confirmations(amount: u64) -> i32 {
+        let usd = amount as f64 / 1_000_000.0;
+
+        if usd > 10_000.0 {
+            32
+        } else if usd > 1_000.0 {
+            16
+        } else {
+            1
+        }
+    }
+
     /// Get required confirmations based on amount
     pub fn get_required_confirmations(amount_lamports: u64) -> i32 {
         let sol = amount_lamports as f64 / 1_000_000_000.0;
@@ -234,14 +386,172 @@ pub struct SolanaTransactionInfo {
 pub struct SolanaPaymentVerification {
     pub is_valid: bool,
     pub is_successful: bool,
+    pub to_matches: bool,
+    pub amount_matches: bool,
     pub confirmations: u64,
+    pub from_address: Option<String>,
+    /// Lamports for SOL payments, token base units for SPL payments
+    pub actual_amount: u64,
     pub slot: u64,
 }
 
+/// Map a configured commitment level onto the RPC commitment, `confirmed` by default
+fn commitment_config(level: &str) -> CommitmentConfig {
+    match level {
+        "processed" => CommitmentConfig::processed(),
+        "finalized" => CommitmentConfig::finalized(),
+        _ => CommitmentConfig::confirmed(),
+    }
+}
+
+/// Transport failures worth retrying; errors answered by the node are final
+fn is_transient(error: &ClientError) -> bool {
+    matches!(error.kind(), ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct TransferSummary {
+    amount: u64,
+    from: Option<String>,
+}
+
+/// All parsed instructions in a JsonParsed transaction, including inner (CPI) instructions
+fn parsed_instructions(tx: &Value) -> impl Iterator<Item = &Value> {
+    let outer = tx["transaction"]["message"]["instructions"]
+        .as_array()
+        .into_iter()
+        .flatten();
+
+    let inner = tx["meta"]["innerInstructions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group["instructions"].as_array())
+        .flatten();
+
+    outer.chain(inner).filter(|ix| ix["parsed"].is_object())
+}
+
+/// Sum system program transfers paying `to`, in lamports
+fn sol_transfers_to(tx: &Value, to: &str) -> TransferSummary {
+    let mut summary = TransferSummary::default();
+
+    for ix in parsed_instructions(tx) {
+        let info = &ix["parsed"]["info"];
+
+        if ix["program"] != "system"
+            || !matches!(ix["parsed"]["type"].as_str(), Some("transfer" | "transferWithSeed"))
+            || info["destination"] != to
+        {
+            continue;
+        }
+
+        if let Some(lamports) = info["lamports"].as_u64() {
+            summary.amount = summary.amount.saturating_add(lamports);
+            if summary.from.is_none() {
+                summary.from = info["source"].as_str().map(String::from);
+            }
+        }
+    }
+
+    summary
+}
+
+/// Sum spl-token `transfer`/`transferChecked` instructions of `mint` into token
+/// accounts owned by `owner`, capped by the owner's pre/post balance change
+fn token_transfers_to(tx: &Value, owner: &str, mint: &str) -> TransferSummary {
+    let account_keys: Vec<&str> = tx["transaction"]["message"]["accountKeys"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|key| key["pubkey"].as_str().or_else(|| key.as_str()).unwrap_or_default())
+        .collect();
+
+    // Token account -> (mint, owner, pre amount, post amount)
+    let mut token_accounts: HashMap<&str, (&str, &str, u64, u64)> = HashMap::new();
+    for (field, is_post) in [("preTokenBalances", false), ("postTokenBalances", true)] {
+        for balance in tx["meta"][field].as_array().into_iter().flatten() {
+            let Some(account) = balance["accountIndex"]
+                .as_u64()
+                .and_then(|i| account_keys.get(i as usize))
+            else {
+                continue;
+            };
+
+            let amount = balance["uiTokenAmount"]["amount"]
+                .as_str()
+                .and_then(|a| a.parse().ok())
+                .unwrap_or(0);
+
+            let entry = token_accounts.entry(account).or_insert((
+                balance["mint"].as_str().unwrap_or_default(),
+                balance["owner"].as_str().unwrap_or_default(),
+                0,
+                0,
+            ));
+
+            if is_post {
+                entry.3 = amount;
+            } else {
+                entry.2 = amount;
+            }
+        }
+    }
+
+    let is_owned_account = |account: &str| {
+        token_accounts
+            .get(account)
+            .map(|(m, o, _, _)| *m == mint && *o == owner)
+            .unwrap_or(false)
+    };
+
+    let mut summary = TransferSummary::default();
+
+    for ix in parsed_instructions(tx) {
+        let info = &ix["parsed"]["info"];
+        let program = ix["program"].as_str().unwrap_or_default();
+
+        if program != "spl-token" && program != "spl-token-2022" {
+            continue;
+        }
+
+        let amount = match ix["parsed"]["type"].as_str() {
+            Some("transfer") => info["amount"].as_str(),
+            Some("transferChecked") if info["mint"] == mint => {
+                info["tokenAmount"]["amount"].as_str()
+            }
+            _ => None,
+        }
+        .and_then(|a| a.parse::<u64>().ok());
+
+        let destination = info["destination"].as_str().unwrap_or_default();
+
+        if let Some(amount) = amount.filter(|_| is_owned_account(destination)) {
+            summary.amount = summary.amount.saturating_add(amount);
+            if summary.from.is_none() {
+                summary.from = info["authority"]
+                    .as_str()
+                    .or_else(|| info["multisigAuthority"].as_str())
+                    .map(String::from);
+            }
+        }
+    }
+
+    // Never credit more than the owner's token accounts actually gained
+    let received: u64 = token_accounts
+        .iter()
+        .filter(|(account, _)| is_owned_account(account))
+        .map(|(_, (_, _, pre, post))| post.saturating_sub(*pre))
+        .sum();
+
+    summary.amount = summary.amount.min(received);
+    summary
+}
+
 /// SPL Token interactions
 impl SolanaService {
     /// Get SPL token balance for an address
-    pub fn get_token_balance(
+    pub async fn get_token_balance(
         &self,
         token_account: &str,
     ) -> AppResult<u64> {
@@ -249,9 +559,10 @@ impl SolanaService {
             .map_err(|e| AppError::InvalidAddress(format!("Invalid token account: {}", e)))?;
 
         let balance = self
-            .client
-            .get_token_account_balance(&pubkey)
-            .map_err(|e| AppError::Solana(format!("Failed to get token balance: {}", e)))?;
+            .retry("Failed to get token balance", || {
+                self.client.get_token_account_balance(&pubkey)
+            })
+            .await?;
 
         balance
             .amount
@@ -259,3 +570,200 @@ impl SolanaService {
             .map_err(|e| AppError::Solana(format!("Failed to parse token balance: {}", e)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PAYER: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const DEPOSIT: &str = "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qFJbbdyHa5AWVCyQ4K7ZUMU6pKmzS";
+    const PAYER_ATA: &str = "3emsAVdmGKERbHjmGfQ6oZ1e35dkf5iYcS6U4CPKFVaa";
+    const DEPOSIT_ATA: &str = "7UX2i7SucgLMQcfZ75s3VXmZZY4YRUyJN9X1RgfMoDUi";
+
+    fn system_transfer(destination: &str, lamports: u64) -> Value {
+        json!({
+            "program": "system",
+            "programId": "11111111111111111111111111111111",
+            "parsed": {
+                "type": "transfer",
+                "info": { "source": PAYER, "destination": destination, "lamports": lamports }
+            }
+        })
+    }
+
+    fn token_tx(instruction: Value, pre_deposit: &str, post_deposit: &str) -> Value {
+        json!({
+            "transaction": {
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": PAYER, "signer": true, "writable": true },
+                        { "pubkey": PAYER_ATA, "signer": false, "writable": true },
+                        { "pubkey": DEPOSIT_ATA, "signer": false, "writable": true }
+                    ],
+                    "instructions": [instruction]
+                }
+            },
+            "meta": {
+                "err": null,
+                "innerInstructions": [],
+                "preTokenBalances": [
+                    { "accountIndex": 1, "mint": USDC, "owner": PAYER, "uiTokenAmount": { "amount": "90000000" } },
+                    { "accountIndex": 2, "mint": USDC, "owner": DEPOSIT, "uiTokenAmount": { "amount": pre_deposit } }
+                ],
+                "postTokenBalances": [
+                    { "accountIndex": 1, "mint": USDC, "owner": PAYER, "uiTokenAmount": { "amount": "65000000" } },
+                    { "accountIndex": 2, "mint": USDC, "owner": DEPOSIT, "uiTokenAmount": { "amount": post_deposit } }
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_sol_transfers_include_inner_instructions() {
+        let tx = json!({
+            "transaction": {
+                "message": {
+                    "instructions": [
+                        system_transfer(DEPOSIT, 400_000_000),
+                        system_transfer(PAYER_ATA, 900_000_000)
+                    ]
+                }
+            },
+            "meta": {
+                "err": null,
+                "innerInstructions": [
+                    { "index": 0, "instructions": [system_transfer(DEPOSIT, 100_000_000)] }
+                ]
+            }
+        });
+
+        let summary = sol_transfers_to(&tx, DEPOSIT);
+        assert_eq!(summary.amount, 500_000_000);
+        assert_eq!(summary.from.as_deref(), Some(PAYER));
+
+        assert_eq!(sol_transfers_to(&tx, DEPOSIT_ATA), TransferSummary::default());
+    }
+
+    #[test]
+    fn test_token_transfer_checked_to_owner() {
+        let tx = token_tx(
+            json!({
+                "program": "spl-token",
+                "parsed": {
+                    "type": "transferChecked",
+                    "info": {
+                        "source": PAYER_ATA,
+                        "destination": DEPOSIT_ATA,
+                        "mint": USDC,
+                        "authority": PAYER,
+                        "tokenAmount": { "amount": "25000000", "decimals": 6 }
+                    }
+                }
+            }),
+            "0",
+            "25000000",
+        );
+
+        let summary = token_transfers_to(&tx, DEPOSIT, USDC);
+        assert_eq!(summary.amount, 25_000_000);
+        assert_eq!(summary.from.as_deref(), Some(PAYER));
+
+        // Same transfer does not count towards another mint or owner
+        assert_eq!(token_transfers_to(&tx, PAYER, USDC).amount, 0);
+        assert_eq!(token_transfers_to(&tx, DEPOSIT, PAYER_ATA).amount, 0);
+    }
+
+    #[test]
+    fn test_token_transfer_capped_by_balance_change() {
+        let tx = token_tx(
+            json!({
+                "program": "spl-token",
+                "parsed": {
+                    "type": "transfer",
+                    "info": {
+                        "source": PAYER_ATA,
+                        "destination": DEPOSIT_ATA,
+                        "authority": PAYER,
+                        "amount": "25000000"
+                    }
+                }
+            }),
+            "5000000",
+            "15000000",
+        );
+
+        let summary = token_transfers_to(&tx, DEPOSIT, USDC);
+        assert_eq!(summary.amount, 10_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_signature_slot_after_status_cache() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        const SIGNATURE: &str =
+            "99eUso3aSbE9tqGSTXzo3TLfKb9RkMTURrHKQ1K7Zh3BbeqPevr5E1iCbpTjqHuTFLtfxTTD5ekfVuZFzQyEQf8";
+
+        let server = MockServer::start().await;
+
+        // Finalized long ago: only found when the ledger history is searched
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({
+                "method": "getSignatureStatuses",
+                "params": [[SIGNATURE], { "searchTransactionHistory": true }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "context": { "slot": 500_000 },
+                    "value": [{
+                        "slot": 1_000,
+                        "confirmations": null,
+                        "err": null,
+                        "status": { "Ok": null },
+                        "confirmationStatus": "finalized"
+                    }]
+                }
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "method": "getSignatureStatuses" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "context": { "slot": 500_000 }, "value": [null] }
+            })))
+            .mount(&server)
+            .await;
+
+        let service = SolanaService::new(&SolanaConfig {
+            rpc_url: server.uri(),
+            commitment: "confirmed".to_string(),
+            timeout_secs: 5,
+            max_retries: 0,
+            usdc_mint: USDC.to_string(),
+        });
+
+        assert_eq!(
+            service.get_signature_slot(SIGNATURE).await.unwrap(),
+            Some(1_000)
+        );
+        assert_eq!(
+            service.get_confirmations(SIGNATURE).await.unwrap(),
+            FINALIZED_CONFIRMATIONS
+        );
+    }
+
+    #[test]
+    fn test_commitment_config() {
+        assert_eq!(commitment_config("processed"), CommitmentConfig::processed());
+        assert_eq!(commitment_config("finalized"), CommitmentConfig::finalized());
+        assert_eq!(commitment_config("confirmed"), CommitmentConfig::confirmed());
+    }
+}
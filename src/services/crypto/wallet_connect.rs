@@ -14,7 +14,7 @@ impl WalletConnectVerifier {
         chain: &ChainType,
     ) -> AppResult<bool> {
         match chain {
-            ChainType::Ethereum | ChainType::Polygon | ChainType::Bsc | ChainType::Arbitrum => {
+            ChainType::Evm(_) => {
                 Self::verify_evm_signature(address, message, signature)
             }
             ChainType::Solana => Self::verify_solana_signature(address, message, signature),
@@ -102,7 +102,7 @@ impl WalletConnectVerifier {
     /// Validate address format for a given chain
     pub fn validate_address(address: &str, chain: &ChainType) -> bool {
         match chain {
-            ChainType::Ethereum | ChainType::Polygon | ChainType::Bsc | ChainType::Arbitrum => {
+            ChainType::Evm(_) => {
                 Self::is_valid_evm_address(address)
             }
             ChainType::Solana => Self::is_valid_solana_address(address),
@@ -146,11 +146,11 @@ mod tests {
     fn test_validate_evm_address() {
         assert!(WalletConnectVerifier::validate_address(
             "0x742d35Cc6634C0532925a3b844Bc9e7595f1E8e4",
-            &ChainType::Ethereum
+            &ChainType::evm("ethereum")
         ));
         assert!(!WalletConnectVerifier::validate_address(
             "invalid_address",
-            &ChainType::Ethereum
+            &ChainType::evm("ethereum")
         ));
     }
 
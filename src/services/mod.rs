@@ -1,7 +1,8 @@
 pub mod razorpay;
 pub mod crypto;
 pub mod payment_processor;
+pub mod monitor;
 
 pub use razorpay::RazorpayService;
-pub use crypto::{EthereumService, SolanaService, LightningService};
+pub use crypto::{BitcoinService, EthereumService, HdWallet, SolanaService, LightningService};
 pub use payment_processor::PaymentProcessor;
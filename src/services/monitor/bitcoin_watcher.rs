@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use super::deposits::{record_deposit, Deposit};
+use crate::db::repositories::AddressRepository;
+use crate::error::AppResult;
+use crate::models::{ChainType, CryptoAddress};
+use crate::services::crypto::bitcoin::EsploraTransaction;
+use crate::services::BitcoinService;
+use crate::AppState;
+
+/// Polls Esplora for transactions paying active Bitcoin deposit addresses and credits
+/// them once mined. Later confirmations are followed by the confirmation tracker.
+pub struct BitcoinWatcher {
+    state: AppState,
+}
+
+impl BitcoinWatcher {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn run(self, poll_interval: Duration) {
+        tracing::info!("Bitcoin deposit watcher started");
+
+        let mut ticker = tokio::time::interval(poll_interval);
+        loop {
+            ticker.tick().await;
+
+            match self.scan_once().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!(deposits = count, "Bitcoin deposits detected"),
+                Err(e) => tracing::warn!("Bitcoin deposit scan failed: {}", e),
+            }
+        }
+    }
+
+    /// Check every active deposit address once, returning the number of new deposits
+    pub async fn scan_once(&self) -> AppResult<usize> {
+        let Some(service) = self.state.payment_processor.bitcoin() else {
+            return Ok(0);
+        };
+
+        let addresses =
+            AddressRepository::find_active_for_monitoring(&self.state.db, ChainType::Bitcoin)
+                .await?;
+        if addresses.is_empty() {
+            return Ok(0);
+        }
+
+        let tip = service.get_block_height().await?;
+        let mut recorded = 0;
+
+        for address in &addresses {
+            // One failing address must not hold up the others
+            match self.check_address(service, address, tip).await {
+                Ok(count) => recorded += count,
+                Err(e) => tracing::warn!(
+                    address = %address.address,
+                    "Failed to check Bitcoin deposit address: {}",
+                    e
+                ),
+            }
+        }
+
+        Ok(recorded)
+    }
+
+    async fn check_address(
+        &self,
+        service: &BitcoinService,
+        address: &CryptoAddress,
+        tip: u64,
+    ) -> AppResult<usize> {
+        let txs = service.find_deposits(&address.address).await?;
+        let mut recorded = 0;
+
+        for deposit in match_deposits(&txs, address) {
+            let confirmations = BitcoinService::confirmations_at(tip, deposit.block_number);
+            if record_deposit(&self.state, "bitcoin", &deposit, confirmations).await? {
+                recorded += 1;
+            }
+        }
+
+        Ok(recorded)
+    }
+}
+
+/// Mined transactions paying `address`, oldest first so deposits are credited
+/// in the order they were made
+pub fn match_deposits(txs: &[EsploraTransaction], address: &CryptoAddress) -> Vec<Deposit> {
+    let mut deposits: Vec<Deposit> = txs
+        .iter()
+        .filter(|tx| tx.status.confirmed)
+        .filter_map(|tx| {
+            let amount = tx.amount_to(&address.address);
+            if amount == 0 {
+                return None;
+            }
+
+            Some(Deposit {
+                address: address.clone(),
+                tx_hash: tx.txid.clone(),
+                from_address: tx.input_addresses().into_iter().next().unwrap_or_default(),
+                amount: amount.min(i64::MAX as u64) as i64,
+                block_number: tx.status.block_height?,
+                block_hash: tx.status.block_hash.clone(),
+            })
+        })
+        .collect();
+
+    deposits.sort_by_key(|deposit| deposit.block_number);
+    deposits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    const DEPOSIT: &str = "bcrt1q6rz28mcfaxtmd6v789l9rrlrusdprr9pz3cppk";
+
+    fn deposit_address() -> CryptoAddress {
+        CryptoAddress {
+            id: Uuid::new_v4(),
+            payment_id: Some(Uuid::new_v4()),
+            address: DEPOSIT.to_string(),
+            chain: ChainType::Bitcoin,
+            is_active: true,
+            label: None,
+            expected_amount: Some(50_000),
+            received_amount: None,
+            token_address: None,
+            derivation_path: None,
+            derivation_index: None,
+            last_checked_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn esplora_tx(value: serde_json::Value) -> EsploraTransaction {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_match_deposits() {
+        let later = esplora_tx(serde_json::json!({
+            "txid": "bb".repeat(32),
+            "status": { "confirmed": true, "block_height": 105, "block_hash": "00bb" },
+            "vout": [{ "scriptpubkey_address": DEPOSIT, "value": 10000 }]
+        }));
+        let earlier = esplora_tx(serde_json::json!({
+            "txid": "aa".repeat(32),
+            "status": { "confirmed": true, "block_height": 100, "block_hash": "00aa" },
+            "vin": [{ "prevout": { "scriptpubkey_address": "bcrt1qsender", "value": 90000 } }],
+            "vout": [
+                { "scriptpubkey_address": DEPOSIT, "value": 30000 },
+                { "scriptpubkey_address": DEPOSIT, "value": 10000 },
+                { "scriptpubkey_address": "bcrt1qchange", "value": 49000 }
+            ]
+        }));
+        let unconfirmed = esplora_tx(serde_json::json!({
+            "txid": "cc".repeat(32),
+            "status": { "confirmed": false },
+            "vout": [{ "scriptpubkey_address": DEPOSIT, "value": 70000 }]
+        }));
+
+        let deposits = match_deposits(&[later, earlier, unconfirmed], &deposit_address());
+
+        assert_eq!(deposits.len(), 2);
+        assert_eq!(deposits[0].tx_hash, "aa".repeat(32));
+        assert_eq!(deposits[0].amount, 40_000);
+        assert_eq!(deposits[0].from_address, "bcrt1qsender");
+        assert_eq!(deposits[0].block_hash.as_deref(), Some("00aa"));
+        assert_eq!(deposits[1].block_number, 105);
+    }
+
+    #[test]
+    fn test_confirmations_at() {
+        assert_eq!(BitcoinService::confirmations_at(100, 100), 1);
+        assert_eq!(BitcoinService::confirmations_at(105, 100), 6);
+    }
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use crate::db::repositories::{PaymentRepository, TransactionRepository};
+use crate::error::AppResult;
+use crate::models::{ChainType, PaymentStatus, Transaction, TransactionStatus};
+use crate::AppState;
+
+/// Re-checks confirmations of on-chain transactions until they reach
+/// `required_confirmations`, then completes their payment
+pub struct ConfirmationTracker {
+    state: AppState,
+}
+
+impl ConfirmationTracker {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn run(self, poll_interval: Duration) {
+        tracing::info!("Confirmation tracker started");
+
+        let mut ticker = tokio::time::interval(poll_interval);
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = self.check_once().await {
+                tracing::warn!("Confirmation check failed: {}", e);
+            }
+        }
+    }
+
+    /// Refresh every transaction awaiting confirmations, returning how many were confirmed
+    pub async fn check_once(&self) -> AppResult<usize> {
+        let pending = TransactionRepository::find_pending_confirmations(&self.state.db).await?;
+        let mut confirmed = 0;
+
+        for tx in &pending {
+            // One unreachable chain must not hold up the others
+            match self.check_transaction(tx).await {
+                Ok(true) => confirmed += 1,
+                Ok(false) => {}
+                Err(e) => tracing::warn!(
+                    transaction_id = %tx.id,
+                    chain = ?tx.chain,
+                    "Failed to refresh confirmations: {}",
+                    e
+                ),
+            }
+        }
+
+        Ok(confirmed)
+    }
+
+    async fn check_transaction(&self, tx: &Transaction) -> AppResult<bool> {
+        let (Some(chain), Some(tx_hash)) = (tx.chain.as_deref(), tx.tx_hash.as_deref()) else {
+            return Ok(false);
+        };
+
+        let Ok(chain) = chain.parse::<ChainType>() else {
+            return Ok(false);
+        };
+
+        let confirmations = self
+            .state
+            .payment_processor
+            .get_confirmations(&chain, tx_hash)
+            .await?;
+
+        let Some((confirmations, status)) = next_confirmations(tx, confirmations) else {
+            return Ok(false);
+        };
+        let is_confirmed = status == TransactionStatus::Confirmed;
+
+        TransactionRepository::update_confirmations(
+            &self.state.db,
+            tx.id,
+            confirmations.min(i32::MAX as u64) as i32,
+            status,
+        )
+        .await?;
+
+        let mut payment = PaymentRepository::find_by_id(&self.state.db, tx.payment_id).await?;
+
+        if is_confirmed && payment.status == PaymentStatus::Processing {
+            payment = PaymentRepository::update_status(
+                &self.state.db,
+                payment.id,
+                PaymentStatus::Completed,
+            )
+            .await?;
+
+            tracing::info!(
+                payment_id = %payment.id,
+                tx_hash = %tx_hash,
+                confirmations,
+                "Payment completed"
+            );
+        }
+
+        if let Some(ref broadcaster) = self.state.ws_broadcaster {
+            let _ = broadcaster
+                .broadcast_confirmations(&payment, confirmations)
+                .await;
+        }
+
+        Ok(is_confirmed)
+    }
+}
+
+/// Confirmations and status to record for `tx` after its chain reported `observed`
+/// confirmations, or `None` when nothing changes. A lower count than the one stored
+/// is ignored: nodes behind a load balancer can lag each other, and a transaction
+/// that really left the chain is handled by the reorg detector.
+pub fn next_confirmations(tx: &Transaction, observed: u64) -> Option<(u64, TransactionStatus)> {
+    if observed <= tx.confirmations.max(0) as u64 {
+        return None;
+    }
+
+    let status = if observed >= tx.required_confirmations.max(0) as u64 {
+        TransactionStatus::Confirmed
+    } else {
+        TransactionStatus::Confirming
+    };
+
+    Some((observed, status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::models::TransactionType;
+
+    fn confirming(confirmations: i32, required_confirmations: i32) -> Transaction {
+        Transaction {
+            id: Uuid::new_v4(),
+            payment_id: Uuid::new_v4(),
+            tx_type: TransactionType::Payment,
+            status: TransactionStatus::Confirming,
+            amount: 1_000,
+            fee: None,
+            currency: "BTC".to_string(),
+            tx_hash: Some("ab".repeat(32)),
+            block_number: Some(100),
+            block_hash: None,
+            confirmations,
+            required_confirmations,
+            from_address: None,
+            to_address: None,
+            chain: Some("bitcoin".to_string()),
+            raw_data: None,
+            error_message: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_confirmations_progress() {
+        let tx = confirming(1, 3);
+
+        assert_eq!(
+            next_confirmations(&tx, 2),
+            Some((2, TransactionStatus::Confirming))
+        );
+        assert_eq!(
+            next_confirmations(&tx, 3),
+            Some((3, TransactionStatus::Confirmed))
+        );
+        assert_eq!(
+            next_confirmations(&tx, 10),
+            Some((10, TransactionStatus::Confirmed))
+        );
+    }
+
+    #[test]
+    fn test_confirmations_never_go_down() {
+        let tx = confirming(2, 3);
+
+        assert_eq!(next_confirmations(&tx, 2), None);
+        // A lagging node, or a status no longer in the node's cache
+        assert_eq!(next_confirmations(&tx, 1), None);
+        assert_eq!(next_confirmations(&tx, 0), None);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::db::repositories::{AddressRepository, PaymentRepository, TransactionRepository};
+use crate::error::AppResult;
+use crate::models::{
+    CryptoAddress, DepositUpdate, PaymentStatus, TransactionStatus, TransactionType,
+};
+use crate::AppState;
+
+/// A transfer into a monitored address found while scanning a chain
+#[derive(Debug, Clone)]
+pub struct Deposit {
+    pub address: CryptoAddress,
+    pub tx_hash: String,
+    pub from_address: String,
+    /// Wei for native EVM transfers, micro-units for stablecoins, satoshis for Bitcoin
+    pub amount: i64,
+    pub block_number: u64,
+    pub block_hash: Option<String>,
+}
+
+/// Credit a deposit to its address and record it on the payment the address belongs to,
+/// completing the payment once the address has received what it expects. A deposit
+/// already recorded for the same transaction and address is skipped, returning `false`.
+pub async fn record_deposit(
+    state: &AppState,
+    chain: &str,
+    deposit: &Deposit,
+    confirmations: u64,
+) -> AppResult<bool> {
+    let pool = &state.db;
+
+    let Some(payment_id) = deposit.address.payment_id else {
+        AddressRepository::add_received_amount(pool, deposit.address.id, deposit.amount).await?;
+        return Ok(true);
+    };
+
+    let payment = PaymentRepository::find_by_id(pool, payment_id).await?;
+
+    let required_confirmations = TransactionRepository::find_by_payment_id(pool, payment_id)
+        .await?
+        .iter()
+        .find(|tx| tx.tx_type == TransactionType::Payment)
+        .map(|tx| tx.required_confirmations)
+        .unwrap_or(1);
+
+    let is_confirmed = confirmations >= required_confirmations.max(0) as u64;
+    let tx_status = if is_confirmed {
+        TransactionStatus::Confirmed
+    } else {
+        TransactionStatus::Confirming
+    };
+
+    // Already credited, e.g. by a previous scan that failed before checkpointing or by
+    // a client-side verification of the same transaction
+    let Some((_, address)) = TransactionRepository::record_deposit(
+        pool,
+        payment_id,
+        &payment.currency.to_string(),
+        chain,
+        deposit.address.id,
+        required_confirmations,
+        &DepositUpdate {
+            tx_hash: deposit.tx_hash.clone(),
+            amount: deposit.amount,
+            block_number: Some(deposit.block_number as i64),
+            block_hash: deposit.block_hash.clone(),
+            from_address: deposit.from_address.clone(),
+            to_address: deposit.address.address.clone(),
+            confirmations: confirmations as i32,
+            status: tx_status,
+        },
+    )
+    .await?
+    else {
+        return Ok(false);
+    };
+    let received = address.received_amount.unwrap_or(0);
+
+    let expected = deposit.address.expected_amount.unwrap_or(payment.amount);
+    let payment = if payment.status == PaymentStatus::Pending && received >= expected {
+        PaymentRepository::update_crypto_details(
+            pool,
+            payment_id,
+            Some(&deposit.tx_hash),
+            Some(&deposit.from_address),
+            None,
+            None,
+        )
+        .await?;
+
+        // Fully paid, nothing more to watch for on this address
+        AddressRepository::deactivate(pool, deposit.address.id).await?;
+
+        let new_status = if is_confirmed {
+            PaymentStatus::Completed
+        } else {
+            PaymentStatus::Processing
+        };
+        PaymentRepository::update_status(pool, payment_id, new_status).await?
+    } else {
+        payment
+    };
+
+    tracing::info!(
+        payment_id = %payment_id,
+        chain = %chain,
+        tx_hash = %deposit.tx_hash,
+        amount = deposit.amount,
+        received,
+        "Deposit detected"
+    );
+
+    if let Some(ref broadcaster) = state.ws_broadcaster {
+        let _ = broadcaster
+            .broadcast_confirmations(&payment, confirmations)
+            .await;
+    }
+
+    Ok(true)
+}
+
+/// Sum deposits made by the same transaction to the same address, keeping the order
+/// they were found in, so a transaction is credited once with its full amount
+pub fn merge_deposits(deposits: Vec<Deposit>) -> Vec<Deposit> {
+    let mut merged: Vec<Deposit> = Vec::with_capacity(deposits.len());
+
+    for deposit in deposits {
+        match merged
+            .iter_mut()
+            .find(|d| d.tx_hash == deposit.tx_hash && d.address.id == deposit.address.id)
+        {
+            Some(existing) => existing.amount = existing.amount.saturating_add(deposit.amount),
+            None => merged.push(deposit),
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::models::ChainType;
+
+    fn address(address: &str) -> CryptoAddress {
+        CryptoAddress {
+            id: Uuid::new_v4(),
+            payment_id: Some(Uuid::new_v4()),
+            address: address.to_string(),
+            chain: ChainType::evm("ethereum"),
+            is_active: true,
+            label: None,
+            expected_amount: Some(1_000),
+            received_amount: None,
+            token_address: None,
+            derivation_path: None,
+            derivation_index: None,
+            last_checked_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_merge_deposits() {
+        let first = address("0x1111111111111111111111111111111111111111");
+        let second = address("0x2222222222222222222222222222222222222222");
+        let deposit = |address: &CryptoAddress, tx_hash: &str, amount: i64| Deposit {
+            address: address.clone(),
+            tx_hash: tx_hash.to_string(),
+            from_address: "0x3333333333333333333333333333333333333333".to_string(),
+            amount,
+            block_number: 300,
+            block_hash: None,
+        };
+
+        let merged = merge_deposits(vec![
+            deposit(&first, "0xaa", 400),
+            deposit(&second, "0xaa", 250),
+            deposit(&first, "0xaa", 600),
+            deposit(&first, "0xbb", 100),
+        ]);
+
+        let amounts: Vec<_> = merged
+            .iter()
+            .map(|d| (d.address.id, d.tx_hash.as_str(), d.amount))
+            .collect();
+        assert_eq!(
+            amounts,
+            vec![
+                (first.id, "0xaa", 1_000),
+                (second.id, "0xaa", 250),
+                (first.id, "0xbb", 100),
+            ]
+        );
+    }
+}
@@ -0,0 +1,703 @@
+use ethers::providers::{Middleware, Provider, SubscriptionStream, Ws};
+use ethers::types::{Address, Log, Transaction as EvmTransaction, H256, U256};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use super::deposits::{merge_deposits, record_deposit, Deposit};
+use crate::db::repositories::{AddressRepository, ScanCheckpointRepository};
+use crate::error::{AppError, AppResult};
+use crate::models::CryptoAddress;
+use crate::services::EthereumService;
+use crate::AppState;
+
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Follows new blocks on one EVM chain and credits deposits to monitored addresses.
+///
+/// With a WebSocket endpoint configured, scans are driven by `newHeads` and token
+/// deposits are picked up from a `logs` subscription as soon as they are mined.
+/// Without one, or while the socket is down, the chain is polled over HTTP.
+pub struct EvmScanner {
+    service: Arc<EthereumService>,
+    state: AppState,
+    chain: String,
+}
+
+impl EvmScanner {
+    pub fn new(service: Arc<EthereumService>, state: AppState) -> Self {
+        let chain = service.chain_type().to_string();
+        Self {
+            service,
+            state,
+            chain,
+        }
+    }
+
+    pub async fn run(self, poll_interval: Duration) {
+        if self.service.has_ws() {
+            tracing::info!(chain = %self.chain, "EVM deposit scanner started (WebSocket)");
+            self.run_subscribed(poll_interval).await;
+        } else {
+            tracing::info!(chain = %self.chain, "EVM deposit scanner started");
+            self.run_polling(poll_interval).await;
+        }
+    }
+
+    async fn run_polling(&self, poll_interval: Duration) {
+        let mut ticker = tokio::time::interval(poll_interval);
+        loop {
+            ticker.tick().await;
+            self.report(self.scan_once().await);
+        }
+    }
+
+    async fn run_subscribed(&self, poll_interval: Duration) {
+        let mut backoff = INITIAL_RECONNECT_BACKOFF;
+
+        loop {
+            match self.service.connect_ws().await {
+                Ok(Some(provider)) => {
+                    if let Err(e) = self.follow(&provider, &mut backoff).await {
+                        tracing::warn!(chain = %self.chain, "WebSocket subscription dropped: {}", e);
+                    }
+                }
+                Ok(None) => return self.run_polling(poll_interval).await,
+                Err(e) => tracing::warn!(chain = %self.chain, "{}", e),
+            }
+
+            // Keep polling over HTTP until the next reconnect attempt
+            tracing::info!(
+                chain = %self.chain,
+                retry_in_secs = backoff.as_secs(),
+                "Falling back to HTTP polling"
+            );
+            self.poll_until(Instant::now() + backoff, poll_interval).await;
+            backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+        }
+    }
+
+    async fn poll_until(&self, deadline: Instant, poll_interval: Duration) {
+        loop {
+            self.report(self.scan_once().await);
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return;
+            }
+            tokio::time::sleep(poll_interval.min(remaining)).await;
+            if Instant::now() >= deadline {
+                return;
+            }
+        }
+    }
+
+    /// Scan on every new head and record token deposits as their logs arrive.
+    /// Only returns once a subscription fails or closes.
+    async fn follow(&self, provider: &Provider<Ws>, backoff: &mut Duration) -> AppResult<()> {
+        let mut heads = provider
+            .subscribe_blocks()
+            .await
+            .map_err(|e| AppError::Ethereum(format!("Failed to subscribe to new heads: {}", e)))?;
+
+        *backoff = INITIAL_RECONNECT_BACKOFF;
+        tracing::info!(chain = %self.chain, "Subscribed to new heads");
+
+        // Catch up on anything mined while the socket was down
+        self.report(self.scan_once().await);
+
+        let mut watched = self.watched_token_addresses().await?;
+        let mut logs = subscribe_token_logs(provider, &watched).await?;
+        let mut head = 0;
+
+        loop {
+            tokio::select! {
+                block = heads.next() => {
+                    let Some(block) = block else {
+                        return Err(AppError::Ethereum("newHeads subscription closed".to_string()));
+                    };
+                    let Some(number) = block.number else {
+                        continue;
+                    };
+
+                    head = number.as_u64();
+                    // The checkpointed range scan stays the source of truth, so a log
+                    // missed between resubscriptions is still picked up here
+                    self.report(self.scan_to(head).await);
+
+                    // Deposit addresses come and go; resubscribe when the watched set changes.
+                    // A failed read keeps the current subscription until the next head.
+                    let current = match self.watched_token_addresses().await {
+                        Ok(current) => current,
+                        Err(e) => {
+                            tracing::warn!(
+                                chain = %self.chain,
+                                "Failed to load watched token addresses: {}",
+                                e
+                            );
+                            continue;
+                        }
+                    };
+                    if address_ids(&current) != address_ids(&watched) {
+                        logs = subscribe_token_logs(provider, &current).await?;
+                        watched = current;
+                    }
+                }
+                log = next_log(&mut logs) => {
+                    let Some(log) = log else {
+                        return Err(AppError::Ethereum("logs subscription closed".to_string()));
+                    };
+
+                    if let Err(e) = self.record_pushed_log(&log, &watched, head).await {
+                        tracing::warn!(
+                            chain = %self.chain,
+                            tx_hash = ?log.transaction_hash,
+                            "Failed to record pushed deposit: {}",
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record the deposit a pushed `Transfer` log belongs to, returning the number of
+    /// new deposits. Deposits the range scan already credited are skipped.
+    pub async fn record_pushed_log(
+        &self,
+        log: &Log,
+        watched: &[CryptoAddress],
+        head: u64,
+    ) -> AppResult<usize> {
+        let Some(pushed) = match_token_transfers(&self.service, std::slice::from_ref(log), watched)
+            .into_iter()
+            .next()
+        else {
+            return Ok(0);
+        };
+
+        // A transaction can pay one address in several transfers, and each log arrives
+        // on its own. Read back the whole block so the deposit is credited with the same
+        // total the range scan would find.
+        let block = pushed.block_number;
+        let deposits = self.find_token_deposits(watched, block, block).await?;
+        let mut recorded = 0;
+
+        for deposit in deposits.iter().filter(|d| d.tx_hash == pushed.tx_hash) {
+            let confirmations = head.saturating_sub(deposit.block_number);
+            if record_deposit(&self.state, &self.chain, deposit, confirmations).await? {
+                recorded += 1;
+            }
+        }
+
+        Ok(recorded)
+    }
+
+    fn report(&self, result: AppResult<usize>) {
+        match result {
+            Ok(0) => {}
+            Ok(count) => {
+                tracing::info!(chain = %self.chain, deposits = count, "Deposits detected")
+            }
+            Err(e) => tracing::warn!(chain = %self.chain, "Deposit scan failed: {}", e),
+        }
+    }
+
+    async fn watched_token_addresses(&self) -> AppResult<Vec<CryptoAddress>> {
+        let addresses = AddressRepository::find_active_for_monitoring(
+            &self.state.db,
+            self.service.chain_type().clone(),
+        )
+        .await?;
+
+        Ok(addresses
+            .into_iter()
+            .filter(|addr| addr.token_address.is_some())
+            .collect())
+    }
+
+    /// Scan blocks after the checkpoint up to the chain head, returning the number of deposits
+    pub async fn scan_once(&self) -> AppResult<usize> {
+        let head = self.service.get_block_number().await?;
+        self.scan_to(head).await
+    }
+
+    /// Scan blocks after the checkpoint up to `head`, returning the number of deposits
+    pub async fn scan_to(&self, head: u64) -> AppResult<usize> {
+        let pool = &self.state.db;
+
+        // First run starts at the current head; older deposits are verified on request
+        let last_scanned = match ScanCheckpointRepository::get_last_block(pool, &self.chain).await?
+        {
+            Some(block) => block as u64,
+            None => {
+                ScanCheckpointRepository::set_last_block(pool, &self.chain, head as i64).await?;
+                return Ok(0);
+            }
+        };
+
+        if head <= last_scanned {
+            return Ok(0);
+        }
+
+        let from_block = last_scanned + 1;
+        let to_block =
+            head.min(last_scanned + self.state.config.monitor.max_blocks_per_poll.max(1));
+
+        let addresses =
+            AddressRepository::find_active_for_monitoring(pool, self.service.chain_type().clone())
+                .await?;
+
+        let deposits = if addresses.is_empty() {
+            Vec::new()
+        } else {
+            self.find_deposits(&addresses, from_block, to_block).await?
+        };
+
+        for deposit in &deposits {
+            let confirmations = head.saturating_sub(deposit.block_number);
+            record_deposit(&self.state, &self.chain, deposit, confirmations).await?;
+        }
+
+        // Only advance once every deposit in the range is recorded
+        ScanCheckpointRepository::set_last_block(pool, &self.chain, to_block as i64).await?;
+
+        Ok(deposits.len())
+    }
+
+    async fn find_deposits(
+        &self,
+        addresses: &[CryptoAddress],
+        from_block: u64,
+        to_block: u64,
+    ) -> AppResult<Vec<Deposit>> {
+        let (native, tokens): (Vec<_>, Vec<_>) = addresses
+            .iter()
+            .cloned()
+            .partition(|addr| addr.token_address.is_none());
+
+        let mut deposits = Vec::new();
+
+        if !native.is_empty() {
+            let watched = index_by_address(native);
+
+            for block_number in from_block..=to_block {
+                if let Some(block) = self
+                    .service
+                    .get_block_with_transactions(block_number)
+                    .await?
+                {
+                    deposits.extend(match_native_transfers(&block.transactions, &watched));
+                }
+            }
+        }
+
+        if !tokens.is_empty() {
+            deposits.extend(
+                self.find_token_deposits(&tokens, from_block, to_block)
+                    .await?,
+            );
+        }
+
+        Ok(merge_deposits(deposits))
+    }
+
+    async fn find_token_deposits(
+        &self,
+        tokens: &[CryptoAddress],
+        from_block: u64,
+        to_block: u64,
+    ) -> AppResult<Vec<Deposit>> {
+        let token_contracts: Vec<Address> = tokens
+            .iter()
+            .filter_map(|addr| addr.token_address.as_deref()?.parse().ok())
+            .collect();
+        let recipients: Vec<Address> = tokens
+            .iter()
+            .filter_map(|addr| addr.address.parse().ok())
+            .collect();
+
+        let logs = self
+            .service
+            .get_token_transfer_logs(from_block, to_block, token_contracts, recipients)
+            .await?;
+
+        Ok(merge_deposits(match_token_transfers(
+            &self.service,
+            &logs,
+            tokens,
+        )))
+    }
+}
+
+/// Subscribe to `Transfer` logs paying the watched token deposit addresses
+async fn subscribe_token_logs<'a>(
+    provider: &'a Provider<Ws>,
+    watched: &[CryptoAddress],
+) -> AppResult<Option<SubscriptionStream<'a, Ws, Log>>> {
+    if watched.is_empty() {
+        return Ok(None);
+    }
+
+    let tokens: Vec<Address> = watched
+        .iter()
+        .filter_map(|addr| addr.token_address.as_deref()?.parse().ok())
+        .collect();
+    let recipients: Vec<Address> = watched
+        .iter()
+        .filter_map(|addr| addr.address.parse().ok())
+        .collect();
+
+    let filter = EthereumService::token_transfer_filter(tokens, recipients)?;
+    provider
+        .subscribe_logs(&filter)
+        .await
+        .map(Some)
+        .map_err(|e| AppError::Ethereum(format!("Failed to subscribe to logs: {}", e)))
+}
+
+/// Next pushed log, or never when nothing is subscribed
+async fn next_log(logs: &mut Option<SubscriptionStream<'_, Ws, Log>>) -> Option<Log> {
+    match logs {
+        Some(stream) => stream.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn address_ids(addresses: &[CryptoAddress]) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = addresses.iter().map(|addr| addr.id).collect();
+    ids.sort();
+    ids
+}
+
+fn index_by_address(addresses: Vec<CryptoAddress>) -> HashMap<Address, CryptoAddress> {
+    addresses
+        .into_iter()
+        .filter_map(|addr| Some((addr.address.parse().ok()?, addr)))
+        .collect()
+}
+
+fn u256_to_i64(value: U256) -> i64 {
+    if value > U256::from(i64::MAX as u64) {
+        i64::MAX
+    } else {
+        value.as_u64() as i64
+    }
+}
+
+fn format_hash(hash: H256) -> String {
+    format!("{:?}", hash)
+}
+
+/// Native value transfers paying a watched address
+pub fn match_native_transfers(
+    transactions: &[EvmTransaction],
+    watched: &HashMap<Address, CryptoAddress>,
+) -> Vec<Deposit> {
+    transactions
+        .iter()
+        .filter(|tx| !tx.value.is_zero())
+        .filter_map(|tx| {
+            let address = watched.get(&tx.to?)?;
+
+            Some(Deposit {
+                address: address.clone(),
+                tx_hash: format_hash(tx.hash),
+                from_address: format!("{:?}", tx.from),
+                amount: u256_to_i64(tx.value),
+                block_number: tx.block_number?.as_u64(),
+                block_hash: tx.block_hash.map(format_hash),
+            })
+        })
+        .collect()
+}
+
+/// ERC-20 `Transfer` logs paying a watched address in the token it expects
+pub fn match_token_transfers(
+    service: &EthereumService,
+    logs: &[Log],
+    watched: &[CryptoAddress],
+) -> Vec<Deposit> {
+    logs.iter()
+        .filter(|log| log.removed != Some(true) && log.topics.len() == 3)
+        .filter_map(|log| {
+            let recipient = Address::from(log.topics[2]);
+            let address = watched.iter().find(|addr| {
+                addr.address.parse::<Address>().ok() == Some(recipient)
+                    && addr
+                        .token_address
+                        .as_deref()
+                        .and_then(|t| t.parse::<Address>().ok())
+                        == Some(log.address)
+            })?;
+
+            let token = service.token_by_address(address.token_address.as_deref()?)?;
+            let amount = EthereumService::transfer_amount(log)?;
+
+            Some(Deposit {
+                address: address.clone(),
+                tx_hash: format_hash(log.transaction_hash?),
+                from_address: format!("{:?}", Address::from(log.topics[1])),
+                amount: token.base_units_to_amount(amount),
+                block_number: log.block_number?.as_u64(),
+                block_hash: log.block_hash.map(format_hash),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    use crate::config::{Config, ConfirmationPolicy, EvmChainConfig, TokenConfig};
+    use crate::db::repositories::{PaymentRepository, TransactionRepository};
+    use crate::models::{ChainType, CreatePaymentRequest, CurrencyType, PaymentMethod};
+    use crate::services::crypto::ethereum::ERC20_TRANSFER_TOPIC;
+    use crate::services::PaymentProcessor;
+
+    const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+    fn usdc_chain(name: &str, rpc_url: &str) -> EvmChainConfig {
+        EvmChainConfig {
+            name: name.to_string(),
+            chain_id: 1,
+            rpc_urls: vec![rpc_url.to_string()],
+            ws_url: None,
+            hedge_after_ms: None,
+            quorum: 1,
+            max_head_lag: 5,
+            native_currency: CurrencyType::ETH,
+            native_decimals: 18,
+            confirmations: ConfirmationPolicy::default(),
+            tokens: vec![TokenConfig {
+                symbol: "USDC".to_string(),
+                address: USDC.to_string(),
+                decimals: 6,
+            }],
+        }
+    }
+
+    fn usdc_transfer(to: Address, amount: u64, tx_hash: H256, block_number: u64) -> Log {
+        let mut data = [0u8; 32];
+        U256::from(amount).to_big_endian(&mut data);
+
+        Log {
+            address: USDC.parse().unwrap(),
+            topics: vec![
+                ERC20_TRANSFER_TOPIC.parse().unwrap(),
+                H256::from(Address::repeat_byte(0x33)),
+                H256::from(to),
+            ],
+            data: data.to_vec().into(),
+            transaction_hash: Some(tx_hash),
+            block_number: Some(block_number.into()),
+            ..Default::default()
+        }
+    }
+
+    fn watched_address(address: Address) -> CryptoAddress {
+        CryptoAddress {
+            id: Uuid::new_v4(),
+            payment_id: Some(Uuid::new_v4()),
+            address: format!("{:?}", address),
+            chain: ChainType::evm("ethereum"),
+            is_active: true,
+            label: None,
+            expected_amount: Some(1_000),
+            received_amount: None,
+            token_address: None,
+            derivation_path: None,
+            derivation_index: None,
+            last_checked_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_match_native_transfers() {
+        let deposit_address = Address::repeat_byte(0x44);
+        let watched = index_by_address(vec![watched_address(deposit_address)]);
+
+        let paying = EvmTransaction {
+            hash: H256::repeat_byte(0x01),
+            from: Address::repeat_byte(0x33),
+            to: Some(deposit_address),
+            value: U256::from(1_500u64),
+            block_number: Some(100u64.into()),
+            ..Default::default()
+        };
+        let zero_value = EvmTransaction {
+            value: U256::zero(),
+            ..paying.clone()
+        };
+        let elsewhere = EvmTransaction {
+            to: Some(Address::repeat_byte(0x55)),
+            ..paying.clone()
+        };
+
+        let deposits = match_native_transfers(&[paying, zero_value, elsewhere], &watched);
+        assert_eq!(deposits.len(), 1);
+        assert_eq!(deposits[0].amount, 1_500);
+        assert_eq!(deposits[0].block_number, 100);
+        assert_eq!(
+            deposits[0].from_address,
+            format!("{:?}", Address::repeat_byte(0x33))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_match_token_transfers() {
+        let service = EthereumService::new(&usdc_chain("ethereum", "http://localhost:8545"))
+            .await
+            .unwrap();
+
+        let deposit_address = Address::repeat_byte(0x44);
+        let mut watched = watched_address(deposit_address);
+        watched.token_address = Some(USDC.to_string());
+
+        let transfer = usdc_transfer(deposit_address, 25_000_000, H256::repeat_byte(0x02), 200);
+        let wrong_token = Log {
+            address: Address::repeat_byte(0x99),
+            ..transfer.clone()
+        };
+        let removed = Log {
+            removed: Some(true),
+            ..transfer.clone()
+        };
+        let oversized = Log {
+            data: vec![0x01; 64].into(),
+            ..transfer.clone()
+        };
+
+        let deposits = match_token_transfers(
+            &service,
+            &[transfer, wrong_token, removed, oversized],
+            &[watched],
+        );
+        assert_eq!(deposits.len(), 1);
+        assert_eq!(deposits[0].amount, 25_000_000);
+        assert_eq!(deposits[0].block_number, 200);
+    }
+
+    /// Point DATABASE_URL at a migrated database, then run
+    /// `cargo test evm_scanner -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a migrated DATABASE_URL"]
+    async fn test_pushed_deposit_is_credited_once() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let config = Config::for_tests();
+        let pool = sqlx::PgPool::connect(&config.database.url).await.unwrap();
+        let processor = PaymentProcessor::new(&config).await.unwrap();
+        let state = AppState::new(config, pool.clone(), processor, None);
+
+        // A chain of its own, so addresses left by other tests are not scanned
+        let chain = ChainType::evm("pushtest");
+        let rpc = MockServer::start().await;
+        let service = EthereumService::new(&usdc_chain("pushtest", &rpc.uri()))
+            .await
+            .unwrap();
+        let scanner = EvmScanner::new(Arc::new(service), state);
+
+        let payment = PaymentRepository::create(
+            &pool,
+            &CreatePaymentRequest {
+                amount: 2_000_000,
+                currency: CurrencyType::USDC,
+                method: PaymentMethod::Evm,
+                chain: Some(chain.clone()),
+                description: None,
+                customer_email: None,
+                customer_phone: None,
+                metadata: None,
+                callback_url: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let deposit_address = Address::random();
+        let address = AddressRepository::create(
+            &pool,
+            &format!("{:?}", deposit_address),
+            chain,
+            Some(payment.id),
+            Some(2_000_000),
+            None,
+            Some(USDC),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // One transaction paying the address in two transfers, pushed one at a time
+        let tx_hash = H256::random();
+        let first = Log {
+            log_index: Some(0.into()),
+            ..usdc_transfer(deposit_address, 600_000, tx_hash, 300)
+        };
+        let second = Log {
+            log_index: Some(1.into()),
+            ..usdc_transfer(deposit_address, 400_000, tx_hash, 300)
+        };
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                serde_json::json!({ "method": "eth_getLogs" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": [first.clone(), second.clone()]
+            })))
+            .mount(&rpc)
+            .await;
+
+        let watched = [address];
+        assert_eq!(
+            scanner
+                .record_pushed_log(&first, &watched, 301)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            scanner
+                .record_pushed_log(&second, &watched, 301)
+                .await
+                .unwrap(),
+            0
+        );
+
+        // The range scan then reaches the same block
+        ScanCheckpointRepository::set_last_block(&pool, "pushtest", 299)
+            .await
+            .unwrap();
+        scanner.scan_to(300).await.unwrap();
+
+        let address = AddressRepository::find_by_payment_id(&pool, payment.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(address.received_amount, Some(1_000_000));
+
+        let txs = TransactionRepository::find_by_payment_id(&pool, payment.id)
+            .await
+            .unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].amount, 1_000_000);
+        assert_eq!(
+            txs[0].tx_hash.as_deref(),
+            Some(format!("{:?}", tx_hash).as_str())
+        );
+    }
+}
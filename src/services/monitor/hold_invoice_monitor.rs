@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use crate::db::repositories::{HoldInvoiceRepository, PaymentRepository};
+use crate::error::AppResult;
+use crate::models::{HoldInvoice, HoldInvoiceState, Payment, PaymentStatus};
+use crate::services::crypto::lightning::PaymentStatus as InvoiceStatus;
+use crate::AppState;
+
+/// Follows unresolved hold invoices on the node. A paid invoice moves its
+/// payment to `Processing` until it is captured, and one that is still held
+/// close to its HTLC expiry is cancelled so the node is never forced to close
+/// a channel on chain.
+pub struct HoldInvoiceMonitor {
+    state: AppState,
+    cancel_margin_blocks: u32,
+}
+
+impl HoldInvoiceMonitor {
+    pub fn new(state: AppState) -> Self {
+        let cancel_margin_blocks = state.config.lightning.hold_cancel_margin_blocks;
+        Self {
+            state,
+            cancel_margin_blocks,
+        }
+    }
+
+    pub async fn run(self, poll_interval: Duration) {
+        tracing::info!("Hold invoice monitor started");
+
+        let mut ticker = tokio::time::interval(poll_interval);
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = self.check_once().await {
+                tracing::warn!("Hold invoice check failed: {}", e);
+            }
+        }
+    }
+
+    /// Refresh every open or held invoice against the node
+    pub async fn check_once(&self) -> AppResult<()> {
+        let unresolved = HoldInvoiceRepository::find_unresolved(&self.state.db).await?;
+        if unresolved.is_empty() {
+            return Ok(());
+        }
+
+        let block_height = self
+            .state
+            .payment_processor
+            .lightning()
+            .node_info()
+            .await?
+            .block_height;
+
+        for hold_invoice in &unresolved {
+            if let Err(e) = self.check_hold_invoice(hold_invoice, block_height).await {
+                tracing::warn!(
+                    payment_id = %hold_invoice.payment_id,
+                    "Failed to refresh hold invoice: {}",
+                    e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_hold_invoice(
+        &self,
+        hold_invoice: &HoldInvoice,
+        block_height: u32,
+    ) -> AppResult<()> {
+        let pool = &self.state.db;
+        let processor = &self.state.payment_processor;
+
+        let Some(invoice) = processor
+            .lightning()
+            .lookup_invoice(&hold_invoice.payment_hash)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        match invoice.status {
+            InvoiceStatus::Accepted => {
+                if hold_invoice.state == HoldInvoiceState::Open
+                    && HoldInvoiceRepository::transition(
+                        pool,
+                        hold_invoice.payment_id,
+                        &[HoldInvoiceState::Open],
+                        HoldInvoiceState::Accepted,
+                        invoice.htlc_expiry_height.map(|height| height as i32),
+                    )
+                    .await?
+                    .is_some()
+                {
+                    let payment = PaymentRepository::update_status(
+                        pool,
+                        hold_invoice.payment_id,
+                        PaymentStatus::Processing,
+                    )
+                    .await?;
+                    tracing::info!(payment_id = %payment.id, "Lightning payment held");
+                    self.broadcast(&payment).await;
+                }
+
+                let Some(expiry_height) = invoice.htlc_expiry_height else {
+                    return Ok(());
+                };
+
+                if block_height + self.cancel_margin_blocks >= expiry_height {
+                    let payment = processor
+                        .cancel_hold_payment(pool, hold_invoice.payment_id)
+                        .await?;
+                    tracing::warn!(
+                        payment_id = %payment.id,
+                        block_height,
+                        expiry_height,
+                        "Held Lightning payment was not captured in time and was cancelled"
+                    );
+                    self.broadcast(&payment).await;
+                }
+            }
+            // Completing the payment is left to the invoice watcher
+            InvoiceStatus::Settled => {
+                HoldInvoiceRepository::transition(
+                    pool,
+                    hold_invoice.payment_id,
+                    &[HoldInvoiceState::Open, HoldInvoiceState::Accepted],
+                    HoldInvoiceState::Settled,
+                    None,
+                )
+                .await?;
+            }
+            // Expired unpaid, or cancelled on the node directly
+            InvoiceStatus::Cancelled => {
+                if HoldInvoiceRepository::transition(
+                    pool,
+                    hold_invoice.payment_id,
+                    &[HoldInvoiceState::Open, HoldInvoiceState::Accepted],
+                    HoldInvoiceState::Cancelled,
+                    None,
+                )
+                .await?
+                .is_none()
+                {
+                    return Ok(());
+                }
+
+                let status = match hold_invoice.state {
+                    HoldInvoiceState::Open => PaymentStatus::Expired,
+                    _ => PaymentStatus::Cancelled,
+                };
+                let payment =
+                    PaymentRepository::update_status(pool, hold_invoice.payment_id, status).await?;
+                self.broadcast(&payment).await;
+            }
+            InvoiceStatus::Pending | InvoiceStatus::Unknown => {}
+        }
+
+        Ok(())
+    }
+
+    async fn broadcast(&self, payment: &Payment) {
+        if let Some(ref broadcaster) = self.state.ws_broadcaster {
+            let _ = broadcaster.broadcast_payment_update(payment).await;
+        }
+    }
+}
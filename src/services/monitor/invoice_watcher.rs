@@ -0,0 +1,225 @@
+use futures::StreamExt;
+use std::time::Duration;
+
+use crate::db::repositories::{PaymentRepository, ScanCheckpointRepository, TransactionRepository};
+use crate::error::AppResult;
+use crate::models::PaymentStatus;
+use crate::services::crypto::lightning::InvoiceLookup;
+use crate::AppState;
+
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Checkpoint row holding the node's settle index, next to the per-chain block checkpoints
+const CHECKPOINT: &str = "lightning";
+
+/// Follows the Lightning node's settled invoices and completes the payments they belong to.
+///
+/// The node's settle index is checkpointed after every recorded settlement, so
+/// after a restart or a dropped connection the subscription resumes where it
+/// left off and the node replays anything settled in between.
+pub struct InvoiceWatcher {
+    state: AppState,
+}
+
+impl InvoiceWatcher {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn run(self) {
+        tracing::info!("Lightning invoice watcher started");
+
+        let mut backoff = INITIAL_RECONNECT_BACKOFF;
+        loop {
+            match self.follow(&mut backoff).await {
+                Ok(()) => tracing::warn!("Lightning invoice subscription closed"),
+                Err(e) => tracing::warn!("Lightning invoice subscription failed: {}", e),
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+        }
+    }
+
+    /// Record settlements until the subscription ends
+    async fn follow(&self, backoff: &mut Duration) -> AppResult<()> {
+        let pool = &self.state.db;
+
+        let settle_index = ScanCheckpointRepository::get_last_block(pool, CHECKPOINT)
+            .await?
+            .unwrap_or(0) as u64;
+
+        let mut settlements = self
+            .state
+            .payment_processor
+            .lightning()
+            .subscribe_settlements(settle_index)
+            .await?;
+
+        *backoff = INITIAL_RECONNECT_BACKOFF;
+        tracing::info!(settle_index, "Subscribed to Lightning invoice settlements");
+
+        while let Some(invoice) = settlements.next().await {
+            let invoice = invoice?;
+            self.record_settlement(&invoice).await?;
+
+            // Only advance once the settlement is recorded
+            if invoice.settle_index > settle_index {
+                ScanCheckpointRepository::set_last_block(
+                    pool,
+                    CHECKPOINT,
+                    invoice.settle_index as i64,
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Complete the payment a settled invoice belongs to, returning whether one was found.
+    /// Invoices not created by the gateway are ignored.
+    pub async fn record_settlement(&self, invoice: &InvoiceLookup) -> AppResult<bool> {
+        let pool = &self.state.db;
+
+        let Some(payment) =
+            PaymentRepository::find_by_lightning_payment_hash(pool, &invoice.payment_hash).await?
+        else {
+            return Ok(false);
+        };
+
+        // Already recorded, e.g. replayed after a restart before checkpointing
+        if TransactionRepository::find_by_tx_hash(pool, &invoice.payment_hash)
+            .await?
+            .is_some()
+        {
+            return Ok(true);
+        }
+
+        let amount_sat = (invoice.amount_paid_msat / 1000) as i64;
+
+        // Settlement is final, there are no confirmations to wait for
+        TransactionRepository::create_settled(
+            pool,
+            payment.id,
+            amount_sat,
+            &payment.currency.to_string(),
+            CHECKPOINT,
+            &invoice.payment_hash,
+            serde_json::json!({
+                "preimage": invoice.preimage,
+                "settle_index": invoice.settle_index,
+                "amount_paid_msat": invoice.amount_paid_msat,
+                "settled_at": invoice.settled_at,
+            }),
+        )
+        .await?;
+
+        let fully_paid = amount_sat >= payment.amount;
+        let completable = matches!(
+            payment.status,
+            PaymentStatus::Pending | PaymentStatus::Processing | PaymentStatus::Expired
+        );
+
+        if !fully_paid || !completable {
+            tracing::warn!(
+                payment_id = %payment.id,
+                payment_hash = %invoice.payment_hash,
+                status = ?payment.status,
+                amount_sat,
+                "Lightning invoice settled but payment was not completed"
+            );
+            return Ok(true);
+        }
+
+        let payment =
+            PaymentRepository::update_status(pool, payment.id, PaymentStatus::Completed).await?;
+
+        tracing::info!(
+            payment_id = %payment.id,
+            payment_hash = %invoice.payment_hash,
+            amount_sat,
+            "Lightning payment settled"
+        );
+
+        if let Some(ref broadcaster) = self.state.ws_broadcaster {
+            let _ = broadcaster.broadcast_payment_update(&payment).await;
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    use crate::config::Config;
+    use crate::models::{CreatePaymentRequest, CurrencyType, PaymentMethod, TransactionStatus};
+    use crate::services::crypto::lightning::PaymentStatus as InvoiceStatus;
+    use crate::services::PaymentProcessor;
+    use crate::websocket::PaymentBroadcaster;
+
+    /// Point DATABASE_URL at a migrated database, then run
+    /// `cargo test invoice_watcher -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a migrated DATABASE_URL"]
+    async fn test_settlement_completes_payment_once() {
+        let config = Config::for_tests();
+        let pool = PgPool::connect(&config.database.url).await.unwrap();
+        let processor = PaymentProcessor::new(&config).await.unwrap();
+        let state = AppState::new(config, pool.clone(), processor, Some(PaymentBroadcaster::new()));
+
+        let payment = PaymentRepository::create(
+            &pool,
+            &CreatePaymentRequest {
+                amount: 1_500,
+                currency: CurrencyType::BTC,
+                method: PaymentMethod::Lightning,
+                chain: None,
+                description: None,
+                customer_email: None,
+                customer_phone: None,
+                metadata: None,
+                callback_url: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let payment_hash = hex::encode(payment.id.as_bytes()).repeat(2);
+        PaymentRepository::update_lightning_details(&pool, payment.id, "lnbcrt15u1p", &payment_hash)
+            .await
+            .unwrap();
+
+        let invoice = InvoiceLookup {
+            payment_hash: payment_hash.clone(),
+            payment_request: "lnbcrt15u1p".to_string(),
+            status: InvoiceStatus::Settled,
+            amount_sat: 1_500,
+            amount_paid_msat: 1_500_000,
+            settle_index: 1,
+            settled_at: Some(1_700_000_000),
+            preimage: Some("ab".repeat(32)),
+            htlc_expiry_height: None,
+        };
+
+        let watcher = InvoiceWatcher::new(state);
+        assert!(watcher.record_settlement(&invoice).await.unwrap());
+        // Replayed after a restart
+        assert!(watcher.record_settlement(&invoice).await.unwrap());
+
+        let payment = PaymentRepository::find_by_id(&pool, payment.id).await.unwrap();
+        assert_eq!(payment.status, PaymentStatus::Completed);
+
+        let txs = TransactionRepository::find_by_payment_id(&pool, payment.id)
+            .await
+            .unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].status, TransactionStatus::Confirmed);
+        assert_eq!(txs[0].tx_hash.as_deref(), Some(payment_hash.as_str()));
+        assert_eq!(txs[0].raw_data.as_ref().unwrap()["preimage"], "ab".repeat(32));
+    }
+}
@@ -0,0 +1,92 @@
+pub mod bitcoin_watcher;
+pub mod confirmation_tracker;
+pub mod deposits;
+pub mod evm_scanner;
+pub mod hold_invoice_monitor;
+pub mod invoice_watcher;
+pub mod outgoing_payment_monitor;
+pub mod reorg_detector;
+
+pub use bitcoin_watcher::BitcoinWatcher;
+pub use confirmation_tracker::ConfirmationTracker;
+pub use evm_scanner::EvmScanner;
+pub use hold_invoice_monitor::HoldInvoiceMonitor;
+pub use invoice_watcher::InvoiceWatcher;
+pub use outgoing_payment_monitor::OutgoingPaymentMonitor;
+pub use reorg_detector::ReorgDetector;
+
+use std::time::Duration;
+
+use crate::AppState;
+
+/// Spawn a deposit scanner for every configured EVM chain
+pub fn spawn_evm_scanners(state: &AppState) {
+    let poll_interval = Duration::from_secs(state.config.monitor.poll_interval_secs.max(1));
+
+    for service in state.payment_processor.evm_services() {
+        let scanner = EvmScanner::new(service, state.clone());
+        tokio::spawn(scanner.run(poll_interval));
+    }
+}
+
+/// Spawn the worker that credits deposits to Bitcoin addresses, if Bitcoin is configured
+pub fn spawn_bitcoin_watcher(state: &AppState) {
+    if state.payment_processor.bitcoin().is_none() {
+        return;
+    }
+
+    let poll_interval = Duration::from_secs(state.config.monitor.poll_interval_secs.max(1));
+
+    let watcher = BitcoinWatcher::new(state.clone());
+    tokio::spawn(watcher.run(poll_interval));
+}
+
+/// Spawn the worker that follows transactions until they are fully confirmed
+pub fn spawn_confirmation_tracker(state: &AppState) {
+    let poll_interval = Duration::from_secs(state.config.monitor.poll_interval_secs.max(1));
+
+    let tracker = ConfirmationTracker::new(state.clone());
+    tokio::spawn(tracker.run(poll_interval));
+}
+
+/// Spawn the worker that re-validates recent transactions against chain reorganizations
+pub fn spawn_reorg_detector(state: &AppState) {
+    let poll_interval = Duration::from_secs(state.config.monitor.poll_interval_secs.max(1));
+
+    let detector = ReorgDetector::new(state.clone());
+    tokio::spawn(detector.run(poll_interval));
+}
+
+/// Spawn the worker that completes Lightning payments as their invoices settle
+pub fn spawn_invoice_watcher(state: &AppState) {
+    if !state.payment_processor.lightning().is_configured() {
+        return;
+    }
+
+    let watcher = InvoiceWatcher::new(state.clone());
+    tokio::spawn(watcher.run());
+}
+
+/// Spawn the worker that tracks hold invoices and cancels them before their HTLCs expire
+pub fn spawn_hold_invoice_monitor(state: &AppState) {
+    if !state.payment_processor.lightning().is_configured() {
+        return;
+    }
+
+    let poll_interval = Duration::from_secs(state.config.monitor.poll_interval_secs.max(1));
+
+    let monitor = HoldInvoiceMonitor::new(state.clone());
+    tokio::spawn(monitor.run(poll_interval));
+}
+
+/// Spawn the worker that settles Lightning refunds and payouts left pending
+pub fn spawn_outgoing_payment_monitor(state: &AppState) {
+    if !state.payment_processor.lightning().is_configured() {
+        return;
+    }
+
+    let poll_interval = Duration::from_secs(state.config.monitor.poll_interval_secs.max(1));
+
+    let monitor = OutgoingPaymentMonitor::new(state.clone());
+    tokio::spawn(monitor.run(poll_interval));
+}
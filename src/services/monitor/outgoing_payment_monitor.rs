@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use crate::db::repositories::TransactionRepository;
+use crate::error::AppResult;
+use crate::models::TransactionStatus;
+use crate::AppState;
+
+/// Follows Lightning refunds and payouts whose outcome was never reported,
+/// because the connection to the node dropped while they were being paid, and
+/// settles them with the state the node has so their amount is not reserved forever
+pub struct OutgoingPaymentMonitor {
+    state: AppState,
+}
+
+impl OutgoingPaymentMonitor {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn run(self, poll_interval: Duration) {
+        tracing::info!("Outgoing Lightning payment monitor started");
+
+        let mut ticker = tokio::time::interval(poll_interval);
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = self.check_once().await {
+                tracing::warn!("Outgoing Lightning payment check failed: {}", e);
+            }
+        }
+    }
+
+    /// Reconcile every pending outgoing Lightning payment once, returning how
+    /// many reached a final state
+    pub async fn check_once(&self) -> AppResult<usize> {
+        let pending =
+            TransactionRepository::find_pending_outgoing(&self.state.db, "lightning").await?;
+        let mut resolved = 0;
+
+        for tx in &pending {
+            match self
+                .state
+                .payment_processor
+                .reconcile_lightning_payment(&self.state.db, tx)
+                .await
+            {
+                Ok(updated) if updated.status != TransactionStatus::Pending => resolved += 1,
+                Ok(_) => {}
+                Err(e) => tracing::warn!(
+                    tx_id = %tx.id,
+                    "Failed to reconcile Lightning payment: {}",
+                    e
+                ),
+            }
+        }
+
+        Ok(resolved)
+    }
+}
@@ -0,0 +1,513 @@
+use std::time::Duration;
+
+use crate::db::repositories::{PaymentRepository, ScanCheckpointRepository, TransactionRepository};
+use crate::error::AppResult;
+use crate::models::{ChainType, Payment, PaymentStatus, Transaction, TransactionStatus};
+use crate::services::payment_processor::{BlockRef, TxInclusion};
+use crate::AppState;
+
+/// How far back transactions are re-validated against the canonical chain
+const REORG_WINDOW_HOURS: i64 = 24;
+
+/// Re-validates the blocks of recent on-chain transactions and rolls payments
+/// back when a chain reorganization removes or moves them
+pub struct ReorgDetector {
+    state: AppState,
+}
+
+impl ReorgDetector {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn run(self, poll_interval: Duration) {
+        tracing::info!("Reorg detector started");
+
+        let mut ticker = tokio::time::interval(poll_interval);
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = self.check_once().await {
+                tracing::warn!("Reorg check failed: {}", e);
+            }
+        }
+    }
+
+    /// Check every recent transaction, returning how many were affected by a reorg
+    pub async fn check_once(&self) -> AppResult<usize> {
+        let since = chrono::Utc::now() - chrono::Duration::hours(REORG_WINDOW_HOURS);
+        let candidates = TransactionRepository::find_reorg_candidates(&self.state.db, since).await?;
+        let mut reorged = 0;
+
+        for tx in &candidates {
+            match self.check_transaction(tx).await {
+                Ok(true) => reorged += 1,
+                Ok(false) => {}
+                Err(e) => tracing::warn!(
+                    transaction_id = %tx.id,
+                    chain = ?tx.chain,
+                    "Failed to check transaction for reorg: {}",
+                    e
+                ),
+            }
+        }
+
+        Ok(reorged)
+    }
+
+    async fn check_transaction(&self, tx: &Transaction) -> AppResult<bool> {
+        let pool = &self.state.db;
+
+        let (Some(chain), Some(tx_hash)) = (tx.chain.as_deref(), tx.tx_hash.as_deref()) else {
+            return Ok(false);
+        };
+
+        let Ok(chain) = chain.parse::<ChainType>() else {
+            return Ok(false);
+        };
+
+        let inclusion = self
+            .state
+            .payment_processor
+            .get_inclusion(&chain, tx_hash)
+            .await?;
+
+        let Some(recorded_number) = tx.block_number else {
+            // Not mined when recorded (or put back in the mempool by an earlier reorg)
+            if let TxInclusion::Included(block) = inclusion {
+                TransactionRepository::update_block(
+                    pool,
+                    tx.id,
+                    Some(block.number as i64),
+                    block.hash.as_deref(),
+                )
+                .await?;
+            }
+            return Ok(false);
+        };
+
+        let recorded = BlockRef {
+            number: recorded_number as u64,
+            hash: tx.block_hash.clone(),
+        };
+
+        let reason = match &inclusion {
+            TxInclusion::Included(block) if block.is_same_block(&recorded) => return Ok(false),
+            TxInclusion::Unknown => return Ok(false),
+            TxInclusion::Included(block) => format!(
+                "Chain reorganization moved transaction {} from block {} to block {}",
+                tx_hash, recorded.number, block.number
+            ),
+            TxInclusion::Pending => format!(
+                "Chain reorganization removed block {}; transaction {} is back in the mempool",
+                recorded.number, tx_hash
+            ),
+            TxInclusion::Dropped => format!(
+                "Chain reorganization removed block {}; transaction {} was dropped",
+                recorded.number, tx_hash
+            ),
+        };
+
+        tracing::warn!(
+            payment_id = %tx.payment_id,
+            chain = %chain,
+            tx_hash = %tx_hash,
+            block_number = recorded.number,
+            "{}",
+            reason
+        );
+
+        let payment = PaymentRepository::find_by_id(pool, tx.payment_id).await?;
+
+        let payment = match inclusion {
+            TxInclusion::Included(block) => {
+                TransactionRepository::update_block(
+                    pool,
+                    tx.id,
+                    Some(block.number as i64),
+                    block.hash.as_deref(),
+                )
+                .await?;
+
+                let confirmations = self
+                    .state
+                    .payment_processor
+                    .get_confirmations(&chain, tx_hash)
+                    .await?;
+                let is_confirmed = confirmations >= tx.required_confirmations.max(0) as u64;
+                let status = if is_confirmed {
+                    TransactionStatus::Confirmed
+                } else {
+                    TransactionStatus::Confirming
+                };
+
+                TransactionRepository::update_confirmations(
+                    pool,
+                    tx.id,
+                    confirmations.min(i32::MAX as u64) as i32,
+                    status,
+                )
+                .await?;
+
+                if is_confirmed {
+                    payment
+                } else {
+                    self.revert_payment(payment, PaymentStatus::Processing, &reason)
+                        .await?
+                }
+            }
+            TxInclusion::Pending => {
+                TransactionRepository::update_block(pool, tx.id, None, None).await?;
+                TransactionRepository::update_confirmations(
+                    pool,
+                    tx.id,
+                    0,
+                    TransactionStatus::Confirming,
+                )
+                .await?;
+
+                self.revert_payment(payment, PaymentStatus::Processing, &reason)
+                    .await?
+            }
+            TxInclusion::Dropped => {
+                // Lets the scanner pick up a replacement payment to the same address
+                TransactionRepository::revert_deposit(pool, tx.id, &reason).await?;
+
+                self.revert_payment(payment, PaymentStatus::Pending, &reason)
+                    .await?
+            }
+            TxInclusion::Unknown => unreachable!("left alone before the reason is built"),
+        };
+
+        // Blocks replaced by the reorg may hold deposits the scanner has not seen
+        if chain.is_evm() {
+            ScanCheckpointRepository::rewind_to(
+                pool,
+                &chain.to_string(),
+                recorded.number.saturating_sub(1) as i64,
+            )
+            .await?;
+        }
+
+        if let Some(ref broadcaster) = self.state.ws_broadcaster {
+            let _ = broadcaster
+                .broadcast_reorg(&payment, tx_hash, recorded.number, &reason)
+                .await;
+        }
+
+        Ok(true)
+    }
+
+    /// Move a payment back to `status` if it had progressed past it
+    async fn revert_payment(
+        &self,
+        payment: Payment,
+        status: PaymentStatus,
+        reason: &str,
+    ) -> AppResult<Payment> {
+        let should_revert = match status {
+            PaymentStatus::Processing => payment.status == PaymentStatus::Completed,
+            PaymentStatus::Pending => matches!(
+                payment.status,
+                PaymentStatus::Processing | PaymentStatus::Completed
+            ),
+            _ => false,
+        };
+
+        if !should_revert {
+            return Ok(payment);
+        }
+
+        PaymentRepository::revert_status(&self.state.db, payment.id, status, reason).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::prelude::*;
+    use sqlx::PgPool;
+
+    use crate::config::Config;
+    use crate::db::repositories::AddressRepository;
+    use crate::models::{CreatePaymentRequest, CurrencyType, PaymentMethod, TransactionType};
+    use crate::services::monitor::deposits::{record_deposit, Deposit};
+    use crate::services::PaymentProcessor;
+    use crate::websocket::handler::WsMessage;
+    use crate::websocket::PaymentBroadcaster;
+
+    #[test]
+    fn test_block_ref_comparison() {
+        let recorded = BlockRef {
+            number: 10,
+            hash: Some("0xAB".to_string()),
+        };
+
+        assert!(recorded.is_same_block(&BlockRef {
+            number: 10,
+            hash: Some("0xab".to_string()),
+        }));
+        assert!(!recorded.is_same_block(&BlockRef {
+            number: 10,
+            hash: Some("0xcd".to_string()),
+        }));
+        assert!(!recorded.is_same_block(&BlockRef {
+            number: 11,
+            hash: None,
+        }));
+        // Solana slots have no hash to compare
+        assert!(recorded.is_same_block(&BlockRef {
+            number: 10,
+            hash: None,
+        }));
+    }
+
+    /// Point DATABASE_URL at a migrated database, then run
+    /// `cargo test dropped_deposit -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a migrated DATABASE_URL"]
+    async fn test_dropped_deposit_no_longer_counts_towards_payment() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // A node that has never seen the transaction
+        let rpc = MockServer::start().await;
+        for rpc_method in ["eth_getTransactionReceipt", "eth_getTransactionByHash"] {
+            Mock::given(method("POST"))
+                .and(body_partial_json(serde_json::json!({ "method": rpc_method })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": null
+                })))
+                .mount(&rpc)
+                .await;
+        }
+
+        // A chain of its own, so transactions left by other tests are not checked
+        let mut config = Config::for_tests();
+        config.evm_chains[0].name = "droptest".to_string();
+        config.evm_chains[0].rpc_urls = vec![rpc.uri()];
+        let pool = PgPool::connect(&config.database.url).await.unwrap();
+        let processor = PaymentProcessor::new(&config).await.unwrap();
+        let state = AppState::new(config, pool.clone(), processor, None);
+
+        let chain = ChainType::evm("droptest");
+        let payment = PaymentRepository::create(
+            &pool,
+            &CreatePaymentRequest {
+                amount: 1_000_000,
+                currency: CurrencyType::ETH,
+                method: PaymentMethod::Evm,
+                chain: Some(chain.clone()),
+                description: None,
+                customer_email: None,
+                customer_phone: None,
+                metadata: None,
+                callback_url: None,
+            },
+        )
+        .await
+        .unwrap();
+        TransactionRepository::create(
+            &pool,
+            payment.id,
+            TransactionType::Payment,
+            payment.amount,
+            "ETH",
+            Some("droptest"),
+            1,
+        )
+        .await
+        .unwrap();
+        let address = AddressRepository::create(
+            &pool,
+            &format!("{:?}", Address::random()),
+            chain,
+            Some(payment.id),
+            Some(payment.amount),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let deposit = |address, amount| Deposit {
+            address,
+            tx_hash: format!("{:?}", H256::random()),
+            from_address: format!("{:?}", Address::random()),
+            amount,
+            block_number: 300,
+            block_hash: None,
+        };
+
+        // Paid in full, then dropped by a reorg
+        assert!(record_deposit(&state, "droptest", &deposit(address, 1_000_000), 1)
+            .await
+            .unwrap());
+        let payment = PaymentRepository::find_by_id(&pool, payment.id).await.unwrap();
+        assert_eq!(payment.status, PaymentStatus::Completed);
+
+        let dropped = TransactionRepository::find_by_payment_id(&pool, payment.id)
+            .await
+            .unwrap()
+            .remove(0);
+        let detector = ReorgDetector::new(state.clone());
+        assert!(detector.check_transaction(&dropped).await.unwrap());
+
+        let dropped = TransactionRepository::find_by_id(&pool, dropped.id).await.unwrap();
+        assert_eq!(dropped.status, TransactionStatus::Failed);
+
+        let address = AddressRepository::find_by_payment_id(&pool, payment.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(address.received_amount, Some(0));
+        assert!(address.is_active);
+
+        // A much smaller deposit does not complete the payment again
+        assert!(record_deposit(&state, "droptest", &deposit(address, 100_000), 1)
+            .await
+            .unwrap());
+        let payment = PaymentRepository::find_by_id(&pool, payment.id).await.unwrap();
+        assert_eq!(payment.status, PaymentStatus::Pending);
+
+        let address = AddressRepository::find_by_payment_id(&pool, payment.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(address.received_amount, Some(100_000));
+    }
+
+    /// Start `anvil` and point DATABASE_URL at a migrated database, then run
+    /// `cargo test reorg -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a local anvil node and a migrated DATABASE_URL"]
+    async fn test_rolls_back_payment_after_anvil_reorg() {
+        let anvil_url =
+            std::env::var("ANVIL_URL").unwrap_or_else(|_| "http://127.0.0.1:8545".to_string());
+
+        let mut config = Config::for_tests();
+        config.evm_chains[0].chain_id = 31337;
+        config.evm_chains[0].rpc_urls = vec![anvil_url.clone()];
+        let pool = PgPool::connect(&config.database.url).await.unwrap();
+        let processor = PaymentProcessor::new(&config).await.unwrap();
+        let state = AppState::new(config, pool.clone(), processor, Some(PaymentBroadcaster::new()));
+        let mut updates = state.ws_broadcaster.as_ref().unwrap().subscribe();
+
+        let provider = Provider::<Http>::try_from(anvil_url.as_str()).unwrap();
+        let snapshot: U256 = provider.request("evm_snapshot", ()).await.unwrap();
+
+        // Pay a fresh deposit address from an unlocked anvil account
+        let accounts = provider.get_accounts().await.unwrap();
+        let deposit = Address::random();
+        let receipt = provider
+            .send_transaction(
+                TransactionRequest::new().from(accounts[0]).to(deposit).value(1_000u64),
+                None,
+            )
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+            .unwrap();
+        let tx_hash = format!("{:?}", receipt.transaction_hash);
+        let block_number = receipt.block_number.unwrap().as_u64() as i64;
+
+        // Record it as a completed payment on the original chain
+        let payment = PaymentRepository::create(
+            &pool,
+            &CreatePaymentRequest {
+                amount: 1_000,
+                currency: CurrencyType::ETH,
+                method: PaymentMethod::Evm,
+                chain: Some(ChainType::evm("ethereum")),
+                description: None,
+                customer_email: None,
+                customer_phone: None,
+                metadata: None,
+                callback_url: None,
+            },
+        )
+        .await
+        .unwrap();
+        PaymentRepository::update_crypto_details(
+            &pool,
+            payment.id,
+            Some(&tx_hash),
+            None,
+            Some(&format!("{:?}", deposit)),
+            Some("ethereum"),
+        )
+        .await
+        .unwrap();
+
+        let tx = TransactionRepository::create(
+            &pool,
+            payment.id,
+            TransactionType::Payment,
+            1_000,
+            "ETH",
+            Some("ethereum"),
+            1,
+        )
+        .await
+        .unwrap();
+        TransactionRepository::update_blockchain_details(
+            &pool,
+            tx.id,
+            &tx_hash,
+            Some(block_number),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        TransactionRepository::update_block(
+            &pool,
+            tx.id,
+            Some(block_number),
+            Some(&format!("{:?}", receipt.block_hash.unwrap())),
+        )
+        .await
+        .unwrap();
+        TransactionRepository::update_confirmations(&pool, tx.id, 1, TransactionStatus::Confirmed)
+            .await
+            .unwrap();
+        PaymentRepository::update_status(&pool, payment.id, PaymentStatus::Completed)
+            .await
+            .unwrap();
+
+        // Rewind past the payment and mine a competing block at the same height
+        let reverted: bool = provider.request("evm_revert", [snapshot]).await.unwrap();
+        assert!(reverted);
+        let _: serde_json::Value = provider.request("evm_mine", ()).await.unwrap();
+
+        let detector = ReorgDetector::new(state.clone());
+        assert!(detector.check_once().await.unwrap() >= 1);
+
+        let tx = TransactionRepository::find_by_id(&pool, tx.id).await.unwrap();
+        assert_eq!(tx.status, TransactionStatus::Failed);
+
+        let payment = PaymentRepository::find_by_id(&pool, payment.id).await.unwrap();
+        assert_eq!(payment.status, PaymentStatus::Pending);
+
+        let history = PaymentRepository::find_status_history(&pool, payment.id)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].previous_status, PaymentStatus::Completed);
+        assert!(history[0].reason.contains(&tx_hash));
+
+        let mut saw_reorg_event = false;
+        while let Ok(msg) = updates.try_recv() {
+            if let WsMessage::PaymentReorg(reorg) = msg {
+                saw_reorg_event |= reorg.payment_id == payment.id;
+            }
+        }
+        assert!(saw_reorg_event);
+    }
+}
@@ -1,69 +1,78 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::config::Config;
-use crate::db::repositories::{AddressRepository, PaymentRepository, TransactionRepository};
+use crate::crypto_utils::encryption;
+use crate::db::repositories::{
+    AddressRepository, HoldInvoiceRepository, PaymentRepository, TransactionRepository,
+    TransferRepository,
+};
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    ChainType, CreatePaymentRequest, CurrencyType, Payment, PaymentMethod, PaymentStatus,
-    TransactionType,
+    ChainType, CreatePaymentRequest, CryptoAddress, CurrencyType, DepositUpdate, HoldInvoice,
+    HoldInvoiceState, LnurlPayLink, Payment, PaymentMethod, PaymentStatus, Transaction,
+    TransactionStatus, TransactionType, TransferSplit,
+};
+use crate::services::crypto::lightning::{
+    OutgoingPayment, OutgoingStatus, PaymentStatus as LightningInvoiceStatus,
 };
+use crate::services::crypto::hd_wallet::DerivedAddress;
 use crate::services::{
-    EthereumService, LightningService, RazorpayService, SolanaService,
+    BitcoinService, EthereumService, HdWallet, LightningService, RazorpayService, SolanaService,
 };
-use crate::services::razorpay::CreateOrderRequest;
+use crate::services::razorpay::{CreateOrderRequest, TransferRequest};
+
+/// How long an outgoing Lightning payment is left to its own request before it
+/// is reconciled with the node, comfortably longer than a payment attempt takes
+pub const LIGHTNING_PAYMENT_GRACE: Duration = Duration::from_secs(600);
 
 pub struct PaymentProcessor {
     razorpay: Arc<RazorpayService>,
-    ethereum: Arc<EthereumService>,
-    polygon: Option<Arc<EthereumService>>,
-    bsc: Option<Arc<EthereumService>>,
-    arbitrum: Option<Arc<EthereumService>>,
+    /// EVM chain registry, keyed by chain name
+    evm: HashMap<String, Arc<EthereumService>>,
     solana: Arc<SolanaService>,
+    bitcoin: Option<Arc<BitcoinService>>,
     lightning: Arc<LightningService>,
+    hd_wallet: Arc<HdWallet>,
+    /// Encrypts hold invoice preimages at rest
+    encryption_key: Vec<u8>,
+    hold_cltv_expiry: u32,
 }
 
 impl PaymentProcessor {
     pub async fn new(config: &Config) -> AppResult<Self> {
         let razorpay = Arc::new(RazorpayService::new(&config.razorpay));
-        let ethereum = Arc::new(EthereumService::new(&config.ethereum).await?);
         let solana = Arc::new(SolanaService::new(&config.solana));
-        let lightning = Arc::new(LightningService::new(&config.lightning));
-
-        // Initialize other EVM chains if configured
-        let polygon = if !config.polygon.rpc_url.is_empty() {
-            Some(Arc::new(
-                EthereumService::new_for_chain(&config.polygon, ChainType::Polygon).await?,
-            ))
-        } else {
-            None
-        };
+        let lightning = Arc::new(LightningService::new(&config.lightning)?);
+        let hd_wallet = Arc::new(HdWallet::new(
+            &config.hd_wallet,
+            &config.security.encryption_key,
+        )?);
 
-        let bsc = if !config.bsc.rpc_url.is_empty() {
-            Some(Arc::new(
-                EthereumService::new_for_chain(&config.bsc, ChainType::Bsc).await?,
-            ))
-        } else {
-            None
-        };
+        let mut evm = HashMap::new();
+        for chain in &config.evm_chains {
+            evm.insert(chain.name.clone(), Arc::new(EthereumService::new(chain).await?));
+        }
 
-        let arbitrum = if !config.arbitrum.rpc_url.is_empty() {
-            Some(Arc::new(
-                EthereumService::new_for_chain(&config.arbitrum, ChainType::Arbitrum).await?,
-            ))
+        let bitcoin = if !config.bitcoin.esplora_url.is_empty() {
+            Some(Arc::new(BitcoinService::new(&config.bitcoin)?))
         } else {
             None
         };
 
         Ok(Self {
             razorpay,
-            ethereum,
-            polygon,
-            bsc,
-            arbitrum,
+            evm,
             solana,
+            bitcoin,
             lightning,
+            hd_wallet,
+            encryption_key: config.security.encryption_key.as_bytes().to_vec(),
+            hold_cltv_expiry: config.lightning.hold_cltv_expiry,
         })
     }
 
@@ -71,34 +80,50 @@ impl PaymentProcessor {
         &self.razorpay
     }
 
-    pub fn ethereum(&self) -> &EthereumService {
-        &self.ethereum
-    }
-
     pub fn solana(&self) -> &SolanaService {
         &self.solana
     }
 
+    pub fn bitcoin(&self) -> Option<&BitcoinService> {
+        self.bitcoin.as_deref()
+    }
+
     pub fn lightning(&self) -> &LightningService {
         &self.lightning
     }
 
     pub fn get_evm_service(&self, chain: &ChainType) -> Option<&EthereumService> {
         match chain {
-            ChainType::Ethereum => Some(&self.ethereum),
-            ChainType::Polygon => self.polygon.as_deref(),
-            ChainType::Bsc => self.bsc.as_deref(),
-            ChainType::Arbitrum => self.arbitrum.as_deref(),
+            ChainType::Evm(name) => self.evm.get(name).map(Arc::as_ref),
             _ => None,
         }
     }
 
+    /// All configured EVM chain services
+    pub fn evm_services(&self) -> Vec<Arc<EthereumService>> {
+        self.evm.values().cloned().collect()
+    }
+
+    /// Names of the registered EVM chains, sorted
+    pub fn evm_chain_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.evm.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
     /// Create a payment based on the payment method
     pub async fn create_payment(
         &self,
         pool: &PgPool,
         request: &CreatePaymentRequest,
     ) -> AppResult<PaymentCreationResult> {
+        // Bank transfers are collected through Smart Collect virtual accounts
+        if request.method == PaymentMethod::BankTransfer {
+            return Err(AppError::Payment(
+                "Bank transfer payments are created with a virtual account".to_string(),
+            ));
+        }
+
         // Create the payment record
         let payment = PaymentRepository::create(pool, request).await?;
 
@@ -108,9 +133,10 @@ impl PaymentProcessor {
             | PaymentMethod::NetBanking
             | PaymentMethod::Wallet
             | PaymentMethod::Emi => {
-                self.create_razorpay_payment(pool, &payment, request).await
+                self.create_razorpay_payment(pool, &payment, request, &[], None).await
             }
-            PaymentMethod::Ethereum
+            PaymentMethod::Evm
+            | PaymentMethod::Ethereum
             | PaymentMethod::Polygon
             | PaymentMethod::Bsc
             | PaymentMethod::Arbitrum => {
@@ -119,17 +145,39 @@ impl PaymentProcessor {
             PaymentMethod::Solana => {
                 self.create_solana_payment(pool, &payment, request).await
             }
+            PaymentMethod::Bitcoin => {
+                self.create_bitcoin_payment(pool, &payment, request).await
+            }
             PaymentMethod::Lightning => {
                 self.create_lightning_payment(pool, &payment, request).await
             }
+            PaymentMethod::BankTransfer => unreachable!("rejected before the payment is created"),
         }
     }
 
+    /// Create a Razorpay order whose payment is split among linked accounts
+    /// with Razorpay Route once it is captured, and whose checkout offers the
+    /// saved cards of `razorpay_customer_id`
+    pub async fn create_razorpay_order(
+        &self,
+        pool: &PgPool,
+        request: &CreatePaymentRequest,
+        transfers: &[TransferSplit],
+        razorpay_customer_id: Option<&str>,
+    ) -> AppResult<PaymentCreationResult> {
+        let payment = PaymentRepository::create(pool, request).await?;
+
+        self.create_razorpay_payment(pool, &payment, request, transfers, razorpay_customer_id)
+            .await
+    }
+
     async fn create_razorpay_payment(
         &self,
         pool: &PgPool,
         payment: &Payment,
         request: &CreatePaymentRequest,
+        transfers: &[TransferSplit],
+        razorpay_customer_id: Option<&str>,
     ) -> AppResult<PaymentCreationResult> {
         let currency = match request.currency {
             CurrencyType::INR => "INR",
@@ -144,10 +192,23 @@ impl PaymentProcessor {
             receipt: Some(payment.id.to_string()),
             notes: request.metadata.clone(),
             partial_payment: Some(false),
+            transfers: (!transfers.is_empty()).then(|| {
+                transfers
+                    .iter()
+                    .map(|split| TransferRequest::from_split(split, currency))
+                    .collect()
+            }),
+            customer_id: razorpay_customer_id.map(str::to_string),
         };
 
         let order = self.razorpay.client().create_order(&order_request).await?;
 
+        // Razorpay only creates the transfers once the payment is captured;
+        // they are matched to these rows when reconciled
+        for split in transfers {
+            TransferRepository::create_split(pool, payment.id, split, &request.currency).await?;
+        }
+
         // Update payment with Razorpay order ID
         PaymentRepository::update_razorpay_details(
             pool,
@@ -162,7 +223,7 @@ impl PaymentProcessor {
             payment_id: payment.id,
             status: PaymentStatus::Pending,
             razorpay_order_id: Some(order.id),
-            razorpay_key_id: Some(self.razorpay.client().webhook_secret().to_string()),
+            razorpay_key_id: Some(self.razorpay.client().key_id().to_string()),
             crypto_address: None,
             lightning_invoice: None,
             chain: None,
@@ -176,30 +237,43 @@ impl PaymentProcessor {
         payment: &Payment,
         request: &CreatePaymentRequest,
     ) -> AppResult<PaymentCreationResult> {
-        let chain_type = match request.method {
-            PaymentMethod::Ethereum => ChainType::Ethereum,
-            PaymentMethod::Polygon => ChainType::Polygon,
-            PaymentMethod::Bsc => ChainType::Bsc,
-            PaymentMethod::Arbitrum => ChainType::Arbitrum,
+        let chain_type = match (&request.method, &request.chain) {
+            (PaymentMethod::Evm, Some(chain)) if chain.is_evm() => chain.clone(),
+            (PaymentMethod::Ethereum, _) => ChainType::evm("ethereum"),
+            (PaymentMethod::Polygon, _) => ChainType::evm("polygon"),
+            (PaymentMethod::Bsc, _) => ChainType::evm("bsc"),
+            (PaymentMethod::Arbitrum, _) => ChainType::evm("arbitrum"),
             _ => return Err(AppError::Payment("Invalid EVM chain".to_string())),
         };
 
-        // In production, you would generate a unique deposit address
-        // For now, we'll use a placeholder approach
-        // You could use HD wallet derivation to generate unique addresses
-        let deposit_address = self.generate_deposit_address(&chain_type)?;
+        let evm_service = self.get_evm_service(&chain_type)
+            .ok_or_else(|| AppError::Payment(format!("{} not configured", chain_type)))?;
 
-        // Create address record
-        AddressRepository::create(
-            pool,
-            &deposit_address,
-            chain_type.clone(),
-            Some(payment.id),
-            Some(request.amount),
-            None,
-            None,
-        )
-        .await?;
+        // Stablecoin payments are ERC-20 transfers to the deposit address
+        let token = match request.currency {
+            CurrencyType::USDT | CurrencyType::USDC => Some(
+                evm_service.token_for_currency(&request.currency).ok_or_else(|| {
+                    AppError::Payment(format!(
+                        "{:?} is not supported on {}",
+                        request.currency, chain_type
+                    ))
+                })?,
+            ),
+            _ => None,
+        };
+
+        // Derive a unique deposit address for this payment
+        let deposit_address = self
+            .allocate_deposit_address(
+                pool,
+                chain_type.clone(),
+                Some(payment.id),
+                Some(request.amount),
+                None,
+                token.as_ref().map(|t| t.address.as_str()),
+            )
+            .await?
+            .address;
 
         // Update payment with crypto details
         PaymentRepository::update_crypto_details(
@@ -213,12 +287,13 @@ impl PaymentProcessor {
         .await?;
 
         // Create transaction record
-        let evm_service = self.get_evm_service(&chain_type)
-            .ok_or_else(|| AppError::Payment(format!("{} not configured", chain_type)))?;
-
-        let required_confirmations = evm_service.get_required_confirmations(
-            ethers::types::U256::from(request.amount as u128),
-        );
+        let required_confirmations = if token.is_some() {
+            evm_service.get_required_token_confirmations(request.amount)
+        } else {
+            evm_service.get_required_confirmations(
+                ethers::types::U256::from(request.amount as u128),
+            )
+        };
 
         TransactionRepository::create(
             pool,
@@ -249,19 +324,29 @@ impl PaymentProcessor {
         payment: &Payment,
         request: &CreatePaymentRequest,
     ) -> AppResult<PaymentCreationResult> {
-        let deposit_address = self.generate_deposit_address(&ChainType::Solana)?;
+        // USDC is paid as an SPL transfer to the deposit address's token account
+        let token_mint = match request.currency {
+            CurrencyType::SOL => None,
+            CurrencyType::USDC => Some(self.solana.usdc_mint()),
+            _ => {
+                return Err(AppError::Payment(format!(
+                    "{:?} is not supported on solana",
+                    request.currency
+                )))
+            }
+        };
 
-        // Create address record
-        AddressRepository::create(
-            pool,
-            &deposit_address,
-            ChainType::Solana,
-            Some(payment.id),
-            Some(request.amount),
-            None,
-            None,
-        )
-        .await?;
+        let deposit_address = self
+            .allocate_deposit_address(
+                pool,
+                ChainType::Solana,
+                Some(payment.id),
+                Some(request.amount),
+                None,
+                token_mint,
+            )
+            .await?
+            .address;
 
         // Update payment
         PaymentRepository::update_crypto_details(
@@ -275,7 +360,11 @@ impl PaymentProcessor {
         .await?;
 
         // Create transaction record
-        let required_confirmations = SolanaService::get_required_confirmations(request.amount as u64);
+        let required_confirmations = if token_mint.is_some() {
+            SolanaService::get_required_token_confirmations(request.amount as u64)
+        } else {
+            SolanaService::get_required_confirmations(request.amount as u64)
+        };
 
         TransactionRepository::create(
             pool,
@@ -300,6 +389,65 @@ impl PaymentProcessor {
         })
     }
 
+    async fn create_bitcoin_payment(
+        &self,
+        pool: &PgPool,
+        payment: &Payment,
+        request: &CreatePaymentRequest,
+    ) -> AppResult<PaymentCreationResult> {
+        if self.bitcoin.is_none() {
+            return Err(AppError::Payment("bitcoin not configured".to_string()));
+        }
+
+        // Derive a unique bech32 deposit address for this payment
+        let deposit_address = self
+            .allocate_deposit_address(
+                pool,
+                ChainType::Bitcoin,
+                Some(payment.id),
+                Some(request.amount),
+                None,
+                None,
+            )
+            .await?
+            .address;
+
+        PaymentRepository::update_crypto_details(
+            pool,
+            payment.id,
+            None,
+            None,
+            Some(&deposit_address),
+            Some("bitcoin"),
+        )
+        .await?;
+
+        let required_confirmations = BitcoinService::get_required_confirmations(request.amount as u64);
+
+        TransactionRepository::create(
+            pool,
+            payment.id,
+            TransactionType::Payment,
+            request.amount,
+            &request.currency.to_string(),
+            Some("bitcoin"),
+            required_confirmations,
+        )
+        .await?;
+
+        Ok(PaymentCreationResult {
+            payment_id: payment.id,
+            status: PaymentStatus::Pending,
+            razorpay_order_id: None,
+            razorpay_key_id: None,
+            crypto_address: Some(deposit_address),
+            lightning_invoice: None,
+            chain: Some("bitcoin".to_string()),
+            // Bitcoin blocks are slower, give the customer more time
+            expires_at: Some(chrono::Utc::now() + chrono::Duration::hours(24)),
+        })
+    }
+
     async fn create_lightning_payment(
         &self,
         pool: &PgPool,
@@ -349,25 +497,509 @@ impl PaymentProcessor {
         }
     }
 
-    fn generate_deposit_address(&self, chain: &ChainType) -> AppResult<String> {
-        // In production, implement HD wallet derivation for unique addresses
-        // For demo purposes, return a placeholder
-        match chain {
-            ChainType::Ethereum | ChainType::Polygon | ChainType::Bsc | ChainType::Arbitrum => {
-                // Would use ethers-rs to derive new address from HD wallet
-                Err(AppError::Payment(
-                    "Configure HD wallet for address generation".to_string(),
-                ))
+    /// Create a payment for an LNURL-pay callback. The invoice commits to the
+    /// link's metadata by hash, which the paying wallet verifies.
+    pub async fn create_lnurl_payment(
+        &self,
+        pool: &PgPool,
+        link: &LnurlPayLink,
+        amount_msat: u64,
+        metadata: &str,
+    ) -> AppResult<PaymentCreationResult> {
+        let request = CreatePaymentRequest {
+            // Payments are kept in whole satoshis
+            amount: (amount_msat / 1000) as i64,
+            currency: CurrencyType::BTC,
+            method: PaymentMethod::Lightning,
+            chain: None,
+            description: Some(link.description.clone()),
+            customer_email: None,
+            customer_phone: None,
+            metadata: Some(serde_json::json!({
+                "lnurl": link.name,
+                "amount_msat": amount_msat,
+            })),
+            callback_url: None,
+        };
+
+        let payment = PaymentRepository::create(pool, &request).await?;
+
+        let invoice = self
+            .lightning
+            .create_hashed_invoice(amount_msat, metadata, 3600)
+            .await
+            .map_err(|e| {
+                AppError::Lightning(format!("Failed to create Lightning invoice: {}", e))
+            })?;
+
+        PaymentRepository::update_lightning_details(
+            pool,
+            payment.id,
+            &invoice.payment_request,
+            &invoice.payment_hash,
+        )
+        .await?;
+
+        Ok(PaymentCreationResult {
+            payment_id: payment.id,
+            status: PaymentStatus::Pending,
+            razorpay_order_id: None,
+            razorpay_key_id: None,
+            crypto_address: None,
+            lightning_invoice: Some(invoice.payment_request),
+            chain: Some("lightning".to_string()),
+            expires_at: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+        })
+    }
+
+    /// Create a Lightning payment backed by a hold invoice. The customer's
+    /// payment is held by the node until [`settle_hold_payment`](Self::settle_hold_payment)
+    /// captures it or [`cancel_hold_payment`](Self::cancel_hold_payment) returns it.
+    pub async fn create_hold_payment(
+        &self,
+        pool: &PgPool,
+        request: &CreatePaymentRequest,
+    ) -> AppResult<PaymentCreationResult> {
+        // Nothing is committed unless the node creates the invoice, so a backend
+        // that refuses hold invoices leaves no payment behind
+        let mut db_tx = pool.begin().await?;
+
+        let payment = PaymentRepository::create_in(&mut db_tx, request).await?;
+
+        let mut preimage = [0u8; 32];
+        getrandom::getrandom(&mut preimage)
+            .map_err(|e| AppError::Internal(format!("Failed to generate preimage: {}", e)))?;
+        let payment_hash = hex::encode(Sha256::digest(preimage));
+        let encrypted_preimage = hex::encode(encryption::encrypt(&preimage, &self.encryption_key)?);
+
+        HoldInvoiceRepository::create(&mut db_tx, payment.id, &payment_hash, &encrypted_preimage)
+            .await?;
+
+        let description = request
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Payment {}", payment.id));
+
+        let invoice = self
+            .lightning
+            .create_hold_invoice(
+                &payment_hash,
+                request.amount as u64,
+                &description,
+                3600,
+                self.hold_cltv_expiry,
+            )
+            .await
+            .map_err(|e| {
+                AppError::Lightning(format!("Failed to create Lightning hold invoice: {}", e))
+            })?;
+
+        // A payable invoice must never outlive its preimage
+        if let Err(e) = db_tx.commit().await {
+            if let Err(cancel_error) = self.lightning.cancel_hold_invoice(&payment_hash).await {
+                tracing::error!(
+                    payment_hash = %payment_hash,
+                    "Failed to cancel hold invoice whose payment was not stored: {}",
+                    cancel_error
+                );
             }
-            ChainType::Solana => {
-                // Would use solana-sdk to derive new address
-                Err(AppError::Payment(
-                    "Configure HD wallet for address generation".to_string(),
+            return Err(e.into());
+        }
+
+        PaymentRepository::update_lightning_details(
+            pool,
+            payment.id,
+            &invoice.payment_request,
+            &payment_hash,
+        )
+        .await?;
+
+        Ok(PaymentCreationResult {
+            payment_id: payment.id,
+            status: PaymentStatus::Pending,
+            razorpay_order_id: None,
+            razorpay_key_id: None,
+            crypto_address: None,
+            lightning_invoice: Some(invoice.payment_request),
+            chain: Some("lightning".to_string()),
+            expires_at: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+        })
+    }
+
+    /// Capture a held payment by revealing the preimage to the node
+    pub async fn settle_hold_payment(
+        &self,
+        pool: &PgPool,
+        payment_id: Uuid,
+    ) -> AppResult<HoldInvoice> {
+        let hold_invoice = HoldInvoiceRepository::find_by_payment_id(pool, payment_id).await?;
+
+        let invoice = self
+            .lightning
+            .lookup_invoice(&hold_invoice.payment_hash)
+            .await?
+            .ok_or_else(|| {
+                AppError::Lightning(format!(
+                    "Node does not know invoice {}",
+                    hold_invoice.payment_hash
                 ))
+            })?;
+
+        if !matches!(invoice.status, LightningInvoiceStatus::Accepted) {
+            return Err(AppError::Validation(format!(
+                "Payment {} is not held (invoice is {:?})",
+                payment_id, invoice.status
+            )));
+        }
+
+        let preimage = encryption::decrypt(
+            &hex::decode(&hold_invoice.encrypted_preimage)
+                .map_err(|e| AppError::Internal(format!("Corrupt hold invoice preimage: {}", e)))?,
+            &self.encryption_key,
+        )?;
+
+        self.lightning.settle_hold_invoice(&hex::encode(preimage)).await?;
+
+        HoldInvoiceRepository::transition(
+            pool,
+            payment_id,
+            &[HoldInvoiceState::Open, HoldInvoiceState::Accepted],
+            HoldInvoiceState::Settled,
+            invoice.htlc_expiry_height.map(|height| height as i32),
+        )
+        .await?
+        .ok_or_else(|| {
+            AppError::Validation(format!("Hold invoice for {} was already resolved", payment_id))
+        })
+    }
+
+    /// Cancel a hold invoice, failing a held payment back to the customer
+    pub async fn cancel_hold_payment(&self, pool: &PgPool, payment_id: Uuid) -> AppResult<Payment> {
+        let hold_invoice = HoldInvoiceRepository::find_by_payment_id(pool, payment_id).await?;
+
+        if !matches!(hold_invoice.state, HoldInvoiceState::Open | HoldInvoiceState::Accepted) {
+            return Err(AppError::Validation(format!(
+                "Hold invoice for {} is already {:?}",
+                payment_id, hold_invoice.state
+            )));
+        }
+
+        self.lightning.cancel_hold_invoice(&hold_invoice.payment_hash).await?;
+
+        HoldInvoiceRepository::transition(
+            pool,
+            payment_id,
+            &[HoldInvoiceState::Open, HoldInvoiceState::Accepted],
+            HoldInvoiceState::Cancelled,
+            None,
+        )
+        .await?;
+
+        PaymentRepository::update_status(pool, payment_id, PaymentStatus::Cancelled).await
+    }
+
+    /// Send part or all of a completed BTC payment back out over Lightning, as a
+    /// refund to the customer or a payout. The invoice is supplied by the
+    /// recipient; its amount counts against what is left of the payment.
+    pub async fn pay_lightning_invoice(
+        &self,
+        pool: &PgPool,
+        payment_id: Uuid,
+        payment_request: &str,
+        tx_type: TransactionType,
+    ) -> AppResult<Transaction> {
+        if !matches!(tx_type, TransactionType::Refund | TransactionType::Withdrawal) {
+            return Err(AppError::Validation(format!(
+                "Not an outgoing transaction type: {:?}",
+                tx_type
+            )));
+        }
+
+        let payment = PaymentRepository::find_by_id(pool, payment_id).await?;
+
+        if payment.currency != CurrencyType::BTC {
+            return Err(AppError::Validation(format!(
+                "Payment {} is in {:?}, only BTC payments can be paid out over Lightning",
+                payment.id, payment.currency
+            )));
+        }
+
+        if payment.status != PaymentStatus::Completed {
+            return Err(AppError::Validation(format!(
+                "Payment {} is {:?}, only completed payments can be paid out",
+                payment.id, payment.status
+            )));
+        }
+
+        let invoice = self.lightning.parse_invoice(payment_request)?;
+        let amount_msat = invoice
+            .amount_msat
+            .filter(|msat| *msat > 0)
+            .ok_or_else(|| AppError::Validation("Invoice must specify an amount".to_string()))?;
+
+        if TransactionRepository::find_live_by_tx_hash(pool, &invoice.payment_hash)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Validation(format!(
+                "Invoice {} has already been paid",
+                invoice.payment_hash
+            )));
+        }
+
+        // Sub-satoshi amounts round up so they are never under-counted
+        let amount_sat = amount_msat.div_ceil(1000);
+
+        let tx = TransactionRepository::create_outgoing(
+            pool,
+            payment.id,
+            tx_type,
+            amount_sat as i64,
+            &payment.currency.to_string(),
+            "lightning",
+            &invoice.payment_hash,
+        )
+        .await?;
+
+        let fee_limit_sat = self.lightning.fee_limit_sat(amount_sat);
+
+        // Stored up front so a payment completed by reconciliation keeps it
+        let tx = TransactionRepository::update_raw_data(
+            pool,
+            tx.id,
+            serde_json::json!({ "fee_limit_sat": fee_limit_sat }),
+        )
+        .await?;
+
+        let outgoing = match self.lightning.pay_invoice(payment_request, fee_limit_sat).await {
+            Ok(OutgoingStatus::Succeeded(outgoing)) => outgoing,
+            // Left pending for the outgoing payment monitor to settle from the node
+            Ok(OutgoingStatus::InFlight) => {
+                tracing::info!(
+                    tx_id = %tx.id,
+                    payment_hash = %invoice.payment_hash,
+                    "Lightning payment still in flight"
+                );
+                return Ok(tx);
+            }
+            Ok(OutgoingStatus::Failed(reason)) => {
+                tracing::warn!(
+                    tx_id = %tx.id,
+                    payment_hash = %invoice.payment_hash,
+                    "Lightning payment failed: {}",
+                    reason
+                );
+                return TransactionRepository::update_status(
+                    pool,
+                    tx.id,
+                    TransactionStatus::Failed,
+                    Some(&reason),
+                )
+                .await;
+            }
+            // The request may have reached the node, so the payment could still
+            // complete; keep the amount reserved until it is reconciled
+            Err(AppError::HttpClient(e)) => {
+                tracing::warn!(
+                    tx_id = %tx.id,
+                    payment_hash = %invoice.payment_hash,
+                    "Lightning payment outcome unknown: {}",
+                    e
+                );
+                TransactionRepository::update_status(
+                    pool,
+                    tx.id,
+                    TransactionStatus::Pending,
+                    Some(&format!("Outcome unknown: {}", e)),
+                )
+                .await?;
+                return Err(AppError::Lightning(format!(
+                    "Lost contact with the Lightning node, transaction {} is pending",
+                    tx.id
+                )));
+            }
+            Err(e) => {
+                tracing::warn!(
+                    tx_id = %tx.id,
+                    payment_hash = %invoice.payment_hash,
+                    "Lightning payment failed: {}",
+                    e
+                );
+                return TransactionRepository::update_status(
+                    pool,
+                    tx.id,
+                    TransactionStatus::Failed,
+                    Some(&e.to_string()),
+                )
+                .await;
+            }
+        };
+
+        self.complete_lightning_payment(pool, &tx, &outgoing).await
+    }
+
+    /// Settle a pending Lightning refund or payout with the state the node has
+    /// for it. Payments younger than [`LIGHTNING_PAYMENT_GRACE`] are left alone,
+    /// as the request that sent them may still be waiting on the node; after
+    /// that, one the node never started cannot start any more and is failed.
+    pub async fn reconcile_lightning_payment(
+        &self,
+        pool: &PgPool,
+        tx: &Transaction,
+    ) -> AppResult<Transaction> {
+        let payment_hash = match tx.tx_hash.as_deref() {
+            Some(payment_hash)
+                if tx.status == TransactionStatus::Pending
+                    && tx.chain.as_deref() == Some("lightning")
+                    && (chrono::Utc::now() - tx.created_at)
+                        .to_std()
+                        .is_ok_and(|age| age >= LIGHTNING_PAYMENT_GRACE) =>
+            {
+                payment_hash
+            }
+            _ => return Ok(tx.clone()),
+        };
+
+        let (status, reason) = match self.lightning.lookup_payment(payment_hash).await? {
+            Some(OutgoingStatus::Succeeded(outgoing)) => {
+                return self.complete_lightning_payment(pool, tx, &outgoing).await;
+            }
+            Some(OutgoingStatus::InFlight) => return Ok(tx.clone()),
+            Some(OutgoingStatus::Failed(reason)) => (TransactionStatus::Failed, reason),
+            None => (
+                TransactionStatus::Failed,
+                "Payment never reached the Lightning node".to_string(),
+            ),
+        };
+
+        tracing::warn!(
+            tx_id = %tx.id,
+            payment_hash,
+            "Pending Lightning payment failed, amount released: {}",
+            reason
+        );
+
+        TransactionRepository::update_status(pool, tx.id, status, Some(&reason)).await
+    }
+
+    /// Record a sent Lightning payment, marking its payment refunded once the
+    /// confirmed refunds cover what was paid
+    async fn complete_lightning_payment(
+        &self,
+        pool: &PgPool,
+        tx: &Transaction,
+        outgoing: &OutgoingPayment,
+    ) -> AppResult<Transaction> {
+        TransactionRepository::update_blockchain_details(
+            pool,
+            tx.id,
+            &outgoing.payment_hash,
+            None,
+            None,
+            None,
+            Some(outgoing.fee_msat.div_ceil(1000) as i64),
+        )
+        .await?;
+
+        let fee_limit_sat = tx
+            .raw_data
+            .as_ref()
+            .and_then(|data| data["fee_limit_sat"].as_u64());
+
+        TransactionRepository::update_raw_data(
+            pool,
+            tx.id,
+            serde_json::json!({
+                "preimage": outgoing.preimage,
+                "amount_msat": outgoing.amount_msat,
+                "fee_msat": outgoing.fee_msat,
+                "fee_limit_sat": fee_limit_sat,
+            }),
+        )
+        .await?;
+
+        let tx =
+            TransactionRepository::update_status(pool, tx.id, TransactionStatus::Confirmed, None)
+                .await?;
+
+        tracing::info!(
+            tx_id = %tx.id,
+            payment_id = %tx.payment_id,
+            tx_type = ?tx.tx_type,
+            amount_sat = tx.amount,
+            fee_msat = outgoing.fee_msat,
+            "Lightning payment sent"
+        );
+
+        if tx.tx_type == TransactionType::Refund {
+            let payment = PaymentRepository::find_by_id(pool, tx.payment_id).await?;
+            let refunded: i64 = TransactionRepository::find_by_payment_id(pool, payment.id)
+                .await?
+                .iter()
+                .filter(|t| {
+                    t.tx_type == TransactionType::Refund && t.status == TransactionStatus::Confirmed
+                })
+                .map(|t| t.amount)
+                .sum();
+
+            if refunded >= payment.amount {
+                PaymentRepository::update_status(pool, payment.id, PaymentStatus::Refunded).await?;
+            }
+        }
+
+        Ok(tx)
+    }
+
+    /// Derive the next deposit address for a chain and record it for monitoring
+    pub async fn allocate_deposit_address(
+        &self,
+        pool: &PgPool,
+        chain: ChainType,
+        payment_id: Option<Uuid>,
+        expected_amount: Option<i64>,
+        label: Option<&str>,
+        token_address: Option<&str>,
+    ) -> AppResult<CryptoAddress> {
+        if !self.hd_wallet.is_configured(&chain) {
+            return Err(AppError::Payment(format!(
+                "HD wallet not configured for {} address generation",
+                chain
+            )));
+        }
+
+        let index = AddressRepository::next_derivation_index(pool, &chain).await?;
+        let derived = self.generate_deposit_address(&chain, index)?;
+
+        AddressRepository::create(
+            pool,
+            &derived.address,
+            chain,
+            payment_id,
+            expected_amount,
+            label,
+            token_address,
+            Some(&derived.derivation_path),
+            Some(index),
+        )
+        .await
+    }
+
+    fn generate_deposit_address(&self, chain: &ChainType, index: i32) -> AppResult<DerivedAddress> {
+        let index = u32::try_from(index)
+            .map_err(|_| AppError::Internal(format!("Invalid derivation index: {}", index)))?;
+
+        match chain {
+            ChainType::Evm(_) => {
+                self.hd_wallet.derive_evm_address(index)
+            }
+            ChainType::Solana => self.hd_wallet.derive_solana_address(index),
+            ChainType::Bitcoin => {
+                let service = self
+                    .bitcoin
+                    .as_ref()
+                    .ok_or_else(|| AppError::Payment("bitcoin not configured".to_string()))?;
+                self.hd_wallet.derive_bitcoin_address(service.network(), index)
             }
-            ChainType::Bitcoin => Err(AppError::Payment(
-                "Bitcoin address generation not implemented".to_string(),
-            )),
         }
     }
 
@@ -396,15 +1028,43 @@ impl PaymentProcessor {
 
         // Verify based on chain
         match chain {
-            ChainType::Ethereum | ChainType::Polygon | ChainType::Bsc | ChainType::Arbitrum => {
+            ChainType::Evm(_) => {
                 let service = self
                     .get_evm_service(&chain)
                     .ok_or_else(|| AppError::Payment(format!("{} not configured", chain)))?;
 
-                let amount_wei = ethers::types::U256::from(payment.amount as u128);
-                let verification = service
-                    .verify_payment(tx_hash, to_address, amount_wei)
-                    .await?;
+                let (verification, required_confirmations, amount) =
+                    match service.token_for_currency(&payment.currency) {
+                        Some(token) => {
+                            let expected = token.amount_to_base_units(payment.amount);
+                            let verification = service
+                                .verify_token_payment(tx_hash, &token, to_address, expected)
+                                .await?;
+                            let amount = token.base_units_to_amount(verification.actual_amount);
+                            (
+                                verification,
+                                service.get_required_token_confirmations(payment.amount),
+                                amount,
+                            )
+                        }
+                        None if matches!(payment.currency, CurrencyType::USDT | CurrencyType::USDC) => {
+                            return Err(AppError::Payment(format!(
+                                "{:?} is not supported on {}",
+                                payment.currency, chain
+                            )));
+                        }
+                        None => {
+                            let amount_wei = ethers::types::U256::from(payment.amount as u128);
+                            let verification = service
+                                .verify_payment(tx_hash, to_address, amount_wei)
+                                .await?;
+                            let amount = verification
+                                .actual_amount
+                                .min(ethers::types::U256::from(i64::MAX as u64))
+                                .as_u64() as i64;
+                            (verification, service.get_required_confirmations(amount_wei), amount)
+                        }
+                    };
 
                 if verification.is_valid {
                     // Update payment status
@@ -418,8 +1078,29 @@ impl PaymentProcessor {
                     )
                     .await?;
 
+                    Self::record_verified_transaction(
+                        pool,
+                        &payment,
+                        chain_str,
+                        &DepositUpdate {
+                            tx_hash: tx_hash.to_string(),
+                            amount,
+                            block_number: verification.block_number.map(|n| n as i64),
+                            block_hash: verification.block_hash.clone(),
+                            from_address: verification.from_address.clone(),
+                            to_address: to_address.clone(),
+                            confirmations: verification.confirmations.min(i32::MAX as u64) as i32,
+                            status: confirmation_status(
+                                verification.confirmations,
+                                required_confirmations,
+                            ),
+                        },
+                        required_confirmations,
+                    )
+                    .await?;
+
                     let new_status = if verification.confirmations
-                        >= service.get_required_confirmations(amount_wei) as u64
+                        >= required_confirmations as u64
                     {
                         PaymentStatus::Completed
                     } else {
@@ -432,22 +1113,108 @@ impl PaymentProcessor {
                 }
             }
             ChainType::Solana => {
-                let verification = self
-                    .solana
-                    .verify_payment(tx_hash, to_address, payment.amount as u64)?;
+                let (verification, required) = if payment.currency == CurrencyType::USDC {
+                    let verification = self
+                        .solana
+                        .verify_token_payment(
+                            tx_hash,
+                            to_address,
+                            self.solana.usdc_mint(),
+                            payment.amount as u64,
+                        )
+                        .await?;
+                    let required =
+                        SolanaService::get_required_token_confirmations(payment.amount as u64);
+                    (verification, required)
+                } else {
+                    let verification = self
+                        .solana
+                        .verify_payment(tx_hash, to_address, payment.amount as u64)
+                        .await?;
+                    let required = SolanaService::get_required_confirmations(payment.amount as u64);
+                    (verification, required)
+                };
 
                 if verification.is_valid {
                     PaymentRepository::update_crypto_details(
                         pool,
                         payment_id,
                         Some(tx_hash),
+                        verification.from_address.as_deref(),
+                        None,
                         None,
+                    )
+                    .await?;
+
+                    Self::record_verified_transaction(
+                        pool,
+                        &payment,
+                        chain_str,
+                        &DepositUpdate {
+                            tx_hash: tx_hash.to_string(),
+                            amount: verification.actual_amount.min(i64::MAX as u64) as i64,
+                            block_number: Some(verification.slot as i64),
+                            block_hash: None,
+                            from_address: verification.from_address.clone().unwrap_or_default(),
+                            to_address: to_address.clone(),
+                            confirmations: verification.confirmations.min(i32::MAX as u64) as i32,
+                            status: confirmation_status(verification.confirmations, required),
+                        },
+                        required,
+                    )
+                    .await?;
+
+                    let new_status = if verification.confirmations >= required as u64 {
+                        PaymentStatus::Completed
+                    } else {
+                        PaymentStatus::Processing
+                    };
+
+                    PaymentRepository::update_status(pool, payment_id, new_status).await
+                } else {
+                    Err(AppError::Payment("Payment verification failed".to_string()))
+                }
+            }
+            ChainType::Bitcoin => {
+                let service = self
+                    .bitcoin
+                    .as_ref()
+                    .ok_or_else(|| AppError::Payment("bitcoin not configured".to_string()))?;
+
+                let verification = service
+                    .verify_payment(tx_hash, to_address, payment.amount as u64)
+                    .await?;
+
+                if verification.is_valid {
+                    PaymentRepository::update_crypto_details(
+                        pool,
+                        payment_id,
+                        Some(tx_hash),
+                        verification.from_address.as_deref(),
                         None,
                         None,
                     )
                     .await?;
 
-                    let required = SolanaService::get_required_confirmations(payment.amount as u64);
+                    let required = BitcoinService::get_required_confirmations(payment.amount as u64);
+                    Self::record_verified_transaction(
+                        pool,
+                        &payment,
+                        chain_str,
+                        &DepositUpdate {
+                            tx_hash: tx_hash.to_string(),
+                            amount: verification.actual_amount.min(i64::MAX as u64) as i64,
+                            block_number: verification.block_height.map(|n| n as i64),
+                            block_hash: verification.block_hash.clone(),
+                            from_address: verification.from_address.clone().unwrap_or_default(),
+                            to_address: to_address.clone(),
+                            confirmations: verification.confirmations.min(i32::MAX as u64) as i32,
+                            status: confirmation_status(verification.confirmations, required),
+                        },
+                        required,
+                    )
+                    .await?;
+
                     let new_status = if verification.confirmations >= required as u64 {
                         PaymentStatus::Completed
                     } else {
@@ -459,9 +1226,172 @@ impl PaymentProcessor {
                     Err(AppError::Payment("Payment verification failed".to_string()))
                 }
             }
-            _ => Err(AppError::Payment("Unsupported chain for verification".to_string())),
         }
     }
+
+    /// Current confirmation count of a transaction on any supported chain
+    pub async fn get_confirmations(&self, chain: &ChainType, tx_hash: &str) -> AppResult<u64> {
+        match chain {
+            ChainType::Evm(_) => {
+                let service = self
+                    .get_evm_service(chain)
+                    .ok_or_else(|| AppError::Payment(format!("{} not configured", chain)))?;
+                service.get_confirmations(tx_hash).await
+            }
+            ChainType::Solana => self.solana.get_confirmations(tx_hash).await,
+            ChainType::Bitcoin => {
+                let service = self
+                    .bitcoin
+                    .as_ref()
+                    .ok_or_else(|| AppError::Payment("bitcoin not configured".to_string()))?;
+                service.get_confirmations(tx_hash).await
+            }
+        }
+    }
+
+    /// Where a transaction currently sits on its chain, used to detect reorganizations
+    pub async fn get_inclusion(&self, chain: &ChainType, tx_hash: &str) -> AppResult<TxInclusion> {
+        match chain {
+            ChainType::Evm(_) => {
+                let service = self
+                    .get_evm_service(chain)
+                    .ok_or_else(|| AppError::Payment(format!("{} not configured", chain)))?;
+
+                if let Some(receipt) = service.get_transaction_receipt(tx_hash).await? {
+                    if let Some(number) = receipt.block_number {
+                        return Ok(TxInclusion::Included(BlockRef {
+                            number: number.as_u64(),
+                            hash: receipt.block_hash.map(|h| format!("{:?}", h)),
+                        }));
+                    }
+                }
+
+                match service.get_transaction(tx_hash).await? {
+                    Some(_) => Ok(TxInclusion::Pending),
+                    None => Ok(TxInclusion::Dropped),
+                }
+            }
+            ChainType::Solana => match self.solana.get_signature_slot(tx_hash).await? {
+                Some(slot) => Ok(TxInclusion::Included(BlockRef {
+                    number: slot,
+                    hash: None,
+                })),
+                // Missing from the ledger history too; a pruned node looks the same
+                None => Ok(TxInclusion::Unknown),
+            },
+            ChainType::Bitcoin => {
+                let service = self
+                    .bitcoin
+                    .as_ref()
+                    .ok_or_else(|| AppError::Payment("bitcoin not configured".to_string()))?;
+
+                match service.get_transaction(tx_hash).await? {
+                    Some(tx) => match tx.status.block_height {
+                        Some(number) if tx.status.confirmed => Ok(TxInclusion::Included(BlockRef {
+                            number,
+                            hash: tx.status.block_hash,
+                        })),
+                        _ => Ok(TxInclusion::Pending),
+                    },
+                    None => Ok(TxInclusion::Dropped),
+                }
+            }
+        }
+    }
+
+    /// Record a verified on-chain transaction as a deposit to the payment's address so
+    /// the confirmation tracker can follow it, crediting the address just as the chain
+    /// scanner would
+    async fn record_verified_transaction(
+        pool: &PgPool,
+        payment: &Payment,
+        chain: &str,
+        deposit: &DepositUpdate,
+        required_confirmations: i32,
+    ) -> AppResult<()> {
+        let Some(address) = AddressRepository::find_by_payment_id(pool, payment.id).await? else {
+            return Ok(());
+        };
+
+        match TransactionRepository::record_deposit(
+            pool,
+            payment.id,
+            &payment.currency.to_string(),
+            chain,
+            address.id,
+            required_confirmations,
+            deposit,
+        )
+        .await?
+        {
+            Some((_, address)) => {
+                let expected = address.expected_amount.unwrap_or(payment.amount);
+                if address.received_amount.unwrap_or(0) >= expected {
+                    // Fully paid, nothing more to watch for on this address
+                    AddressRepository::deactivate(pool, address.id).await?;
+                }
+            }
+            // Already recorded, e.g. by the chain scanner
+            None => {
+                if let Some(tx) = TransactionRepository::find_by_tx_hash_and_address(
+                    pool,
+                    &deposit.tx_hash,
+                    &deposit.to_address,
+                )
+                .await?
+                {
+                    TransactionRepository::update_confirmations(
+                        pool,
+                        tx.id,
+                        deposit.confirmations.max(tx.confirmations),
+                        deposit.status.clone(),
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Status of a deposit with `confirmations` of the `required` confirmations
+fn confirmation_status(confirmations: u64, required: i32) -> TransactionStatus {
+    if confirmations >= required.max(0) as u64 {
+        TransactionStatus::Confirmed
+    } else {
+        TransactionStatus::Confirming
+    }
+}
+
+/// Block a transaction was included in; Solana slots carry no hash
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockRef {
+    pub number: u64,
+    pub hash: Option<String>,
+}
+
+impl BlockRef {
+    /// Same block, comparing hashes only when both sides know them
+    pub fn is_same_block(&self, other: &BlockRef) -> bool {
+        self.number == other.number
+            && match (&self.hash, &other.hash) {
+                (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+                _ => true,
+            }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxInclusion {
+    Included(BlockRef),
+    /// Known to the node but not in any block (e.g. back in the mempool)
+    Pending,
+    /// No longer known to the chain
+    Dropped,
+    /// The node cannot say either way, e.g. a Solana signature outside its history.
+    /// Never treated as a reorganization.
+    Unknown,
 }
 
 #[derive(Debug, Clone)]
@@ -477,3 +1407,49 @@ pub struct PaymentCreationResult {
 }
 
 pub type SharedPaymentProcessor = Arc<PaymentProcessor>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Point DATABASE_URL at a migrated database, then run
+    /// `cargo test payment_processor -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a migrated DATABASE_URL"]
+    async fn test_refused_hold_invoice_stores_nothing() {
+        // Core Lightning has no hold invoices, so the node is never contacted
+        let mut config = Config::for_tests();
+        config.lightning.backend = "cln".to_string();
+        config.lightning.node_url = "http://127.0.0.1:9".to_string();
+        config.lightning.rune = Some("test-rune".to_string());
+
+        let pool = PgPool::connect(&config.database.url).await.unwrap();
+        let processor = PaymentProcessor::new(&config).await.unwrap();
+        let description = format!("hold {}", Uuid::new_v4());
+
+        let result = processor
+            .create_hold_payment(
+                &pool,
+                &CreatePaymentRequest {
+                    amount: 1_500,
+                    currency: CurrencyType::BTC,
+                    method: PaymentMethod::Lightning,
+                    chain: None,
+                    description: Some(description.clone()),
+                    customer_email: None,
+                    customer_phone: None,
+                    metadata: None,
+                    callback_url: None,
+                },
+            )
+            .await;
+        assert!(result.is_err());
+
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payments WHERE description = $1")
+            .bind(&description)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+    }
+}
@@ -1,10 +1,12 @@
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, RequestBuilder, StatusCode};
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::config::RazorpayConfig;
 use crate::error::{AppError, AppResult};
 
 const RAZORPAY_API_URL: &str = "https://api.razorpay.com/v1";
+/// Linked accounts are only served by v2 of the API
+const RAZORPAY_API_V2_URL: &str = "https://api.razorpay.com/v2";
 
 #[derive(Clone)]
 pub struct RazorpayClient {
@@ -12,6 +14,7 @@ pub struct RazorpayClient {
     key_id: String,
     key_secret: String,
     webhook_secret: String,
+    payout_account_number: Option<String>,
 }
 
 impl RazorpayClient {
@@ -26,38 +29,96 @@ impl RazorpayClient {
             key_id: config.key_id.clone(),
             key_secret: config.key_secret.clone(),
             webhook_secret: config.webhook_secret.clone(),
+            payout_account_number: config.payout_account_number.clone(),
         }
     }
 
+    /// Public key ID, which Checkout is opened with
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
     pub fn webhook_secret(&self) -> &str {
         &self.webhook_secret
     }
 
+    /// RazorpayX account payouts are sent from, if configured
+    pub fn payout_account_number(&self) -> Option<&str> {
+        self.payout_account_number.as_deref()
+    }
+
     pub async fn get<T: DeserializeOwned>(&self, endpoint: &str) -> AppResult<T> {
         let url = format!("{}{}", RAZORPAY_API_URL, endpoint);
+        self.send(self.http_client.get(&url)).await
+    }
 
-        let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.key_id, Some(&self.key_secret))
-            .send()
-            .await?;
+    pub async fn post<T: DeserializeOwned, B: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> AppResult<T> {
+        let url = format!("{}{}", RAZORPAY_API_URL, endpoint);
+        self.send(self.http_client.post(&url).json(body)).await
+    }
 
-        self.handle_response(response).await
+    pub async fn patch<T: DeserializeOwned, B: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> AppResult<T> {
+        let url = format!("{}{}", RAZORPAY_API_URL, endpoint);
+        self.send(self.http_client.patch(&url).json(body)).await
     }
 
-    pub async fn post<T: DeserializeOwned, B: Serialize>(
+    pub async fn put<T: DeserializeOwned, B: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> AppResult<T> {
+        let url = format!("{}{}", RAZORPAY_API_URL, endpoint);
+        self.send(self.http_client.put(&url).json(body)).await
+    }
+
+    pub async fn delete<T: DeserializeOwned>(&self, endpoint: &str) -> AppResult<T> {
+        let url = format!("{}{}", RAZORPAY_API_URL, endpoint);
+        self.send(self.http_client.delete(&url)).await
+    }
+
+    /// POST that Razorpay performs at most once per `idempotency_key`, so it
+    /// can be retried safely when the outcome of an earlier attempt is unknown
+    pub async fn post_idempotent<T: DeserializeOwned, B: Serialize>(
         &self,
         endpoint: &str,
         body: &B,
+        idempotency_key: &str,
     ) -> AppResult<T> {
         let url = format!("{}{}", RAZORPAY_API_URL, endpoint);
+        self.send(
+            self.http_client
+                .post(&url)
+                .header("X-Payout-Idempotency", idempotency_key)
+                .json(body),
+        )
+        .await
+    }
+
+    pub async fn get_v2<T: DeserializeOwned>(&self, endpoint: &str) -> AppResult<T> {
+        let url = format!("{}{}", RAZORPAY_API_V2_URL, endpoint);
+        self.send(self.http_client.get(&url)).await
+    }
+
+    pub async fn post_v2<T: DeserializeOwned, B: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> AppResult<T> {
+        let url = format!("{}{}", RAZORPAY_API_V2_URL, endpoint);
+        self.send(self.http_client.post(&url).json(body)).await
+    }
 
-        let response = self
-            .http_client
-            .post(&url)
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> AppResult<T> {
+        let response = request
             .basic_auth(&self.key_id, Some(&self.key_secret))
-            .json(body)
             .send()
             .await?;
 
@@ -93,11 +154,23 @@ impl RazorpayClient {
                 _ => format!("API error: {}", status),
             };
 
-            Err(AppError::Razorpay(error_msg))
+            if rejects_request(status) {
+                Err(AppError::RazorpayRejected(error_msg))
+            } else {
+                Err(AppError::Razorpay(error_msg))
+            }
         }
     }
 }
 
+/// Whether Razorpay answering `status` means it did not act on the request.
+/// A 5xx may come from a gateway after Razorpay did, and a 408 or 409 may
+/// answer a request that is still being processed.
+fn rejects_request(status: StatusCode) -> bool {
+    status.is_client_error()
+        && !matches!(status, StatusCode::REQUEST_TIMEOUT | StatusCode::CONFLICT)
+}
+
 #[derive(Debug, serde::Deserialize)]
 struct RazorpayError {
     error: RazorpayErrorDetail,
@@ -108,3 +181,20 @@ struct RazorpayErrorDetail {
     code: String,
     description: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_request() {
+        assert!(rejects_request(StatusCode::BAD_REQUEST));
+        assert!(rejects_request(StatusCode::UNAUTHORIZED));
+        assert!(rejects_request(StatusCode::TOO_MANY_REQUESTS));
+
+        assert!(!rejects_request(StatusCode::CONFLICT));
+        assert!(!rejects_request(StatusCode::REQUEST_TIMEOUT));
+        assert!(!rejects_request(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!rejects_request(StatusCode::GATEWAY_TIMEOUT));
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use super::RazorpayClient;
+use crate::error::AppResult;
+use crate::models::CustomerDetails;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateCustomerRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    /// `0` returns the existing customer with the same email and contact
+    /// instead of failing
+    pub fail_existing: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gstin: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateCustomerRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpayCustomer {
+    pub id: String,
+    pub entity: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub contact: Option<String>,
+    pub gstin: Option<String>,
+    pub notes: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+impl RazorpayCustomer {
+    pub fn to_details(&self) -> CustomerDetails {
+        CustomerDetails {
+            name: self.name.clone(),
+            email: self.email.clone(),
+            contact: self.contact.clone(),
+            gstin: self.gstin.clone(),
+            notes: self.notes.clone(),
+        }
+    }
+}
+
+/// Card or other payment instrument saved for a customer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayToken {
+    pub id: String,
+    pub entity: String,
+    pub token: String,
+    pub method: String,
+    pub bank: Option<String>,
+    pub wallet: Option<String>,
+    pub card: Option<RazorpayTokenCard>,
+    pub vpa: Option<serde_json::Value>,
+    /// Usable for recurring payments
+    #[serde(default)]
+    pub recurring: bool,
+    pub auth_type: Option<String>,
+    pub used_at: Option<i64>,
+    pub expired_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayTokenCard {
+    pub name: Option<String>,
+    pub last4: String,
+    pub network: Option<String>,
+    #[serde(rename = "type")]
+    pub card_type: Option<String>,
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub international: bool,
+    #[serde(default)]
+    pub emi: bool,
+    /// A number or a string, depending on the card
+    pub expiry_month: Option<serde_json::Value>,
+    pub expiry_year: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokensResponse {
+    pub entity: String,
+    pub count: i32,
+    pub items: Vec<RazorpayToken>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedTokenResponse {
+    pub deleted: bool,
+}
+
+impl RazorpayClient {
+    pub async fn create_customer(
+        &self,
+        request: &CreateCustomerRequest,
+    ) -> AppResult<RazorpayCustomer> {
+        self.post("/customers", request).await
+    }
+
+    pub async fn get_customer(&self, customer_id: &str) -> AppResult<RazorpayCustomer> {
+        self.get(&format!("/customers/{}", customer_id)).await
+    }
+
+    pub async fn update_customer(
+        &self,
+        customer_id: &str,
+        request: &UpdateCustomerRequest,
+    ) -> AppResult<RazorpayCustomer> {
+        self.put(&format!("/customers/{}", customer_id), request)
+            .await
+    }
+
+    /// Cards and other instruments the customer saved at checkout
+    pub async fn get_customer_tokens(&self, customer_id: &str) -> AppResult<TokensResponse> {
+        self.get(&format!("/customers/{}/tokens", customer_id))
+            .await
+    }
+
+    pub async fn delete_customer_token(
+        &self,
+        customer_id: &str,
+        token_id: &str,
+    ) -> AppResult<DeletedTokenResponse> {
+        self.delete(&format!("/customers/{}/tokens/{}", customer_id, token_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_saved_card_tokens() {
+        let response: TokensResponse = serde_json::from_value(serde_json::json!({
+            "entity": "collection",
+            "count": 1,
+            "items": [{
+                "id": "token_00000000000001",
+                "entity": "token",
+                "token": "EhYXHrLsJdwRhM",
+                "bank": null,
+                "wallet": null,
+                "method": "card",
+                "card": {
+                    "entity": "card",
+                    "name": "Gaurav Kumar",
+                    "last4": "1111",
+                    "network": "Visa",
+                    "type": "debit",
+                    "issuer": null,
+                    "international": false,
+                    "emi": false,
+                    "sub_type": "consumer",
+                    "expiry_month": 12,
+                    "expiry_year": 2030
+                },
+                "recurring": true,
+                "auth_type": null,
+                "mrn": null,
+                "used_at": 1_700_000_000,
+                "created_at": 1_700_000_000,
+                "expired_at": 1_924_905_600,
+                "dcc_enabled": false
+            }]
+        }))
+        .unwrap();
+
+        let token = &response.items[0];
+        let card = token.card.as_ref().unwrap();
+
+        assert_eq!(token.method, "card");
+        assert!(token.recurring);
+        assert_eq!(card.last4, "1111");
+        assert_eq!(card.card_type.as_deref(), Some("debit"));
+    }
+}
@@ -1,11 +1,25 @@
 mod client;
+mod customers;
 mod orders;
+mod payment_links;
 mod payments;
+mod payouts;
+mod qr_codes;
+mod route;
+mod subscriptions;
+mod virtual_accounts;
 mod webhooks;
 
 pub use client::RazorpayClient;
+pub use customers::*;
 pub use orders::*;
+pub use payment_links::*;
 pub use payments::*;
+pub use payouts::*;
+pub use qr_codes::*;
+pub use route::*;
+pub use subscriptions::*;
+pub use virtual_accounts::*;
 pub use webhooks::*;
 
 use std::sync::Arc;
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use super::RazorpayClient;
+use super::{RazorpayClient, TransferRequest};
 use crate::error::AppResult;
 
 #[derive(Debug, Clone, Serialize)]
@@ -13,6 +13,12 @@ pub struct CreateOrderRequest {
     pub notes: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub partial_payment: Option<bool>,
+    /// Route transfers made once the order's payment is captured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfers: Option<Vec<TransferRequest>>,
+    /// Razorpay customer (`cust_...`) whose saved cards checkout offers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+use super::RazorpayClient;
+use crate::error::AppResult;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatePaymentLinkRequest {
+    pub amount: i64,
+    pub currency: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Our own identifier for the link, unique per account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer: Option<PaymentLinkCustomer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify: Option<PaymentLinkNotify>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reminder_enable: Option<bool>,
+    /// Unix time after which the link can no longer be paid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_by: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentLinkCustomer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+}
+
+/// Whether Razorpay sends the link to the customer itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentLinkNotify {
+    pub sms: bool,
+    pub email: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpayPaymentLink {
+    pub id: String,
+    pub short_url: String,
+    pub status: String,
+    pub amount: i64,
+    pub amount_paid: i64,
+    pub currency: String,
+    pub description: Option<String>,
+    pub reference_id: Option<String>,
+    pub expire_by: Option<i64>,
+    pub expired_at: Option<i64>,
+    pub cancelled_at: Option<i64>,
+    /// Order Razorpay created for the link's payments
+    pub order_id: Option<String>,
+    pub payments: Option<Vec<PaymentLinkPayment>>,
+    pub notes: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaymentLinkPayment {
+    pub payment_id: String,
+    pub amount: i64,
+    pub status: String,
+    pub method: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaymentLinksResponse {
+    pub payment_links: Vec<RazorpayPaymentLink>,
+}
+
+impl RazorpayClient {
+    pub async fn create_payment_link(
+        &self,
+        request: &CreatePaymentLinkRequest,
+    ) -> AppResult<RazorpayPaymentLink> {
+        self.post("/payment_links", request).await
+    }
+
+    pub async fn get_payment_link(&self, payment_link_id: &str) -> AppResult<RazorpayPaymentLink> {
+        self.get(&format!("/payment_links/{}", payment_link_id))
+            .await
+    }
+
+    pub async fn cancel_payment_link(
+        &self,
+        payment_link_id: &str,
+    ) -> AppResult<RazorpayPaymentLink> {
+        self.post(
+            &format!("/payment_links/{}/cancel", payment_link_id),
+            &serde_json::json!({}),
+        )
+        .await
+    }
+
+    /// List payment links, optionally only those with our `reference_id`
+    pub async fn list_payment_links(
+        &self,
+        reference_id: Option<&str>,
+    ) -> AppResult<PaymentLinksResponse> {
+        self.get(&list_endpoint(reference_id)).await
+    }
+}
+
+fn list_endpoint(reference_id: Option<&str>) -> String {
+    match reference_id {
+        Some(reference_id) => format!("/payment_links?reference_id={}", reference_id),
+        None => "/payment_links".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_endpoint() {
+        assert_eq!(list_endpoint(None), "/payment_links");
+        assert_eq!(list_endpoint(Some("order_42")), "/payment_links?reference_id=order_42");
+    }
+
+    #[test]
+    fn test_parse_payment_links() {
+        let response: PaymentLinksResponse = serde_json::from_value(serde_json::json!({
+            "payment_links": [{
+                "id": "plink_00000000000001",
+                "short_url": "https://rzp.io/i/alnb7p0",
+                "status": "paid",
+                "amount": 50_000,
+                "amount_paid": 50_000,
+                "currency": "INR",
+                "description": "Order 42",
+                "reference_id": "order_42",
+                "expire_by": 1_700_086_400,
+                "expired_at": 0,
+                "cancelled_at": 0,
+                "order_id": "order_00000000000001",
+                "payments": [{
+                    "payment_id": "pay_00000000000001",
+                    "amount": 50_000,
+                    "status": "captured",
+                    "method": "upi",
+                    "created_at": 1_700_000_100
+                }],
+                "notes": null,
+                "created_at": 1_700_000_000
+            }, {
+                "id": "plink_00000000000002",
+                "short_url": "https://rzp.io/i/bkq9r2x",
+                "status": "created",
+                "amount": 12_500,
+                "amount_paid": 0,
+                "currency": "INR",
+                "description": null,
+                "reference_id": null,
+                "expire_by": null,
+                "expired_at": null,
+                "cancelled_at": null,
+                "order_id": null,
+                "payments": null,
+                "notes": {},
+                "created_at": 1_700_000_000
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(response.payment_links.len(), 2);
+
+        let paid = &response.payment_links[0];
+        assert_eq!(paid.reference_id.as_deref(), Some("order_42"));
+        let payments = paid.payments.as_ref().unwrap();
+        assert_eq!(payments[0].payment_id, "pay_00000000000001");
+        assert_eq!(payments[0].amount, paid.amount_paid);
+
+        assert!(response.payment_links[1].payments.is_none());
+    }
+}
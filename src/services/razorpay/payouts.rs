@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use super::RazorpayClient;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    payout_transaction_status, FundAccountDetails, PayoutMode, PayoutUpdate,
+    RazorpayPayoutStatusDetails,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateContactRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    /// `customer`, `vendor`, `employee` or `self`
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub contact_type: Option<String>,
+    /// Our own identifier for the contact
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayContact {
+    pub id: String,
+    pub entity: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub contact: Option<String>,
+    #[serde(rename = "type")]
+    pub contact_type: Option<String>,
+    pub reference_id: Option<String>,
+    pub active: bool,
+    pub notes: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateFundAccountRequest {
+    pub contact_id: String,
+    #[serde(flatten)]
+    pub details: FundAccountDetails,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayFundAccount {
+    pub id: String,
+    pub entity: String,
+    pub contact_id: String,
+    /// `bank_account` or `vpa`
+    pub account_type: String,
+    pub bank_account: Option<RazorpayFundAccountBank>,
+    pub vpa: Option<RazorpayFundAccountVpa>,
+    pub active: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayFundAccountBank {
+    pub ifsc: Option<String>,
+    pub bank_name: Option<String>,
+    pub name: Option<String>,
+    pub account_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayFundAccountVpa {
+    pub username: Option<String>,
+    pub handle: Option<String>,
+    pub address: Option<String>,
+}
+
+/// Also kept with the withdrawal transaction, so the exact request can be
+/// sent again with its idempotency key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePayoutRequest {
+    /// RazorpayX account the payout is debited from
+    pub account_number: String,
+    pub fund_account_id: String,
+    pub amount: i64,
+    /// Always `INR`
+    pub currency: String,
+    pub mode: PayoutMode,
+    /// e.g. `refund`, `payout`, `vendor bill`
+    pub purpose: String,
+    /// Queue the payout rather than fail it while the balance is too low
+    pub queue_if_low_balance: bool,
+    /// Our transaction ID
+    pub reference_id: String,
+    /// Shown on the recipient's bank statement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub narration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpayPayout {
+    pub id: String,
+    pub entity: String,
+    pub fund_account_id: String,
+    pub amount: i64,
+    pub currency: String,
+    pub status: String,
+    pub mode: Option<String>,
+    pub purpose: Option<String>,
+    pub utr: Option<String>,
+    pub fees: Option<i64>,
+    pub tax: Option<i64>,
+    pub reference_id: Option<String>,
+    pub narration: Option<String>,
+    pub status_details: Option<RazorpayPayoutStatusDetails>,
+    pub notes: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+impl RazorpayPayout {
+    pub fn to_update(&self) -> AppResult<PayoutUpdate> {
+        Ok(PayoutUpdate {
+            razorpay_payout_id: self.id.clone(),
+            fund_account_id: self.fund_account_id.clone(),
+            status: payout_transaction_status(&self.status).ok_or_else(|| {
+                AppError::Razorpay(format!("Unknown payout status: {}", self.status))
+            })?,
+            razorpay_status: self.status.clone(),
+            utr: self.utr.clone(),
+            fees: self.fees,
+            failure_reason: self
+                .status_details
+                .as_ref()
+                .and_then(|details| details.description.clone()),
+        })
+    }
+}
+
+impl RazorpayClient {
+    pub async fn create_contact(
+        &self,
+        request: &CreateContactRequest,
+    ) -> AppResult<RazorpayContact> {
+        self.post("/contacts", request).await
+    }
+
+    pub async fn create_fund_account(
+        &self,
+        request: &CreateFundAccountRequest,
+    ) -> AppResult<RazorpayFundAccount> {
+        self.post("/fund_accounts", request).await
+    }
+
+    pub async fn get_fund_account(&self, fund_account_id: &str) -> AppResult<RazorpayFundAccount> {
+        self.get(&format!("/fund_accounts/{}", fund_account_id))
+            .await
+    }
+
+    /// Create a payout, or return the one already created for `idempotency_key`
+    pub async fn create_payout(
+        &self,
+        request: &CreatePayoutRequest,
+        idempotency_key: &str,
+    ) -> AppResult<RazorpayPayout> {
+        self.post_idempotent("/payouts", request, idempotency_key)
+            .await
+    }
+
+    pub async fn get_payout(&self, payout_id: &str) -> AppResult<RazorpayPayout> {
+        self.get(&format!("/payouts/{}", payout_id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::VpaDetails;
+
+    #[test]
+    fn test_fund_account_request_body() {
+        let request = CreateFundAccountRequest {
+            contact_id: "cont_00000000000001".to_string(),
+            details: FundAccountDetails::Vpa {
+                vpa: VpaDetails {
+                    address: "gaurav@exampleupi".to_string(),
+                },
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "contact_id": "cont_00000000000001",
+                "account_type": "vpa",
+                "vpa": { "address": "gaurav@exampleupi" }
+            })
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use super::{RazorpayClient, RazorpayPayment};
+use crate::error::AppResult;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateQrCodeRequest {
+    /// Always `upi_qr`
+    #[serde(rename = "type")]
+    pub qr_type: String,
+    /// Shown to the customer in their UPI app
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// `single_use` or `multiple_use`
+    pub usage: String,
+    /// Only `payment_amount` can be paid; required for single use
+    pub fixed_amount: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_amount: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<String>,
+    /// Unix time at which Razorpay closes the QR code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close_by: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+impl CreateQrCodeRequest {
+    /// QR code for exactly one payment of `amount`
+    pub fn single_use(amount: i64) -> Self {
+        Self {
+            qr_type: "upi_qr".to_string(),
+            name: None,
+            usage: "single_use".to_string(),
+            fixed_amount: true,
+            payment_amount: Some(amount),
+            description: None,
+            customer_id: None,
+            close_by: None,
+            notes: None,
+        }
+    }
+
+    /// QR code taking any number of payments, of any amount unless `amount`
+    /// fixes it
+    pub fn multiple_use(amount: Option<i64>) -> Self {
+        Self {
+            qr_type: "upi_qr".to_string(),
+            name: None,
+            usage: "multiple_use".to_string(),
+            fixed_amount: amount.is_some(),
+            payment_amount: amount,
+            description: None,
+            customer_id: None,
+            close_by: None,
+            notes: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpayQrCode {
+    pub id: String,
+    pub entity: String,
+    pub name: Option<String>,
+    pub usage: String,
+    pub fixed_amount: bool,
+    pub payment_amount: Option<i64>,
+    pub payments_amount_received: i64,
+    pub payments_count_received: i64,
+    pub status: String,
+    pub image_url: String,
+    pub description: Option<String>,
+    pub customer_id: Option<String>,
+    pub close_by: Option<i64>,
+    pub closed_at: Option<i64>,
+    pub close_reason: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QrCodePaymentsResponse {
+    pub entity: String,
+    pub count: i32,
+    pub items: Vec<RazorpayPayment>,
+}
+
+impl RazorpayClient {
+    pub async fn create_qr_code(&self, request: &CreateQrCodeRequest) -> AppResult<RazorpayQrCode> {
+        self.post("/payments/qr_codes", request).await
+    }
+
+    pub async fn get_qr_code(&self, qr_code_id: &str) -> AppResult<RazorpayQrCode> {
+        self.get(&format!("/payments/qr_codes/{}", qr_code_id))
+            .await
+    }
+
+    /// Stop accepting payments to the QR code
+    pub async fn close_qr_code(&self, qr_code_id: &str) -> AppResult<RazorpayQrCode> {
+        self.post(
+            &format!("/payments/qr_codes/{}/close", qr_code_id),
+            &serde_json::json!({}),
+        )
+        .await
+    }
+
+    pub async fn get_qr_code_payments(
+        &self,
+        qr_code_id: &str,
+    ) -> AppResult<QrCodePaymentsResponse> {
+        self.get(&format!("/payments/qr_codes/{}/payments", qr_code_id))
+            .await
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use super::RazorpayClient;
+use crate::error::{AppError, AppResult};
+use crate::models::{CurrencyType, TransferSplit, TransferStatus, TransferUpdate};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateLinkedAccountRequest {
+    pub email: String,
+    pub phone: String,
+    /// Always `route` for Route linked accounts
+    #[serde(rename = "type")]
+    pub account_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_id: Option<String>,
+    pub legal_business_name: String,
+    pub business_type: String,
+    pub contact_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legal_info: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayLinkedAccount {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub account_type: String,
+    pub status: String,
+    pub email: String,
+    pub legal_business_name: String,
+    pub reference_id: Option<String>,
+    pub activated_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferRequest {
+    /// Linked account ID (`acc_...`)
+    pub account: String,
+    pub amount: i64,
+    pub currency: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_hold: Option<bool>,
+    /// Unix time at which a held settlement is released
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_hold_until: Option<i64>,
+}
+
+impl TransferRequest {
+    pub fn from_split(split: &TransferSplit, currency: &str) -> Self {
+        Self {
+            account: split.account.clone(),
+            amount: split.amount,
+            currency: currency.to_string(),
+            notes: split.notes.clone(),
+            on_hold: split.on_hold.then_some(true),
+            on_hold_until: split.on_hold_until.map(|t| t.timestamp()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentTransfersRequest {
+    pub transfers: Vec<TransferRequest>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpayTransfer {
+    pub id: String,
+    pub entity: String,
+    pub status: String,
+    /// Payment, order or account the funds are transferred from
+    pub source: String,
+    pub recipient: String,
+    pub amount: i64,
+    pub currency: String,
+    pub amount_reversed: i64,
+    pub notes: Option<serde_json::Value>,
+    pub on_hold: bool,
+    pub on_hold_until: Option<i64>,
+    pub processed_at: Option<i64>,
+    pub error: Option<RazorpayTransferErrorDetail>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpayTransferErrorDetail {
+    pub code: Option<String>,
+    pub description: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl RazorpayTransfer {
+    pub fn to_update(&self) -> AppResult<TransferUpdate> {
+        Ok(TransferUpdate {
+            razorpay_transfer_id: self.id.clone(),
+            linked_account_id: self.recipient.clone(),
+            amount: self.amount,
+            currency: CurrencyType::from_razorpay(&self.currency).ok_or_else(|| {
+                AppError::Razorpay(format!("Unsupported transfer currency: {}", self.currency))
+            })?,
+            status: TransferStatus::from_razorpay(&self.status).ok_or_else(|| {
+                AppError::Razorpay(format!("Unknown transfer status: {}", self.status))
+            })?,
+            amount_reversed: self.amount_reversed,
+            on_hold: self.on_hold,
+            on_hold_until: self
+                .on_hold_until
+                .and_then(|t| DateTime::from_timestamp(t, 0)),
+            error_message: self.error.as_ref().and_then(|e| e.description.clone()),
+            processed_at: self
+                .processed_at
+                .and_then(|t| DateTime::from_timestamp(t, 0)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransfersResponse {
+    pub entity: String,
+    pub count: i32,
+    pub items: Vec<RazorpayTransfer>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReversalRequest {
+    /// Reverses the whole transfer when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpayReversal {
+    pub id: String,
+    pub entity: String,
+    pub transfer_id: String,
+    pub amount: i64,
+    pub currency: String,
+    pub notes: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferHoldRequest {
+    pub on_hold: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_hold_until: Option<i64>,
+}
+
+impl RazorpayClient {
+    pub async fn create_linked_account(
+        &self,
+        request: &CreateLinkedAccountRequest,
+    ) -> AppResult<RazorpayLinkedAccount> {
+        self.post_v2("/accounts", request).await
+    }
+
+    pub async fn get_linked_account(&self, account_id: &str) -> AppResult<RazorpayLinkedAccount> {
+        self.get_v2(&format!("/accounts/{}", account_id)).await
+    }
+
+    /// Transfer from the merchant's account balance to a linked account
+    pub async fn create_transfer(&self, request: &TransferRequest) -> AppResult<RazorpayTransfer> {
+        self.post("/transfers", request).await
+    }
+
+    pub async fn get_transfer(&self, transfer_id: &str) -> AppResult<RazorpayTransfer> {
+        self.get(&format!("/transfers/{}", transfer_id)).await
+    }
+
+    /// Transfer parts of a captured payment to linked accounts
+    pub async fn create_payment_transfers(
+        &self,
+        payment_id: &str,
+        request: &PaymentTransfersRequest,
+    ) -> AppResult<TransfersResponse> {
+        self.post(&format!("/payments/{}/transfers", payment_id), request)
+            .await
+    }
+
+    pub async fn get_payment_transfers(&self, payment_id: &str) -> AppResult<TransfersResponse> {
+        self.get(&format!("/payments/{}/transfers", payment_id))
+            .await
+    }
+
+    /// Pull funds back from the linked account
+    pub async fn reverse_transfer(
+        &self,
+        transfer_id: &str,
+        request: &ReversalRequest,
+    ) -> AppResult<RazorpayReversal> {
+        self.post(&format!("/transfers/{}/reversals", transfer_id), request)
+            .await
+    }
+
+    /// Hold or release settlement of a transfer to the linked account
+    pub async fn update_transfer_hold(
+        &self,
+        transfer_id: &str,
+        request: &TransferHoldRequest,
+    ) -> AppResult<RazorpayTransfer> {
+        self.patch(&format!("/transfers/{}", transfer_id), request)
+            .await
+    }
+}
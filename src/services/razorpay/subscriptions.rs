@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+use super::RazorpayClient;
+use crate::error::{AppError, AppResult};
+use crate::models::SubscriptionUpdate;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatePlanRequest {
+    /// `daily`, `weekly`, `monthly` or `yearly`
+    pub period: String,
+    /// Number of periods between charges
+    pub interval: i32,
+    pub item: PlanItem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanItem {
+    pub name: String,
+    pub amount: i64,
+    pub currency: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayPlan {
+    pub id: String,
+    pub entity: String,
+    pub interval: i32,
+    pub period: String,
+    pub item: RazorpayPlanItem,
+    pub notes: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RazorpayPlanItem {
+    pub id: String,
+    pub name: String,
+    pub amount: i64,
+    pub currency: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateSubscriptionRequest {
+    pub plan_id: String,
+    /// Number of billing cycles to charge
+    pub total_count: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_notify: Option<bool>,
+    /// Unix time of the first charge; charged on authentication when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpaySubscription {
+    pub id: String,
+    pub entity: String,
+    pub plan_id: String,
+    pub customer_id: Option<String>,
+    pub status: String,
+    pub current_start: Option<i64>,
+    pub current_end: Option<i64>,
+    pub ended_at: Option<i64>,
+    pub quantity: i32,
+    pub total_count: i32,
+    pub paid_count: i32,
+    pub charge_at: Option<i64>,
+    pub start_at: Option<i64>,
+    pub end_at: Option<i64>,
+    pub short_url: Option<String>,
+    pub notes: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+impl RazorpaySubscription {
+    pub fn to_update(&self) -> AppResult<SubscriptionUpdate> {
+        SubscriptionUpdate::from_razorpay(
+            &self.status,
+            self.paid_count,
+            self.current_start,
+            self.current_end,
+            self.ended_at,
+        )
+        .map_err(AppError::Razorpay)
+    }
+}
+
+impl RazorpayClient {
+    pub async fn create_plan(&self, request: &CreatePlanRequest) -> AppResult<RazorpayPlan> {
+        self.post("/plans", request).await
+    }
+
+    pub async fn get_plan(&self, plan_id: &str) -> AppResult<RazorpayPlan> {
+        self.get(&format!("/plans/{}", plan_id)).await
+    }
+
+    pub async fn create_subscription(
+        &self,
+        request: &CreateSubscriptionRequest,
+    ) -> AppResult<RazorpaySubscription> {
+        self.post("/subscriptions", request).await
+    }
+
+    pub async fn get_subscription(&self, subscription_id: &str) -> AppResult<RazorpaySubscription> {
+        self.get(&format!("/subscriptions/{}", subscription_id))
+            .await
+    }
+
+    pub async fn pause_subscription(
+        &self,
+        subscription_id: &str,
+    ) -> AppResult<RazorpaySubscription> {
+        self.post(
+            &format!("/subscriptions/{}/pause", subscription_id),
+            &serde_json::json!({ "pause_at": "now" }),
+        )
+        .await
+    }
+
+    pub async fn resume_subscription(
+        &self,
+        subscription_id: &str,
+    ) -> AppResult<RazorpaySubscription> {
+        self.post(
+            &format!("/subscriptions/{}/resume", subscription_id),
+            &serde_json::json!({ "resume_at": "now" }),
+        )
+        .await
+    }
+
+    /// Cancel immediately, or after the current billing cycle has been charged
+    pub async fn cancel_subscription(
+        &self,
+        subscription_id: &str,
+        cancel_at_cycle_end: bool,
+    ) -> AppResult<RazorpaySubscription> {
+        self.post(
+            &format!("/subscriptions/{}/cancel", subscription_id),
+            &serde_json::json!({ "cancel_at_cycle_end": cancel_at_cycle_end }),
+        )
+        .await
+    }
+}
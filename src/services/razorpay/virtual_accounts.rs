@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use super::{RazorpayClient, RazorpayPayment};
+use crate::error::AppResult;
+use crate::models::RazorpayBankTransferData;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateVirtualAccountRequest {
+    pub receivers: VirtualAccountReceivers,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Razorpay customer (`cust_...`) the account belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<String>,
+    /// Unix time at which Razorpay closes the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close_by: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_expected: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VirtualAccountReceivers {
+    /// `bank_account` for NEFT/RTGS/IMPS
+    pub types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RazorpayVirtualAccount {
+    pub id: String,
+    pub entity: String,
+    pub name: Option<String>,
+    pub status: String,
+    pub description: Option<String>,
+    pub amount_expected: Option<i64>,
+    pub amount_paid: i64,
+    pub customer_id: Option<String>,
+    pub receivers: Vec<VirtualAccountReceiver>,
+    pub close_by: Option<i64>,
+    pub closed_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl RazorpayVirtualAccount {
+    /// The bank account customers transfer to
+    pub fn bank_account(&self) -> Option<&VirtualAccountReceiver> {
+        self.receivers
+            .iter()
+            .find(|receiver| receiver.entity == "bank_account")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VirtualAccountReceiver {
+    pub id: String,
+    pub entity: String,
+    pub name: Option<String>,
+    pub bank_name: Option<String>,
+    pub ifsc: Option<String>,
+    pub account_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VirtualAccountPaymentsResponse {
+    pub entity: String,
+    pub count: i32,
+    pub items: Vec<RazorpayPayment>,
+}
+
+impl RazorpayClient {
+    pub async fn create_virtual_account(
+        &self,
+        request: &CreateVirtualAccountRequest,
+    ) -> AppResult<RazorpayVirtualAccount> {
+        self.post("/virtual_accounts", request).await
+    }
+
+    pub async fn get_virtual_account(
+        &self,
+        virtual_account_id: &str,
+    ) -> AppResult<RazorpayVirtualAccount> {
+        self.get(&format!("/virtual_accounts/{}", virtual_account_id))
+            .await
+    }
+
+    /// Stop accepting transfers to the account
+    pub async fn close_virtual_account(
+        &self,
+        virtual_account_id: &str,
+    ) -> AppResult<RazorpayVirtualAccount> {
+        self.post(
+            &format!("/virtual_accounts/{}/close", virtual_account_id),
+            &serde_json::json!({}),
+        )
+        .await
+    }
+
+    pub async fn get_virtual_account_payments(
+        &self,
+        virtual_account_id: &str,
+    ) -> AppResult<VirtualAccountPaymentsResponse> {
+        self.get(&format!(
+            "/virtual_accounts/{}/payments",
+            virtual_account_id
+        ))
+        .await
+    }
+
+    /// Mode, reference and payer of a payment made by bank transfer
+    pub async fn get_bank_transfer(&self, payment_id: &str) -> AppResult<RazorpayBankTransferData> {
+        self.get(&format!("/payments/{}/bank_transfer", payment_id))
+            .await
+    }
+}
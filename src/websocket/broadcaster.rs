@@ -2,13 +2,13 @@ use std::sync::Arc;
 use tokio::sync::broadcast;
 
 use crate::models::Payment;
-use crate::websocket::handler::PaymentUpdateData;
+use crate::websocket::handler::{PaymentReorgData, PaymentUpdateData, WsMessage};
 
 const BROADCAST_CHANNEL_SIZE: usize = 1000;
 
 #[derive(Clone)]
 pub struct PaymentBroadcaster {
-    sender: broadcast::Sender<PaymentUpdateData>,
+    sender: broadcast::Sender<WsMessage>,
 }
 
 impl PaymentBroadcaster {
@@ -18,7 +18,7 @@ impl PaymentBroadcaster {
     }
 
     /// Subscribe to payment updates
-    pub fn subscribe(&self) -> broadcast::Receiver<PaymentUpdateData> {
+    pub fn subscribe(&self) -> broadcast::Receiver<WsMessage> {
         self.sender.subscribe()
     }
 
@@ -32,7 +32,7 @@ impl PaymentBroadcaster {
             timestamp: chrono::Utc::now().timestamp(),
         };
 
-        match self.sender.send(update) {
+        match self.sender.send(WsMessage::PaymentUpdate(update)) {
             Ok(count) => {
                 tracing::debug!(
                     "Broadcast payment update for {} to {} clients",
@@ -48,10 +48,50 @@ impl PaymentBroadcaster {
         }
     }
 
+    /// Broadcast a payment update with the current on-chain confirmation count
+    pub async fn broadcast_confirmations(
+        &self,
+        payment: &Payment,
+        confirmations: u64,
+    ) -> Result<(), String> {
+        let update = PaymentUpdateData {
+            payment_id: payment.id,
+            status: format!("{:?}", payment.status),
+            tx_hash: payment.crypto_tx_hash.clone(),
+            confirmations: Some(confirmations),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        // No active receivers is fine
+        let _ = self.sender.send(WsMessage::PaymentUpdate(update));
+        Ok(())
+    }
+
+    /// Broadcast that a chain reorganization affected a payment's transaction
+    pub async fn broadcast_reorg(
+        &self,
+        payment: &Payment,
+        tx_hash: &str,
+        block_number: u64,
+        reason: &str,
+    ) -> Result<(), String> {
+        let reorg = PaymentReorgData {
+            payment_id: payment.id,
+            status: format!("{:?}", payment.status),
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            reason: reason.to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        let _ = self.sender.send(WsMessage::PaymentReorg(reorg));
+        Ok(())
+    }
+
     /// Broadcast a custom update
     pub async fn broadcast(&self, update: PaymentUpdateData) -> Result<usize, String> {
         self.sender
-            .send(update)
+            .send(WsMessage::PaymentUpdate(update))
             .map_err(|e| format!("Failed to broadcast: {}", e))
     }
 
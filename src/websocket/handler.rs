@@ -26,10 +26,23 @@ pub enum WsMessage {
     Pong,
     #[serde(rename = "payment_update")]
     PaymentUpdate(PaymentUpdateData),
+    #[serde(rename = "payment_reorg")]
+    PaymentReorg(PaymentReorgData),
     #[serde(rename = "error")]
     Error { message: String },
 }
 
+impl WsMessage {
+    /// Payment a server-sent message concerns, for subscription filtering
+    pub fn payment_id(&self) -> Option<Uuid> {
+        match self {
+            WsMessage::PaymentUpdate(update) => Some(update.payment_id),
+            WsMessage::PaymentReorg(reorg) => Some(reorg.payment_id),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentUpdateData {
     pub payment_id: Uuid,
@@ -41,6 +54,18 @@ pub struct PaymentUpdateData {
     pub timestamp: i64,
 }
 
+/// Sent when a chain reorganization removes or moves a payment's transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentReorgData {
+    pub payment_id: Uuid,
+    pub status: String,
+    pub tx_hash: String,
+    /// Block the transaction was recorded in before the reorganization
+    pub block_number: u64,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
@@ -118,15 +143,17 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                 // Handle broadcast messages
                 result = rx.recv() => {
                     match result {
-                        Ok(update) => {
+                        Ok(msg) => {
                             // Check if client is subscribed to this payment
-                            let is_subscribed = {
-                                let subs = subscribed_payments.read();
-                                subs.is_empty() || subs.contains(&update.payment_id)
+                            let is_subscribed = match msg.payment_id() {
+                                Some(payment_id) => {
+                                    let subs = subscribed_payments.read();
+                                    subs.is_empty() || subs.contains(&payment_id)
+                                }
+                                None => true,
                             };
 
                             if is_subscribed {
-                                let msg = WsMessage::PaymentUpdate(update);
                                 if let Ok(json) = serde_json::to_string(&msg) {
                                     if sender.send(Message::Text(json)).await.is_err() {
                                         break;